@@ -75,6 +75,17 @@ pub(crate) fn bindgen_rust_to_dart(
     )
 }
 
+/// Same as [bindgen_rust_to_dart], except that the Dart bindings are left to the user running
+/// ffigen, see `--ffigen-config`.
+pub(crate) fn bindgen_rust_to_c(arg: BindgenRustToDartArg) -> anyhow::Result<()> {
+    cbindgen(
+        arg.rust_crate_dir,
+        arg.c_output_path,
+        arg.c_struct_names,
+        arg.exclude_symbols,
+    )
+}
+
 #[must_use = "Error path must be handled."]
 fn execute_command(bin: &str, args: &[&str], current_dir: Option<&str>) -> Output {
     let mut cmd = Command::new(bin);
@@ -187,6 +198,50 @@ fn ffigen(
         "execute ffigen c_path={} dart_path={} llvm_path={:?}",
         c_path, dart_path, llvm_path
     );
+    let config = ffigen_config(
+        c_path,
+        dart_path,
+        dart_class_name,
+        llvm_path,
+        llvm_compiler_opts,
+    )?;
+
+    debug!("ffigen config: {}", config);
+
+    let mut config_file = tempfile::NamedTempFile::new()?;
+    std::io::Write::write_all(&mut config_file, config.as_bytes())?;
+    debug!("ffigen config_file: {:?}", config_file);
+
+    let repo = DartRepository::from_str(dart_root).unwrap();
+    let cmd = format!("{} run", repo.toolchain.as_run_command());
+    let res = call_shell(&format!(
+        "cd {}{}{} ffigen --config \"{}\"",
+        dart_root,
+        if cfg!(windows) { "; " } else { " && " },
+        cmd,
+        config_file.path().to_string_lossy()
+    ));
+    if !res.status.success() {
+        let err = String::from_utf8_lossy(&res.stderr);
+        let out = String::from_utf8_lossy(&res.stdout);
+        let pat = "Couldn't find dynamic library in default locations.";
+        if err.contains(pat) || out.contains(pat) {
+            return Err(Error::FfigenLlvm.into());
+        }
+        return Err(
+            Error::string(format!("ffigen failed:\nstderr: {}\nstdout: {}", err, out)).into(),
+        );
+    }
+    Ok(())
+}
+
+fn ffigen_config(
+    c_path: &str,
+    dart_path: &str,
+    dart_class_name: &str,
+    llvm_path: &[String],
+    llvm_compiler_opts: &str,
+) -> anyhow::Result<String> {
     let mut config = format!(
         "
         output: '{}'
@@ -223,38 +278,72 @@ fn ffigen(
         );
     }
 
-    debug!("ffigen config: {}", config);
+    Ok(config)
+}
 
-    let mut config_file = tempfile::NamedTempFile::new()?;
-    std::io::Write::write_all(&mut config_file, config.as_bytes())?;
-    debug!("ffigen config_file: {:?}", config_file);
+/// The paths of the ffigen config written by `--ffigen-config`, relative to its directory.
+pub(crate) struct StandaloneFfigenArg<'a> {
+    pub c_path: &'a str,
+    pub dart_path: &'a str,
+    pub dart_class_name: &'a str,
+    pub symbol_prefix: &'a str,
+    pub llvm_install_path: &'a [String],
+    pub llvm_compiler_opts: &'a str,
+}
 
-    let repo = DartRepository::from_str(dart_root).unwrap();
-    let cmd = format!("{} run", repo.toolchain.as_run_command());
-    let res = call_shell(&format!(
-        "cd {}{}{} ffigen --config \"{}\"",
-        dart_root,
-        if cfg!(windows) { "; " } else { " && " },
-        cmd,
-        config_file.path().to_string_lossy()
-    ));
-    if !res.status.success() {
-        let err = String::from_utf8_lossy(&res.stderr);
-        let out = String::from_utf8_lossy(&res.stdout);
-        let pat = "Couldn't find dynamic library in default locations.";
-        if err.contains(pat) || out.contains(pat) {
-            return Err(Error::FfigenLlvm.into());
-        }
-        return Err(
-            Error::string(format!("ffigen failed:\nstderr: {}\nstdout: {}", err, out)).into(),
-        );
+/// The ffigen config written by `--ffigen-config`. Unlike the output of [ffigen], that of the user
+/// is not edited afterwards: `WireSyncReturnStruct` is mapped to the one of flutter_rust_bridge,
+/// and `--symbol-prefix` is removed from the names by renames.
+pub(crate) fn standalone_ffigen_config(arg: &StandaloneFfigenArg) -> anyhow::Result<String> {
+    let mut config = ffigen_config(
+        arg.c_path,
+        arg.dart_path,
+        arg.dart_class_name,
+        arg.llvm_install_path,
+        arg.llvm_compiler_opts,
+    )?;
+    write!(
+        &mut config,
+        "
+        library-imports:
+          frb: 'package:flutter_rust_bridge/flutter_rust_bridge.dart'
+        type-map:
+          structs:
+            '{prefix}WireSyncReturnStruct':
+              lib: 'frb'
+              c-type: 'WireSyncReturnStruct'
+              dart-type: 'WireSyncReturnStruct'
+        functions:
+          exclude:
+            - 'dummy_method_to_enforce_bundling'
+        ",
+        prefix = arg.symbol_prefix,
+    )?;
+    if !arg.symbol_prefix.is_empty() {
+        write!(
+            &mut config,
+            "  rename:
+            '{prefix}(.*)': '$1'
+        structs:
+          rename:
+            '{prefix}(.*)': '$1'
+        ",
+            prefix = arg.symbol_prefix,
+        )?;
     }
-    Ok(())
+    Ok(config)
 }
 
-pub fn format_rust(path: &str) -> Result {
-    debug!("execute format_rust path={}", path);
-    let res = execute_command("rustfmt", &[path], None);
+pub fn format_rust(path: &str, config_path: Option<&str>) -> Result {
+    debug!(
+        "execute format_rust path={} config_path={:?}",
+        path, config_path
+    );
+    let mut args = vec![path];
+    if let Some(config_path) = config_path {
+        args.extend(["--config-path", config_path]);
+    }
+    let res = execute_command("rustfmt", &args, None);
     if !res.status.success() {
         return Err(Error::Rustfmt(
             String::from_utf8_lossy(&res.stderr).to_string(),
@@ -0,0 +1,508 @@
+//! The `check-compat` subcommand: compares the API of each rust input with a snapshot stored by an
+//! earlier release, and reports the changes breaking the generated Dart API or its wire format,
+//! such as removed functions, changed types or reordered enum variants.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::anyhow;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CheckCompatOpts, Opts};
+use crate::ir::*;
+use crate::transformer;
+
+pub fn check_compat(configs: &[Opts], opts: &CheckCompatOpts) -> anyhow::Result<()> {
+    if opts.baseline.len() != configs.len() {
+        return Err(anyhow!(
+            "--baseline is given {} times, but there are {} rust inputs",
+            opts.baseline.len(),
+            configs.len()
+        ));
+    }
+
+    let mut breaking_count = 0;
+    for (config, baseline_path) in configs.iter().zip(&opts.baseline) {
+        let ir_file = transformer::transform(config.get_ir_file());
+        let current = ApiSnapshot::new(&ir_file);
+
+        if opts.update {
+            if let Some(dir) = Path::new(baseline_path).parent() {
+                fs::create_dir_all(dir)?;
+            }
+            info!("Writing {}", baseline_path);
+            fs::write(baseline_path, serde_json::to_string_pretty(&current)?)?;
+            continue;
+        }
+
+        let baseline = fs::read_to_string(baseline_path).map_err(|err| {
+            anyhow!(
+                "Cannot read the baseline {} ({}); create it with --update",
+                baseline_path,
+                err
+            )
+        })?;
+        let baseline: ApiSnapshot = serde_json::from_str(&baseline)
+            .map_err(|err| anyhow!("Invalid baseline {}: {}", baseline_path, err))?;
+
+        let changes = baseline.compare(&current);
+        info!(
+            "{} compared with {}: {} breaking, {} compatible changes",
+            config.rust_input_path,
+            baseline_path,
+            changes.breaking.len(),
+            changes.compatible.len()
+        );
+        for change in &changes.breaking {
+            warn!("Breaking: {}", change);
+        }
+        for change in &changes.compatible {
+            info!("Compatible: {}", change);
+        }
+        breaking_count += changes.breaking.len();
+    }
+
+    if breaking_count > 0 {
+        return Err(anyhow!("{} breaking changes of the API", breaking_count));
+    }
+    Ok(())
+}
+
+/// The parts of an [IrFile] visible to Dart code or encoded on the wire, stored as the baseline.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiSnapshot {
+    pub funcs: Vec<FuncSnapshot>,
+    pub structs: Vec<StructSnapshot>,
+    pub enums: Vec<EnumSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FuncSnapshot {
+    pub name: String,
+    pub inputs: Vec<FieldSnapshot>,
+    pub output: String,
+    pub fallible: bool,
+    /// `normal`, `sync`, `stream` or `stream after setup`.
+    pub mode: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StructSnapshot {
+    pub name: String,
+    pub fields: Vec<FieldSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnumSnapshot {
+    pub name: String,
+    /// Whether the variants are encoded by their names, see `#[frb(repr = "string")]`.
+    #[serde(default)]
+    pub string_repr: bool,
+    pub variants: Vec<VariantSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VariantSnapshot {
+    pub name: String,
+    /// [None] for a variant without data.
+    pub fields: Option<Vec<FieldSnapshot>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FieldSnapshot {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Changes {
+    pub breaking: Vec<String>,
+    pub compatible: Vec<String>,
+}
+
+impl ApiSnapshot {
+    pub fn new(ir_file: &IrFile) -> Self {
+        let mut structs = ir_file
+            .struct_pool
+            .values()
+            .map(|st| StructSnapshot {
+                name: st.name.clone(),
+                fields: field_snapshots(&st.fields),
+            })
+            .collect::<Vec<_>>();
+        structs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut enums = ir_file
+            .enum_pool
+            .values()
+            .map(|enu| EnumSnapshot {
+                name: enu.name.clone(),
+                string_repr: enu.string_repr,
+                variants: enu
+                    .variants()
+                    .iter()
+                    .map(|variant| VariantSnapshot {
+                        name: variant.name.raw.clone(),
+                        fields: match &variant.kind {
+                            IrVariantKind::Value => None,
+                            IrVariantKind::Struct(st) => Some(field_snapshots(&st.fields)),
+                        },
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        enums.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ApiSnapshot {
+            // The functions generated for closures and handles follow the types using them.
+            funcs: ir_file
+                .funcs
+                .iter()
+                .filter(|func| func.closure.is_none())
+                .map(|func| FuncSnapshot {
+                    name: func.name.clone(),
+                    inputs: field_snapshots(&func.inputs),
+                    output: func.output.rust_api_type(),
+                    fallible: func.fallible,
+                    mode: match func.mode {
+                        IrFuncMode::Normal => "normal",
+                        IrFuncMode::Sync => "sync",
+                        // a `Future` of the stream in Dart
+                        IrFuncMode::Stream { .. } if func.stream_setup => "stream after setup",
+                        IrFuncMode::Stream { .. } => "stream",
+                    }
+                    .to_owned(),
+                })
+                .collect(),
+            structs,
+            enums,
+        }
+    }
+
+    /// Changes from `self`, the baseline, to `current`.
+    pub fn compare(&self, current: &ApiSnapshot) -> Changes {
+        let mut changes = Changes::default();
+
+        let current_funcs = by_name(&current.funcs, |func| &func.name);
+        for old in &self.funcs {
+            let what = format!("function `{}`", old.name);
+            match current_funcs.get(old.name.as_str()) {
+                None => changes.breaking.push(format!("{} is removed", what)),
+                Some(new) => {
+                    compare_fields(&what, "argument", &old.inputs, &new.inputs, &mut changes);
+                    if old.output != new.output {
+                        changes.breaking.push(format!(
+                            "{} returns `{}` instead of `{}`",
+                            what, new.output, old.output
+                        ));
+                    }
+                    if old.fallible && !new.fallible {
+                        changes
+                            .compatible
+                            .push(format!("{} cannot fail anymore", what));
+                    } else if !old.fallible && new.fallible {
+                        changes.breaking.push(format!("{} can fail", what));
+                    }
+                    if old.mode != new.mode {
+                        changes
+                            .breaking
+                            .push(format!("{} is {} instead of {}", what, new.mode, old.mode));
+                    }
+                }
+            }
+        }
+        let old_funcs = by_name(&self.funcs, |func| &func.name);
+        for new in &current.funcs {
+            if !old_funcs.contains_key(new.name.as_str()) {
+                changes
+                    .compatible
+                    .push(format!("function `{}` is added", new.name));
+            }
+        }
+
+        let current_structs = by_name(&current.structs, |st| &st.name);
+        for old in &self.structs {
+            let what = format!("struct `{}`", old.name);
+            match current_structs.get(old.name.as_str()) {
+                None => changes.breaking.push(format!("{} is removed", what)),
+                Some(new) => compare_fields(&what, "field", &old.fields, &new.fields, &mut changes),
+            }
+        }
+
+        let current_enums = by_name(&current.enums, |enu| &enu.name);
+        for old in &self.enums {
+            let what = format!("enum `{}`", old.name);
+            let new = match current_enums.get(old.name.as_str()) {
+                None => {
+                    changes.breaking.push(format!("{} is removed", what));
+                    continue;
+                }
+                Some(new) => new,
+            };
+            if old.string_repr != new.string_repr {
+                changes.breaking.push(format!(
+                    "{} changes from being encoded by {} to {}",
+                    what,
+                    repr_name(old.string_repr),
+                    repr_name(new.string_repr)
+                ));
+                continue;
+            }
+            // Variants are encoded by their index unless they are encoded by their names, so
+            // moving one changes its meaning on the wire.
+            for (old_index, old_variant) in old.variants.iter().enumerate() {
+                let variant_what = format!("variant `{}::{}`", old.name, old_variant.name);
+                match new
+                    .variants
+                    .iter()
+                    .position(|it| it.name == old_variant.name)
+                {
+                    None => changes
+                        .breaking
+                        .push(format!("{} is removed", variant_what)),
+                    Some(new_index) => {
+                        if new_index != old_index && !new.string_repr {
+                            changes.breaking.push(format!(
+                                "{} moves from index {} to {}",
+                                variant_what, old_index, new_index
+                            ));
+                        }
+                        match (&old_variant.fields, &new.variants[new_index].fields) {
+                            (Some(old_fields), Some(new_fields)) => compare_fields(
+                                &variant_what,
+                                "field",
+                                old_fields,
+                                new_fields,
+                                &mut changes,
+                            ),
+                            (None, None) => {}
+                            _ => changes
+                                .breaking
+                                .push(format!("{} changes between data and no data", variant_what)),
+                        }
+                    }
+                }
+            }
+            for (index, new_variant) in new.variants.iter().enumerate() {
+                if old.variants.iter().any(|it| it.name == new_variant.name) {
+                    continue;
+                }
+                if new.string_repr {
+                    changes.compatible.push(format!(
+                        "variant `{}::{}` is added",
+                        new.name, new_variant.name
+                    ));
+                } else if index >= old.variants.len() {
+                    changes.compatible.push(format!(
+                        "variant `{}::{}` is added at the end, at index {}",
+                        new.name, new_variant.name, index
+                    ));
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+fn repr_name(string_repr: bool) -> &'static str {
+    if string_repr {
+        "name"
+    } else {
+        "index"
+    }
+}
+
+fn field_snapshots(fields: &[IrField]) -> Vec<FieldSnapshot> {
+    fields
+        .iter()
+        .map(|field| FieldSnapshot {
+            name: field.name.raw.clone(),
+            ty: field.ty.rust_api_type(),
+        })
+        .collect()
+}
+
+fn by_name<T>(items: &[T], name: impl Fn(&T) -> &String) -> HashMap<&str, &T> {
+    items
+        .iter()
+        .map(|item| (name(item).as_str(), item))
+        .collect()
+}
+
+/// Fields are passed to and from Dart in order, so any change of them is breaking.
+fn compare_fields(
+    what: &str,
+    kind: &str,
+    old: &[FieldSnapshot],
+    new: &[FieldSnapshot],
+    changes: &mut Changes,
+) {
+    if old == new {
+        return;
+    }
+    for old_field in old {
+        match new.iter().find(|it| it.name == old_field.name) {
+            None => changes.breaking.push(format!(
+                "{} `{}` of {} is removed",
+                kind, old_field.name, what
+            )),
+            Some(new_field) if new_field.ty != old_field.ty => changes.breaking.push(format!(
+                "{} `{}` of {} is `{}` instead of `{}`",
+                kind, old_field.name, what, new_field.ty, old_field.ty
+            )),
+            _ => {}
+        }
+    }
+    for new_field in new {
+        if !old.iter().any(|it| it.name == new_field.name) {
+            changes.breaking.push(format!(
+                "{} `{}` of {} is added",
+                kind, new_field.name, what
+            ));
+        }
+    }
+    let old_order = old.iter().map(|it| &it.name);
+    let new_order = new
+        .iter()
+        .map(|it| &it.name)
+        .filter(|name| old.iter().any(|it| &it.name == *name));
+    if !old_order
+        .filter(|name| new.iter().any(|it| &it.name == *name))
+        .eq(new_order)
+    {
+        changes
+            .breaking
+            .push(format!("the {}s of {} are reordered", kind, what));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, ty: &str) -> FieldSnapshot {
+        FieldSnapshot {
+            name: name.to_owned(),
+            ty: ty.to_owned(),
+        }
+    }
+
+    fn func(name: &str, inputs: Vec<FieldSnapshot>, fallible: bool) -> FuncSnapshot {
+        FuncSnapshot {
+            name: name.to_owned(),
+            inputs,
+            output: "String".to_owned(),
+            fallible,
+            mode: "normal".to_owned(),
+        }
+    }
+
+    fn simple_enum(name: &str, variants: &[&str]) -> EnumSnapshot {
+        EnumSnapshot {
+            name: name.to_owned(),
+            string_repr: false,
+            variants: variants
+                .iter()
+                .map(|variant| VariantSnapshot {
+                    name: (*variant).to_owned(),
+                    fields: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn snapshot(funcs: Vec<FuncSnapshot>, enums: Vec<EnumSnapshot>) -> ApiSnapshot {
+        ApiSnapshot {
+            funcs,
+            structs: vec![],
+            enums,
+        }
+    }
+
+    #[test]
+    fn changes_of_functions() {
+        let baseline = snapshot(
+            vec![
+                func("greet", vec![field("name", "String")], true),
+                func("removed", vec![], false),
+            ],
+            vec![],
+        );
+        let current = snapshot(
+            vec![
+                func("greet", vec![field("name", "Option<String>")], false),
+                func("added", vec![], false),
+            ],
+            vec![],
+        );
+
+        let changes = baseline.compare(&current);
+        assert_eq!(
+            changes.breaking,
+            [
+                "argument `name` of function `greet` is `Option<String>` instead of `String`",
+                "function `removed` is removed",
+            ]
+        );
+        assert_eq!(
+            changes.compatible,
+            [
+                "function `greet` cannot fail anymore",
+                "function `added` is added",
+            ]
+        );
+    }
+
+    #[test]
+    fn variants_are_only_added_at_the_end_when_encoded_by_index() {
+        let baseline = snapshot(vec![], vec![simple_enum("Color", &["Red", "Green"])]);
+
+        let appended = snapshot(
+            vec![],
+            vec![simple_enum("Color", &["Red", "Green", "Blue"])],
+        );
+        let changes = baseline.compare(&appended);
+        assert!(changes.breaking.is_empty());
+        assert_eq!(
+            changes.compatible,
+            ["variant `Color::Blue` is added at the end, at index 2"]
+        );
+
+        let inserted = snapshot(
+            vec![],
+            vec![simple_enum("Color", &["Red", "Blue", "Green"])],
+        );
+        assert_eq!(
+            baseline.compare(&inserted).breaking,
+            ["variant `Color::Green` moves from index 1 to 2"]
+        );
+
+        let mut by_name = simple_enum("Color", &["Red", "Blue", "Green"]);
+        by_name.string_repr = true;
+        let mut baseline_by_name = simple_enum("Color", &["Red", "Green"]);
+        baseline_by_name.string_repr = true;
+        let changes =
+            snapshot(vec![], vec![baseline_by_name]).compare(&snapshot(vec![], vec![by_name]));
+        assert!(changes.breaking.is_empty());
+        assert_eq!(changes.compatible, ["variant `Color::Blue` is added"]);
+    }
+
+    #[test]
+    fn reordered_fields_are_breaking() {
+        let mut changes = Changes::default();
+        compare_fields(
+            "struct `Point`",
+            "field",
+            &[field("x", "i32"), field("y", "i32")],
+            &[field("y", "i32"), field("x", "i32")],
+            &mut changes,
+        );
+        assert_eq!(
+            changes.breaking,
+            ["the fields of struct `Point` are reordered"]
+        );
+    }
+}
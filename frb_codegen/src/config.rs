@@ -12,8 +12,11 @@ use structopt::clap::AppSettings;
 use structopt::StructOpt;
 use toml::Value;
 
-use crate::ir::IrFile;
-use crate::parser;
+use crate::generator::dart::DartSdkVersion;
+use crate::generator::rust::PanicPolicy;
+use crate::ir::{DartCase, IrFile, NamingPolicy};
+use crate::parser::{self, DEFAULT_MAX_TYPE_DEPTH};
+use crate::templates::Templates;
 use crate::utils::BlockIndex;
 
 #[derive(StructOpt, Debug, PartialEq, Deserialize, Default)]
@@ -28,6 +31,42 @@ pub struct RawOpts {
     /// If provided, generated Dart declaration code to this separate file
     #[structopt(long)]
     pub dart_decl_output: Option<String>,
+    /// Make the low-level wire class and FFI structs library-private (`_`-prefixed), so that
+    /// only the high-level API is visible to users of the generated Dart code
+    #[structopt(long)]
+    pub dart_private_wire: bool,
+    /// Shrink the generated Dart implementation: no comments, shorter names for the private
+    /// helpers, and identical decoding functions merged
+    #[structopt(long)]
+    pub dart_minify: bool,
+    /// Add dartdoc categories to the generated types, and a library documentation listing the
+    /// methods of the API class, both after the Rust modules defining them
+    #[structopt(long)]
+    pub dart_doc_categories: bool,
+    /// Case of the Dart methods, parameters and fields: `camel`, `preserve` (as in Rust) or
+    /// `screaming` [default: camel]
+    #[structopt(long)]
+    pub dart_member_case: Option<String>,
+    /// Case of the values of Dart enums: `preserve` (as in Rust), `camel` or `screaming`
+    /// [default: preserve]
+    #[structopt(long)]
+    pub dart_enum_case: Option<String>,
+    /// Oldest Dart SDK the generated code must compile with, e.g. `3.0` for sealed classes
+    /// instead of freezed unions [default: 2.14]
+    #[structopt(long)]
+    pub dart_sdk_min: Option<String>,
+    /// Generate a Dart builder class, with chained setters and `build()`, for the structs with at
+    /// least this many fields
+    #[structopt(long)]
+    pub dart_builder_min_fields: Option<usize>,
+    /// Dart library declaring a type the generated code refers to, e.g. in an annotation or a
+    /// template, as `Type=package:foo/foo.dart`, imported by the Dart output using the type
+    #[structopt(long)]
+    pub dart_import: Option<Vec<String>>,
+    /// The Dart output runs in the Dart VM alone, e.g. in command line tools and servers: fail
+    /// when it would import Flutter, and scaffold `package:test` tests
+    #[structopt(long)]
+    pub pure_dart: bool,
 
     /// Path of output generated C header
     #[structopt(short, long)]
@@ -41,27 +80,185 @@ pub struct RawOpts {
     /// Generated class name
     #[structopt(long)]
     pub class_name: Option<Vec<String>>,
+    /// How the values cross the FFI boundary: `wire` [default], i.e. a C struct per value, or
+    /// `msgpack`, i.e. one MessagePack buffer for the inputs and one for the output of each call,
+    /// faster for deep object graphs; requires the `msgpack` feature of flutter_rust_bridge
+    #[structopt(long)]
+    pub codec: Option<String>,
     /// Line length for dart formatting
     #[structopt(long)]
     pub dart_format_line_length: Option<i32>,
     /// Skip automatically adding `mod bridge_generated;` to `lib.rs`
     #[structopt(long)]
     pub skip_add_mod_to_lib: bool,
+    /// Compile the generated Rust code only with this feature of the crate, e.g. `bridge`, so that
+    /// the crate can be built without flutter_rust_bridge when it is off
+    #[structopt(long)]
+    pub rust_cfg_feature: Option<String>,
     /// Path to the installed LLVM
     #[structopt(long)]
     pub llvm_path: Option<Vec<String>>,
     /// LLVM compiler opts
     #[structopt(long)]
     pub llvm_compiler_opts: Option<String>,
+    /// Path of output ffigen config, one per rust input. ffigen is then not run, and the Dart
+    /// output uses the bindings generated by running ffigen with this config instead
+    #[structopt(long)]
+    pub ffigen_config: Option<Vec<String>>,
+    /// Path of a Rust file of hand-written `#[no_mangle] extern "C"` functions, one per rust input,
+    /// listed along with the generated ones in the C header and the Dart bindings
+    #[structopt(long)]
+    pub extern_c_input: Option<Vec<String>>,
     /// Path to root of Dart project, otherwise inferred from --dart-output
     #[structopt(long)]
     pub dart_root: Option<Vec<String>>,
     /// Skip running build_runner even when codegen-capable code is detected
     #[structopt(long)]
     pub no_build_runner: bool,
+    /// Skip formatting the generated Rust code with rustfmt
+    #[structopt(long)]
+    pub no_rustfmt: bool,
+    /// Path to a rustfmt.toml used for the generated Rust code, otherwise rustfmt discovers it
+    #[structopt(long)]
+    pub rustfmt_config: Option<String>,
+    /// Use the `FLUTTER_RUST_BRIDGE_HANDLER` provided by the Rust input even when it is not detected,
+    /// e.g. because it is declared by another macro
+    #[structopt(long)]
+    pub custom_handler: bool,
+    /// Skip the functions which cannot be bridged, e.g. because they reference unsupported types,
+    /// and list them with the reason instead of failing
+    #[structopt(long)]
+    pub skip_unresolvable: bool,
+    /// Make the generated Rust code call the API through a `BridgeApi` trait, whose implementation
+    /// can be replaced with `register_bridge_api`, e.g. in tests
+    #[structopt(long)]
+    pub bridge_api_trait: bool,
+    /// Print a breakdown of the generated lines and extern symbols per function and per type
+    #[structopt(long)]
+    pub size_report: bool,
+    /// Path of output size report in JSON, one per rust input
+    #[structopt(long)]
+    pub size_report_output: Option<Vec<String>>,
+    /// Warn when more extern symbols than this are generated for a rust input
+    #[structopt(long)]
+    pub symbol_budget: Option<usize>,
+    /// Fail when a type nests more layers of generic types than this, e.g. `Option<Vec<..>>`,
+    /// instead of risking a stack overflow [default: 64]
+    #[structopt(long)]
+    pub max_type_depth: Option<usize>,
+    /// Prefix of the generated extern functions and C structs, e.g. `myplugin_`, so that several
+    /// bridged libraries can be linked into one app
+    #[structopt(long)]
+    pub symbol_prefix: Option<String>,
+    /// Path of output Windows module-definition file (`.def`) exporting only the symbols of the
+    /// bridge, of all rust inputs, e.g. to link the cdylib with `/DEF:`
+    #[structopt(long)]
+    pub export_def: Option<String>,
+    /// Path of output linker version script exporting only the symbols of the bridge, of all rust
+    /// inputs, e.g. to link the cdylib with `-Wl,--version-script=`
+    #[structopt(long)]
+    pub export_version_script: Option<String>,
+    /// Path of output Dart file of a class creating the API classes of all rust inputs, named
+    /// after the file, e.g. `RustLib` for `rust_lib.dart`, whose `init` sets the library up only
+    /// once however many times it is called, e.g. by each package of an app using it
+    #[structopt(long)]
+    pub dart_group_output: Option<String>,
+    /// Generate `getBridgeHealth()`, which reports the load of the Rust executor, e.g. to detect
+    /// when its workers are saturated
+    #[structopt(long)]
+    pub bridge_health: bool,
+    /// Generate `captureRustOutput()`, a Dart stream of the lines written to the standard output
+    /// and error, e.g. by `println!`, which are otherwise invisible in iOS release builds
+    #[structopt(long)]
+    pub capture_stdio: bool,
+    /// Generate `setLogLevel(LogLevel)`, which changes the maximum level of the `log` crate at
+    /// runtime, e.g. to turn on verbose logs remotely; requires the `log` feature of flutter_rust_bridge
+    #[structopt(long)]
+    pub log_level_control: bool,
+    /// Generate `rustCrashes()`, a Dart stream of the panics of the Rust functions with their
+    /// backtraces, e.g. to forward them to a crash reporting service
+    #[structopt(long)]
+    pub crash_reports: bool,
+    /// What the default handler does when a function panics on a worker: `recover` [default], i.e.
+    /// fail the call and go on, `abort` the process, or call the function of this path, e.g.
+    /// `crate::api::on_panic`, then recover
+    #[structopt(long)]
+    pub panic_policy: Option<String>,
+    /// Generate `rpc_dispatch` in each Rust output, calling the API functions from JSON-RPC
+    /// requests, e.g. with `flutter_rust_bridge::rpc::serve`; requires the `rpc` feature of
+    /// flutter_rust_bridge
+    #[structopt(long)]
+    pub rpc_gateway: bool,
+    /// Decode the strings of up to this many characters to a single instance each in Dart, saving
+    /// memory when large lists repeat the same strings, e.g. names of categories
+    #[structopt(long)]
+    pub intern_strings: Option<usize>,
+    /// Generate an empty protected region in each plain Dart class and at the end of the Dart
+    /// declarations, whose content, e.g. extra getters, is kept when regenerating
+    #[structopt(long)]
+    pub protected_regions: bool,
+    /// Generate a `sample()` constructor of placeholder data for each struct, both in Dart and in
+    /// Rust, e.g. for widget previews and tests
+    #[structopt(long)]
+    pub sample_factories: bool,
+    /// Directory of Handlebars templates overriding the generated wire functions, Dart methods or
+    /// Dart API class, e.g. `wire_func.rs.hbs`
+    #[structopt(long)]
+    pub templates: Option<String>,
     /// Show debug messages.
     #[structopt(short, long)]
     pub verbose: bool,
+    #[structopt(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug, PartialEq)]
+pub enum Command {
+    /// Delete the files generated by earlier runs which are not generated anymore, e.g. after
+    /// renaming an output
+    Clean(CleanOpts),
+    /// Write a Flutter integration test calling every generated function with sample arguments,
+    /// along with Rust functions echoing each type of the API for the test to check round trips
+    ScaffoldTests(ScaffoldTestsOpts),
+    /// Compare the API with a snapshot stored by an earlier release, and fail on the changes
+    /// breaking the generated Dart API or its wire format
+    CheckCompat(CheckCompatOpts),
+}
+
+#[derive(StructOpt, Debug, PartialEq)]
+pub struct CleanOpts {
+    /// Crate directory for your Rust project, whose record of the generated files is used
+    #[structopt(long, default_value = ".")]
+    pub rust_crate_dir: String,
+    /// Also delete the files generated by the last run
+    #[structopt(long)]
+    pub all: bool,
+}
+
+#[derive(StructOpt, Debug, PartialEq)]
+pub struct ScaffoldTestsOpts {
+    /// Path of the Rust echo functions, one per rust input [default: `api_echo.rs` next to
+    /// `api.rs`]
+    #[structopt(long)]
+    pub echo_output: Vec<String>,
+    /// Path of the Dart integration test, one per rust input [default:
+    /// `integration_test/bridge_generated_test.dart` in the Dart project]
+    #[structopt(long)]
+    pub test_output: Vec<String>,
+    /// Replace the files scaffolded by an earlier run
+    #[structopt(long)]
+    pub force: bool,
+}
+
+#[derive(StructOpt, Debug, PartialEq)]
+pub struct CheckCompatOpts {
+    /// Path of the API snapshot in JSON, one per rust input
+    #[structopt(long, required = true)]
+    pub baseline: Vec<String>,
+    /// Write the current API to the baseline instead of comparing, e.g. when releasing
+    #[structopt(long)]
+    pub update: bool,
 }
 
 #[derive(Debug)]
@@ -69,20 +266,100 @@ pub struct Opts {
     pub rust_input_path: String,
     pub dart_output_path: String,
     pub dart_decl_output_path: Option<String>,
+    pub dart_private_wire: bool,
+    pub dart_minify: bool,
+    pub dart_doc_categories: bool,
+    pub naming_policy: NamingPolicy,
+    pub dart_sdk_min: DartSdkVersion,
+    pub dart_builder_min_fields: Option<usize>,
+    /// The Dart library of each type of `--dart-import`.
+    pub dart_imports: Vec<(String, String)>,
+    pub pure_dart: bool,
     pub c_output_path: Vec<String>,
     pub rust_crate_dir: String,
     pub rust_output_path: String,
     pub class_name: String,
+    pub codec: Codec,
     pub dart_format_line_length: i32,
     pub skip_add_mod_to_lib: bool,
+    pub rust_cfg_feature: Option<String>,
     pub llvm_path: Vec<String>,
     pub llvm_compiler_opts: String,
+    pub ffigen_config_path: Option<String>,
+    pub extern_c_input_path: Option<String>,
     pub manifest_path: String,
     pub dart_root: Option<String>,
     pub build_runner: bool,
+    pub rustfmt: bool,
+    pub rustfmt_config_path: Option<String>,
+    pub custom_handler: bool,
+    pub skip_unresolvable: bool,
+    pub bridge_api_trait: bool,
+    pub size_report: bool,
+    pub size_report_output_path: Option<String>,
+    pub symbol_budget: Option<usize>,
+    pub max_type_depth: usize,
+    pub symbol_prefix: String,
+    pub export_def_path: Option<String>,
+    pub export_version_script_path: Option<String>,
+    pub bridge_health: bool,
+    pub capture_stdio: bool,
+    pub log_level_control: bool,
+    pub crash_reports: bool,
+    pub panic_policy: PanicPolicy,
+    pub rpc_gateway: bool,
+    pub intern_strings: Option<usize>,
+    pub protected_regions: bool,
+    pub templates: Templates,
+    pub dart_group: Option<DartGroup>,
+    pub sample_factories: bool,
     pub block_index: BlockIndex,
 }
 
+/// How the values cross the FFI boundary, from `--codec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// A C struct per value, allocated by Dart for the inputs, and built by allo-isolate for the
+    /// outputs.
+    #[default]
+    Wire,
+    /// One MessagePack buffer for the inputs and one for the output of each call, made with serde
+    /// in Rust, for the functions supporting it, see [crate::ir::IrFunc::supports_msgpack].
+    MsgPack,
+}
+
+impl FromStr for Codec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wire" => Ok(Codec::Wire),
+            "msgpack" => Ok(Codec::MsgPack),
+            _ => Err(format!("`{}` is neither `wire` nor `msgpack`", s)),
+        }
+    }
+}
+
+/// The Dart class creating the API classes of all rust api blocks together, from
+/// `--dart-group-output`.
+#[derive(Debug, Clone)]
+pub struct DartGroup {
+    pub output_path: String,
+    /// The class name and Dart output path of each rust api block.
+    pub members: Vec<(String, String)>,
+}
+
+impl DartGroup {
+    /// The name of the class, after the file, e.g. `RustLib` for `rust_lib.dart`.
+    pub fn class_name(&self) -> String {
+        Path::new(&self.output_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_else(|| panic!("invalid --dart-group-output `{}`", self.output_path))
+            .to_case(Case::Pascal)
+    }
+}
+
 pub fn parse(raw: RawOpts) -> Vec<Opts> {
     // rust input path(s)
     let rust_input_paths = get_valid_canon_paths(&raw.rust_input);
@@ -159,6 +436,16 @@ pub fn parse(raw: RawOpts) -> Vec<Opts> {
         "class_name(s) should have the same number of path(s) as rust input(s)"
     );
 
+    // dart group path (only 1, for the classes of all rust inputs)
+    let dart_group = raw.dart_group_output.as_deref().map(|path| DartGroup {
+        output_path: canon_path(path),
+        members: class_names
+            .iter()
+            .cloned()
+            .zip(dart_output_paths.iter().cloned())
+            .collect(),
+    });
+
     // c output path(s) (only 1 list is needed, nothing to do with number of rust_input_paths)
     let c_output_paths = raw
         .c_output
@@ -173,6 +460,57 @@ pub fn parse(raw: RawOpts) -> Vec<Opts> {
                 .unwrap_or_else(|_| panic!("{}", format_fail_to_guess_error("c_output")))]
         });
 
+    // size report output path(s)
+    let size_report_output_paths = raw.size_report_output.map(|outputs| {
+        outputs
+            .iter()
+            .map(|output| canon_path(output))
+            .collect::<Vec<_>>()
+    });
+    if let Some(size_report_output_paths) = &size_report_output_paths {
+        assert!(
+            size_report_output_paths.len() == rust_input_paths.len(),
+            "size report output path(s) should have the same number of path(s) as rust input(s)"
+        );
+    }
+
+    // symbol export list paths (only 1 of each, for the symbols of all rust inputs)
+    let export_def_path = raw.export_def.as_deref().map(canon_path);
+    let export_version_script_path = raw.export_version_script.as_deref().map(canon_path);
+
+    // ffigen config path(s)
+    let ffigen_config_paths = raw.ffigen_config.map(|outputs| {
+        outputs
+            .iter()
+            .map(|output| canon_path(output))
+            .collect::<Vec<_>>()
+    });
+    if let Some(ffigen_config_paths) = &ffigen_config_paths {
+        assert!(
+            ffigen_config_paths.len() == rust_input_paths.len(),
+            "ffigen config path(s) should have the same number of path(s) as rust input(s)"
+        );
+        // the bindings are then in a library of their own, whose names cannot be private
+        assert!(
+            !raw.dart_private_wire,
+            "--ffigen-config cannot be combined with --dart-private-wire"
+        );
+    }
+
+    // hand-written extern function file(s)
+    let extern_c_input_paths = raw.extern_c_input.map(|inputs| {
+        inputs
+            .iter()
+            .map(|input| canon_path(input))
+            .collect::<Vec<_>>()
+    });
+    if let Some(extern_c_input_paths) = &extern_c_input_paths {
+        assert!(
+            extern_c_input_paths.len() == rust_input_paths.len(),
+            "extern C input path(s) should have the same number of path(s) as rust input(s)"
+        );
+    }
+
     // dart root(s)
     let dart_roots = match raw.dart_root {
         Some(dart_roots) => dart_roots
@@ -190,14 +528,101 @@ pub fn parse(raw: RawOpts) -> Vec<Opts> {
         .dart_decl_output
         .as_ref()
         .map(|s| canon_path(s.as_str()));
+    let dart_private_wire = raw.dart_private_wire;
+    let dart_minify = raw.dart_minify;
+    let dart_doc_categories = raw.dart_doc_categories;
+    let parse_case = |flag: &str, value: Option<String>, default: DartCase| {
+        value.map_or(default, |value| {
+            value
+                .parse()
+                .unwrap_or_else(|err| panic!("invalid --{}: {}", flag, err))
+        })
+    };
+    let naming_policy = NamingPolicy {
+        members: parse_case(
+            "dart-member-case",
+            raw.dart_member_case,
+            NamingPolicy::default().members,
+        ),
+        enum_values: parse_case(
+            "dart-enum-case",
+            raw.dart_enum_case,
+            NamingPolicy::default().enum_values,
+        ),
+    };
+    let dart_sdk_min = raw
+        .dart_sdk_min
+        .map_or_else(DartSdkVersion::default, |value| {
+            value
+                .parse()
+                .unwrap_or_else(|err| panic!("invalid --dart-sdk-min: {}", err))
+        });
+    let dart_builder_min_fields = raw.dart_builder_min_fields;
+    let dart_imports = raw
+        .dart_import
+        .unwrap_or_default()
+        .iter()
+        .map(|mapping| match mapping.split_once('=') {
+            Some((ty, uri)) if !ty.trim().is_empty() && !uri.trim().is_empty() => {
+                (ty.trim().to_owned(), uri.trim().to_owned())
+            }
+            _ => panic!(
+                "invalid --dart-import `{}`, expected e.g. `Type=package:foo/foo.dart`",
+                mapping
+            ),
+        })
+        .collect::<Vec<_>>();
+    let pure_dart = raw.pure_dart;
     let dart_format_line_length = raw.dart_format_line_length.unwrap_or(80);
+    let codec = raw.codec.map_or_else(Codec::default, |value| {
+        value
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid --codec: {}", err))
+    });
     let llvm_paths = get_llvm_paths(&raw.llvm_path);
     let llvm_compiler_opts = raw
         .llvm_compiler_opts
         .clone()
         .unwrap_or_else(|| "".to_string());
     let skip_add_mod_to_lib = raw.skip_add_mod_to_lib;
+    let rust_cfg_feature = raw.rust_cfg_feature;
     let build_runner = !raw.no_build_runner;
+    let rustfmt = !raw.no_rustfmt;
+    let rustfmt_config_path = raw.rustfmt_config.as_ref().map(|s| canon_path(s.as_str()));
+    let custom_handler = raw.custom_handler;
+    let skip_unresolvable = raw.skip_unresolvable;
+    let bridge_api_trait = raw.bridge_api_trait;
+    let size_report = raw.size_report;
+    let symbol_budget = raw.symbol_budget;
+    let max_type_depth = raw.max_type_depth.unwrap_or(DEFAULT_MAX_TYPE_DEPTH);
+    let bridge_health = raw.bridge_health;
+    let capture_stdio = raw.capture_stdio;
+    let log_level_control = raw.log_level_control;
+    let crash_reports = raw.crash_reports;
+    if raw.panic_policy.is_some() && custom_handler {
+        panic!("--panic-policy only applies to the default handler, not with --custom-handler");
+    }
+    let panic_policy = raw.panic_policy.map_or_else(PanicPolicy::default, |value| {
+        value
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid --panic-policy: {}", err))
+    });
+    let rpc_gateway = raw.rpc_gateway;
+    let intern_strings = raw.intern_strings;
+    let protected_regions = raw.protected_regions;
+    let sample_factories = raw.sample_factories;
+    let templates = Templates::load(raw.templates.map(|dir| canon_path(&dir)).as_deref());
+    let symbol_prefix = raw.symbol_prefix.unwrap_or_default();
+    if !symbol_prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || symbol_prefix.starts_with(|c: char| c.is_ascii_digit())
+    {
+        panic!(
+            "--symbol-prefix `{}` should only contain letters, digits and underscores, and not start with a digit",
+            symbol_prefix
+        );
+    }
 
     (0..rust_input_paths.len())
         .map(|i| {
@@ -205,17 +630,54 @@ pub fn parse(raw: RawOpts) -> Vec<Opts> {
                 rust_input_path: rust_input_paths[i].clone(),
                 dart_output_path: dart_output_paths[i].clone(),
                 dart_decl_output_path: dart_decl_output_path.clone(),
+                dart_private_wire,                  //same for all rust api blocks
+                dart_minify,                        //same for all rust api blocks
+                dart_doc_categories,                //same for all rust api blocks
+                naming_policy,                      //same for all rust api blocks
+                dart_sdk_min,                       //same for all rust api blocks
+                dart_builder_min_fields,            //same for all rust api blocks
+                dart_imports: dart_imports.clone(), //same for all rust api blocks
+                pure_dart,                          //same for all rust api blocks
                 c_output_path: c_output_paths.clone(), //same for all rust api blocks
                 rust_crate_dir: rust_crate_dirs[i].clone(),
                 rust_output_path: rust_output_paths[i].clone(),
                 class_name: class_names[i].clone(),
+                codec, //same for all rust api blocks
                 dart_format_line_length,
                 skip_add_mod_to_lib, //same for all rust api blocks
+                rust_cfg_feature: rust_cfg_feature.clone(), //same for all rust api blocks
                 llvm_path: llvm_paths.clone(),
                 llvm_compiler_opts: llvm_compiler_opts.clone(),
+                ffigen_config_path: ffigen_config_paths.as_ref().map(|paths| paths[i].clone()),
+                extern_c_input_path: extern_c_input_paths.as_ref().map(|paths| paths[i].clone()),
                 manifest_path: manifest_paths[i].clone(),
                 dart_root: dart_roots[i].clone(),
                 build_runner, //same for all rust api blocks
+                rustfmt,      //same for all rust api blocks
+                rustfmt_config_path: rustfmt_config_path.clone(),
+                export_def_path: export_def_path.clone(), //same for all rust api blocks
+                export_version_script_path: export_version_script_path.clone(), //same for all rust api blocks
+                custom_handler,    //same for all rust api blocks
+                skip_unresolvable, //same for all rust api blocks
+                bridge_api_trait,  //same for all rust api blocks
+                size_report,       //same for all rust api blocks
+                size_report_output_path: size_report_output_paths
+                    .as_ref()
+                    .map(|paths| paths[i].clone()),
+                symbol_budget,                        //same for all rust api blocks
+                max_type_depth,                       //same for all rust api blocks
+                symbol_prefix: symbol_prefix.clone(), //same for all rust api blocks
+                bridge_health,                        //same for all rust api blocks
+                capture_stdio,                        //same for all rust api blocks
+                log_level_control,                    //same for all rust api blocks
+                crash_reports,                        //same for all rust api blocks
+                panic_policy: panic_policy.clone(),   //same for all rust api blocks
+                rpc_gateway,                          //same for all rust api blocks
+                intern_strings,                       //same for all rust api blocks
+                protected_regions,                    //same for all rust api blocks
+                templates: templates.clone(),         //same for all rust api blocks
+                dart_group: dart_group.clone(),       //same for all rust api blocks
+                sample_factories,                     //same for all rust api blocks
                 block_index: BlockIndex(i),
             }
         })
@@ -382,7 +844,44 @@ impl Opts {
 
         // info!("Phase: Parse AST to IR");
 
-        parser::parse(&source_rust_content, file_ast, &self.manifest_path)
+        let mut ir_file = parser::parse(
+            file_ast,
+            &self.manifest_path,
+            &self.rust_input_path,
+            self.custom_handler,
+            self.skip_unresolvable,
+            self.max_type_depth,
+        );
+        if self.codec == Codec::MsgPack {
+            let msgpack = ir_file
+                .funcs
+                .iter()
+                .map(|func| func.supports_msgpack(&ir_file))
+                .collect::<Vec<_>>();
+            for (func, msgpack) in ir_file.funcs.iter_mut().zip(msgpack) {
+                func.msgpack = msgpack;
+            }
+        }
+        if let Some(min_fields) = self.dart_builder_min_fields {
+            for st in ir_file.struct_pool.values_mut() {
+                st.dart_builder |= st.fields.len() >= min_fields;
+            }
+        }
+        ir_file
+    }
+
+    /// The hand-written extern functions of `--extern-c-input`, if any.
+    pub fn extern_c_func_names(&self) -> Vec<String> {
+        match &self.extern_c_input_path {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .unwrap_or_else(|_| panic!("panic with file: {}", path));
+                let file = syn::parse_file(&content)
+                    .unwrap_or_else(|err| panic!("cannot parse {}: {}", path, err));
+                parser::extern_c_func_names(&file.items)
+            }
+            None => vec![],
+        }
     }
 
     pub fn dart_api_class_name(&self) -> String {
@@ -417,6 +916,16 @@ impl Opts {
         )
     }
 
+    /// Where the ffigen config of `--ffigen-config` has ffigen write the bindings.
+    pub fn dart_ffigen_output_path(&self) -> Option<String> {
+        Some(
+            Path::new(&self.dart_output_path)
+                .with_extension("ffigen.dart")
+                .to_str()?
+                .to_owned(),
+        )
+    }
+
     pub fn dart_root_or_default(&self) -> String {
         self.dart_root
             .clone()
@@ -12,3 +12,25 @@ pub fn generate_dummy(func_names: &[String]) -> String {
             .join("\n"),
     )
 }
+
+/// Windows module-definition file exporting only `symbols` from the linked library.
+pub fn generate_module_definition(symbols: &[String]) -> String {
+    format!(
+        "EXPORTS\n{}",
+        symbols
+            .iter()
+            .map(|symbol| format!("    {}\n", symbol))
+            .collect::<String>()
+    )
+}
+
+/// Linker version script exporting only `symbols` from the linked library.
+pub fn generate_version_script(symbols: &[String]) -> String {
+    format!(
+        "{{\n  global:\n{}  local:\n    *;\n}};\n",
+        symbols
+            .iter()
+            .map(|symbol| format!("    {};\n", symbol))
+            .collect::<String>()
+    )
+}
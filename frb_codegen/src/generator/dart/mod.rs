@@ -1,3 +1,4 @@
+mod msgpack;
 mod ty;
 mod ty_boxed;
 mod ty_delegate;
@@ -22,27 +23,103 @@ pub use ty_struct::*;
 
 use convert_case::{Case, Casing};
 use log::debug;
+use regex::Regex;
 
+use crate::config::Opts;
+use crate::error::Error;
 use crate::ir::IrType::*;
 use crate::ir::*;
 use crate::method_utils::{FunctionName, MethodNamingUtil};
 use crate::others::*;
+use crate::templates::{DartApiClassData, DartMethodData};
 use crate::utils::BlockIndex;
 
+/// The oldest Dart SDK the generated code must compile with, from `--dart-sdk-min`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DartSdkVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Default for DartSdkVersion {
+    /// The oldest supported by flutter_rust_bridge.
+    fn default() -> Self {
+        Self {
+            major: 2,
+            minor: 14,
+        }
+    }
+}
+
+impl DartSdkVersion {
+    /// Whether enums with fields become sealed classes matched with patterns, instead of freezed
+    /// unions.
+    pub fn has_sealed_classes(&self) -> bool {
+        self.major >= 3
+    }
+}
+
+impl std::str::FromStr for DartSdkVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a version such as 2.19 or 3.0", s);
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+        let version = Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        };
+        if version < Self::default() {
+            return Err(format!(
+                "the generated code requires Dart {}.{} or later",
+                Self::default().major,
+                Self::default().minor
+            ));
+        }
+        Ok(version)
+    }
+}
+
 pub struct Output {
     pub file_prelude: DartBasicCode,
+    /// The header of the files without the implementation, e.g. from `--dart-decl-output`.
+    pub decl_prelude: DartBasicCode,
     pub decl_code: DartBasicCode,
     pub impl_code: DartBasicCode,
 }
 
-pub fn generate(
-    ir_file: &IrFile,
-    dart_api_class_name: &str,
-    dart_api_impl_class_name: &str,
-    dart_wire_class_name: &str,
-    dart_output_file_root: &str,
-    block_index: BlockIndex,
-) -> (Output, bool) {
+pub fn generate(ir_file: &IrFile, config: &Opts, dart_output_file_root: &str) -> (Output, bool) {
+    let dart_api_class_name = &config.dart_api_class_name();
+    for (enabled, flag, rust_name, dart_name) in [
+        (
+            config.bridge_health,
+            "--bridge-health",
+            "get_bridge_health",
+            "getBridgeHealth",
+        ),
+        (
+            config.capture_stdio,
+            "--capture-stdio",
+            "capture_stdio",
+            "captureRustOutput",
+        ),
+        (
+            config.log_level_control,
+            "--log-level-control",
+            "set_log_level",
+            "setLogLevel",
+        ),
+        (
+            config.crash_reports,
+            "--crash-reports",
+            "crash_reports",
+            "rustCrashes",
+        ),
+    ] {
+        if has_utility(config, enabled) {
+            check_utility_name(ir_file, flag, rust_name, dart_name, config.naming_policy);
+        }
+    }
     let DartApiSpec {
         dart_funcs,
         dart_structs,
@@ -50,34 +127,42 @@ pub fn generate(
         dart_api_fill_to_wire_funcs,
         dart_wire2api_funcs,
         needs_freezed,
-    } = get_dart_api_spec_from_ir_file(ir_file, block_index, dart_api_class_name);
+    } = get_dart_api_spec_from_ir_file(ir_file, config);
     let common_header = generate_common_header();
 
-    let decl_code = generate_dart_declaration_code(
+    let declaration_body =
+        generate_dart_declaration_body(&dart_funcs, &dart_structs, ir_file, config);
+    let mut decl_code = generate_dart_declaration_code(
         &common_header,
         generate_freezed_header(dart_output_file_root, needs_freezed),
-        generate_import_header(get_dart_imports(ir_file)),
-        generate_dart_declaration_body(dart_api_class_name, &dart_funcs, &dart_structs),
+        &generate_import_header(get_dart_imports(ir_file))
+            + &generate_mapped_imports(&config.dart_imports, &declaration_body)
+            + &generate_meta_import(&declaration_body),
+        declaration_body,
     );
-
-    let impl_code = generate_dart_implementation_code(
-        &common_header,
-        generate_dart_implementation_body(
-            &dart_funcs,
-            &dart_api2wire_funcs,
-            &dart_api_fill_to_wire_funcs,
-            &dart_wire2api_funcs,
-            dart_api_impl_class_name,
-            dart_wire_class_name,
+    if config.dart_doc_categories {
+        decl_code = &generate_library_header(
+            ir_file,
             dart_api_class_name,
-        ),
-    );
+            dart_output_file_root,
+            config.naming_policy,
+        ) + &decl_code;
+    }
 
-    let file_prelude = generate_file_prelude();
+    let implementation_body = generate_dart_implementation_body(
+        &dart_funcs,
+        &dart_api2wire_funcs,
+        &dart_api_fill_to_wire_funcs,
+        &dart_wire2api_funcs,
+        config,
+    );
+    let impl_code = &generate_dart_implementation_code(&common_header, implementation_body.clone())
+        + &generate_mapped_imports(&config.dart_imports, &implementation_body);
 
     (
         Output {
-            file_prelude,
+            file_prelude: generate_file_prelude(false),
+            decl_prelude: generate_file_prelude(true),
             decl_code,
             impl_code,
         },
@@ -94,45 +179,105 @@ struct DartApiSpec {
     needs_freezed: bool,
 }
 
-fn get_dart_api_spec_from_ir_file(
-    ir_file: &IrFile,
-    block_index: BlockIndex,
-    dart_api_class_name: &str,
-) -> DartApiSpec {
+fn get_dart_api_spec_from_ir_file(ir_file: &IrFile, config: &Opts) -> DartApiSpec {
+    let block_index = config.block_index;
+    let dart_api_class_name = &config.dart_api_class_name();
+    let dart_sdk_min = config.dart_sdk_min;
+    let naming_policy = config.naming_policy;
+    let intern_strings = config.intern_strings;
     let distinct_types = ir_file.distinct_types(true, true);
     let distinct_input_types = ir_file.distinct_types(true, false);
     let distinct_output_types = ir_file.distinct_types(false, true);
     debug!("distinct_input_types={:?}", distinct_input_types);
     debug!("distinct_output_types={:?}", distinct_output_types);
 
-    let dart_funcs = ir_file
+    let mut dart_funcs = ir_file
         .funcs
         .iter()
-        .map(|f| generate_api_func(f, ir_file))
+        .map(|f| {
+            let mut generated = generate_api_func(f, ir_file, naming_policy);
+            if let Some(implementation) = config.templates.dart_method(&DartMethodData {
+                func: &f.name,
+                mode: f.mode.ffi_call_mode(),
+                comments: &generated.comments,
+                signature: &generated.signature,
+                default: &generated.implementation,
+            }) {
+                generated.implementation = implementation;
+            }
+            generated
+        })
         .collect::<Vec<_>>();
+    dart_funcs.extend(generate_api_overloads(ir_file, naming_policy));
+    dart_funcs.extend(generate_api_renames(ir_file, naming_policy));
     let dart_structs = distinct_types
         .iter()
         .map(|ty| {
-            TypeDartGenerator::new(ty.clone(), ir_file, Some(dart_api_class_name.to_string()))
-                .structs()
+            let code = TypeDartGenerator::new(
+                ty.clone(),
+                ir_file,
+                Some(dart_api_class_name.to_string()),
+                dart_sdk_min,
+                naming_policy,
+                config.protected_regions,
+                config.sample_factories,
+            )
+            .structs();
+            match type_module_path(ty, ir_file) {
+                Some(module_path) if config.dart_doc_categories && !code.trim().is_empty() => {
+                    format!(
+                        "/// {{@category {}}}\n{}",
+                        dart_doc_category(module_path),
+                        code.trim_start()
+                    )
+                }
+                _ => code,
+            }
         })
         .collect::<Vec<_>>();
 
     let dart_api2wire_funcs = distinct_input_types
         .iter()
-        .map(|ty| generate_api2wire_func(ty, ir_file, block_index))
+        .map(|ty| generate_api2wire_func(ty, ir_file, block_index, dart_sdk_min, naming_policy))
         .collect::<Vec<_>>();
     let dart_api_fill_to_wire_funcs = distinct_input_types
         .iter()
-        .map(|ty| generate_api_fill_to_wire_func(ty, ir_file))
+        .map(|ty| generate_api_fill_to_wire_func(ty, ir_file, dart_sdk_min, naming_policy))
         .collect::<Vec<_>>();
-    let dart_wire2api_funcs = distinct_output_types
+    let mut dart_wire2api_funcs = distinct_output_types
         .iter()
-        .map(|ty| generate_wire2api_func(ty, ir_file, dart_api_class_name))
+        .map(|ty| {
+            generate_wire2api_func(
+                ty,
+                ir_file,
+                dart_api_class_name,
+                intern_strings,
+                dart_sdk_min,
+                naming_policy,
+            )
+        })
         .collect::<Vec<_>>();
+    dart_wire2api_funcs.extend(
+        ir_file
+            .msgpack_types(true, false)
+            .iter()
+            .map(|ty| msgpack::generate_api2msgpack_func(ty, ir_file, naming_policy)),
+    );
+    dart_wire2api_funcs.extend(
+        ir_file
+            .msgpack_types(false, true)
+            .iter()
+            .map(|ty| msgpack::generate_msgpack2api_func(ty, ir_file, naming_policy)),
+    );
+    if let Some(max_length) = intern_strings {
+        dart_wire2api_funcs.push(format!(
+            "final _stringInterner = StringInterner(maxLength: {});",
+            max_length
+        ));
+    }
 
     let needs_freezed = distinct_types.iter().any(|ty| match ty {
-        EnumRef(_) => true,
+        EnumRef(enu) => !enu.get(ir_file).enhanced && !dart_sdk_min.has_sealed_classes(),
         StructRef(st) if st.freezed => true,
         _ => false,
     });
@@ -147,6 +292,69 @@ fn get_dart_api_spec_from_ir_file(
     }
 }
 
+/// Path of the Rust module defining a struct or an enum.
+fn type_module_path<'a>(ty: &'a IrType, ir_file: &'a IrFile) -> Option<&'a [String]> {
+    let path = match ty {
+        StructRef(st) => st.get(ir_file).path.as_ref()?,
+        EnumRef(enu) => &enu.get(ir_file).path,
+        Delegate(IrTypeDelegate::Transparent(transparent)) => &transparent.path,
+        _ => return None,
+    };
+    path.split_last().map(|(_, module_path)| module_path)
+}
+
+/// Name of the dartdoc category of a Rust module, e.g. `imp::sub` for `crate::imp::sub`.
+fn dart_doc_category(module_path: &[String]) -> String {
+    match module_path {
+        [first, rest @ ..] if first == "crate" && !rest.is_empty() => rest.join("::"),
+        _ => module_path.join("::"),
+    }
+}
+
+/// The documentation of the library, listing the methods of the API class by the Rust module
+/// defining them, since dartdoc categories do not apply to methods.
+fn generate_library_header(
+    ir_file: &IrFile,
+    dart_api_class_name: &str,
+    dart_output_file_root: &str,
+    naming_policy: NamingPolicy,
+) -> DartBasicCode {
+    let mut modules: Vec<(String, Vec<String>)> = Vec::new();
+    for func in ir_file.funcs.iter().filter(|f| f.closure.is_none()) {
+        let category = dart_doc_category(&func.module_path);
+        let method = format!(
+            "/// * [{}.{}]",
+            dart_api_class_name,
+            naming_policy.member_name(&func.name)
+        );
+        match modules.iter_mut().find(|(it, _)| *it == category) {
+            Some((_, methods)) => methods.push(method),
+            None => modules.push((category, vec![method])),
+        }
+    }
+
+    DartBasicCode {
+        import: format!(
+            "/// The API of the Rust library, by the Rust module defining each method of [{}].
+            {}
+            library {};",
+            dart_api_class_name,
+            modules
+                .iter()
+                .map(|(category, methods)| format!(
+                    "///\n/// ## `{}`\n///\n{}",
+                    category,
+                    methods.join("\n")
+                ))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            dart_output_file_root,
+        ),
+        part: "".to_string(),
+        body: "".to_string(),
+    }
+}
+
 fn generate_freezed_header(dart_output_file_root: &str, needs_freezed: bool) -> DartBasicCode {
     if needs_freezed {
         DartBasicCode {
@@ -178,6 +386,39 @@ fn generate_import_header(imports: HashSet<&IrDartImport>) -> DartBasicCode {
     }
 }
 
+/// The imports of `--dart-import` for the types which `body` refers to, since the Dart output
+/// would not compile without them.
+fn generate_mapped_imports(dart_imports: &[(String, String)], body: &str) -> DartBasicCode {
+    let imports = dart_imports
+        .iter()
+        .filter(|(ty, _)| {
+            Regex::new(&format!(r"\b{}\b", regex::escape(ty)))
+                .unwrap()
+                .is_match(body)
+        })
+        .map(|(_, uri)| format!("import '{}';", uri))
+        .collect::<Vec<_>>();
+    DartBasicCode {
+        import: imports.join("\n"),
+        part: "".to_string(),
+        body: "".to_string(),
+    }
+}
+
+/// Imports `package:meta` if `body` uses it, e.g. for `@meta.useResult`, with the same prefix as
+/// in the usual `dart_metadata`, so that both imports are the same.
+fn generate_meta_import(body: &str) -> DartBasicCode {
+    DartBasicCode {
+        import: if body.contains("@meta.") {
+            "import 'package:meta/meta.dart' as meta;".to_string()
+        } else {
+            "".to_string()
+        },
+        part: "".to_string(),
+        body: "".to_string(),
+    }
+}
+
 fn generate_common_header() -> DartBasicCode {
     DartBasicCode {
         import: "import 'dart:convert';
@@ -197,18 +438,49 @@ fn get_dart_imports(ir_file: &IrFile) -> HashSet<&IrDartImport> {
 }
 
 fn generate_dart_declaration_body(
-    dart_api_class_name: &str,
     dart_funcs: &[GeneratedApiFunc],
     dart_structs: &[String],
+    ir_file: &IrFile,
+    config: &Opts,
 ) -> String {
-    format!(
-        "abstract class {} {{
-            {}
-        }}
+    let dart_api_class_name = &config.dart_api_class_name();
+    let trait_names = ir_file.trait_names();
+    let dart_traits = trait_names
+        .iter()
+        .map(|trait_name| {
+            format!(
+                "abstract class {} {{
+                    {}
+                }}",
+                trait_name,
+                ir_file
+                    .funcs
+                    .iter()
+                    .filter(|ir_func| matches!(&ir_func.trait_impl, Some(it) if &it.trait_name == trait_name))
+                    .filter_map(|ir_func| {
+                        dart_funcs
+                            .iter()
+                            .find(|func| func.ir_func_name.as_ref() == Some(&ir_func.name))
+                    })
+                    .map(|func| format!("{}{}", func.comments, func.signature))
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            )
+        })
+        .collect::<Vec<_>>();
+    let implements = if trait_names.is_empty() {
+        "".to_string()
+    } else {
+        format!(" implements {}", trait_names.join(", "))
+    };
 
-        {}
-        ",
-        dart_api_class_name,
+    let body = format!(
+        "{}
+
+        /// Turns the errors of Rust, otherwise thrown as `FfiException`, into the error types of
+        /// the app, so that call sites catch those instead. Errors are thrown as is when null.
+        abstract Object Function(FfiException error)? errorMapper;
+        {}{}{}{}",
         dart_funcs
             .iter()
             .map(|func| format!(
@@ -217,7 +489,80 @@ fn generate_dart_declaration_body(
             ))
             .collect::<Vec<_>>()
             .join("\n\n"),
+        if has_utility(config, config.bridge_health) {
+            "
+            /// Reports the load of the Rust executor, e.g. to detect when its workers are saturated.
+            /// The request does not go through the executor, so it completes even when all workers are busy.
+            Future<BridgeHealth> getBridgeHealth();"
+        } else {
+            ""
+        },
+        if has_utility(config, config.capture_stdio) {
+            "
+            /// The lines written by Rust to the standard output and error, e.g. with `println!`, from the
+            /// first listen on. They are still written to the original output as well.
+            Stream<String> captureRustOutput();"
+        } else {
+            ""
+        },
+        if has_utility(config, config.log_level_control) {
+            "
+            /// Sets the maximum level of the logs of the Rust `log` crate, e.g. to turn on verbose logs
+            /// for debugging. Takes effect immediately, for all threads.
+            void setLogLevel(LogLevel level);"
+        } else {
+            ""
+        },
+        if has_utility(config, config.crash_reports) {
+            "
+            /// The panics of the Rust functions called from Dart, from the first listen on, e.g. to
+            /// forward them to a crash reporting service. The calls still fail with a `FfiException`.
+            Stream<RustCrash> rustCrashes();"
+        } else {
+            ""
+        },
+    );
+    let api_class = format!(
+        "abstract class {}{} {{
+            {}
+        }}",
+        dart_api_class_name, implements, body,
+    );
+    let api_class = config
+        .templates
+        .dart_api_class(&DartApiClassData {
+            name: dart_api_class_name,
+            implements: &trait_names,
+            body: &body,
+            default: &api_class,
+        })
+        .unwrap_or(api_class);
+    // a custom handler does whatever it does
+    let api_class_comments = if ir_file.custom_handler.is_none() {
+        format!("/// {}\n", config.panic_policy.description())
+    } else {
+        "".to_owned()
+    };
+
+    let extras = if config.protected_regions {
+        protected_region("extras")
+    } else {
+        "".to_owned()
+    };
+
+    format!(
+        "{}{}
+
+        {}
+
+        {}
+
+        {}",
+        api_class_comments,
+        api_class,
+        dart_traits.join("\n\n"),
         dart_structs.join("\n\n"),
+        extras,
     )
 }
 
@@ -226,10 +571,55 @@ fn generate_dart_implementation_body(
     dart_api2wire_funcs: &[String],
     dart_api_fill_to_wire_funcs: &[String],
     dart_wire2api_funcs: &[String],
-    dart_api_impl_class_name: &str,
-    dart_wire_class_name: &str,
-    dart_api_class_name: &str,
+    config: &Opts,
 ) -> String {
+    let bridge_health_implementation = if has_utility(config, config.bridge_health) {
+        "Future<BridgeHealth> getBridgeHealth() => executeNormal(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.wire_get_bridge_health(port_),
+            parseSuccessData: BridgeHealth.fromWire,
+            constMeta: const FlutterRustBridgeTaskConstMeta(
+                debugName: 'get_bridge_health',
+                argNames: [],
+            ),
+            argValues: [],
+            hint: null,
+        ));"
+    } else {
+        ""
+    };
+    let capture_stdio_implementation = if has_utility(config, config.capture_stdio) {
+        "Stream<String> captureRustOutput() => executeStream(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.wire_capture_stdio(port_),
+            parseSuccessData: (d) => d as String,
+            constMeta: const FlutterRustBridgeTaskConstMeta(
+                debugName: 'capture_stdio',
+                argNames: [],
+            ),
+            argValues: [],
+            hint: null,
+        ));"
+    } else {
+        ""
+    };
+    let log_level_implementation = if has_utility(config, config.log_level_control) {
+        "void setLogLevel(LogLevel level) => inner.wire_set_log_level(level.index);"
+    } else {
+        ""
+    };
+    let crash_reports_implementation = if has_utility(config, config.crash_reports) {
+        "Stream<RustCrash> rustCrashes() => executeStream(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.wire_crash_reports(port_),
+            parseSuccessData: RustCrash.fromWire,
+            constMeta: const FlutterRustBridgeTaskConstMeta(
+                debugName: 'crash_reports',
+                argNames: [],
+            ),
+            argValues: [],
+            hint: null,
+        ));"
+    } else {
+        ""
+    };
     format!(
         "class {dart_api_impl_class_name} extends FlutterRustBridgeBase<{dart_wire_class_name}> implements {dart_api_class_name} {{
             factory {dart_api_impl_class_name}(ffi.DynamicLibrary dylib) => {dart_api_impl_class_name}.raw({dart_wire_class_name}(dylib));
@@ -238,6 +628,14 @@ fn generate_dart_implementation_body(
 
             {}
 
+            {}
+
+            {}
+
+            {}
+
+            {}
+
             // Section: api2wire
             {}
 
@@ -256,15 +654,46 @@ fn generate_dart_implementation_body(
             ))
             .collect::<Vec<_>>()
             .join("\n\n"),
+        bridge_health_implementation,
+        capture_stdio_implementation,
+        log_level_implementation,
+        crash_reports_implementation,
         dart_api2wire_funcs.join("\n\n"),
         dart_api_fill_to_wire_funcs.join("\n\n"),
         dart_wire2api_funcs.join("\n\n"),
-        dart_api_impl_class_name = dart_api_impl_class_name,
-        dart_wire_class_name = dart_wire_class_name,
-        dart_api_class_name = dart_api_class_name,
+        dart_api_impl_class_name = config.dart_api_impl_class_name(),
+        dart_wire_class_name = config.dart_wire_class_name(),
+        dart_api_class_name = config.dart_api_class_name(),
     )
 }
 
+/// Whether a utility turned on by `enabled`, e.g. `--capture-stdio`, is generated: like the other
+/// utilities, only in the first block.
+fn has_utility(config: &Opts, enabled: bool) -> bool {
+    enabled && config.block_index == BlockIndex::PRIMARY
+}
+
+/// A utility has a Dart method next to those of the API, and a wire function named after
+/// `rust_name`, which no function of the API may take.
+fn check_utility_name(
+    ir_file: &IrFile,
+    flag: &str,
+    rust_name: &str,
+    dart_name: &str,
+    naming_policy: NamingPolicy,
+) {
+    if let Some(clash) = ir_file
+        .funcs
+        .iter()
+        .find(|func| func.name == rust_name || naming_policy.member_name(&func.name) == dart_name)
+    {
+        panic!(
+            "`{}` clashes with `{}()`, generated by {}; rename the function",
+            clash.name, dart_name, flag
+        );
+    }
+}
+
 fn generate_dart_declaration_code(
     common_header: &DartBasicCode,
     freezed_header: DartBasicCode,
@@ -293,13 +722,156 @@ fn generate_dart_implementation_code(
         }
 }
 
-fn generate_file_prelude() -> DartBasicCode {
+/// Libraries only available with Flutter, which the Dart VM alone cannot load.
+const FLUTTER_LIBRARIES: &[&str] = &["package:flutter/", "package:flutter_test/", "dart:ui"];
+
+/// With `--pure-dart`, fails if `output` imports Flutter, e.g. because of `dart_metadata` or
+/// `--dart-import`, since it would not compile in a Dart command line tool or server.
+pub fn check_pure_dart(output: &Output) -> Result<(), Error> {
+    let import = Regex::new(r#"(?m)^\s*(?:import|export)\s+['"]([^'"]+)['"]"#).unwrap();
+    for code in [
+        &output.file_prelude,
+        &output.decl_prelude,
+        &output.decl_code,
+        &output.impl_code,
+    ] {
+        if let Some(uri) = import
+            .captures_iter(&code.import)
+            .map(|captures| captures[1].to_owned())
+            .find(|uri| FLUTTER_LIBRARIES.iter().any(|lib| uri.starts_with(lib)))
+        {
+            return Err(Error::string(format!(
+                "the Dart code imports `{}`, which needs Flutter, while --pure-dart is given",
+                uri
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The wire class over the bindings generated by the user running ffigen, see `--ffigen-config`.
+/// Its name and constructors are those of the class generated by ffigen otherwise.
+pub fn generate_ffigen_wire_code(
+    dart_wire_class_name: &str,
+    ffigen_output_import_path: &str,
+) -> DartBasicCode {
+    DartBasicCode {
+        import: format!(
+            "import 'dart:ffi' as ffi;
+            import '{}';",
+            ffigen_output_import_path
+        ),
+        part: "".to_string(),
+        body: format!(
+            "class {wire} extends {wire}Bindings implements FlutterRustBridgeWireBase {{
+                {wire}(ffi.DynamicLibrary dynamicLibrary) : super(dynamicLibrary);
+
+                {wire}.fromLookup(
+                    ffi.Pointer<T> Function<T extends ffi.NativeType>(String symbolName) lookup)
+                    : super.fromLookup(lookup);
+            }}
+            ",
+            wire = dart_wire_class_name,
+        ),
+    }
+}
+
+/// The class of `--dart-group-output`, creating the API classes of all the blocks, given by their
+/// class name and import path, from the library they are linked into. Its `init` only creates them
+/// once, since the setup of the library, e.g. where Rust posts its messages, must not be redone.
+pub fn generate_group_code(group_class_name: &str, members: &[(String, String)]) -> DartBasicCode {
+    let field_name = |class_name: &str| class_name.to_case(Case::Camel);
+    DartBasicCode {
+        import: std::iter::once("import 'dart:ffi' as ffi;".to_owned())
+            .chain(
+                members
+                    .iter()
+                    .map(|(_, import_path)| format!("import '{}';", import_path)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n"),
+        part: "".to_string(),
+        body: format!(
+            "/// The API classes of all the Rust inputs, which are linked into the same library.
+            class {group} {{
+                {group}._(ffi.DynamicLibrary dylib)
+                    : {initializers};
+
+                static {group}? _instance;
+
+                /// Creates the API classes from [dylib] on the first call, and returns the same ones
+                /// afterwards, e.g. when each package of the app initializes the library it uses.
+                static {group} init(ffi.DynamicLibrary dylib) => _instance ??= {group}._(dylib);
+
+                /// The API classes created by [init].
+                static {group} get instance =>
+                    _instance ?? (throw StateError('{group}.init() has not been called'));
+
+                {fields}
+            }}
+            ",
+            group = group_class_name,
+            initializers = members
+                .iter()
+                .map(|(class_name, _)| {
+                    format!("{} = {}Impl(dylib)", field_name(class_name), class_name)
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            fields = members
+                .iter()
+                .map(|(class_name, _)| {
+                    format!("final {} {};", class_name, field_name(class_name))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    }
+}
+
+/// The lints ignored by the generated Dart files, and whether only those with the implementation
+/// break them, i.e. not those with the declarations alone.
+const DART_IGNORED_LINTS: &[(&str, bool)] = &[
+    // names of the wire functions, and those coming from Rust
+    ("non_constant_identifier_names", false),
+    ("unused_element", true),
+    // the ignores of the ffigen output
+    ("duplicate_ignore", true),
+    // imports and exports are gathered from several parts, in any order and possibly twice
+    ("directives_ordering", false),
+    ("curly_braces_in_flow_control_structures", true),
+    ("unnecessary_lambdas", true),
+    // the doc comments of the ffigen output
+    ("slash_for_doc_comments", true),
+    ("prefer_const_literals_to_create_immutables", true),
+    ("implicit_dynamic_list_literal", true),
+    ("duplicate_import", false),
+    ("unused_import", false),
+    ("prefer_single_quotes", false),
+    ("prefer_const_constructors", true),
+    ("use_super_parameters", true),
+    // the generated files import each other by their relative paths
+    ("always_use_package_imports", false),
+    // `package:meta` comes with flutter_rust_bridge, for `@meta.useResult`
+    ("depend_on_referenced_packages", false),
+];
+
+/// The header of the generated Dart files, ignoring the lints broken by the implementation too
+/// unless `decl_only`.
+fn generate_file_prelude(decl_only: bool) -> DartBasicCode {
+    let lints = DART_IGNORED_LINTS
+        .iter()
+        .filter(|(_, impl_only)| !(decl_only && *impl_only))
+        .map(|(lint, _)| *lint)
+        .collect::<Vec<_>>();
     DartBasicCode {
-        import: format!("{}
+        import: format!(
+            "{}
 
-                // ignore_for_file: non_constant_identifier_names, unused_element, duplicate_ignore, directives_ordering, curly_braces_in_flow_control_structures, unnecessary_lambdas, slash_for_doc_comments, prefer_const_literals_to_create_immutables, implicit_dynamic_list_literal, duplicate_import, unused_import, prefer_single_quotes, prefer_const_constructors, use_super_parameters, always_use_package_imports
-                ",
-                        CODE_HEADER
+            // ignore_for_file: {}
+            ",
+            CODE_HEADER,
+            lints.join(", ")
         ),
         part: "".to_string(),
         body: "".to_string(),
@@ -308,6 +880,8 @@ fn generate_file_prelude() -> DartBasicCode {
 
 #[derive(Debug)]
 struct GeneratedApiFunc {
+    /// The [IrFunc] it calls, or [None] for the overloads and former names calling several.
+    ir_func_name: Option<String>,
     signature: String,
     implementation: String,
     comments: String,
@@ -321,7 +895,282 @@ struct GeneratedApiMethod {
     implementation: String,
 }
 
-fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
+/// One Dart method per `#[frb(dart_name = "..")]`, calling whichever of the functions sharing
+/// that name matches the given arguments.
+fn generate_api_overloads(ir_file: &IrFile, naming_policy: NamingPolicy) -> Vec<GeneratedApiFunc> {
+    let mut groups: Vec<(&str, Vec<&IrFunc>)> = vec![];
+    for func in &ir_file.funcs {
+        if let Some(dart_name) = &func.dart_name {
+            match groups
+                .iter_mut()
+                .find(|(name, _)| *name == dart_name.as_str())
+            {
+                Some((_, funcs)) => funcs.push(func),
+                None => groups.push((dart_name, vec![func])),
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(dart_name, funcs)| generate_api_overload(dart_name, &funcs, ir_file, naming_policy))
+        .collect()
+}
+
+fn generate_api_overload(
+    dart_name: &str,
+    funcs: &[&IrFunc],
+    ir_file: &IrFile,
+    naming_policy: NamingPolicy,
+) -> GeneratedApiFunc {
+    let method_name = naming_policy.member_name(dart_name);
+    let func_names = funcs
+        .iter()
+        .map(|func| format!("`{}`", func.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if funcs.len() < 2 {
+        panic!(
+            "#[frb(dart_name = \"{}\")] is only used by {}, while it merges several functions into one Dart method",
+            dart_name, func_names
+        );
+    }
+    if let Some(func) = ir_file
+        .funcs
+        .iter()
+        .find(|func| naming_policy.member_name(&func.name) == method_name)
+    {
+        panic!(
+            "#[frb(dart_name = \"{}\")] clashes with the Dart method of `{}`",
+            dart_name, func.name
+        );
+    }
+
+    let return_type = funcs[0].dart_return_type();
+    if funcs
+        .iter()
+        .any(|func| func.dart_return_type() != return_type)
+    {
+        panic!(
+            "{}, merged into the Dart method `{}`, must have the same return type in Dart",
+            func_names, method_name
+        );
+    }
+
+    // The parameters of every overload, each optional since only some overloads take it
+    let mut params: Vec<(String, String)> = vec![];
+    for input in funcs.iter().flat_map(|func| &func.inputs) {
+        let (name, ty) = (
+            input.name.dart_style(naming_policy),
+            input.ty.dart_api_type(),
+        );
+        match params.iter().find(|(other_name, _)| *other_name == name) {
+            Some((_, other_ty)) if *other_ty != ty => panic!(
+                "Parameter `{}` is `{}` in one of {} and `{}` in another, while they are merged into the Dart method `{}`",
+                input.name.raw, other_ty, func_names, ty, method_name
+            ),
+            Some(_) => {}
+            None => params.push((name, ty)),
+        }
+    }
+
+    let param_names = |func: &IrFunc| {
+        let mut names = func
+            .inputs
+            .iter()
+            .map(|input| input.name.dart_style(naming_policy))
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    for (i, func) in funcs.iter().enumerate() {
+        if let Some(other) = funcs[..i]
+            .iter()
+            .find(|other| param_names(other) == param_names(func))
+        {
+            panic!(
+                "`{}` and `{}` take the same parameters, so the Dart method `{}` cannot tell which one to call",
+                other.name, func.name, method_name
+            );
+        }
+    }
+
+    let branches = funcs
+        .iter()
+        .map(|func| {
+            let own_names = param_names(func);
+            let condition = func
+                .inputs
+                .iter()
+                .filter(|input| !matches!(input.ty, Optional(_)))
+                .map(|input| format!("{} != null", input.name.dart_style(naming_policy)))
+                .chain(
+                    params
+                        .iter()
+                        .filter(|(name, _)| !own_names.contains(name))
+                        .map(|(name, _)| format!("{} == null", name)),
+                )
+                .collect::<Vec<_>>();
+            let condition = if condition.is_empty() {
+                "true".to_string()
+            } else {
+                condition.join(" && ")
+            };
+            let call = if func.is_dart_getter() {
+                naming_policy.member_name(&func.name)
+            } else {
+                format!(
+                    "{}({})",
+                    naming_policy.member_name(&func.name),
+                    func.inputs
+                        .iter()
+                        .map(|input| format!("{0}: {0}", input.name.dart_style(naming_policy)))
+                        .chain(std::iter::once("hint: hint".to_string()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
+            format!("if ({}) return {};", condition, call)
+        })
+        .collect::<Vec<_>>();
+
+    let param_list = params
+        .iter()
+        .map(|(name, ty)| {
+            if ty.ends_with('?') {
+                format!("{} {}", ty, name)
+            } else {
+                format!("{}? {}", ty, name)
+            }
+        })
+        .chain(std::iter::once("dynamic hint".to_string()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let partial = format!("{} {}({{ {} }})", return_type, method_name, param_list);
+
+    let targets = funcs
+        .iter()
+        .map(|func| format!("[{}]", naming_policy.member_name(&func.name)))
+        .collect::<Vec<_>>();
+    let targets = format!(
+        "{} or {}",
+        targets[..targets.len() - 1].join(", "),
+        targets[targets.len() - 1]
+    );
+
+    GeneratedApiFunc {
+        ir_func_name: None,
+        signature: format!("{};", partial),
+        implementation: format!(
+            "{} {{
+                {}
+                throw ArgumentError('No overload of {} matches the given arguments');
+            }}",
+            partial,
+            branches.join("\n"),
+            method_name
+        ),
+        comments: format!("/// Calls {}, depending on the given arguments.\n", targets),
+        companion_field_signature: "".to_string(),
+        companion_field_implementation: "".to_string(),
+    }
+}
+
+/// One deprecated Dart method per `#[frb(renamed_from = "..")]`, calling the method of the
+/// function under its new name, so that callers have time to migrate.
+fn generate_api_renames(ir_file: &IrFile, naming_policy: NamingPolicy) -> Vec<GeneratedApiFunc> {
+    ir_file
+        .funcs
+        .iter()
+        .flat_map(|func| {
+            func.renamed_from
+                .iter()
+                .map(move |old_name| generate_api_rename(func, old_name, ir_file, naming_policy))
+        })
+        .collect()
+}
+
+fn generate_api_rename(
+    func: &IrFunc,
+    old_name: &str,
+    ir_file: &IrFile,
+    naming_policy: NamingPolicy,
+) -> GeneratedApiFunc {
+    let f = FunctionName::deserialize(&func.name);
+    if f.is_non_static_method() || f.is_static_method() {
+        panic!(
+            "#[frb(renamed_from = \"{}\")] on `{}` is only supported on functions, not methods",
+            old_name, func.name
+        );
+    }
+    let old_method_name = naming_policy.member_name(old_name);
+    if let Some(clash) = ir_file.funcs.iter().find(|other| {
+        naming_policy.member_name(&other.name) == old_method_name
+            || other
+                .dart_name
+                .as_deref()
+                .map(|name| naming_policy.member_name(name))
+                == Some(old_method_name.clone())
+    }) {
+        panic!(
+            "#[frb(renamed_from = \"{}\")] on `{}` clashes with the Dart method of `{}`",
+            old_name, func.name, clash.name
+        );
+    }
+
+    let method_name = naming_policy.member_name(&func.name);
+    let deprecated = format!("@Deprecated('Use {} instead')", method_name);
+    let (partial, call) = if func.is_dart_getter() {
+        (
+            format!("{} get {}", func.dart_return_type(), old_method_name),
+            method_name.clone(),
+        )
+    } else {
+        let params = func
+            .inputs
+            .iter()
+            .map(|input| {
+                format!(
+                    "{}{} {}",
+                    input.ty.dart_required_modifier(),
+                    input.ty.dart_api_type(),
+                    input.name.dart_style(naming_policy)
+                )
+            })
+            .chain(std::iter::once("dynamic hint".to_owned()))
+            .collect::<Vec<_>>();
+        let args = func
+            .inputs
+            .iter()
+            .map(|input| format!("{0}: {0}", input.name.dart_style(naming_policy)))
+            .chain(std::iter::once("hint: hint".to_owned()))
+            .collect::<Vec<_>>();
+        (
+            format!(
+                "{} {}({{ {} }})",
+                func.dart_return_type(),
+                old_method_name,
+                params.join(", ")
+            ),
+            format!("{}({})", method_name, args.join(", ")),
+        )
+    };
+
+    GeneratedApiFunc {
+        ir_func_name: None,
+        signature: format!("{}\n{};", deprecated, partial),
+        implementation: format!("{} => {};", partial, call),
+        comments: format!("/// Former name of [{}].\n", method_name),
+        companion_field_signature: "".to_string(),
+        companion_field_implementation: "".to_string(),
+    }
+}
+
+fn generate_api_func(
+    func: &IrFunc,
+    ir_file: &IrFile,
+    naming_policy: NamingPolicy,
+) -> GeneratedApiFunc {
     let raw_func_param_list = func
         .inputs
         .iter()
@@ -330,7 +1179,7 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
                 "{}{} {}",
                 input.ty.dart_required_modifier(),
                 input.ty.dart_api_type(),
-                input.name.dart_style()
+                input.name.dart_style(naming_policy)
             )
         })
         .collect::<Vec<_>>();
@@ -347,12 +1196,12 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
             .map(|input| {
                 // edge case: ffigen performs its own bool-to-int conversions
                 if let Primitive(IrTypePrimitive::Bool) = input.ty {
-                    input.name.dart_style()
+                    input.name.dart_style(naming_policy)
                 } else {
                     format!(
                         "_api2wire_{}({})",
                         &input.ty.safe_ident(),
-                        &input.name.dart_style()
+                        &input.name.dart_style(naming_policy)
                     )
                 }
             })
@@ -360,37 +1209,110 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
     ]
     .concat();
 
-    let partial = format!(
-        "{} {}({{ {} }})",
-        func.mode.dart_return_type(&func.output.dart_api_type()),
-        func.name.to_case(Case::Camel),
-        full_func_param_list.join(","),
-    );
+    let partial = if func.is_dart_getter() || func.event {
+        format!(
+            "{} get {}",
+            func.dart_return_type(),
+            naming_policy.member_name(&func.name),
+        )
+    } else {
+        format!(
+            "{} {}({{ {} }})",
+            func.dart_return_type(),
+            naming_policy.member_name(&func.name),
+            full_func_param_list.join(","),
+        )
+    };
 
     let execute_func_name = match func.mode {
         IrFuncMode::Normal => "executeNormal",
         IrFuncMode::Sync => "executeSync",
+        IrFuncMode::Stream { .. } if func.stream_setup => "executeStreamAfterSetup",
         IrFuncMode::Stream { .. } => "executeStream",
     };
 
     let const_meta_field_name = format!("k{}ConstMeta", func.name.to_case(Case::Pascal));
 
-    let signature = format!("{};", partial);
+    // Stream functions not set up first also get a variant returning a handle to control the Rust
+    // producer, and cancelable ones a variant returning a handle to cancel the Rust task
+    let handle = match func.mode {
+        IrFuncMode::Stream { .. } if !func.event && !func.stream_setup => Some((
+            "RustStreamHandle",
+            "executeStreamWithHandle",
+            "pause, resume or throttle the Rust producer",
+        )),
+        IrFuncMode::Normal if func.cancelable => Some((
+            "RustTaskHandle",
+            "executeNormalWithHandle",
+            "cancel the Rust task",
+        )),
+        _ => None,
+    };
+    let handle_partial = handle.map(|(handle_type, _, _)| {
+        format!(
+            "{}<{}> {}WithHandle({{ {} }})",
+            handle_type,
+            func.output.dart_api_type(),
+            naming_policy.member_name(&func.name),
+            full_func_param_list.join(","),
+        )
+    });
+
+    // Rust is only called once the stream is listened to, and the handle is the point of the
+    // variant returning it, so discarding either is a mistake
+    let use_result = if matches!(func.mode, IrFuncMode::Stream { .. }) {
+        "@meta.useResult\n"
+    } else {
+        ""
+    };
+    let signature = match (&handle_partial, handle) {
+        (Some(handle_partial), Some((_, _, handle_purpose))) => format!(
+            "{}{};
+
+            /// Same as [{}], but also returns a handle to {}.
+            @meta.useResult
+            {};",
+            use_result,
+            partial,
+            naming_policy.member_name(&func.name),
+            handle_purpose,
+            handle_partial
+        ),
+        _ => format!("{}{};", use_result, partial),
+    };
 
-    let comments = dart_comments(&func.comments);
+    let mut comments = dart_comments(&func.comments);
+    if func.debug_only {
+        if !comments.is_empty() {
+            comments += "///\n";
+        }
+        comments +=
+            "/// Only available in debug builds, and throws an [UnsupportedError] otherwise.\n";
+    }
+    if let Some(lock) = func.receiver_lock {
+        if !comments.is_empty() {
+            comments += "///\n";
+        }
+        comments += lock.dart_comment();
+    }
 
     let task_common_args = format!(
         "
         constMeta: {},
         argValues: [{}],
-        hint: hint,
+        hint: {},
         ",
         const_meta_field_name,
         func.inputs
             .iter()
-            .map(|input| input.name.dart_style())
+            .map(|input| input.name.dart_style(naming_policy))
             .collect::<Vec<_>>()
             .join(", "),
+        if func.is_dart_getter() || func.event {
+            "null"
+        } else {
+            "hint"
+        },
     );
 
     let input_0 = func.inputs.get(0).as_ref().map(|x| &x.ty);
@@ -418,24 +1340,164 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
         //If output is a struct with methods
         || (func_output_struct_name.is_some()
             && MethodNamingUtil::has_methods(func_output_struct_name.unwrap(), ir_file))
+        // Closures, iterators and opaque handles call back into the bridge
+        || matches!(func.output, Delegate(IrTypeDelegate::Closure(_) | IrTypeDelegate::Iterator(_) | IrTypeDelegate::RustOpaque(_)))
     {
         format!("(d) => _wire2api_{}(this, d)", func.output.safe_ident())
     } else {
         format!("_wire2api_{}", func.output.safe_ident())
     };
 
+    // the arguments go as a single MessagePack array, and the output comes back the same way
+    let (wire_param_list, parse_sucess_data) = if func.msgpack {
+        let args = func
+            .inputs
+            .iter()
+            .map(|input| {
+                format!(
+                    "_api2msgpack_{}({})",
+                    input.ty.safe_ident(),
+                    input.name.dart_style(naming_policy)
+                )
+            })
+            .collect::<Vec<_>>();
+        (
+            vec![
+                "port_".to_owned(),
+                format!(
+                    "_api2wire_uint_8_list(msgpackEncode([{}]))",
+                    args.join(", ")
+                ),
+            ],
+            format!(
+                "(d) => _msgpack2api_{}(msgpackDecode(_wire2api_uint_8_list(d)))",
+                func.output.safe_ident()
+            ),
+        )
+    } else {
+        (wire_param_list, parse_sucess_data)
+    };
+
+    let parse_sucess_data = match (func.compress, &func.output) {
+        (Some(_), Delegate(IrTypeDelegate::String)) => {
+            "(d) => utf8.decode(decompressPayload(d))".to_owned()
+        }
+        (Some(_), _) => "decompressPayload".to_owned(),
+        (None, _) => parse_sucess_data,
+    };
+
+    // the items are decoded on access instead, from the list the Dart VM builds on receipt
+    let parse_sucess_data = match &func.output {
+        GeneralList(IrTypeGeneralList { inner }) if func.lazy_decode => format!(
+            "(d) => LazyDecodedList(d as List<dynamic>, {})",
+            if wire2api_needs_bridge(inner, ir_file) {
+                format!("(e) => _wire2api_{}(this, e)", inner.safe_ident())
+            } else {
+                format!("_wire2api_{}", inner.safe_ident())
+            }
+        ),
+        _ => parse_sucess_data,
+    };
+
     let implementation = match func.mode {
+        // a single Rust sink per event type, shared by all the Dart listeners
+        IrFuncMode::Stream { .. } if func.event => format!(
+            "late final {} {} = executeStream(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.{}({}),
+            parseSuccessData: {},
+            {}
+        )).asBroadcastStream();",
+            func.dart_return_type(),
+            naming_policy.member_name(&func.name),
+            func.wire_func_name(),
+            wire_param_list.join(", "),
+            parse_sucess_data,
+            task_common_args,
+        ),
+        IrFuncMode::Sync if func.is_primitive_fast_path() => format!(
+            "{} => executePrimitiveSync(inner.{}({}));",
+            partial,
+            func.wire_func_name(),
+            wire_param_list
+                .iter()
+                .map(String::as_str)
+                .chain(["primitiveSyncOutcome"])
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
         IrFuncMode::Sync => format!(
-            "{} => {}(FlutterRustBridgeSyncTask(
+            "{} => _wire2api_{}({}(FlutterRustBridgeSyncTask(
             callFfi: () => inner.{}({}),
             {}
-        ));",
+        )));",
             partial,
+            func.output.safe_ident(),
             execute_func_name,
             func.wire_func_name(),
             wire_param_list.join(", "),
             task_common_args,
         ),
+        IrFuncMode::Normal if func.retry.is_some() => {
+            let retry = func.retry.as_ref().unwrap();
+            format!(
+                "{} => executeNormalWithRetry(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.{}({}),
+            parseSuccessData: {},
+            {}
+        ), times: {}, backoff: const Duration(milliseconds: {}), retryOn: const [{}]);",
+                partial,
+                func.wire_func_name(),
+                wire_param_list.join(", "),
+                parse_sucess_data,
+                task_common_args,
+                retry.times,
+                retry.backoff_ms,
+                retry
+                    .on
+                    .iter()
+                    .map(|prefix| format!(
+                        "\"{}\"",
+                        prefix
+                            .replace('\\', "\\\\")
+                            .replace('"', "\\\"")
+                            .replace('$', "\\$")
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        }
+        IrFuncMode::Normal if func.debounce_ms.is_some() => format!(
+            "{} => executeNormalDebounced(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.{}({}),
+            parseSuccessData: {},
+            {}
+        ), key: '{}', delay: const Duration(milliseconds: {}));",
+            partial,
+            func.wire_func_name(),
+            wire_param_list.join(", "),
+            parse_sucess_data,
+            task_common_args,
+            func.name,
+            func.debounce_ms.unwrap(),
+        ),
+        IrFuncMode::Stream { .. } if func.distinct => format!(
+            "{} => {}(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.{}({}),
+            parseSuccessData: {},
+            {}
+        )){};",
+            partial,
+            execute_func_name,
+            func.wire_func_name(),
+            wire_param_list.join(", "),
+            parse_sucess_data,
+            task_common_args,
+            if func.stream_setup {
+                ".then((stream) => stream.distinct())"
+            } else {
+                ".distinct()"
+            },
+        ),
         _ => format!(
             "{} => {}(FlutterRustBridgeTask(
             callFfi: (port_) => inner.{}({}),
@@ -451,6 +1513,91 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
         ),
     };
 
+    let assertions =
+        func.inputs
+            .iter()
+            .flat_map(|input| {
+                input.validations.iter().map(move |v| {
+                    v.dart_assertion(&input.name.dart_style(naming_policy), &input.ty)
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    // the wire function is missing from release builds of the Rust library, which go along with
+    // release builds of the app
+    let assertions = if func.debug_only {
+        format!(
+            "if (const bool.fromEnvironment('dart.vm.product')) {{
+                throw UnsupportedError('{} is only available in debug builds');
+            }}
+            {}",
+            naming_policy.member_name(&func.name),
+            assertions
+        )
+    } else {
+        assertions
+    };
+    let implementation = with_dart_assertions(implementation, &partial, &assertions);
+    let implementation = match (&handle_partial, handle) {
+        (Some(handle_partial), Some((_, execute_func_name, _))) => {
+            let handle_implementation = format!(
+                "{} => {}(FlutterRustBridgeTask(
+            callFfi: (port_) => inner.{}({}),
+            parseSuccessData: {},
+            {}
+        ));",
+                handle_partial,
+                execute_func_name,
+                func.wire_func_name(),
+                wire_param_list.join(", "),
+                parse_sucess_data,
+                task_common_args,
+            );
+            format!(
+                "{}
+
+            {}",
+                implementation,
+                with_dart_assertions(handle_implementation, handle_partial, &assertions),
+            )
+        }
+        _ => implementation,
+    };
+
+    // Polling calls the function itself, so that it is retried or validated as usual
+    let (signature, implementation) = if func.poll {
+        let poll_partial = format!(
+            "Stream<{}> {}({{Duration interval = const Duration(seconds: 1)}})",
+            func.output.dart_api_type(),
+            naming_policy.member_name(&format!("watch_{}", func.name)),
+        );
+        let call = if func.is_dart_getter() {
+            naming_policy.member_name(&func.name)
+        } else {
+            format!("{}()", naming_policy.member_name(&func.name))
+        };
+        (
+            format!(
+                "{}
+
+            /// Calls [{}] every [interval], and emits its value whenever it changes.
+            @meta.useResult
+            {};",
+                signature,
+                naming_policy.member_name(&func.name),
+                poll_partial
+            ),
+            format!(
+                "{}
+
+            {} => executePoll(() => {}, interval);",
+                implementation, poll_partial, call
+            ),
+        )
+    } else {
+        (signature, implementation)
+    };
+
     let companion_field_signature = format!(
         "FlutterRustBridgeTaskConstMeta get {};",
         const_meta_field_name,
@@ -467,12 +1614,16 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
         func.name,
         func.inputs
             .iter()
-            .map(|input| format!("\"{}\"", input.name.dart_style()))
+            .map(|input| format!(
+                "\"{}\"",
+                input.name.dart_style(naming_policy).replace('$', "\\$")
+            ))
             .collect::<Vec<_>>()
             .join(", "),
     );
 
     GeneratedApiFunc {
+        ir_func_name: Some(func.name.clone()),
         signature,
         implementation,
         comments,
@@ -481,8 +1632,34 @@ fn generate_api_func(func: &IrFunc, ir_file: &IrFile) -> GeneratedApiFunc {
     }
 }
 
-fn generate_api2wire_func(ty: &IrType, ir_file: &IrFile, block_index: BlockIndex) -> String {
-    if let Some(body) = TypeDartGenerator::new(ty.clone(), ir_file, None).api2wire_body(block_index)
+/// Turns `partial => expr;` into a block body checking the assertions before returning `expr`.
+fn with_dart_assertions(implementation: String, partial: &str, assertions: &str) -> String {
+    if assertions.is_empty() {
+        return implementation;
+    }
+    let expr = implementation
+        .strip_prefix(&format!("{} =>", partial))
+        .expect("implementation should be an arrow function");
+    format!("{} {{\n{}\nreturn {}\n}}", partial, assertions, expr)
+}
+
+fn generate_api2wire_func(
+    ty: &IrType,
+    ir_file: &IrFile,
+    block_index: BlockIndex,
+    dart_sdk_min: DartSdkVersion,
+    naming_policy: NamingPolicy,
+) -> String {
+    if let Some(body) = TypeDartGenerator::new(
+        ty.clone(),
+        ir_file,
+        None,
+        dart_sdk_min,
+        naming_policy,
+        false,
+        false,
+    )
+    .api2wire_body(block_index)
     {
         format!(
             "{} _api2wire_{}({} raw) {{
@@ -499,8 +1676,23 @@ fn generate_api2wire_func(ty: &IrType, ir_file: &IrFile, block_index: BlockIndex
     }
 }
 
-fn generate_api_fill_to_wire_func(ty: &IrType, ir_file: &IrFile) -> String {
-    if let Some(body) = TypeDartGenerator::new(ty.clone(), ir_file, None).api_fill_to_wire_body() {
+fn generate_api_fill_to_wire_func(
+    ty: &IrType,
+    ir_file: &IrFile,
+    dart_sdk_min: DartSdkVersion,
+    naming_policy: NamingPolicy,
+) -> String {
+    if let Some(body) = TypeDartGenerator::new(
+        ty.clone(),
+        ir_file,
+        None,
+        dart_sdk_min,
+        naming_policy,
+        false,
+        false,
+    )
+    .api_fill_to_wire_body()
+    {
         let target_wire_type = match ty {
             Optional(inner) => &inner.inner,
             it => it,
@@ -520,14 +1712,44 @@ fn generate_api_fill_to_wire_func(ty: &IrType, ir_file: &IrFile) -> String {
     }
 }
 
-fn generate_wire2api_func(ty: &IrType, ir_file: &IrFile, dart_api_class_name: &str) -> String {
-    let extra_argument = if matches!(ty, StructRef(IrTypeStructRef { name, freezed: _ }) if MethodNamingUtil::has_methods(name, ir_file))
-    {
+/// Whether the decoding function of the type takes the API class, to build the values calling back
+/// into the bridge.
+fn wire2api_needs_bridge(ty: &IrType, ir_file: &IrFile) -> bool {
+    matches!(ty, StructRef(IrTypeStructRef { name, freezed: _ }) if MethodNamingUtil::has_methods(name, ir_file))
+        || matches!(
+            ty,
+            Delegate(
+                IrTypeDelegate::Closure(_)
+                    | IrTypeDelegate::Iterator(_)
+                    | IrTypeDelegate::RustOpaque(_)
+            )
+        )
+}
+
+fn generate_wire2api_func(
+    ty: &IrType,
+    ir_file: &IrFile,
+    dart_api_class_name: &str,
+    intern_strings: Option<usize>,
+    dart_sdk_min: DartSdkVersion,
+    naming_policy: NamingPolicy,
+) -> String {
+    let extra_argument = if wire2api_needs_bridge(ty, ir_file) {
         format!("{} bridge,", dart_api_class_name)
     } else {
         "".to_string()
     };
-    let body = TypeDartGenerator::new(ty.clone(), ir_file, None).wire2api_body();
+    let body = match ty {
+        // the other types decode their strings with these
+        Delegate(IrTypeDelegate::String) if intern_strings.is_some() => {
+            "return _stringInterner.intern(raw as String);".to_owned()
+        }
+        Delegate(IrTypeDelegate::StringList) if intern_strings.is_some() => {
+            "return (raw as List<dynamic>).map((e) => _stringInterner.intern(e as String)).toList();"
+                .to_owned()
+        }
+        _ => TypeDartGenerator::new(ty.clone(), ir_file, None, dart_sdk_min, naming_policy, false, false).wire2api_body(),
+    };
     format!(
         "{} _wire2api_{}({}dynamic raw) {{
             {}
@@ -0,0 +1,303 @@
+//! The conversions between Dart values and the MessagePack values of `--codec msgpack`, laid out
+//! the way `rmp-serde` serializes the derived serde implementations: structs as the array of
+//! their fields, tuple structs of one field as that field, unit variants as their name and the
+//! other variants as a map from their name to their field, or to the array of their fields.
+
+use crate::ir::IrType::*;
+use crate::ir::*;
+
+/// `_api2msgpack_xxx`, turning a value of the Dart API into what `msgpackEncode` writes.
+pub fn generate_api2msgpack_func(
+    ty: &IrType,
+    ir_file: &IrFile,
+    naming_policy: NamingPolicy,
+) -> String {
+    let body = match ty {
+        Primitive(_) | PrimitiveList(_) => "return raw;".to_owned(),
+        Optional(IrTypeOptional { inner }) => format!(
+            "return raw == null ? null : _api2msgpack_{}(raw);",
+            inner.safe_ident()
+        ),
+        Boxed(IrTypeBoxed { inner, .. }) => {
+            format!("return _api2msgpack_{}(raw);", inner.safe_ident())
+        }
+        GeneralList(IrTypeGeneralList { inner }) => format!(
+            "return raw.map(_api2msgpack_{}).toList();",
+            inner.safe_ident()
+        ),
+        StructRef(st) => {
+            let st = st.get(ir_file);
+            let fields = st
+                .fields
+                .iter()
+                .map(|field| {
+                    format!(
+                        "_api2msgpack_{}(raw.{})",
+                        field.ty.safe_ident(),
+                        field.name.dart_style(naming_policy)
+                    )
+                })
+                .collect::<Vec<_>>();
+            if is_newtype(st) {
+                format!("return {};", fields[0])
+            } else {
+                format!("return [{}];", fields.join(", "))
+            }
+        }
+        EnumRef(enu) => {
+            let enu = enu.get(ir_file);
+            let variants = enu
+                .variants()
+                .iter()
+                .map(|variant| {
+                    let value = match &variant.kind {
+                        IrVariantKind::Value => format!("'{}'", variant.name.raw),
+                        IrVariantKind::Struct(st) => {
+                            let fields = st
+                                .fields
+                                .iter()
+                                .map(|field| {
+                                    format!(
+                                        "_api2msgpack_{}(raw.{})",
+                                        field.ty.safe_ident(),
+                                        field.name.dart_style(naming_policy)
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            if is_newtype(st) {
+                                format!("{{'{}': {}}}", variant.name.raw, fields[0])
+                            } else {
+                                format!("{{'{}': [{}]}}", variant.name.raw, fields.join(", "))
+                            }
+                        }
+                    };
+                    format!("if (raw is {}) return {};", variant.name, value)
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "{}
+                throw Exception(\"unreachable\");",
+                variants.join("\n")
+            )
+        }
+        Delegate(IrTypeDelegate::String | IrTypeDelegate::StringList) => "return raw;".to_owned(),
+        Delegate(IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir)) => {
+            let enu = ir.get(ir_file);
+            let cases = enu
+                .variants()
+                .iter()
+                .map(|variant| {
+                    format!(
+                        "case {}.{}: return '{}';",
+                        enu.name,
+                        variant.name.dart_enum_value_style(naming_policy),
+                        variant.name.raw
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "switch (raw) {{
+                    {}
+                    default: throw Exception(\"unreachable\");
+                }}",
+                cases.join("\n")
+            )
+        }
+        Delegate(IrTypeDelegate::BTreeMap { key, value }) => format!(
+            "return raw.map((k, v) => MapEntry(_api2msgpack_{}(k), _api2msgpack_{}(v)));",
+            key.safe_ident(),
+            value.safe_ident()
+        ),
+        Delegate(IrTypeDelegate::Transparent(transparent)) => format!(
+            "return _api2msgpack_{}(raw);",
+            transparent.inner.safe_ident()
+        ),
+        Delegate(_) => unreachable!("{:?} is not carried in MessagePack", ty),
+    };
+    format!(
+        "dynamic _api2msgpack_{}({} raw) {{
+            {}
+        }}
+        ",
+        ty.safe_ident(),
+        ty.dart_api_type(),
+        body,
+    )
+}
+
+/// `_msgpack2api_xxx`, turning what `msgpackDecode` reads into a value of the Dart API.
+pub fn generate_msgpack2api_func(
+    ty: &IrType,
+    ir_file: &IrFile,
+    naming_policy: NamingPolicy,
+) -> String {
+    let body = match ty {
+        Primitive(IrTypePrimitive::Unit) => "return;".to_owned(),
+        // `rmp-serde` writes whole floats as floats, but be lenient
+        Primitive(IrTypePrimitive::F32 | IrTypePrimitive::F64) => {
+            "return (raw as num).toDouble();".to_owned()
+        }
+        Primitive(_) => format!("return raw as {};", ty.dart_api_type()),
+        PrimitiveList(list) => format!(
+            "return {}.fromList((raw as List<dynamic>).cast<{}>());",
+            ty.dart_api_type(),
+            Primitive(list.primitive.clone()).dart_api_type()
+        ),
+        Optional(IrTypeOptional { inner }) => format!(
+            "return raw == null ? null : _msgpack2api_{}(raw);",
+            inner.safe_ident()
+        ),
+        Boxed(IrTypeBoxed { inner, .. }) => {
+            format!("return _msgpack2api_{}(raw);", inner.safe_ident())
+        }
+        GeneralList(IrTypeGeneralList { inner }) => format!(
+            "return (raw as List<dynamic>).map(_msgpack2api_{}).toList();",
+            inner.safe_ident()
+        ),
+        StructRef(st) => {
+            let st = st.get(ir_file);
+            if is_newtype(st) {
+                let field = &st.fields[0];
+                format!(
+                    "return {}({}: _msgpack2api_{}(raw));",
+                    st.name,
+                    field.name.dart_style(naming_policy),
+                    field.ty.safe_ident()
+                )
+            } else {
+                let fields = st
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        format!(
+                            "{}: _msgpack2api_{}(arr[{}]),",
+                            field.name.dart_style(naming_policy),
+                            field.ty.safe_ident(),
+                            idx
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "final arr = raw as List<dynamic>;
+                    if (arr.length != {0}) throw Exception('unexpected arr length: expect {0} but see ${{arr.length}}');
+                    return {1}({2});",
+                    st.fields.len(),
+                    st.name,
+                    fields.join("\n"),
+                )
+            }
+        }
+        EnumRef(enu) => {
+            let enu = enu.get(ir_file);
+            let mut unit_cases = vec![];
+            let mut other_cases = vec![];
+            for variant in enu.variants() {
+                match &variant.kind {
+                    IrVariantKind::Value => unit_cases.push(format!(
+                        "case '{}': return {}();",
+                        variant.name.raw, variant.name
+                    )),
+                    IrVariantKind::Struct(st) => {
+                        let args = st
+                            .fields
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, field)| {
+                                let value = if is_newtype(st) {
+                                    format!("_msgpack2api_{}(entry.value),", field.ty.safe_ident())
+                                } else {
+                                    format!(
+                                        "_msgpack2api_{}((entry.value as List<dynamic>)[{}]),",
+                                        field.ty.safe_ident(),
+                                        idx
+                                    )
+                                };
+                                if st.is_fields_named {
+                                    format!("{}: {}", field.name.dart_style(naming_policy), value)
+                                } else {
+                                    value
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        other_cases.push(format!(
+                            "case '{}': return {}({});",
+                            variant.name.raw,
+                            variant.name,
+                            args.join("")
+                        ));
+                    }
+                }
+            }
+            format!(
+                "if (raw is String) {{
+                    switch (raw) {{
+                        {}
+                    }}
+                }} else {{
+                    final entry = (raw as Map<dynamic, dynamic>).entries.single;
+                    switch (entry.key as String) {{
+                        {}
+                    }}
+                }}
+                throw ArgumentError.value(raw, 'raw', 'Invalid variant for {}');",
+                unit_cases.join("\n"),
+                other_cases.join("\n"),
+                enu.name
+            )
+        }
+        Delegate(IrTypeDelegate::String) => "return raw as String;".to_owned(),
+        Delegate(IrTypeDelegate::StringList) => {
+            "return (raw as List<dynamic>).cast<String>();".to_owned()
+        }
+        Delegate(IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir)) => {
+            let enu = ir.get(ir_file);
+            let cases = enu
+                .variants()
+                .iter()
+                .map(|variant| {
+                    format!(
+                        "case '{}': return {}.{};",
+                        variant.name.raw,
+                        enu.name,
+                        variant.name.dart_enum_value_style(naming_policy)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "switch (raw as String) {{
+                    {}
+                    default: throw ArgumentError.value(raw, 'raw', 'Invalid variant for {}');
+                }}",
+                cases.join("\n"),
+                enu.name
+            )
+        }
+        // `msgpackDecode` keeps the order of the entries
+        Delegate(IrTypeDelegate::BTreeMap { key, value }) => format!(
+            "return Map.fromEntries((raw as Map<dynamic, dynamic>).entries.map((e) =>
+                MapEntry(_msgpack2api_{}(e.key), _msgpack2api_{}(e.value))));",
+            key.safe_ident(),
+            value.safe_ident()
+        ),
+        Delegate(IrTypeDelegate::Transparent(transparent)) => format!(
+            "return _msgpack2api_{}(raw);",
+            transparent.inner.safe_ident()
+        ),
+        Delegate(_) => unreachable!("{:?} is not carried in MessagePack", ty),
+    };
+    format!(
+        "{} _msgpack2api_{}(dynamic raw) {{
+            {}
+        }}
+        ",
+        ty.dart_api_type(),
+        ty.safe_ident(),
+        body,
+    )
+}
+
+/// A tuple struct or variant of a single field, which serde carries as that field.
+fn is_newtype(st: &IrStruct) -> bool {
+    !st.is_fields_named && st.fields.len() == 1
+}
@@ -22,6 +22,10 @@ pub trait TypeDartGeneratorTrait {
 pub struct TypeGeneratorContext<'a> {
     pub ir_file: &'a IrFile,
     pub dart_api_class_name: Option<String>,
+    pub dart_sdk_min: DartSdkVersion,
+    pub naming_policy: NamingPolicy,
+    pub protected_regions: bool,
+    pub sample_factories: bool,
 }
 
 #[macro_export]
@@ -49,10 +53,22 @@ pub enum TypeDartGenerator<'a> {
 }
 
 impl<'a> TypeDartGenerator<'a> {
-    pub fn new(ty: IrType, ir_file: &'a IrFile, dart_api_class_name: Option<String>) -> Self {
+    pub fn new(
+        ty: IrType,
+        ir_file: &'a IrFile,
+        dart_api_class_name: Option<String>,
+        dart_sdk_min: DartSdkVersion,
+        naming_policy: NamingPolicy,
+        protected_regions: bool,
+        sample_factories: bool,
+    ) -> Self {
         let context = TypeGeneratorContext {
             ir_file,
             dart_api_class_name,
+            dart_sdk_min,
+            naming_policy,
+            protected_regions,
+            sample_factories,
         };
         match ty {
             Primitive(ir) => TypePrimitiveGenerator { ir, context }.into(),
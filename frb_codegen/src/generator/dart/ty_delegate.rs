@@ -1,5 +1,5 @@
-use crate::generator::dart::gen_wire2api_simple_type_cast;
 use crate::generator::dart::ty::*;
+use crate::generator::dart::{dart_comments, gen_wire2api_simple_type_cast, generate_api_method};
 use crate::ir::*;
 use crate::type_dart_generator_struct;
 use crate::utils::BlockIndex;
@@ -8,56 +8,443 @@ type_dart_generator_struct!(TypeDelegateGenerator, IrTypeDelegate);
 
 impl TypeDartGeneratorTrait for TypeDelegateGenerator<'_> {
     fn api2wire_body(&self, block_index: BlockIndex) -> Option<String> {
+        let naming_policy = self.context.naming_policy;
         Some(match self.ir {
             IrTypeDelegate::String => {
                 "return _api2wire_uint_8_list(utf8.encoder.convert(raw));".to_string()
             }
-            IrTypeDelegate::SyncReturnVecU8 => "/*unsupported*/".to_string(),
+            IrTypeDelegate::SyncReturnVecU8
+            | IrTypeDelegate::SyncReturn(_)
+            | IrTypeDelegate::Closure(_)
+            | IrTypeDelegate::Iterator(_) => "/*unsupported*/".to_string(),
             IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 format!(
                     "return _api2wire_{}(raw);",
                     self.ir.get_delegate().safe_ident()
                 )
             }
+            // one buffer for all the strings, instead of one allocation each
             IrTypeDelegate::StringList => format!(
-                "final ans = inner.new_StringList_{}(raw.length);
-                for (var i = 0; i < raw.length; i++){{
-                    ans.ref.ptr[i] = _api2wire_String(raw[i]);
+                "final encoded = raw.map(utf8.encoder.convert).toList();
+                final ends = inner.new_int_32_list_{block}(encoded.length);
+                final endsView = ends.ref.ptr.asTypedList(encoded.length);
+                var total = 0;
+                for (var i = 0; i < encoded.length; i++) {{
+                    total += encoded[i].length;
+                    endsView[i] = total;
                 }}
-                return ans;",
-                block_index
+                final bytes = inner.new_uint_8_list_{block}(total);
+                final bytesView = bytes.ref.ptr.asTypedList(total);
+                var start = 0;
+                for (final string in encoded) {{
+                    bytesView.setAll(start, string);
+                    start += string.length;
+                }}
+                return inner.new_StringList_{block}(bytes, ends);",
+                block = block_index
+            ),
+            IrTypeDelegate::Url => "return _api2wire_String(raw.toString());".to_owned(),
+            IrTypeDelegate::UrlList => {
+                "return _api2wire_StringList(raw.map((e) => e.toString()).toList());".to_owned()
+            }
+            // fails here rather than in Rust, with the value at hand
+            IrTypeDelegate::CString => "if (raw.contains('\\u0000')) {
+                    throw ArgumentError.value(raw, 'raw', 'A CString cannot contain a nul character');
+                }
+                return _api2wire_String(raw);"
+                .to_owned(),
+            IrTypeDelegate::CStringList => "for (final string in raw) {
+                    if (string.contains('\\u0000')) {
+                        throw ArgumentError.value(string, 'raw', 'A CString cannot contain a nul character');
+                    }
+                }
+                return _api2wire_StringList(raw);"
+                .to_owned(),
+            IrTypeDelegate::ZonedDateTime => format!(
+                "return _api2wire_{}(Int64List.fromList([raw.utc.microsecondsSinceEpoch, raw.offset.inSeconds]));",
+                self.ir.get_delegate().safe_ident()
             ),
             IrTypeDelegate::PrimitiveEnum { ref repr, .. } => {
                 format!("return _api2wire_{}(raw.index);", repr.safe_ident())
             }
+            IrTypeDelegate::StringEnum(ref ir) => {
+                let enu = ir.get(self.context.ir_file);
+                let names = enu
+                    .variants()
+                    .iter()
+                    .map(|variant| {
+                        format!(
+                            "{}.{}: '{}',",
+                            enu.name,
+                            variant.name.dart_enum_value_style(naming_policy),
+                            variant.name.raw
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "return _api2wire_String(const {{ {} }}[raw]!);",
+                    names.join("")
+                )
+            }
+            // Rust would use the value after it is dropped
+            IrTypeDelegate::RustOpaque(_) => {
+                "if (raw.isDisposed) throw StateError('${raw.runtimeType} has been disposed');
+                return _api2wire_i64(raw.handle);"
+                    .to_owned()
+            }
+            IrTypeDelegate::UnsafeOpaquePtr(_) => "return _api2wire_i64(raw);".to_owned(),
+            IrTypeDelegate::DartStream(ref inner) => {
+                // edge case: ffigen performs its own bool-to-int conversions
+                let item = if let IrType::Primitive(IrTypePrimitive::Bool) = inner.as_ref() {
+                    "item".to_owned()
+                } else {
+                    format!("_api2wire_{}(item)", inner.safe_ident())
+                };
+                format!(
+                    "final handle = inner.new_{ident}_{block}();
+                    // an error ends the stream on the Rust side as well
+                    raw.listen(
+                        (item) => inner.add_{ident}_{block}(handle, {item}),
+                        onError: (Object _) => inner.close_{ident}_{block}(handle),
+                        onDone: () => inner.close_{ident}_{block}(handle),
+                        cancelOnError: true,
+                    );
+                    return handle;",
+                    ident = self.ir.safe_ident(),
+                    block = block_index,
+                    item = item,
+                )
+            }
+            IrTypeDelegate::BTreeMap { ref key, ref value } => format!(
+                "return inner.new_{}_{}({}, {});",
+                self.ir.safe_ident(),
+                block_index,
+                api2wire_list(key, "raw.keys"),
+                api2wire_list(value, "raw.values"),
+            ),
+            IrTypeDelegate::Result { ref ok, ref err } => format!(
+                "return raw.when(
+                    ok: (value) => inner.new_{ident}_{block}(0, _api2wire_{ok}(value), ffi.nullptr),
+                    err: (error) => inner.new_{ident}_{block}(1, ffi.nullptr, _api2wire_{err}(error)),
+                );",
+                ident = self.ir.safe_ident(),
+                block = block_index,
+                ok = IrType::optional_of(ok.as_ref()).safe_ident(),
+                err = IrType::optional_of(err.as_ref()).safe_ident(),
+            ),
+            IrTypeDelegate::Transparent(ref transparent) => {
+                format!("return _api2wire_{}(raw);", transparent.inner.safe_ident())
+            }
         })
     }
 
     fn wire2api_body(&self) -> String {
+        let naming_policy = self.context.naming_policy;
         match &self.ir {
             IrTypeDelegate::String
             | IrTypeDelegate::SyncReturnVecU8
             | IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 gen_wire2api_simple_type_cast(&self.ir.dart_api_type())
             }
-            IrTypeDelegate::StringList => {
+            IrTypeDelegate::StringList | IrTypeDelegate::CStringList => {
                 "return (raw as List<dynamic>).cast<String>();".to_owned()
             }
+            IrTypeDelegate::CString => gen_wire2api_simple_type_cast("String"),
+            IrTypeDelegate::Url => "return Uri.parse(raw as String);".to_owned(),
+            IrTypeDelegate::UrlList => {
+                "return (raw as List<dynamic>).map((e) => Uri.parse(e as String)).toList();"
+                    .to_owned()
+            }
+            IrTypeDelegate::ZonedDateTime => "final parts = raw as List<int>;
+                return ZonedDateTime.fromMicrosecondsSinceEpoch(parts[0], Duration(seconds: parts[1]));"
+                .to_owned(),
+            IrTypeDelegate::SyncReturn(inner) => match inner.as_ref() {
+                IrType::Optional(IrTypeOptional { inner }) => format!(
+                    "if (raw == null) return null;
+                    {}",
+                    sync_return_wire2api_body(inner)
+                ),
+                inner => sync_return_wire2api_body(inner),
+            },
             IrTypeDelegate::PrimitiveEnum { ir, .. } => {
                 format!("return {}.values[raw];", ir.dart_api_type())
             }
+            IrTypeDelegate::StringEnum(ir) => {
+                let enu = ir.get(self.context.ir_file);
+                let cases = enu
+                    .variants()
+                    .iter()
+                    .map(|variant| {
+                        format!(
+                            "case '{}': return {}.{};",
+                            variant.name.raw,
+                            enu.name,
+                            variant.name.dart_enum_value_style(naming_policy)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "switch (raw as String) {{
+                        {}
+                        default: throw ArgumentError.value(raw, 'raw', 'Invalid variant for {}');
+                    }}",
+                    cases.join("\n"),
+                    enu.name
+                )
+            }
+            IrTypeDelegate::Closure(closure) => format!(
+                "return {}(bridge: bridge, handle: raw as int);",
+                closure.dart_class_name()
+            ),
+            IrTypeDelegate::RustOpaque(opaque) => format!(
+                "return {}(bridge: bridge, handle: raw as int);",
+                opaque.dart_class_name()
+            ),
+            IrTypeDelegate::Iterator(iterator) => format!(
+                "return {}(bridge: bridge, handle: raw as int);",
+                iterator.dart_class_name()
+            ),
+            IrTypeDelegate::UnsafeOpaquePtr(_) => gen_wire2api_simple_type_cast("int"),
+            IrTypeDelegate::DartStream(_) => "/*unsupported*/".to_owned(),
+            // `Map.fromEntries` keeps the key order of the entries
+            IrTypeDelegate::BTreeMap { key, value } => format!(
+                "return Map.fromEntries((raw as List<dynamic>).map((e) {{
+                    final entry = e as List<dynamic>;
+                    return MapEntry(_wire2api_{}(entry[0]), _wire2api_{}(entry[1]));
+                }}));",
+                key.safe_ident(),
+                value.safe_ident()
+            ),
+            IrTypeDelegate::Result { ok, err } => format!(
+                "final arr = raw as List<dynamic>;
+                return arr[0] == 0
+                    ? {api_type}.ok(_wire2api_{}(arr[1]))
+                    : {api_type}.err(_wire2api_{}(arr[1]));",
+                ok.safe_ident(),
+                err.safe_ident(),
+                api_type = self.ir.dart_api_type(),
+            ),
+            IrTypeDelegate::Transparent(transparent) => {
+                format!("return _wire2api_{}(raw);", transparent.inner.safe_ident())
+            }
         }
     }
 
     fn structs(&self) -> String {
-        if let IrTypeDelegate::PrimitiveEnum { ir, .. } = &self.ir {
-            super::TypeEnumRefGenerator {
-                ir: ir.clone(),
-                context: self.context.clone(),
-            }
-            .structs()
-        } else {
-            "".into()
+        let naming_policy = self.context.naming_policy;
+        match &self.ir {
+            IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) => {
+                super::TypeEnumRefGenerator {
+                    ir: ir.clone(),
+                    context: self.context.clone(),
+                }
+                .structs()
+            }
+            IrTypeDelegate::Closure(closure) => {
+                let class_name = closure.dart_class_name();
+                let params = closure
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| format!("{} arg{}", ty.dart_api_type(), i))
+                    .collect::<Vec<_>>();
+                let args = (0..closure.inputs.len())
+                    .map(|i| format!("arg{i}: arg{i}", i = i))
+                    .collect::<Vec<_>>();
+                format!(
+                    "/// A Rust closure returned by [{api_class}.{func}]. Call [dispose] once it is no longer needed.
+                    class {class_name} extends RustOpaqueHandle {{
+                        final {api_class} bridge;
+                        final int handle;
+
+                        {class_name}({{required this.bridge, required this.handle}});
+
+                        {} call({}) {{
+                            checkNotDisposed();
+                            return bridge.{}(handle: handle, {});
+                        }}
+
+                        /// Releases the closure on the Rust side. Calls that already started are not affected.
+                        @override
+                        void release() => bridge.{}(handle: handle);
+                    }}",
+                    IrFuncMode::Normal.dart_return_type(&closure.output.dart_api_type()),
+                    params.join(", "),
+                    naming_policy.member_name(&closure.call_func_name()),
+                    args.join(", "),
+                    naming_policy.member_name(&closure.drop_func_name()),
+                    api_class = self.context.dart_api_class_name.as_ref().unwrap(),
+                    func = naming_policy.member_name(&closure.func_name),
+                    class_name = class_name,
+                )
+            }
+            IrTypeDelegate::RustOpaque(opaque) => {
+                let debug_string = if opaque.debug {
+                    format!(
+                        "/// The `Debug` output of the Rust value.
+                        String debugString() {{
+                            checkNotDisposed();
+                            return bridge.{}(handle: handle);
+                        }}
+",
+                        naming_policy.member_name(&opaque.debug_func_name())
+                    )
+                } else {
+                    "".to_owned()
+                };
+                // the methods marked with a lock, called on the value behind the handle
+                let api_class = self.context.dart_api_class_name.as_ref().unwrap();
+                let methods = self
+                    .context
+                    .ir_file
+                    .funcs
+                    .iter()
+                    .filter(|func| {
+                        func.receiver_lock.is_some()
+                            && matches!(&func.inputs[0].ty, IrType::Delegate(IrTypeDelegate::RustOpaque(it)) if it.inner == opaque.inner)
+                    })
+                    .map(|func| {
+                        let method = generate_api_method(func, &opaque.dart_class_name(), api_class.clone(), naming_policy);
+                        let mut comments = dart_comments(&func.comments);
+                        if !comments.is_empty() {
+                            comments += "///\n";
+                        }
+                        comments += func.receiver_lock.unwrap().dart_comment();
+                        format!(
+                            "{}{} => {};\n\n",
+                            comments, method.signature, method.implementation
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .concat();
+                format!(
+                    "/// A handle to a Rust `{inner}`, which stays in Rust. Call [dispose] once it is no longer needed.
+                    ///
+                    /// Handles to the same value are equal, including the ones made by [clone].
+                    class {class_name} extends RustOpaqueHandle {{
+                        final {api_class} bridge;
+                        final int handle;
+
+                        {class_name}({{required this.bridge, required this.handle}});
+
+                        /// Another handle to the same Rust value, which has to be disposed of as well.
+                        /// The value is dropped once all the handles to it are.
+                        {class_name} clone() {{
+                            checkNotDisposed();
+                            bridge.{clone}(handle: handle);
+                            return {class_name}(bridge: bridge, handle: handle);
+                        }}
+
+                        {debug_string}
+                        {methods}@override
+                        bool operator ==(Object other) => other is {class_name} && other.handle == handle;
+
+                        @override
+                        int get hashCode => handle.hashCode;
+
+                        /// Releases this handle on the Rust side. Calls that already started are not affected.
+                        @override
+                        void release() => bridge.{drop}(handle: handle);
+                    }}",
+                    inner = opaque.inner,
+                    api_class = api_class,
+                    clone = naming_policy.member_name(&opaque.clone_func_name()),
+                    drop = naming_policy.member_name(&opaque.drop_func_name()),
+                    debug_string = debug_string,
+                    methods = methods,
+                    class_name = opaque.dart_class_name(),
+                )
+            }
+            IrTypeDelegate::Iterator(iterator) => format!(
+                "/// A Rust iterator returned by [{api_class}.{func}], read a page of items at a time, so that
+                /// large results are never held in memory all at once. Call [dispose] once it is no longer
+                /// needed, which [toStream] does by itself.
+                class {class_name} extends RustOpaqueHandle {{
+                    final {api_class} bridge;
+                    final int handle;
+
+                    {class_name}({{required this.bridge, required this.handle}});
+
+                    /// The next [count] items, fewer only once the iterator is exhausted.
+                    Future<List<{item}>> next(int count) {{
+                        checkNotDisposed();
+                        return bridge.{next}(handle: handle, count: count);
+                    }}
+
+                    /// The remaining items, fetched [pageSize] at a time while the stream is listened to
+                    /// and not paused. Disposes the iterator once the stream is done or cancelled.
+                    Stream<{item}> toStream({{int pageSize = 256}}) async* {{
+                        try {{
+                            while (true) {{
+                                final page = await next(pageSize);
+                                yield* Stream.fromIterable(page);
+                                if (page.length < pageSize) break;
+                            }}
+                        }} finally {{
+                            dispose();
+                        }}
+                    }}
+
+                    /// Releases the iterator on the Rust side. Pages being fetched are not affected.
+                    @override
+                    void release() => bridge.{drop}(handle: handle);
+                }}",
+                item = iterator.item.dart_api_type(),
+                next = naming_policy.member_name(&iterator.next_func_name()),
+                drop = naming_policy.member_name(&iterator.drop_func_name()),
+                api_class = self.context.dart_api_class_name.as_ref().unwrap(),
+                func = naming_policy.member_name(&iterator.func_name),
+                class_name = iterator.dart_class_name(),
+            ),
+            IrTypeDelegate::Transparent(transparent) => format!(
+                "{}typedef {} = {};",
+                dart_comments(&transparent.comments),
+                transparent.name,
+                transparent.inner.dart_api_type()
+            ),
+            _ => "".into(),
         }
     }
 }
+
+/// Converts an iterable of `ty` to the wire list of `ty`.
+fn api2wire_list(ty: &IrType, iterable: &str) -> String {
+    let list = IrType::list_of(ty);
+    let raw = match list {
+        IrType::PrimitiveList(_) => {
+            format!("{}.fromList({}.toList())", list.dart_api_type(), iterable)
+        }
+        _ => format!("{}.toList()", iterable),
+    };
+    format!("_api2wire_{}({})", list.safe_ident(), raw)
+}
+
+/// Decodes the bytes returned by `executeSync`, which are never null here.
+fn sync_return_wire2api_body(ty: &IrType) -> String {
+    match ty {
+        IrType::Primitive(IrTypePrimitive::Unit) => "return;".to_owned(),
+        IrType::Primitive(IrTypePrimitive::Bool) => "return raw[0] != 0;".to_owned(),
+        IrType::Primitive(primitive) => format!(
+            "return ByteData.sublistView(raw as Uint8List).{};",
+            byte_data_getter(primitive)
+        ),
+        _ => "return utf8.decode(raw as Uint8List);".to_owned(),
+    }
+}
+
+/// Reads a primitive encoded by `IntoSyncReturnBytes` on the Rust side, which is little-endian.
+fn byte_data_getter(primitive: &IrTypePrimitive) -> &'static str {
+    match primitive {
+        IrTypePrimitive::U8 => "getUint8(0)",
+        IrTypePrimitive::I8 => "getInt8(0)",
+        IrTypePrimitive::U16 => "getUint16(0, Endian.little)",
+        IrTypePrimitive::I16 => "getInt16(0, Endian.little)",
+        IrTypePrimitive::U32 => "getUint32(0, Endian.little)",
+        IrTypePrimitive::I32 => "getInt32(0, Endian.little)",
+        IrTypePrimitive::U64 | IrTypePrimitive::Usize => "getUint64(0, Endian.little)",
+        IrTypePrimitive::I64 => "getInt64(0, Endian.little)",
+        IrTypePrimitive::F32 => "getFloat32(0, Endian.little)",
+        IrTypePrimitive::F64 => "getFloat64(0, Endian.little)",
+        IrTypePrimitive::Bool | IrTypePrimitive::Unit => unreachable!(),
+    }
+}
@@ -12,10 +12,16 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
     }
 
     fn api_fill_to_wire_body(&self) -> Option<String> {
+        let naming_policy = self.context.naming_policy;
+        let enu = self.ir.get(self.context.ir_file);
+        if enu.enhanced {
+            return Some(self.enhanced_api_fill_to_wire_body(enu));
+        }
+        if self.context.dart_sdk_min.has_sealed_classes() {
+            return Some(self.sealed_api_fill_to_wire_body(enu));
+        }
         Some(
-            self.ir
-                .get(self.context.ir_file)
-                .variants()
+            enu.variants()
                 .iter()
                 .enumerate()
                 .map(|(idx, variant)| {
@@ -34,9 +40,9 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                                     format!(
                                         "{}.{} = _api2wire_{}(apiObj.{});",
                                         r,
-                                        field.name.rust_style(),
+                                        field.name.wire_style(),
                                         field.ty.safe_ident(),
-                                        field.name.dart_style()
+                                        field.name.dart_style(naming_policy)
                                     )
                                 })
                                 .collect(),
@@ -61,8 +67,18 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
     }
 
     fn wire2api_body(&self) -> String {
+        let naming_policy = self.context.naming_policy;
         let enu = self.ir.get(self.context.ir_file);
         assert!(enu.is_struct());
+        if enu.enhanced {
+            return format!(
+                "return {}(kind: {}.values[raw[0]], {}: _wire2api_{}(raw[1]),);",
+                enu.name,
+                enu.enhanced_kind_name(),
+                enu.enhanced_payload_name().dart_style(naming_policy),
+                enu.enhanced_payload().unwrap().ty.safe_ident(),
+            );
+        }
         let variants = enu
             .variants()
             .iter()
@@ -78,7 +94,7 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                             let val =
                                 format!("_wire2api_{}(raw[{}]),", field.ty.safe_ident(), idx + 1);
                             if st.is_fields_named {
-                                format!("{}: {}", field.name.dart_style(), val)
+                                format!("{}: {}", field.name.dart_style(naming_policy), val)
                             } else {
                                 val
                             }
@@ -86,9 +102,22 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                         .collect::<Vec<_>>()
                         .join(""),
                 };
-                format!("case {}: return {}({});", idx, variant.name, args)
+                if self.context.dart_sdk_min.has_sealed_classes() {
+                    format!("{} => {}({}),", idx, variant.name, args)
+                } else {
+                    format!("case {}: return {}({});", idx, variant.name, args)
+                }
             })
             .collect::<Vec<_>>();
+        if self.context.dart_sdk_min.has_sealed_classes() {
+            return format!(
+                "return switch (raw[0]) {{
+                    {}
+                    _ => throw Exception(\"unreachable\"),
+                }};",
+                variants.join("\n"),
+            );
+        }
         format!(
             "switch (raw[0]) {{
                 {}
@@ -99,10 +128,15 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
     }
 
     fn structs(&self) -> String {
+        let naming_policy = self.context.naming_policy;
         let src = self.ir.get(self.context.ir_file);
 
         let comments = dart_comments(&src.comments);
-        if src.is_struct() {
+        if src.enhanced {
+            self.enhanced_structs(src, &comments)
+        } else if src.is_struct() && self.context.dart_sdk_min.has_sealed_classes() {
+            self.sealed_structs(src, &comments)
+        } else if src.is_struct() {
             let variants = src
                 .variants()
                 .iter()
@@ -123,7 +157,7 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                                         "{}{} {},",
                                         dart_comments(&field.comments),
                                         field.ty.dart_api_type(),
-                                        field.name.dart_style()
+                                        field.name.dart_style(naming_policy)
                                     )
                                 })
                                 .collect::<Vec<_>>();
@@ -145,7 +179,7 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                                         dart_comments(&field.comments),
                                         field.ty.dart_required_modifier(),
                                         field.ty.dart_api_type(),
-                                        field.name.dart_style()
+                                        field.name.dart_style(naming_policy)
                                     )
                                 })
                                 .collect::<Vec<_>>();
@@ -156,7 +190,7 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                         "{}const factory {}.{}({}) = {};",
                         dart_comments(&variant.comments),
                         self.ir.name,
-                        variant.name.dart_style(),
+                        variant.name.dart_style(naming_policy),
                         args,
                         variant.name.rust_style(),
                     )
@@ -178,7 +212,7 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
                     format!(
                         "{}{},",
                         dart_comments(&variant.comments),
-                        variant.name.rust_style()
+                        variant.name.dart_enum_value_style(naming_policy)
                     )
                 })
                 .collect::<Vec<_>>()
@@ -192,3 +226,262 @@ impl TypeDartGeneratorTrait for TypeEnumRefGenerator<'_> {
         }
     }
 }
+
+impl TypeEnumRefGenerator<'_> {
+    /// An exhaustive switch, which fails to compile rather than to send a variant it misses.
+    fn sealed_api_fill_to_wire_body(&self, enu: &IrEnum) -> String {
+        let naming_policy = self.context.naming_policy;
+        let cases = enu
+            .variants()
+            .iter()
+            .enumerate()
+            .map(|(idx, variant)| match &variant.kind {
+                IrVariantKind::Value => {
+                    format!("case {}(): wireObj.tag = {};", variant.name, idx)
+                }
+                IrVariantKind::Struct(st) => {
+                    let r = format!("wireObj.kind.ref.{}.ref", variant.name);
+                    let body = st
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            format!(
+                                "{}.{} = _api2wire_{}(variant.{});",
+                                r,
+                                field.name.wire_style(),
+                                field.ty.safe_ident(),
+                                field.name.dart_style(naming_policy)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    format!(
+                        "case {0} variant:
+                            wireObj.tag = {1};
+                            wireObj.kind = inner.inflate_{2}_{0}();
+                            {3}",
+                        variant.name,
+                        idx,
+                        self.ir.name,
+                        body.join("\n")
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "switch (apiObj) {{
+                {}
+            }}",
+            cases.join("\n")
+        )
+    }
+
+    /// A sealed class with a final subclass per variant, along with the factory constructors of
+    /// the freezed unions generated for older SDKs, so that both are built the same way.
+    fn sealed_structs(&self, enu: &IrEnum, comments: &str) -> String {
+        let naming_policy = self.context.naming_policy;
+        let name = &self.ir.name;
+        let mut factories = vec![];
+        let mut classes = vec![];
+        for variant in enu.variants() {
+            let fields = match &variant.kind {
+                IrVariantKind::Value => &[][..],
+                IrVariantKind::Struct(st) => &st.fields[..],
+            };
+            let is_fields_named =
+                matches!(&variant.kind, IrVariantKind::Struct(st) if st.is_fields_named);
+            let params = |this: bool| {
+                let params = fields
+                    .iter()
+                    .map(|field| {
+                        let param = if this {
+                            format!("this.{}", field.name.dart_style(naming_policy))
+                        } else {
+                            format!(
+                                "{} {}",
+                                field.ty.dart_api_type(),
+                                field.name.dart_style(naming_policy)
+                            )
+                        };
+                        if is_fields_named {
+                            format!("{}{},", field.ty.dart_required_modifier(), param)
+                        } else {
+                            format!("{},", param)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if is_fields_named {
+                    return format!("{{ {} }}", params.join(""));
+                }
+                let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+                match optional_boundary_index(&types) {
+                    Some(idx) => format!("{}[{}]", params[..idx].join(""), params[idx..].join("")),
+                    None => params.join(""),
+                }
+            };
+            let field_names = fields
+                .iter()
+                .map(|field| field.name.dart_style(naming_policy))
+                .collect::<Vec<_>>();
+            let variant_comments = dart_comments(&variant.comments);
+
+            factories.push(format!(
+                "{}const factory {}.{}({}) = {};",
+                variant_comments,
+                name,
+                variant.name.dart_style(naming_policy),
+                params(false),
+                variant.name,
+            ));
+            let (equals, hash_code) = if fields.is_empty() {
+                (
+                    format!("other is {}", variant.name),
+                    "runtimeType.hashCode".to_owned(),
+                )
+            } else {
+                (
+                    format!(
+                        "identical(this, other) || other is {} && deepFieldsEqual([{}], [{}])",
+                        variant.name,
+                        field_names.join(", "),
+                        field_names
+                            .iter()
+                            .map(|it| format!("other.{}", it))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    format!("deepFieldsHash([runtimeType, {}])", field_names.join(", ")),
+                )
+            };
+            classes.push(format!(
+                "{comments}final class {variant} extends {name} {{
+                    {fields}
+
+                    const {variant}({params});
+
+                    @override
+                    bool operator ==(Object other) => {equals};
+
+                    @override
+                    int get hashCode => {hash_code};
+
+                    @override
+                    String toString() => '{name}.{factory}({to_string})';
+                }}",
+                comments = variant_comments,
+                variant = variant.name,
+                name = name,
+                fields = fields
+                    .iter()
+                    .map(|field| format!(
+                        "{}final {} {};",
+                        dart_comments(&field.comments),
+                        field.ty.dart_api_type(),
+                        field.name.dart_style(naming_policy)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                params = params(true),
+                equals = equals,
+                hash_code = hash_code,
+                factory = variant.name.dart_style(naming_policy),
+                to_string = field_names
+                    .iter()
+                    .map(|it| format!("{it}: ${it}", it = it))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+        format!(
+            "{}sealed class {} {{
+                const {}();
+
+                {}
+            }}
+
+            {}",
+            comments,
+            name,
+            name,
+            factories.join("\n"),
+            classes.join("\n\n")
+        )
+    }
+
+    fn enhanced_api_fill_to_wire_body(&self, enu: &IrEnum) -> String {
+        let naming_policy = self.context.naming_policy;
+        let payload = enu.enhanced_payload().unwrap();
+        let payload_name = enu.enhanced_payload_name().dart_style(naming_policy);
+        let cases = enu
+            .variants()
+            .iter()
+            .map(|variant| {
+                format!(
+                    "case {}.{6}:
+                        wireObj.kind = inner.inflate_{2}_{1}();
+                        wireObj.kind.ref.{1}.ref.{3} = _api2wire_{4}(apiObj.{5});
+                        break;",
+                    enu.enhanced_kind_name(),
+                    variant.name,
+                    enu.name,
+                    variant_payload_field(variant).name.wire_style(),
+                    payload.ty.safe_ident(),
+                    payload_name,
+                    variant.name.dart_enum_value_style(naming_policy),
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "wireObj.tag = apiObj.kind.index;
+            switch (apiObj.kind) {{
+                {}
+            }}",
+            cases.join("\n")
+        )
+    }
+
+    fn enhanced_structs(&self, enu: &IrEnum, comments: &str) -> String {
+        let naming_policy = self.context.naming_policy;
+        let payload = enu.enhanced_payload().unwrap();
+        let payload_type = payload.ty.dart_api_type();
+        let payload_name = enu.enhanced_payload_name().dart_style(naming_policy);
+        let variants = enu
+            .variants()
+            .iter()
+            .map(|variant| {
+                format!(
+                    "{}{}",
+                    dart_comments(&variant.comments),
+                    variant.name.dart_enum_value_style(naming_policy)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        format!(
+            "enum {kind} {{
+                {variants};
+
+                {name} call({payload_type} {payload_name}) => {name}(kind: this, {payload_name}: {payload_name});
+            }}
+
+            {comments}class {name} {{
+                final {kind} kind;
+                final {payload_type} {payload_name};
+
+                const {name}({{required this.kind, required this.{payload_name}}});
+            }}",
+            kind = enu.enhanced_kind_name(),
+            name = enu.name,
+            variants = variants,
+            comments = comments,
+            payload_type = payload_type,
+            payload_name = payload_name,
+        )
+    }
+}
+
+fn variant_payload_field(variant: &IrVariant) -> &IrField {
+    match &variant.kind {
+        IrVariantKind::Struct(st) => &st.fields[0],
+        IrVariantKind::Value => unreachable!(),
+    }
+}
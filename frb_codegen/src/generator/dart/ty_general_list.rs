@@ -8,15 +8,31 @@ type_dart_generator_struct!(TypeGeneralListGenerator, IrTypeGeneralList);
 impl TypeDartGeneratorTrait for TypeGeneralListGenerator<'_> {
     fn api2wire_body(&self, block_index: BlockIndex) -> Option<String> {
         // NOTE the memory strategy is same as PrimitiveList, see comments there.
+        // Optional items, results and dates are pointers, allocated on their own (unless null)
+        let fill_item = if matches!(
+            *self.ir.inner,
+            IrType::Optional(_)
+                | IrType::Delegate(IrTypeDelegate::ZonedDateTime | IrTypeDelegate::Result { .. })
+        ) {
+            format!(
+                "ans.ref.ptr[i] = _api2wire_{}(raw[i]);",
+                self.ir.inner.safe_ident()
+            )
+        } else {
+            format!(
+                "_api_fill_to_wire_{}(raw[i], ans.ref.ptr[i]);",
+                self.ir.inner.safe_ident()
+            )
+        };
         Some(format!(
             "final ans = inner.new_{}_{}(raw.length);
                 for (var i = 0; i < raw.length; ++i) {{
-                    _api_fill_to_wire_{}(raw[i], ans.ref.ptr[i]);
+                    {}
                 }}
                 return ans;",
             self.ir.safe_ident(),
             block_index,
-            self.ir.inner.safe_ident()
+            fill_item
         ))
     }
 
@@ -1,10 +1,10 @@
 use crate::generator::dart::ty::*;
 use crate::generator::dart::{dart_comments, dart_metadata, GeneratedApiMethod};
 use crate::ir::*;
-use crate::method_utils::FunctionName;
+use crate::method_utils::{FunctionName, MethodNamingUtil};
+use crate::others::protected_region;
 use crate::type_dart_generator_struct;
 use crate::utils::BlockIndex;
-use convert_case::{Case, Casing};
 
 type_dart_generator_struct!(TypeStructRefGenerator, IrTypeStructRef);
 
@@ -14,6 +14,7 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
     }
 
     fn api_fill_to_wire_body(&self) -> Option<String> {
+        let naming_policy = self.context.naming_policy;
         let s = self.ir.get(self.context.ir_file);
         Some(
             s.fields
@@ -21,9 +22,9 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                 .map(|field| {
                     format!(
                         "wireObj.{} = _api2wire_{}(apiObj.{});",
-                        field.name.rust_style(),
+                        field.name.wire_style(),
                         field.ty.safe_ident(),
-                        field.name.dart_style()
+                        field.name.dart_style(naming_policy)
                     )
                 })
                 .collect::<Vec<_>>()
@@ -32,6 +33,7 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
     }
 
     fn wire2api_body(&self) -> String {
+        let naming_policy = self.context.naming_policy;
         let src = self.ir.get(self.context.ir_file);
         let s = self.ir.get(self.context.ir_file);
 
@@ -40,17 +42,31 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
             f.is_method_for_struct(&src.name) || f.is_static_method_for_struct(&src.name)
         });
         let has_methods = methods.next().is_some();
+        // Only trailing optional fields may be missing
+        let min_len = if s.skew_tolerant {
+            s.fields
+                .iter()
+                .rposition(|field| !matches!(field.ty, IrType::Optional(_)))
+                .map_or(0, |idx| idx + 1)
+        } else {
+            s.fields.len()
+        };
         let mut inner = s
             .fields
             .iter()
             .enumerate()
             .map(|(idx, field)| {
-                format!(
-                    "{}: _wire2api_{}(arr[{}]),",
-                    field.name.dart_style(),
-                    field.ty.safe_ident(),
-                    idx
-                )
+                let value = format!("_wire2api_{}(arr[{}])", field.ty.safe_ident(), idx);
+                if idx >= min_len {
+                    format!(
+                        "{}: arr.length > {} ? {} : null,",
+                        field.name.dart_style(naming_policy),
+                        idx,
+                        value
+                    )
+                } else {
+                    format!("{}: {},", field.name.dart_style(naming_policy), value)
+                }
             })
             .collect::<Vec<_>>();
         if has_methods {
@@ -58,17 +74,29 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
         }
         let inner = inner.join("\n");
 
+        let length_check = if s.skew_tolerant {
+            format!(
+                "if (arr.length < {}) throw Exception('unexpected arr length: expect at least {} but see ${{arr.length}}');",
+                min_len, min_len,
+            )
+        } else {
+            format!(
+                "if (arr.length != {}) throw Exception('unexpected arr length: expect {} but see ${{arr.length}}');",
+                s.fields.len(),
+                s.fields.len(),
+            )
+        };
+
         format!(
             "final arr = raw as List<dynamic>;
-                if (arr.length != {}) throw Exception('unexpected arr length: expect {} but see ${{arr.length}}');
+                {}
                 return {}({});",
-            s.fields.len(),
-            s.fields.len(),
-            s.name, inner,
+            length_check, s.name, inner,
         )
     }
 
     fn structs(&self) -> String {
+        let naming_policy = self.context.naming_policy;
         let src = self.ir.get(self.context.ir_file);
         let comments = dart_comments(&src.comments);
         let metadata = dart_metadata(&src.dart_metadata);
@@ -89,8 +117,9 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
             .map(|func| {
                 generate_api_method(
                     func,
-                    src,
+                    &src.name,
                     self.context.dart_api_class_name.as_ref().unwrap().clone(),
+                    naming_policy,
                 )
             })
             .collect::<Vec<_>>();
@@ -105,7 +134,17 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
             "final {} bridge;",
             self.context.dart_api_class_name.as_ref().unwrap()
         );
-        if src.using_freezed() {
+        let sample = if self.context.sample_factories && has_dart_sample(src, ir_file, 0) {
+            generate_sample_factory(
+                src,
+                ir_file,
+                has_methods.then(|| self.context.dart_api_class_name.as_deref().unwrap()),
+                naming_policy,
+            )
+        } else {
+            "".to_owned()
+        };
+        let class = if src.using_freezed() {
             let mut constructor_params = src
                 .fields
                 .iter()
@@ -114,7 +153,7 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                         "{} {} {},",
                         f.ty.dart_required_modifier(),
                         f.ty.dart_api_type(),
-                        f.name.dart_style()
+                        f.name.dart_style(naming_policy)
                     )
                 })
                 .collect::<Vec<_>>();
@@ -122,19 +161,38 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                 constructor_params.insert(0, extra_argument);
             }
             let constructor_params = constructor_params.join("");
+            let assertions = src
+                .fields
+                .iter()
+                .flat_map(|f| {
+                    f.validations.iter().map(move |v| {
+                        let name = f.name.dart_style(naming_policy);
+                        format!(
+                            "@Assert('{}', '{}: expected {}')\n",
+                            v.dart_condition(&name, &f.ty).replace('$', "\\$"),
+                            name.replace('$', "\\$"),
+                            v.source()
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+                .concat();
 
             format!(
                 "{}{}class {} with _${} {{
-                const factory {}({{{}}}) = _{};
+                {}const factory {}({{{}}}) = _{};
+                {}
                 {}
             }}",
                 comments,
                 metadata,
                 self.ir.name,
                 self.ir.name,
+                assertions,
                 self.ir.name,
                 constructor_params,
                 self.ir.name,
+                sample,
                 methods_string
             )
         } else {
@@ -144,11 +202,14 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                 .map(|f| {
                     let comments = dart_comments(&f.comments);
                     format!(
-                        "{}{} {} {};",
+                        "{}{}{} {} {};",
                         comments,
+                        f.name
+                            .dart_mapping_comment(naming_policy)
+                            .unwrap_or_default(),
                         if f.is_final { "final" } else { "" },
                         f.ty.dart_api_type(),
-                        f.name.dart_style()
+                        f.name.dart_style(naming_policy)
                     )
                 })
                 .collect::<Vec<_>>();
@@ -164,7 +225,7 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                     format!(
                         "{}this.{},",
                         f.ty.dart_required_modifier(),
-                        f.name.dart_style()
+                        f.name.dart_style(naming_policy)
                     )
                 })
                 .collect::<Vec<_>>();
@@ -173,14 +234,68 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
             }
 
             let constructor_params = constructor_params.join("");
+            let initializers = src
+                .fields
+                .iter()
+                .flat_map(|f| {
+                    f.validations.iter().map(move |v| {
+                        v.dart_assertion(&f.name.dart_style(naming_policy), &f.ty)
+                            .trim_end_matches(';')
+                            .to_owned()
+                    })
+                })
+                .collect::<Vec<_>>();
+            let initializers = if initializers.is_empty() {
+                "".to_owned()
+            } else {
+                format!(" : {}", initializers.join(", "))
+            };
+
+            // for `Stream.distinct`, which relies on `==`
+            let equality = if self
+                .context
+                .ir_file
+                .distinct_struct_names()
+                .contains(&src.name)
+            {
+                let fields = src
+                    .fields
+                    .iter()
+                    .map(|f| f.name.dart_style(naming_policy))
+                    .collect::<Vec<_>>();
+                format!(
+                    "@override
+                    bool operator ==(Object other) =>
+                        identical(this, other) || other is {} && deepFieldsEqual([{}], [{}]);
+
+                    @override
+                    int get hashCode => deepFieldsHash([{}]);
+                    ",
+                    self.ir.name,
+                    fields.join(", "),
+                    fields
+                        .iter()
+                        .map(|it| format!("other.{}", it))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    fields.join(", ")
+                )
+            } else {
+                "".to_owned()
+            };
+            let region = if self.context.protected_regions {
+                protected_region(&self.ir.name)
+            } else {
+                "".to_owned()
+            };
 
             format!(
                 "{}{}class {} {{
                 {}
 
-                {}({{{}}});
+                {}({{{}}}){};
 
-                {}
+                {}{}{}{}
             }}",
                 comments,
                 metadata,
@@ -188,18 +303,132 @@ impl TypeDartGeneratorTrait for TypeStructRefGenerator<'_> {
                 field_declarations,
                 self.ir.name,
                 constructor_params,
-                methods_string
+                initializers,
+                sample,
+                equality,
+                methods_string,
+                region
+            )
+        };
+
+        if src.dart_builder {
+            let dart_api_class_name = self.context.dart_api_class_name.as_ref().unwrap();
+            format!(
+                "{}\n\n{}",
+                class,
+                generate_builder(
+                    src,
+                    has_methods.then_some(dart_api_class_name.as_str()),
+                    naming_policy,
+                )
+            )
+        } else {
+            class
+        }
+    }
+}
+
+/// A `{name}Builder` class, with a setter returning the builder for each field, and `build()`
+/// calling the constructor. Optional fields may be left out, while `build()` throws if any
+/// other one is not set.
+fn generate_builder(
+    src: &IrStruct,
+    dart_api_class_name: Option<&str>,
+    naming_policy: NamingPolicy,
+) -> String {
+    let builder_name = format!("{}Builder", src.name);
+    let mut declarations = src
+        .fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{} _{};",
+                dart_nullable_type(&f.ty),
+                f.name.dart_style(naming_policy)
             )
+        })
+        .collect::<Vec<_>>();
+    let mut args = src
+        .fields
+        .iter()
+        .map(|f| {
+            let name = f.name.dart_style(naming_policy);
+            if matches!(f.ty, IrType::Optional(_)) {
+                format!("{}: _{},", name, name)
+            } else {
+                format!(
+                    "{name}: _{name} ?? (throw StateError('{builder}: `{name}` is not set')),",
+                    name = name,
+                    builder = builder_name
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+    let constructor = match dart_api_class_name {
+        Some(dart_api_class_name) => {
+            declarations.insert(0, format!("final {} bridge;", dart_api_class_name));
+            args.insert(0, "bridge: bridge,".to_owned());
+            format!("{}(this.bridge);", builder_name)
         }
+        None => "".to_owned(),
+    };
+    let setters = src
+        .fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{comments}{builder} {name}({ty} value) {{
+                    _{name} = value;
+                    return this;
+                }}",
+                comments = dart_comments(&f.comments),
+                builder = builder_name,
+                name = f.name.dart_style(naming_policy),
+                ty = f.ty.dart_api_type(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "/// Builder of [{name}], whose setters can be chained before calling [build].
+        class {builder} {{
+            {declarations}
+
+            {constructor}
+
+            {setters}
+
+            {name} build() => {name}({args});
+        }}",
+        name = src.name,
+        builder = builder_name,
+        declarations = declarations.join("\n"),
+        constructor = constructor,
+        setters = setters.join("\n\n"),
+        args = args.join(""),
+    )
+}
+
+/// The Dart type of `ty` allowing null, e.g. `int?` for both `i32` and `Option<i32>`.
+fn dart_nullable_type(ty: &IrType) -> String {
+    if matches!(ty, IrType::Optional(_)) {
+        ty.dart_api_type()
+    } else {
+        format!("{}?", ty.dart_api_type())
     }
 }
 
-fn generate_api_method(
+/// The method of the class [struct_name] calling [func] through the API class.
+pub(super) fn generate_api_method(
     func: &IrFunc,
-    ir_struct: &IrStruct,
+    struct_name: &str,
     dart_api_class_name: String,
+    naming_policy: NamingPolicy,
 ) -> GeneratedApiMethod {
     let f = FunctionName::deserialize(&func.name);
+    if let Some(accessor) = func.accessor {
+        return generate_api_accessor(func, accessor, &f.method_name(), naming_policy);
+    }
     let skip_count = if f.is_static_method() { 0 } else { 1 };
     let mut raw_func_param_list = func
         .inputs
@@ -210,7 +439,7 @@ fn generate_api_method(
                 "{}{} {}",
                 input.ty.dart_required_modifier(),
                 input.ty.dart_api_type(),
-                input.name.dart_style()
+                input.name.dart_style(naming_policy)
             )
         })
         .collect::<Vec<_>>();
@@ -226,15 +455,15 @@ fn generate_api_method(
     let partial = format!(
         "{} {} {}({{ {} }})",
         if f.is_static_method() { "static" } else { "" },
-        func.mode.dart_return_type(&func.output.dart_api_type()),
+        func.dart_return_type(),
         if f.is_static_method() {
             if static_function_name == "new" {
-                format!("new{}", ir_struct.name)
+                format!("new{}", struct_name)
             } else {
-                static_function_name.to_case(Case::Camel)
+                naming_policy.member_name(&static_function_name)
             }
         } else {
-            f.method_name().to_case(Case::Camel)
+            naming_policy.member_name(&f.method_name())
         },
         full_func_param_list.join(","),
     );
@@ -245,7 +474,13 @@ fn generate_api_method(
         .inputs
         .iter()
         .skip(skip_count) //skip the first as it's the method 'self'
-        .map(|input| format!("{}:{},", input.name.dart_style(), input.name.dart_style()))
+        .map(|input| {
+            format!(
+                "{}:{},",
+                input.name.dart_style(naming_policy),
+                input.name.dart_style(naming_policy)
+            )
+        })
         .collect::<Vec<_>>();
 
     let implementation = if f.is_static_method() {
@@ -253,15 +488,15 @@ fn generate_api_method(
         let arg_names = arg_names.concat();
         format!(
             "bridge.{}({})",
-            func.name.clone().to_case(Case::Camel),
+            naming_policy.member_name(&func.name),
             arg_names
         )
     } else {
         let arg_names = arg_names.concat();
         format!(
             "bridge.{}({}: this, {})",
-            func.name.clone().to_case(Case::Camel),
-            func.inputs[0].name.dart_style(),
+            naming_policy.member_name(&func.name),
+            func.inputs[0].name.dart_style(naming_policy),
             arg_names
         )
     };
@@ -271,3 +506,161 @@ fn generate_api_method(
         implementation,
     }
 }
+
+/// A getter or a setter calling the method of [func], which takes no argument but `that` or one
+/// argument respectively.
+fn generate_api_accessor(
+    func: &IrFunc,
+    accessor: IrFuncAccessor,
+    method_name: &str,
+    naming_policy: NamingPolicy,
+) -> GeneratedApiMethod {
+    let property_name = accessor.property_name(method_name, naming_policy);
+    let bridge_method = naming_policy.member_name(&func.name);
+    let that = func.inputs[0].name.dart_style(naming_policy);
+    match accessor {
+        IrFuncAccessor::Getter => GeneratedApiMethod {
+            signature: format!("{} get {}", func.dart_return_type(), property_name),
+            implementation: format!("bridge.{}({}: this)", bridge_method, that),
+        },
+        IrFuncAccessor::Setter => {
+            let value = &func.inputs[1];
+            GeneratedApiMethod {
+                signature: format!(
+                    "set {}({} {})",
+                    property_name,
+                    value.ty.dart_api_type(),
+                    value.name.dart_style(naming_policy)
+                ),
+                implementation: format!(
+                    "bridge.{}({}: this, {}: {})",
+                    bridge_method,
+                    that,
+                    value.name.dart_style(naming_policy),
+                    value.name.dart_style(naming_policy)
+                ),
+            }
+        }
+    }
+}
+
+/// Structs nested deeper than this in a sample are not worth it.
+const MAX_SAMPLE_DEPTH: usize = 8;
+
+/// Whether `{name}.sample()` can fill every field of the struct. The structs with validated fields
+/// have none, since placeholder data could fail their checks.
+fn has_dart_sample(src: &IrStruct, ir_file: &IrFile, depth: usize) -> bool {
+    let bridge = MethodNamingUtil::has_methods(&src.name, ir_file);
+    depth < MAX_SAMPLE_DEPTH
+        && src.fields.iter().all(|field| {
+            field.validations.is_empty()
+                && dart_sample(&field.ty, ir_file, bridge, depth + 1).is_some()
+        })
+}
+
+/// `{name}.sample()`, taking the API class as well if the struct has methods.
+fn generate_sample_factory(
+    src: &IrStruct,
+    ir_file: &IrFile,
+    dart_api_class_name: Option<&str>,
+    naming_policy: NamingPolicy,
+) -> String {
+    let mut args = src
+        .fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{}: {},",
+                field.name.dart_style(naming_policy),
+                dart_sample(&field.ty, ir_file, dart_api_class_name.is_some(), 1).unwrap()
+            )
+        })
+        .collect::<Vec<_>>();
+    if dart_api_class_name.is_some() {
+        args.insert(0, "bridge: bridge,".to_owned());
+    }
+    format!(
+        "/// Placeholder data, e.g. for widget previews and golden tests.
+        factory {name}.sample({param}) => {name}({args});
+
+        ",
+        name = src.name,
+        param = dart_api_class_name
+            .map(|name| format!("{} bridge", name))
+            .unwrap_or_default(),
+        args = args.concat(),
+    )
+}
+
+/// A Dart expression of placeholder data of the type, or [None] if it cannot be made up, e.g. for
+/// closures. Optional values and lists which could hold structs are left empty, so that the
+/// samples of recursive structs end. `bridge` is whether the API class is at hand.
+fn dart_sample(ty: &IrType, ir_file: &IrFile, bridge: bool, depth: usize) -> Option<String> {
+    Some(match ty {
+        IrType::Primitive(primitive) => match primitive {
+            IrTypePrimitive::Bool => "true".to_owned(),
+            IrTypePrimitive::F32 | IrTypePrimitive::F64 => "1.5".to_owned(),
+            IrTypePrimitive::Unit => return None,
+            _ => "42".to_owned(),
+        },
+        IrType::PrimitiveList(list) => {
+            let items = match list.primitive {
+                IrTypePrimitive::F32 | IrTypePrimitive::F64 => "[1.5, 2.5]",
+                _ => "[1, 2, 3]",
+            };
+            format!("{}.fromList({})", ty.dart_api_type(), items)
+        }
+        IrType::Optional(optional) if optional.inner.holds_struct(ir_file) => "null".to_owned(),
+        IrType::Optional(optional) => dart_sample(&optional.inner, ir_file, bridge, depth)
+            .unwrap_or_else(|| "null".to_owned()),
+        IrType::Boxed(boxed) => dart_sample(&boxed.inner, ir_file, bridge, depth)?,
+        IrType::GeneralList(list) if list.inner.holds_struct(ir_file) => "[]".to_owned(),
+        IrType::GeneralList(list) => {
+            format!("[{}]", dart_sample(&list.inner, ir_file, bridge, depth)?)
+        }
+        IrType::StructRef(struct_ref) => {
+            let st = struct_ref.get(ir_file);
+            if !has_dart_sample(st, ir_file, depth) {
+                return None;
+            }
+            if MethodNamingUtil::has_methods(&st.name, ir_file) {
+                if !bridge {
+                    return None;
+                }
+                format!("{}.sample(bridge)", st.name)
+            } else {
+                format!("{}.sample()", st.name)
+            }
+        }
+        IrType::EnumRef(enum_ref) if !enum_ref.get(ir_file).is_struct() => {
+            format!("{}.values.first", ty.dart_api_type())
+        }
+        IrType::EnumRef(_) => return None,
+        IrType::Delegate(delegate) => match delegate {
+            IrTypeDelegate::String | IrTypeDelegate::CString => "'frb'".to_owned(),
+            IrTypeDelegate::StringList | IrTypeDelegate::CStringList => {
+                "['flutter', 'rust']".to_owned()
+            }
+            IrTypeDelegate::Url => "Uri.parse('https://example.com/')".to_owned(),
+            IrTypeDelegate::UrlList => "[Uri.parse('https://example.com/')]".to_owned(),
+            IrTypeDelegate::ZonedDateTime => {
+                "ZonedDateTime(DateTime.utc(2022), Duration.zero)".to_owned()
+            }
+            IrTypeDelegate::PrimitiveEnum { .. } | IrTypeDelegate::StringEnum(_) => {
+                format!("{}.values.first", ty.dart_api_type())
+            }
+            IrTypeDelegate::BTreeMap { value, .. } if value.holds_struct(ir_file) => {
+                "{}".to_owned()
+            }
+            IrTypeDelegate::BTreeMap { key, value } => format!(
+                "{{{}: {}}}",
+                dart_sample(key, ir_file, bridge, depth)?,
+                dart_sample(value, ir_file, bridge, depth)?
+            ),
+            IrTypeDelegate::Transparent(transparent) => {
+                dart_sample(&transparent.inner, ir_file, bridge, depth)?
+            }
+            _ => return None,
+        },
+    })
+}
@@ -19,17 +19,71 @@ pub use ty_struct::*;
 
 use std::collections::HashSet;
 
+use crate::config::Opts;
 use crate::ir::IrType::*;
 use crate::ir::*;
 use crate::method_utils::FunctionName;
 use crate::others::*;
+use crate::size_report::{GeneratedCode, SizeKey, SizeReport};
+use crate::templates::{Templates, WireFuncData};
 use crate::utils::BlockIndex;
 
 pub const HANDLER_NAME: &str = "FLUTTER_RUST_BRIDGE_HANDLER";
 
+/// What the default handler does when a function panics on a worker, from `--panic-policy`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    #[default]
+    Recover,
+    Abort,
+    /// The path of the function called with the panic, e.g. `crate::api::on_panic`
+    Callback(String),
+}
+
+impl PanicPolicy {
+    /// The `flutter_rust_bridge::handler::PanicPolicy` given to the default handler.
+    fn rust_value(&self) -> String {
+        match self {
+            PanicPolicy::Recover => "handler::PanicPolicy::Recover".to_owned(),
+            PanicPolicy::Abort => "handler::PanicPolicy::Abort".to_owned(),
+            PanicPolicy::Callback(path) => format!("handler::PanicPolicy::Callback({})", path),
+        }
+    }
+
+    /// What happens to a call which panics, for the generated documentation.
+    pub fn description(&self) -> String {
+        match self {
+            PanicPolicy::Recover => "A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.".to_owned(),
+            PanicPolicy::Abort => "A Rust function panicking on a worker aborts the whole process, see `--panic-policy`.".to_owned(),
+            PanicPolicy::Callback(path) => format!("A Rust function panicking on a worker is reported to `{}`, then fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.", path),
+        }
+    }
+}
+
+impl std::str::FromStr for PanicPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recover" => Ok(PanicPolicy::Recover),
+            "abort" => Ok(PanicPolicy::Abort),
+            path if syn::parse_str::<syn::Path>(path).is_ok() => {
+                Ok(PanicPolicy::Callback(path.to_owned()))
+            }
+            _ => Err(format!(
+                "`{}` is neither `recover`, `abort` nor the path of a function",
+                s
+            )),
+        }
+    }
+}
+
 pub struct Output {
     pub code: String,
     pub extern_func_names: Vec<String>,
+    /// Those of `extern_func_names` missing from release builds, see `#[frb(debug_only)]`.
+    pub debug_only_func_names: Vec<String>,
+    pub size_report: SizeReport,
 }
 
 impl Output {
@@ -42,32 +96,91 @@ impl Output {
     }
 }
 
-pub fn generate(ir_file: &IrFile, rust_wire_mod: &str, block_index: BlockIndex) -> Output {
-    let mut generator = Generator::new();
-    let code = generator.generate(ir_file, rust_wire_mod, block_index);
+pub fn generate(ir_file: &IrFile, rust_wire_mod: &str, config: &Opts) -> Output {
+    let mut generator = Generator::new(config);
+    let code = generator.generate(
+        ir_file,
+        rust_wire_mod,
+        config.block_index,
+        config.rust_cfg_feature.as_deref(),
+    );
+    generator
+        .size_report
+        .finish(&code, &generator.extern_func_collector.names, ir_file);
 
     Output {
         code,
         extern_func_names: generator.extern_func_collector.names,
+        debug_only_func_names: generator.extern_func_collector.debug_only_names,
+        size_report: generator.size_report,
     }
 }
 
 struct Generator {
     extern_func_collector: ExternFuncCollector,
+    /// Methods of the `BridgeApi` trait as `(signature, default implementation)`,
+    /// or [None] if the wire functions call the API directly.
+    bridge_api_methods: Option<Vec<(String, String)>>,
+    /// Arms of the `match` of `rpc_dispatch`, or [None] without `--rpc-gateway`.
+    rpc_methods: Option<Vec<String>>,
+    size_report: SizeReport,
+    bridge_health: bool,
+    capture_stdio: bool,
+    log_level_control: bool,
+    crash_reports: bool,
+    panic_policy: PanicPolicy,
+    sample_factories: bool,
 }
 
 impl Generator {
-    fn new() -> Self {
+    fn new(config: &Opts) -> Self {
         Self {
-            extern_func_collector: ExternFuncCollector::new(),
+            extern_func_collector: ExternFuncCollector::new(
+                &config.symbol_prefix,
+                &config.templates,
+            ),
+            bridge_api_methods: if config.bridge_api_trait {
+                Some(vec![])
+            } else {
+                None
+            },
+            rpc_methods: if config.rpc_gateway {
+                Some(vec![])
+            } else {
+                None
+            },
+            size_report: SizeReport::default(),
+            bridge_health: config.bridge_health,
+            capture_stdio: config.capture_stdio,
+            log_level_control: config.log_level_control,
+            crash_reports: config.crash_reports,
+            panic_policy: config.panic_policy.clone(),
+            sample_factories: config.sample_factories,
         }
     }
 
+    /// Generates code with [generate], accounting its lines and extern functions to [key].
+    fn measured<T: GeneratedCode>(
+        &mut self,
+        key: SizeKey,
+        generate: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let first_symbol = self.extern_func_collector.names.len();
+        let code = generate(self);
+        self.size_report.add(
+            key,
+            code.line_count(),
+            &self.extern_func_collector.names[first_symbol..],
+        );
+        code
+    }
+
     fn generate(
         &mut self,
         ir_file: &IrFile,
         rust_wire_mod: &str,
         block_index: BlockIndex,
+        cfg_feature: Option<&str>,
     ) -> String {
         let mut lines: Vec<String> = vec![];
 
@@ -75,11 +188,16 @@ impl Generator {
         let distinct_output_types = ir_file.distinct_types(false, true);
 
         lines.push(r#"#![allow(non_camel_case_types, unused, clippy::redundant_closure, clippy::useless_conversion, clippy::unit_arg, clippy::double_parens, non_snake_case)]"#.to_string());
+        if let Some(feature) = cfg_feature {
+            lines.push(format!("#![cfg(feature = {:?})]", feature));
+        }
         lines.push(CODE_HEADER.to_string());
 
         lines.push(String::new());
         lines.push(format!("use crate::{}::*;", rust_wire_mod));
         lines.push("use flutter_rust_bridge::*;".to_string());
+        // for the outputs converted in the wire functions, e.g. of `Url`
+        lines.push("use flutter_rust_bridge::support::IntoDart;".to_string());
         lines.push(String::new());
 
         lines.push(self.section_header_comment("imports"));
@@ -96,31 +214,44 @@ impl Generator {
             ir_file
                 .funcs
                 .iter()
-                .map(|f| self.generate_wire_func(f, ir_file)),
+                .map(|f| self.measured(SizeKey::Func(f), |s| s.generate_wire_func(f, ir_file))),
         );
 
+        if self.bridge_api_methods.is_some() {
+            lines.push(self.section_header_comment("bridge api"));
+            lines.push(self.generate_bridge_api());
+        }
+
+        if self.rpc_methods.is_some() {
+            lines.push(self.section_header_comment("rpc gateway"));
+            lines.push(self.generate_rpc_dispatch());
+        }
+
         lines.push(self.section_header_comment("wire structs"));
         lines.extend(
-            distinct_input_types
-                .iter()
-                .map(|ty| self.generate_wire_struct(ty, ir_file)),
-        );
-        lines.extend(
-            distinct_input_types
-                .iter()
-                .map(|ty| TypeRustGenerator::new(ty.clone(), ir_file).structs()),
+            distinct_input_types.iter().map(|ty| {
+                self.measured(SizeKey::Type(ty), |s| s.generate_wire_struct(ty, ir_file))
+            }),
         );
+        lines.extend(distinct_input_types.iter().map(|ty| {
+            self.measured(SizeKey::Type(ty), |_| {
+                TypeRustGenerator::new(ty.clone(), ir_file).structs()
+            })
+        }));
 
         lines.push(self.section_header_comment("wrapper structs"));
-        lines.extend(
-            distinct_output_types
-                .iter()
-                .filter_map(|ty| self.generate_wrapper_struct(ty, ir_file)),
-        );
+        lines.extend(distinct_output_types.iter().filter_map(|ty| {
+            self.measured(SizeKey::Type(ty), |s| {
+                s.generate_wrapper_struct(ty, ir_file)
+            })
+        }));
         lines.push(self.section_header_comment("static checks"));
         let static_checks: Vec<_> = distinct_output_types
             .iter()
-            .filter_map(|ty| self.generate_static_checks(ty, ir_file))
+            .filter_map(|ty| {
+                self.measured(SizeKey::Type(ty), |s| s.generate_static_checks(ty, ir_file))
+            })
+            .chain(ir_file.funcs.iter().filter_map(generate_stream_sink_check))
             .collect();
         if !static_checks.is_empty() {
             lines.push("const _: fn() = || {".to_owned());
@@ -129,41 +260,94 @@ impl Generator {
         }
 
         lines.push(self.section_header_comment("allocate functions"));
-        lines.extend(
-            distinct_input_types
-                .iter()
-                .map(|f| self.generate_allocate_funcs(f, ir_file, block_index)),
-        );
+        lines.extend(distinct_input_types.iter().map(|ty| {
+            self.measured(SizeKey::Type(ty), |s| {
+                s.generate_allocate_funcs(ty, ir_file, block_index)
+            })
+        }));
 
         lines.push(self.section_header_comment("impl Wire2Api"));
         lines.push(self.generate_wire2api_misc().to_string());
         lines.extend(
-            distinct_input_types
-                .iter()
-                .map(|ty| self.generate_wire2api_func(ty, ir_file)),
+            distinct_input_types.iter().map(|ty| {
+                self.measured(SizeKey::Type(ty), |s| s.generate_wire2api_func(ty, ir_file))
+            }),
         );
 
         lines.push(self.section_header_comment("impl NewWithNullPtr"));
         lines.push(self.generate_new_with_nullptr_misc().to_string());
-        lines.extend(
-            distinct_input_types
-                .iter()
-                .map(|ty| self.generate_new_with_nullptr_func(ty, ir_file)),
-        );
+        lines.extend(distinct_input_types.iter().map(|ty| {
+            self.measured(SizeKey::Type(ty), |s| {
+                s.generate_new_with_nullptr_func(ty, ir_file)
+            })
+        }));
 
         lines.push(self.section_header_comment("impl IntoDart"));
         lines.extend(
-            distinct_output_types
-                .iter()
-                .map(|ty| self.generate_impl_intodart(ty, ir_file)),
+            distinct_output_types.iter().map(|ty| {
+                self.measured(SizeKey::Type(ty), |s| s.generate_impl_intodart(ty, ir_file))
+            }),
         );
 
+        if self.sample_factories {
+            lines.push(self.section_header_comment("sample constructors"));
+            lines.extend(
+                ir_file
+                    .distinct_types(true, true)
+                    .iter()
+                    .filter_map(|ty| match ty {
+                        StructRef(st) => generate_sample_impl(st.get(ir_file), ir_file),
+                        _ => None,
+                    }),
+            );
+        }
+
+        let event_impls = ir_file
+            .funcs
+            .iter()
+            .filter(|f| f.event)
+            .map(|f| generate_impl_event(&f.output))
+            .collect::<Vec<_>>();
+        if !event_impls.is_empty() {
+            lines.push(self.section_header_comment("impl Event"));
+            lines.extend(event_impls);
+        }
+
         lines.push(self.section_header_comment("executor"));
-        lines.push(self.generate_executor(ir_file));
+        lines.push(self.generate_executor(ir_file, rust_wire_mod));
 
         if block_index == BlockIndex::PRIMARY {
             lines.push(self.section_header_comment("sync execution mode utility"));
             lines.push(self.generate_sync_execution_mode_utility());
+
+            if self.bridge_health {
+                lines.push(self.section_header_comment("health"));
+                lines.push(self.generate_health_utility());
+            }
+
+            lines.push(self.section_header_comment("stream control"));
+            lines.push(self.generate_stream_control_utility());
+
+            lines.push(self.section_header_comment("reinit"));
+            lines.push(self.generate_reinit_utility());
+
+            lines.push(self.section_header_comment("cancel"));
+            lines.push(self.generate_cancel_utility());
+
+            if self.capture_stdio {
+                lines.push(self.section_header_comment("stdio"));
+                lines.push(self.generate_stdio_utility());
+            }
+
+            if self.log_level_control {
+                lines.push(self.section_header_comment("log level"));
+                lines.push(self.generate_log_level_utility());
+            }
+
+            if self.crash_reports {
+                lines.push(self.section_header_comment("crash reports"));
+                lines.push(self.generate_crash_reports_utility());
+            }
         }
 
         lines.join("\n")
@@ -198,29 +382,305 @@ impl Generator {
             .into_iter()
     }
 
-    fn generate_executor(&mut self, ir_file: &IrFile) -> String {
-        if ir_file.has_executor {
-            "/* nothing since executor detected */".to_string()
-        } else {
-            format!(
+    fn generate_executor(&mut self, ir_file: &IrFile, rust_wire_mod: &str) -> String {
+        if ir_file.custom_handler.is_some() && self.panic_policy != PanicPolicy::default() {
+            panic!("--panic-policy only applies to the default handler, while the API file provides its own");
+        }
+        match &ir_file.custom_handler {
+            Some(name) if name == HANDLER_NAME => {
+                "/* nothing since executor detected */".to_string()
+            }
+            Some(name) => format!(
+                "use crate::{}::{} as {};",
+                rust_wire_mod, name, HANDLER_NAME
+            ),
+            None if self.panic_policy == PanicPolicy::default() => format!(
                 "support::lazy_static! {{
+                /// {}
                 pub static ref {}: support::DefaultHandler = Default::default();
             }}
             ",
+                self.panic_policy.description(),
                 HANDLER_NAME
-            )
+            ),
+            None => format!(
+                "support::lazy_static! {{
+                /// {}
+                pub static ref {}: support::DefaultHandler = support::DefaultHandler::with_panic_policy({});
+            }}
+            ",
+                self.panic_policy.description(),
+                HANDLER_NAME,
+                self.panic_policy.rust_value()
+            ),
         }
     }
 
+    fn generate_bridge_api(&mut self) -> String {
+        let methods = self.bridge_api_methods.as_ref().unwrap();
+        format!(
+            "/// The functions of the API, called by the wire functions through the implementation
+            /// registered with [register_bridge_api], e.g. to replace it in tests.
+            pub trait BridgeApi: Send + Sync {{
+                {}
+            }}
+
+            /// Calls the functions of the API file.
+            pub struct DefaultBridgeApi;
+
+            impl BridgeApi for DefaultBridgeApi {{
+                {}
+            }}
+
+            support::lazy_static! {{
+                static ref BRIDGE_API: std::sync::RwLock<std::sync::Arc<dyn BridgeApi>> =
+                    std::sync::RwLock::new(std::sync::Arc::new(DefaultBridgeApi));
+            }}
+
+            /// Replaces the implementation called by the wire functions.
+            pub fn register_bridge_api(api: impl BridgeApi + 'static) {{
+                *BRIDGE_API.write().unwrap() = std::sync::Arc::new(api);
+            }}
+
+            /// The implementation currently called by the wire functions.
+            pub fn bridge_api() -> std::sync::Arc<dyn BridgeApi> {{
+                BRIDGE_API.read().unwrap().clone()
+            }}
+            ",
+            methods
+                .iter()
+                .map(|(signature, _)| format!("{};", signature))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            methods
+                .iter()
+                .map(|(signature, body)| format!("{} {{ {} }}", signature, body))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )
+    }
+
+    fn generate_rpc_dispatch(&mut self) -> String {
+        format!(
+            "/// Calls the function of this API block named `method`, for the requests answered by
+            /// [rpc::serve] or [rpc::handle].
+            pub fn rpc_dispatch(method: &str, params: rpc::Value) -> Result<rpc::Value, rpc::RpcError> {{
+                let mut params = rpc::Params::new(params)?;
+                match method {{
+                    {}
+                    _ => Err(rpc::RpcError::method_not_found(method)),
+                }}
+            }}
+            ",
+            self.rpc_methods.as_ref().unwrap().join("\n"),
+        )
+    }
+
     fn generate_sync_execution_mode_utility(&mut self) -> String {
-        self.extern_func_collector.generate(
+        let free = self.extern_func_collector.generate(
             "free_WireSyncReturnStruct",
             &["val: support::WireSyncReturnStruct"],
             None,
             "unsafe { let _ = support::vec_from_leak_ptr(val.ptr, val.len); }",
+        );
+        let new_outcome = self.extern_func_collector.generate(
+            "new_sync_outcome",
+            &[],
+            Some("*mut support::WireSyncReturnStruct"),
+            "support::new_sync_outcome()",
+        );
+        format!("{}\n{}", free, new_outcome)
+    }
+
+    fn generate_health_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_get_bridge_health",
+            &["port_: i64"],
+            None,
+            &format!(
+                "support::report_health(port_, || {}.health())",
+                HANDLER_NAME
+            ),
         )
     }
 
+    fn generate_stream_control_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_stream_control",
+            &["port_: i64", "command: i32", "value: i64"],
+            None,
+            "support::stream_control(port_, command, value)",
+        )
+    }
+
+    fn generate_reinit_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_reinit_bridge",
+            &["token: i64"],
+            None,
+            "support::reinit(token)",
+        )
+    }
+
+    fn generate_cancel_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_cancel_task",
+            &["port_: i64"],
+            None,
+            "support::cancel_task(port_)",
+        )
+    }
+
+    fn generate_stdio_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_capture_stdio",
+            &["port_: i64"],
+            None,
+            "support::capture_stdio(port_)",
+        )
+    }
+
+    fn generate_log_level_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_set_log_level",
+            &["level: i32"],
+            None,
+            "support::set_log_level(level)",
+        )
+    }
+
+    fn generate_crash_reports_utility(&mut self) -> String {
+        self.extern_func_collector.generate(
+            "wire_crash_reports",
+            &["port_: i64"],
+            None,
+            "support::crash_reports(port_)",
+        )
+    }
+
+    /// Adds the arm of `func` to `rpc_dispatch`, calling it like its wire function does, unless
+    /// its inputs or output can not be carried in JSON, e.g. streams and closures.
+    fn add_rpc_method(
+        &mut self,
+        func: &IrFunc,
+        inner_func_call: &str,
+        via_bridge_api: bool,
+        ir_file: &IrFile,
+    ) {
+        if !is_rpc_compatible(func, ir_file) {
+            return;
+        }
+        let params = func
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                format!(
+                    "let {}api_{}: {} = params.take({}, {:?})?;",
+                    if field.borrow == Some(IrBorrow::Mut) && !via_bridge_api {
+                        "mut "
+                    } else {
+                        ""
+                    },
+                    field.name.wire_style(),
+                    field.ty.rust_api_type(),
+                    index,
+                    field.name.rust_style(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let is_sync_return = matches!(
+            func.output,
+            Delegate(IrTypeDelegate::SyncReturn(_) | IrTypeDelegate::SyncReturnVecU8)
+        );
+        let respond = match (func.fallible, is_sync_return) {
+            (true, true) => format!(
+                "rpc::respond_result(support::IntoSyncReturnResult::into_sync_return_result({}).map(|ret| ret.0))",
+                inner_func_call
+            ),
+            (true, false) => format!("rpc::respond_result({})", inner_func_call),
+            (false, true) => format!("rpc::respond({}.0)", inner_func_call),
+            (false, false) => format!("rpc::respond({})", inner_func_call),
+        };
+        self.rpc_methods.as_mut().unwrap().push(format!(
+            "{:?} => {{
+                {}
+                {}
+            }}",
+            func.name, params, respond
+        ));
+    }
+
+    /// Adds the method of `func` to the `BridgeApi` trait. Fallible functions return an
+    /// [anyhow::Result] whatever the error type of the original function.
+    fn add_bridge_api_method(&mut self, func: &IrFunc, inner_func_call: String) {
+        let mut params = func
+            .inputs
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}api_{}: {}",
+                    if field.borrow == Some(IrBorrow::Mut) {
+                        "mut "
+                    } else {
+                        ""
+                    },
+                    field.name.wire_style(),
+                    field.ty.rust_api_type()
+                )
+            })
+            .collect::<Vec<_>>();
+        let output = if let IrFuncMode::Stream { argument_index } = func.mode {
+            params.insert(
+                argument_index,
+                format!("sink: StreamSink<{}>", func.output.rust_api_type()),
+            );
+            if func.stream_setup {
+                "StreamSetup"
+            } else {
+                "()"
+            }
+            .to_owned()
+        } else {
+            func.output.rust_api_type()
+        };
+        let output = if func.fallible {
+            format!(" -> support::anyhow::Result<{}>", output)
+        } else if output == "()" {
+            String::new()
+        } else {
+            format!(" -> {}", output)
+        };
+        let signature = format!(
+            "{}fn {}(&self, {}){}",
+            if func.debug_only {
+                "#[cfg(debug_assertions)] "
+            } else {
+                ""
+            },
+            func.name,
+            params.join(", "),
+            output
+        );
+        let body = if func.dyn_error {
+            format!("support::dyn_error_result({})", inner_func_call)
+        } else if func.fallible && func.mode == IrFuncMode::Sync {
+            format!(
+                "support::IntoSyncReturnResult::into_sync_return_result({})",
+                inner_func_call
+            )
+        } else {
+            inner_func_call
+        }
+        .replace("task_callback.stream_sink()", "sink");
+
+        self.bridge_api_methods
+            .as_mut()
+            .unwrap()
+            .push((signature, body));
+    }
+
     fn generate_wire_func(&mut self, func: &IrFunc, ir_file: &IrFile) -> String {
         let f = FunctionName::deserialize(&func.name);
         let struct_name = f.struct_name();
@@ -235,7 +695,7 @@ impl Generator {
                 .map(|field| {
                     format!(
                         "{}: {}{}",
-                        field.name.rust_style(),
+                        field.name.wire_style(),
                         field.ty.rust_wire_modifier(),
                         field.ty.rust_wire_type()
                     )
@@ -244,19 +704,31 @@ impl Generator {
         ]
         .concat();
 
+        if let Some(message) = &func.compile_error {
+            return self.extern_func_collector.generate_api(
+                func,
+                &params
+                    .iter()
+                    .map(std::ops::Deref::deref)
+                    .collect::<Vec<_>>(),
+                None,
+                &format!("compile_error!({:?});", message),
+            );
+        }
+
         let mut inner_func_params = [
             vec![],
             func.inputs
                 .iter()
-                .map(|field| format!("api_{}", field.name.rust_style()))
+                .map(|field| format!("api_{}", field.name.wire_style()))
                 .collect::<Vec<_>>(),
         ]
         .concat();
-        if let IrFuncMode::Stream { argument_index } = func.mode {
-            inner_func_params.insert(argument_index, "task_callback.stream_sink()".to_string());
+        if let (IrFuncMode::Stream { argument_index }, None) = (&func.mode, &func.channel) {
+            inner_func_params.insert(*argument_index, "task_callback.stream_sink()".to_string());
         }
         let wrap_info_obj = format!(
-            "WrapInfo{{ debug_name: \"{}\", port: {}, mode: FfiCallMode::{} }}",
+            "WrapInfo::new(\"{}\", {}, FfiCallMode::{})",
             func.name,
             if func.mode.has_port_argument() {
                 "Some(port_)"
@@ -266,22 +738,159 @@ impl Generator {
             func.mode.ffi_call_mode(),
         );
 
-        let code_wire2api = func
+        // Closures, iterators and events are not part of the API file, so they are always called
+        // directly, and so are the functions returning a channel, whose receiver never reaches Dart
+        let via_bridge_api = self.bridge_api_methods.is_some()
+            && func.closure.is_none()
+            && !func.event
+            && func.channel.is_none();
+
+        let mut code_wire2api = func
             .inputs
             .iter()
             .map(|field| {
+                // the validations below call methods on the value, and so do the methods of
+                // opaque types on their receiver, e.g. `lock`, so its type must be known
+                let ty = if field.validations.is_empty()
+                    && !matches!(field.ty, Delegate(IrTypeDelegate::RustOpaque(_)))
+                {
+                    String::new()
+                } else {
+                    format!(": {}", field.ty.rust_api_type())
+                };
                 format!(
-                    "let api_{} = {}.wire2api();",
-                    field.name.rust_style(),
-                    field.name.rust_style()
+                    "let {}api_{}{} = {}.wire2api();",
+                    if field.borrow == Some(IrBorrow::Mut) && !via_bridge_api {
+                        "mut "
+                    } else {
+                        ""
+                    },
+                    field.name.wire_style(),
+                    ty,
+                    field.name.wire_style()
                 )
             })
             .collect::<Vec<_>>()
             .join("");
+        for field in &func.inputs {
+            for validation in &field.validations {
+                code_wire2api += &validation.rust_debug_assertion(
+                    field.name.rust_style(),
+                    &format!("api_{}", field.name.wire_style()),
+                    &field.ty,
+                );
+            }
+        }
+        // must happen before leaving the Dart thread, see `support::clone_closure_handle`
+        match &func.closure {
+            // the closure may well not be `RefUnwindSafe`, but a panic inside it is reported to
+            // Dart like any other, and the closure is never looked at again after it
+            Some(IrFuncClosure::Call(closure)) => {
+                code_wire2api += &format!(
+                    "let {handle} = std::panic::AssertUnwindSafe(unsafe {{ support::clone_closure_handle::<{}>({handle}) }});",
+                    closure.rust_dyn_type(),
+                    handle = inner_func_params[0],
+                );
+            }
+            Some(IrFuncClosure::IteratorNext(iterator)) => {
+                code_wire2api += &format!(
+                    "let {handle} = unsafe {{ support::clone_iterator_handle::<{}>({handle}) }};",
+                    iterator.rust_item_type(),
+                    handle = inner_func_params[0],
+                );
+            }
+            _ => {}
+        }
 
-        let code_call_inner_func = if f.is_non_static_method() || f.is_static_method() {
+        let mut bridge_api_args = inner_func_params.clone();
+        // raw pointers are not `Send`, so they reach the worker thread wrapped, see `support::UnsafeSend`
+        for field in &func.inputs {
+            if let Delegate(IrTypeDelegate::UnsafeOpaquePtr(_)) = &field.ty {
+                let param = format!("api_{}", field.name.wire_style());
+                code_wire2api +=
+                    &format!("let {param} = support::UnsafeSend({param});", param = param);
+                // the `BridgeApi` method is called on the worker, and takes the pointer itself
+                let params = if via_bridge_api {
+                    &mut bridge_api_args
+                } else {
+                    &mut inner_func_params
+                };
+                if let Some(inner_func_param) = params.iter_mut().find(|it| **it == param) {
+                    *inner_func_param = format!("{}.into_inner()", param);
+                }
+            }
+        }
+        for (argument_index, adapter) in &func.io_adapters {
+            inner_func_params[*argument_index] = adapter.wrap(&inner_func_params[*argument_index]);
+        }
+        for field in &func.inputs {
+            if let Some(borrow) = field.borrow {
+                let param = format!("api_{}", field.name.wire_style());
+                if let Some(inner_func_param) =
+                    inner_func_params.iter_mut().find(|it| **it == param)
+                {
+                    *inner_func_param = format!("{}{}", borrow.rust_prefix(), param);
+                }
+            }
+        }
+        let inner_func_call = if func.event {
+            format!(
+                "<{} as flutter_rust_bridge::event::Event>::event_sink().register({})",
+                func.output.rust_api_type(),
+                inner_func_params[0]
+            )
+        } else if let Some(closure) = &func.closure {
+            match closure {
+                IrFuncClosure::Call(_) => format!(
+                    "(**{})({})",
+                    inner_func_params[0],
+                    inner_func_params[1..].join(", ")
+                ),
+                IrFuncClosure::Drop(closure) => format!(
+                    "SyncReturn(unsafe {{ support::drop_closure_handle::<{}>({}) }})",
+                    closure.rust_dyn_type(),
+                    inner_func_params[0]
+                ),
+                IrFuncClosure::OpaqueClone(opaque) => format!(
+                    "SyncReturn(unsafe {{ support::share_opaque_handle::<{}>({}) }})",
+                    opaque.inner, inner_func_params[0]
+                ),
+                IrFuncClosure::OpaqueDrop(opaque) => format!(
+                    "SyncReturn(unsafe {{ support::drop_opaque_handle::<{}>({}) }})",
+                    opaque.inner, inner_func_params[0]
+                ),
+                IrFuncClosure::OpaqueDebug(opaque) => format!(
+                    "SyncReturn(unsafe {{ support::opaque_debug_string::<{}>({}) }})",
+                    opaque.inner, inner_func_params[0]
+                ),
+                IrFuncClosure::IteratorNext(_) => format!(
+                    "support::iterator_next(&{}, {} as usize)",
+                    inner_func_params[0], inner_func_params[1]
+                ),
+                IrFuncClosure::IteratorDrop(iterator) => format!(
+                    "SyncReturn(unsafe {{ support::drop_iterator_handle::<{}>({}) }})",
+                    iterator.rust_item_type(),
+                    inner_func_params[0]
+                ),
+            }
+        } else if let Some(trait_impl) = &func.trait_impl {
+            let params = trait_impl
+                .receiver_arg()
+                .into_iter()
+                .chain(inner_func_params.iter().cloned())
+                .collect::<Vec<_>>();
+            format!(
+                "{}({})",
+                trait_impl.call_path(&func.name),
+                params.join(", ")
+            )
+        } else if f.is_non_static_method() || f.is_static_method() {
             let method_name = if f.is_non_static_method() {
-                inner_func_params[0] = format!("&{}", inner_func_params[0]);
+                inner_func_params[0] = match func.receiver_lock {
+                    // the guard is a temporary, so that the lock is held for the whole call
+                    Some(lock) => lock.borrow_receiver(&inner_func_params[0]),
+                    None => format!("&{}", inner_func_params[0]),
+                };
                 FunctionName::deserialize(&func.name).method_name()
             } else if f.is_static_method() {
                 FunctionName::deserialize(&func.name)
@@ -293,28 +902,177 @@ impl Generator {
                     func.name
                 )
             };
-            TypeRustGenerator::new(func.output.clone(), ir_file).wrap_obj(format!(
+            format!(
                 r"{}::{}({})",
                 struct_name.unwrap(),
                 method_name,
                 inner_func_params.join(", ")
-            ))
+            )
         } else {
-            TypeRustGenerator::new(func.output.clone(), ir_file).wrap_obj(format!(
+            format!(
                 "{}({})",
-                func.name,
+                func.rust_call_path.as_deref().unwrap_or(&func.name),
                 inner_func_params.join(", ")
-            ))
+            )
         };
-        let code_call_inner_func_result = if func.fallible {
-            code_call_inner_func
+        let inner_func_call = match func.to_owned_output {
+            Some(IrFuncOwnedOutput::Reference) => format!("ToOwned::to_owned({})", inner_func_call),
+            Some(IrFuncOwnedOutput::Inner) => format!("{}.map(ToOwned::to_owned)", inner_func_call),
+            None => inner_func_call,
+        };
+        let inner_func_call = match func.channel {
+            Some(channel) if func.fallible => format!(
+                "{}.map(|receiver| {})",
+                inner_func_call,
+                channel.forward("receiver", "task_callback.stream_sink()")
+            ),
+            Some(channel) => channel.forward(&inner_func_call, "task_callback.stream_sink()"),
+            None => inner_func_call,
+        };
+
+        let inner_func_call = if via_bridge_api {
+            self.add_bridge_api_method(func, inner_func_call);
+            format!("bridge_api().{}({})", func.name, bridge_api_args.join(", "))
         } else {
+            inner_func_call
+        };
+        if self.rpc_methods.is_some() {
+            self.add_rpc_method(func, &inner_func_call, via_bridge_api, ir_file);
+        }
+        // Dart awaits the outcome of the setup before listening to the stream
+        let inner_func_call = if func.stream_setup {
+            format!(
+                "{}.map(|setup| task_callback.start_stream(setup))",
+                inner_func_call
+            )
+        } else {
+            inner_func_call
+        };
+
+        // The limit is a `static` inside the extern function, so each function has its own.
+        let (code_concurrency_limit, inner_func_call) = match func.max_concurrency {
+            Some(max) => (
+                format!(
+                    "static CONCURRENCY_LIMIT: support::ConcurrencyLimit = support::ConcurrencyLimit::new({});",
+                    max
+                ),
+                format!("CONCURRENCY_LIMIT.run(|| {})", inner_func_call),
+            ),
+            None => (String::new(), inner_func_call),
+        };
+
+        if func.is_primitive_fast_path() {
+            let return_type = match &func.output {
+                Delegate(IrTypeDelegate::SyncReturn(inner)) => Some(inner.rust_wire_type()),
+                _ => None,
+            };
+            // Dart reads the outcome from the slot it passes, to throw if the function panicked
+            return self.extern_func_collector.generate_api(
+                func,
+                &params
+                    .iter()
+                    .map(std::ops::Deref::deref)
+                    .chain(["outcome_: *mut support::WireSyncReturnStruct"])
+                    .collect::<Vec<_>>(),
+                return_type.as_deref(),
+                &format!(
+                    "let (value, outcome) = {}.wrap_primitive_sync({}, move || {{
+                        {}
+                        {}
+                        {}.0
+                    }});
+                    unsafe {{ *outcome_ = outcome }};
+                    value",
+                    HANDLER_NAME,
+                    wrap_info_obj,
+                    code_concurrency_limit,
+                    code_wire2api,
+                    inner_func_call
+                ),
+            );
+        }
+
+        let inner_func_call = if func.dyn_error && !via_bridge_api {
+            format!("support::dyn_error_result({})", inner_func_call)
+        } else {
+            inner_func_call
+        };
+        if func.msgpack {
+            return self.generate_msgpack_wire_func(
+                func,
+                &wrap_info_obj,
+                &code_concurrency_limit,
+                &inner_func_call,
+                via_bridge_api,
+            );
+        }
+        let inner_func_call = match func.compress {
+            Some(IrFuncCompression::Zstd) if func.fallible => {
+                format!("{}.map(support::compress_zstd)", inner_func_call)
+            }
+            Some(IrFuncCompression::Zstd) => format!("support::compress_zstd({})", inner_func_call),
+            None => inner_func_call,
+        };
+        let code_call_inner_func =
+            TypeRustGenerator::new(func.output.clone(), ir_file).wrap_obj(inner_func_call);
+        let code_call_inner_func_result = if !func.fallible {
             format!("Ok({})", code_call_inner_func)
+        } else if func.mode == IrFuncMode::Sync {
+            format!(
+                "support::IntoSyncReturnResult::into_sync_return_result({})",
+                code_call_inner_func
+            )
+        } else {
+            code_call_inner_func
+        };
+        let code_call_inner_func_result = if let Delegate(IrTypeDelegate::SyncReturn(inner)) =
+            &func.output
+        {
+            if let Optional(_) = inner.as_ref() {
+                format!(
+                    "{}.map(|ret| SyncReturn(ret.0.map(support::IntoSyncReturnBytes::into_sync_return_bytes)))",
+                    code_call_inner_func_result
+                )
+            } else {
+                format!(
+                    "{}.map(|ret| SyncReturn(support::IntoSyncReturnBytes::into_sync_return_bytes(ret.0)))",
+                    code_call_inner_func_result
+                )
+            }
+        } else if matches!(
+            &func.output,
+            Delegate(
+                IrTypeDelegate::Url
+                    | IrTypeDelegate::UrlList
+                    | IrTypeDelegate::CString
+                    | IrTypeDelegate::CStringList
+                    | IrTypeDelegate::ZonedDateTime
+                    | IrTypeDelegate::Closure(_)
+                    | IrTypeDelegate::RustOpaque(_)
+                    | IrTypeDelegate::Iterator(_)
+                    | IrTypeDelegate::UnsafeOpaquePtr(_)
+                    | IrTypeDelegate::BTreeMap { .. }
+                    | IrTypeDelegate::Result { .. },
+            )
+        ) || matches!(&func.output, GeneralList(list) if matches!(*list.inner, Optional(_) | Delegate(IrTypeDelegate::Result { .. })))
+        {
+            format!(
+                "{}.map(|ret| {})",
+                code_call_inner_func_result,
+                TypeRustGenerator::new(func.output.clone(), ir_file).convert_to_dart("ret".into())
+            )
+        } else {
+            code_call_inner_func_result
         };
 
         let (handler_func_name, return_type, code_closure) = match func.mode {
             IrFuncMode::Sync => (
-                "wrap_sync",
+                if matches!(&func.output, Delegate(IrTypeDelegate::SyncReturn(inner)) if matches!(**inner, Optional(_)))
+                {
+                    "wrap_sync_nullable"
+                } else {
+                    "wrap_sync"
+                },
                 Some("support::WireSyncReturnStruct"),
                 format!(
                     "{}
@@ -334,20 +1092,95 @@ impl Generator {
             ),
         };
 
-        self.extern_func_collector.generate(
-            &func.wire_func_name(),
+        self.extern_func_collector.generate_api(
+            func,
             &params
                 .iter()
                 .map(std::ops::Deref::deref)
                 .collect::<Vec<_>>(),
             return_type,
             &format!(
-                "
+                "{}
                 {}.{}({}, move || {{
                     {}
                 }})
                 ",
-                HANDLER_NAME, handler_func_name, wrap_info_obj, code_closure,
+                code_concurrency_limit,
+                HANDLER_NAME,
+                handler_func_name,
+                wrap_info_obj,
+                code_closure,
+            ),
+        )
+    }
+
+    /// The wire function of a function of `--codec msgpack`, taking its inputs as a MessagePack
+    /// array and returning its output as MessagePack, both (de)serialized on the worker.
+    fn generate_msgpack_wire_func(
+        &mut self,
+        func: &IrFunc,
+        wrap_info_obj: &str,
+        code_concurrency_limit: &str,
+        inner_func_call: &str,
+        via_bridge_api: bool,
+    ) -> String {
+        let mut code_decode = if func.inputs.is_empty() {
+            String::new()
+        } else {
+            let (names, types): (Vec<_>, Vec<_>) = func
+                .inputs
+                .iter()
+                .map(|field| {
+                    (
+                        format!(
+                            "{}api_{}",
+                            if field.borrow == Some(IrBorrow::Mut) && !via_bridge_api {
+                                "mut "
+                            } else {
+                                ""
+                            },
+                            field.name.wire_style()
+                        ),
+                        field.ty.rust_api_type(),
+                    )
+                })
+                .unzip();
+            format!(
+                "let ({},): ({},) = support::msgpack_decode(&api_payload_)?;",
+                names.join(", "),
+                types.join(", ")
+            )
+        };
+        for field in &func.inputs {
+            for validation in &field.validations {
+                code_decode += &validation.rust_debug_assertion(
+                    field.name.rust_style(),
+                    &format!("api_{}", field.name.wire_style()),
+                    &field.ty,
+                );
+            }
+        }
+        let code_encode = if func.fallible {
+            format!("support::msgpack_encode(&{}?)", inner_func_call)
+        } else {
+            format!("support::msgpack_encode(&{})", inner_func_call)
+        };
+
+        self.extern_func_collector.generate_api(
+            func,
+            &["port_: i64", "payload_: *mut wire_uint_8_list"],
+            None,
+            &format!(
+                "{}
+                {}.wrap({}, move || {{
+                    let api_payload_: Vec<u8> = payload_.wire2api();
+                    move |task_callback| {{
+                        {}
+                        {}
+                    }}
+                }})
+                ",
+                code_concurrency_limit, HANDLER_NAME, wrap_info_obj, code_decode, code_encode,
             ),
         )
     }
@@ -427,20 +1260,20 @@ impl Generator {
         match ty {
             IrType::StructRef(_)
             | IrType::EnumRef(_)
-            | IrType::Delegate(IrTypeDelegate::PrimitiveEnum { .. }) => {
-                TypeRustGenerator::new(ty.clone(), ir_file)
-                    .wrapper_struct()
-                    .map(|wrapper| {
-                        format!(
-                            r###"
+            | IrType::Delegate(
+                IrTypeDelegate::PrimitiveEnum { .. } | IrTypeDelegate::StringEnum(_),
+            ) => TypeRustGenerator::new(ty.clone(), ir_file)
+                .wrapper_struct()
+                .map(|wrapper| {
+                    format!(
+                        r###"
                 #[derive(Clone)]
                 struct {}({});
                 "###,
-                            wrapper,
-                            ty.rust_api_type(),
-                        )
-                    })
-            }
+                        wrapper,
+                        ty.rust_api_type(),
+                    )
+                }),
             _ => None,
         }
     }
@@ -498,11 +1331,20 @@ pub fn generate_list_allocate_func(
 
 pub struct ExternFuncCollector {
     names: Vec<String>,
+    debug_only_names: Vec<String>,
+    /// Prepended to the exported names, see `--symbol-prefix`.
+    prefix: String,
+    templates: Templates,
 }
 
 impl ExternFuncCollector {
-    fn new() -> Self {
-        ExternFuncCollector { names: vec![] }
+    fn new(prefix: &str, templates: &Templates) -> Self {
+        ExternFuncCollector {
+            names: vec![],
+            debug_only_names: vec![],
+            prefix: prefix.to_owned(),
+            templates: templates.clone(),
+        }
     }
 
     fn generate(
@@ -512,9 +1354,38 @@ impl ExternFuncCollector {
         return_type: Option<&str>,
         body: &str,
     ) -> String {
-        self.names.push(func_name.to_string());
+        self.generate_for(None, func_name, params, return_type, body)
+    }
 
-        format!(
+    /// Like [Self::generate], for the wire function of an API function.
+    fn generate_api(
+        &mut self,
+        func: &IrFunc,
+        params: &[&str],
+        return_type: Option<&str>,
+        body: &str,
+    ) -> String {
+        self.generate_for(
+            Some(func),
+            &func.wire_func_name(),
+            params,
+            return_type,
+            body,
+        )
+    }
+
+    fn generate_for(
+        &mut self,
+        func: Option<&IrFunc>,
+        func_name: &str,
+        params: &[&str],
+        return_type: Option<&str>,
+        body: &str,
+    ) -> String {
+        let func_name = format!("{}{}", self.prefix, func_name);
+        self.names.push(func_name.clone());
+
+        let default = format!(
             r#"
                 #[no_mangle]
                 pub extern "C" fn {}({}) {} {{
@@ -525,6 +1396,103 @@ impl ExternFuncCollector {
             params.join(", "),
             return_type.map_or("".to_string(), |r| format!("-> {}", r)),
             body,
-        )
+        );
+        let code = self
+            .templates
+            .wire_func(&WireFuncData {
+                name: &func_name,
+                func: func.map(|func| func.name.as_str()),
+                mode: func.map(|func| func.mode.ffi_call_mode()),
+                params,
+                return_type,
+                body,
+                default: &default,
+            })
+            .unwrap_or(default);
+        // outside of the template, so that it cannot be left out
+        if func.is_some_and(|func| func.debug_only) {
+            self.debug_only_names.push(func_name);
+            format!("#[cfg(debug_assertions)]\n{}", code)
+        } else {
+            code
+        }
+    }
+}
+
+/// The sink an `#[frb(event)]` type is emitted to, registered by its `*_events` function.
+fn generate_impl_event(ty: &IrType) -> String {
+    format!(
+        "impl flutter_rust_bridge::event::Event for {ty} {{
+            fn event_sink() -> &'static flutter_rust_bridge::event::EventSink<Self> {{
+                static SINK: flutter_rust_bridge::event::EventSink<{ty}> = flutter_rust_bridge::event::EventSink::new();
+                &SINK
+            }}
+        }}
+        ",
+        ty = ty.rust_api_type(),
+    )
+}
+
+/// Asserts that the `StreamSink` of a stream function can be moved to another thread and kept
+/// after the function returns. The helper is named after the function, so that the compiler error
+/// says which function is at fault instead of pointing at a lifetime deep inside the wire function.
+fn generate_stream_sink_check(func: &IrFunc) -> Option<String> {
+    if !matches!(func.mode, IrFuncMode::Stream { .. }) || func.compile_error.is_some() {
+        return None;
+    }
+    let check = format!("{}_stream_sink_must_be_send_and_static", func.name);
+    Some(format!(
+        "{{
+            fn {check}<T: Send + 'static>() {{}}
+            {check}::<StreamSink<{}>>();
+        }}",
+        func.output.rust_api_type(),
+        check = check,
+    ))
+}
+
+/// Whether `func` can be called by `rpc_dispatch`, i.e. it returns once, and its inputs and output
+/// can be (de)serialized with serde: closures, iterators, opaque handles, Dart streams, zero-copy
+/// buffers, URLs, C strings and dates with an offset can not.
+fn is_rpc_compatible(func: &IrFunc, ir_file: &IrFile) -> bool {
+    if matches!(func.mode, IrFuncMode::Stream { .. })
+        || func.closure.is_some()
+        || func.event
+        || func.debug_only
+    {
+        return false;
+    }
+    let mut compatible = true;
+    for ty in func
+        .inputs
+        .iter()
+        .map(|field| &field.ty)
+        .chain([&func.output])
+    {
+        ty.visit_types(
+            &mut |ty| {
+                if matches!(
+                    ty,
+                    Delegate(
+                        IrTypeDelegate::Closure(_)
+                            | IrTypeDelegate::RustOpaque(_)
+                            | IrTypeDelegate::Iterator(_)
+                            | IrTypeDelegate::UnsafeOpaquePtr(_)
+                            | IrTypeDelegate::DartStream(_)
+                            | IrTypeDelegate::ZeroCopyBufferVecPrimitive(_)
+                            | IrTypeDelegate::Url
+                            | IrTypeDelegate::UrlList
+                            | IrTypeDelegate::CString
+                            | IrTypeDelegate::CStringList
+                            | IrTypeDelegate::ZonedDateTime
+                    )
+                ) {
+                    compatible = false;
+                }
+                !compatible
+            },
+            ir_file,
+        );
     }
+    compatible
 }
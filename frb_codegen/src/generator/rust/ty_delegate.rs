@@ -1,7 +1,5 @@
 use crate::generator::rust::ty::*;
-use crate::generator::rust::{
-    generate_list_allocate_func, ExternFuncCollector, TypeGeneralListGenerator,
-};
+use crate::generator::rust::{ExternFuncCollector, TypeRustGenerator};
 use crate::ir::*;
 use crate::type_rust_generator_struct;
 use crate::utils::BlockIndex;
@@ -10,7 +8,7 @@ type_rust_generator_struct!(TypeDelegateGenerator, IrTypeDelegate);
 
 macro_rules! delegate_enum {
     ($self:ident, $func:ident($( $tokens:tt )*), $ret:expr) => {
-        if let IrTypeDelegate::PrimitiveEnum { ir, .. } = &$self.ir {
+        if let IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) = &$self.ir {
             super::TypeEnumRefGenerator {
                 ir: ir.clone(),
                 context: $self.context.clone(),
@@ -28,11 +26,73 @@ impl TypeRustGeneratorTrait for TypeDelegateGenerator<'_> {
             IrTypeDelegate::String => "let vec: Vec<u8> = self.wire2api();
             String::from_utf8_lossy(&vec).into_owned()"
                 .into(),
-            IrTypeDelegate::SyncReturnVecU8 => "/*unsupported*/".into(),
+            IrTypeDelegate::SyncReturnVecU8
+            | IrTypeDelegate::SyncReturn(_)
+            | IrTypeDelegate::Closure(_)
+            | IrTypeDelegate::Iterator(_) => "/*unsupported*/".into(),
             IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 "ZeroCopyBuffer(self.wire2api())".into()
             }
-            IrTypeDelegate::StringList => TypeGeneralListGenerator::WIRE2API_BODY.to_string(),
+            // must happen before leaving the Dart thread, see `support::clone_opaque_handle`
+            IrTypeDelegate::RustOpaque(opaque) => format!(
+                "unsafe {{ support::clone_opaque_handle::<{}>(self) }}",
+                opaque.inner
+            ),
+            // the bridge only carries the address, whatever it points to is up to the function
+            IrTypeDelegate::UnsafeOpaquePtr(_) => {
+                format!("self as usize as {}", self.ir.rust_api_type())
+            }
+            // must happen before leaving the Dart thread, see `support::take_dart_stream`
+            IrTypeDelegate::DartStream(_) => "unsafe { support::take_dart_stream(self) }".into(),
+            IrTypeDelegate::StringList => "let wrap = unsafe { support::box_from_leak_ptr(self) };
+            let bytes: Vec<u8> = wrap.bytes.wire2api();
+            let ends: Vec<i32> = wrap.ends.wire2api();
+            support::unpack_strings(&bytes, &ends).expect(\"invalid packed strings\")"
+                .into(),
+            IrTypeDelegate::Url => "let string: String = self.wire2api();
+            url::Url::parse(&string).expect(\"invalid URL\")"
+                .into(),
+            IrTypeDelegate::UrlList => "let vec: Vec<String> = self.wire2api();
+            vec.iter().map(|string| url::Url::parse(string).expect(\"invalid URL\")).collect()"
+                .into(),
+            IrTypeDelegate::CString => "let string: String = self.wire2api();
+            std::ffi::CString::new(string).expect(\"a CString cannot contain a nul character\")"
+                .into(),
+            IrTypeDelegate::CStringList => "let vec: Vec<String> = self.wire2api();
+            vec.into_iter()
+                .map(|string| std::ffi::CString::new(string).expect(\"a CString cannot contain a nul character\"))
+                .collect()"
+                .into(),
+            IrTypeDelegate::ZonedDateTime => "let vec: Vec<i64> = self.wire2api();
+            let utc = chrono::NaiveDateTime::from_timestamp_opt(
+                vec[0].div_euclid(1_000_000),
+                (vec[0].rem_euclid(1_000_000) * 1_000) as u32,
+            )
+            .expect(\"invalid date time\");
+            let offset = chrono::FixedOffset::east_opt(vec[1] as i32).expect(\"invalid offset\");
+            chrono::DateTime::from_utc(utc, offset)"
+                .into(),
+            IrTypeDelegate::BTreeMap { key, value } => format!(
+                "let wrap = unsafe {{ support::box_from_leak_ptr(self) }};
+                let keys: Vec<{}> = wrap.keys.wire2api();
+                let values: Vec<{}> = wrap.values.wire2api();
+                keys.into_iter().zip(values).collect()",
+                key.rust_api_type(),
+                value.rust_api_type()
+            ),
+            IrTypeDelegate::Result { ok, err } => format!(
+                "let wrap = unsafe {{ support::box_from_leak_ptr(self) }};
+                let ok: Option<{}> = wrap.ok.wire2api();
+                let err: Option<{}> = wrap.err.wire2api();
+                match (wrap.tag, ok, err) {{
+                    (0, Some(ok), _) => Ok(ok),
+                    (1, _, Some(err)) => Err(err),
+                    _ => unreachable!(\"Invalid variant for Result: {{}}\", wrap.tag),
+                }}",
+                ok.rust_api_type(),
+                err.rust_api_type()
+            ),
+            IrTypeDelegate::Transparent(transparent) => transparent.wrap("self.wire2api()"),
             IrTypeDelegate::PrimitiveEnum { ir, .. } => {
                 let enu = ir.get(self.context.ir_file);
                 let variants = enu
@@ -50,15 +110,57 @@ impl TypeRustGeneratorTrait for TypeDelegateGenerator<'_> {
                     variants, enu.name
                 )
             }
+            IrTypeDelegate::StringEnum(ir) => {
+                let enu = ir.get(self.context.ir_file);
+                let variants = enu
+                    .variants()
+                    .iter()
+                    .map(|variant| {
+                        format!("{:?} => {}::{},", variant.name.raw, enu.name, variant.name)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(
+                    "let string: String = self.wire2api();
+                    match string.as_str() {{
+                        {}
+                        _ => unreachable!(\"Invalid variant for {}: {{}}\", string),
+                    }}",
+                    variants, enu.name
+                )
+            }
         })
     }
 
     fn wire_struct_fields(&self) -> Option<Vec<String>> {
         match &self.ir {
-            ty @ IrTypeDelegate::StringList => Some(vec![
-                format!("ptr: *mut *mut {}", ty.get_delegate().rust_wire_type()),
-                "len: i32".to_owned(),
+            IrTypeDelegate::StringList => Some(vec![
+                "bytes: *mut wire_uint_8_list".to_owned(),
+                "ends: *mut wire_int_32_list".to_owned(),
             ]),
+            IrTypeDelegate::BTreeMap { key, value } => {
+                let (keys, values) = (IrType::list_of(key), IrType::list_of(value));
+                Some(vec![
+                    format!(
+                        "keys: {}{}",
+                        keys.rust_wire_modifier(),
+                        keys.rust_wire_type()
+                    ),
+                    format!(
+                        "values: {}{}",
+                        values.rust_wire_modifier(),
+                        values.rust_wire_type()
+                    ),
+                ])
+            }
+            IrTypeDelegate::Result { ok, err } => {
+                let (ok, err) = (IrType::optional_of(ok), IrType::optional_of(err));
+                Some(vec![
+                    "tag: i32".to_owned(),
+                    format!("ok: {}{}", ok.rust_wire_modifier(), ok.rust_wire_type()),
+                    format!("err: {}{}", err.rust_wire_modifier(), err.rust_wire_type()),
+                ])
+            }
             _ => None,
         }
     }
@@ -69,13 +171,94 @@ impl TypeRustGeneratorTrait for TypeDelegateGenerator<'_> {
         block_index: BlockIndex,
     ) -> String {
         match &self.ir {
-            list @ IrTypeDelegate::StringList => generate_list_allocate_func(
-                collector,
-                &self.ir.safe_ident(),
-                list,
-                &list.get_delegate(),
-                block_index,
-            ),
+            IrTypeDelegate::StringList => {
+                let wire_type = self.ir.rust_wire_type();
+                collector.generate(
+                    &format!("new_{}_{}", self.ir.safe_ident(), block_index),
+                    &[
+                        "bytes: *mut wire_uint_8_list",
+                        "ends: *mut wire_int_32_list",
+                    ],
+                    Some(&format!("{}{}", self.ir.rust_wire_modifier(), wire_type)),
+                    &format!("support::new_leak_box_ptr({} {{ bytes, ends }})", wire_type),
+                )
+            }
+            IrTypeDelegate::DartStream(inner) => {
+                let safe_ident = self.ir.safe_ident();
+                let item_type = inner.rust_api_type();
+                let item_param = format!(
+                    "item: {}{}",
+                    inner.rust_wire_modifier(),
+                    inner.rust_wire_type()
+                );
+                [
+                    collector.generate(
+                        &format!("new_{}_{}", safe_ident, block_index),
+                        &[],
+                        Some("i64"),
+                        &format!("support::new_dart_stream::<{}>()", item_type),
+                    ),
+                    collector.generate(
+                        &format!("add_{}_{}", safe_ident, block_index),
+                        &["handle: i64", item_param.as_str()],
+                        None,
+                        &format!(
+                            "unsafe {{ support::dart_stream_add::<{}>(handle, item.wire2api()) }}",
+                            item_type
+                        ),
+                    ),
+                    collector.generate(
+                        &format!("close_{}_{}", safe_ident, block_index),
+                        &["handle: i64"],
+                        None,
+                        &format!(
+                            "unsafe {{ support::close_dart_stream::<{}>(handle) }}",
+                            item_type
+                        ),
+                    ),
+                ]
+                .concat()
+            }
+            IrTypeDelegate::BTreeMap { key, value } => {
+                let (keys, values) = (IrType::list_of(key), IrType::list_of(value));
+                let keys_param = format!(
+                    "keys: {}{}",
+                    keys.rust_wire_modifier(),
+                    keys.rust_wire_type()
+                );
+                let values_param = format!(
+                    "values: {}{}",
+                    values.rust_wire_modifier(),
+                    values.rust_wire_type()
+                );
+                let wire_type = self.ir.rust_wire_type();
+                collector.generate(
+                    &format!("new_{}_{}", self.ir.safe_ident(), block_index),
+                    &[keys_param.as_str(), values_param.as_str()],
+                    Some(&format!("{}{}", self.ir.rust_wire_modifier(), wire_type)),
+                    &format!(
+                        "support::new_leak_box_ptr({} {{ keys, values }})",
+                        wire_type
+                    ),
+                )
+            }
+            // the field of the other variant is null
+            IrTypeDelegate::Result { ok, err } => {
+                let (ok, err) = (IrType::optional_of(ok), IrType::optional_of(err));
+                let ok_param = format!("ok: {}{}", ok.rust_wire_modifier(), ok.rust_wire_type());
+                let err_param =
+                    format!("err: {}{}", err.rust_wire_modifier(), err.rust_wire_type());
+                let wire_type = self.ir.rust_wire_type();
+                collector.generate(
+                    &format!("new_{}_{}", self.ir.safe_ident(), block_index),
+                    &["tag: i32", ok_param.as_str(), err_param.as_str()],
+                    Some(&format!("{}{}", self.ir.rust_wire_modifier(), wire_type)),
+                    &format!(
+                        "support::new_leak_box_ptr({} {{ tag, ok, err }})",
+                        wire_type
+                    ),
+                )
+            }
             _ => "".to_string(),
         }
     }
@@ -106,11 +289,115 @@ impl TypeRustGeneratorTrait for TypeDelegateGenerator<'_> {
                 name, self_ref, variants
             );
         }
+        if let IrTypeDelegate::StringEnum(ir) = &self.ir {
+            let src = ir.get(self.context.ir_file);
+            let (name, self_path): (&str, &str) = match &src.wrapper_name {
+                Some(wrapper) => (wrapper, &src.name),
+                None => (&src.name, "Self"),
+            };
+            let self_ref = self.self_access("self".to_owned());
+            let variants = src
+                .variants()
+                .iter()
+                .map(|variant| {
+                    format!("{}::{} => {:?},", self_path, variant.name, variant.name.raw)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            return format!(
+                "impl support::IntoDart for {} {{
+                    fn into_dart(self) -> support::DartCObject {{
+                        match {} {{
+                            {}
+                        }}.to_owned().into_dart()
+                    }}
+                }}
+                impl support::IntoDartExceptPrimitive for {0} {{}}",
+                name, self_ref, variants
+            );
+        }
+        if let IrTypeDelegate::Transparent(transparent) = &self.ir {
+            let inner = TypeRustGenerator::new(*transparent.inner.clone(), self.context.ir_file);
+            return format!(
+                "impl support::IntoDart for {name} {{
+                    fn into_dart(self) -> support::DartCObject {{
+                        {}
+                    }}
+                }}
+                impl support::IntoDartExceptPrimitive for {name} {{}}
+                ",
+                inner.convert_to_dart(inner.wrap_obj(transparent.access("self"))),
+                name = transparent.name,
+            );
+        }
 
         "".into()
     }
 
+    fn convert_to_dart(&self, obj: String) -> String {
+        match &self.ir {
+            IrTypeDelegate::Url => format!("{}.to_string().into_dart()", obj),
+            IrTypeDelegate::UrlList => format!(
+                "{}.iter().map(ToString::to_string).collect::<Vec<_>>().into_dart()",
+                obj
+            ),
+            // Dart strings are UTF-16, so bytes which are not UTF-8 cannot be kept as they are
+            IrTypeDelegate::CString => format!("{}.to_string_lossy().into_owned().into_dart()", obj),
+            IrTypeDelegate::CStringList => format!(
+                "{}.iter().map(|string| string.to_string_lossy().into_owned()).collect::<Vec<_>>().into_dart()",
+                obj
+            ),
+            IrTypeDelegate::ZonedDateTime => format!(
+                "{{ let time = {}; vec![time.timestamp_micros(), time.offset().local_minus_utc() as i64] }}.into_dart()",
+                obj
+            ),
+            IrTypeDelegate::Closure(closure) => format!(
+                "support::new_closure_handle::<{}>({}).into_dart()",
+                closure.rust_dyn_type(),
+                obj
+            ),
+            IrTypeDelegate::RustOpaque(_) => {
+                format!("support::new_opaque_handle({}).into_dart()", obj)
+            }
+            IrTypeDelegate::Iterator(iterator) => format!(
+                "support::new_iterator_handle::<{}>({}).into_dart()",
+                iterator.rust_item_type(),
+                obj
+            ),
+            IrTypeDelegate::UnsafeOpaquePtr(_) => format!("({} as usize as i64).into_dart()", obj),
+            // in key order, which the Dart side keeps by inserting the entries one by one
+            IrTypeDelegate::BTreeMap { key, value } => {
+                let key = TypeRustGenerator::new(*key.clone(), self.context.ir_file);
+                let value = TypeRustGenerator::new(*value.clone(), self.context.ir_file);
+                format!(
+                    "{}.into_iter().map(|(k, v)| vec![{}, {}]).collect::<Vec<_>>().into_dart()",
+                    obj,
+                    key.convert_to_dart(key.wrap_obj("k".to_owned())),
+                    value.convert_to_dart(value.wrap_obj("v".to_owned())),
+                )
+            }
+            // the same as an enum of two variants with one field each
+            IrTypeDelegate::Result { ok, err } => {
+                let ok = TypeRustGenerator::new(*ok.clone(), self.context.ir_file);
+                let err = TypeRustGenerator::new(*err.clone(), self.context.ir_file);
+                format!(
+                    "match {} {{
+                        Ok(v) => vec![0i32.into_dart(), {}],
+                        Err(e) => vec![1i32.into_dart(), {}],
+                    }}.into_dart()",
+                    obj,
+                    ok.convert_to_dart(ok.wrap_obj("v".to_owned())),
+                    err.convert_to_dart(err.wrap_obj("e".to_owned())),
+                )
+            }
+            _ => delegate_enum!(self, convert_to_dart(obj), format!("{}.into_dart()", obj)),
+        }
+    }
+
     fn imports(&self) -> Option<String> {
+        if let IrTypeDelegate::Transparent(transparent) = &self.ir {
+            return Some(format!("use {};", transparent.path.join("::")));
+        }
         delegate_enum!(self, imports(), None)
     }
 
@@ -22,9 +22,13 @@ impl TypeRustGeneratorTrait for TypeEnumRefGenerator<'_> {
                         .iter()
                         .map(|field| {
                             if st.is_fields_named {
-                                format!("{0}: ans.{0}.wire2api()", field.name.rust_style())
+                                format!(
+                                    "{}: ans.{}.wire2api()",
+                                    field.name.rust_style(),
+                                    field.name.wire_style()
+                                )
                             } else {
-                                format!("ans.{}.wire2api()", field.name.rust_style())
+                                format!("ans.{}.wire2api()", field.name.wire_style())
                             }
                         })
                         .collect();
@@ -71,7 +75,7 @@ impl TypeRustGeneratorTrait for TypeEnumRefGenerator<'_> {
                         .map(|field| {
                             format!(
                                 "{}: {}{},",
-                                field.name.rust_style(),
+                                field.name.wire_style(),
                                 field.ty.rust_wire_modifier(),
                                 field.ty.rust_wire_type()
                             )
@@ -253,7 +257,7 @@ impl TypeRustGeneratorTrait for TypeEnumRefGenerator<'_> {
                 let body: Vec<_> = if let IrVariantKind::Struct(st) = &variant.kind {
                     st.fields
                         .iter()
-                        .map(|field| format!("{}: {}", field.name.rust_style(), init_of(&field.ty)))
+                        .map(|field| format!("{}: {}", field.name.wire_style(), init_of(&field.ty)))
                         .collect()
                 } else {
                     return None;
@@ -46,6 +46,22 @@ impl TypeRustGeneratorTrait for TypeGeneralListGenerator<'_> {
             .unwrap_or(obj)
     }
 
+    fn convert_to_dart(&self, obj: String) -> String {
+        match &*self.ir.inner {
+            // each item on its own, for `None` to become `null` in `List<T?>`
+            IrType::Optional(_)
+            | IrType::Delegate(IrTypeDelegate::ZonedDateTime | IrTypeDelegate::Result { .. }) => {
+                let inner = TypeRustGenerator::new(*self.ir.inner.clone(), self.context.ir_file);
+                format!(
+                    "{}.into_iter().map(|v| {}).collect::<Vec<_>>().into_dart()",
+                    obj,
+                    inner.convert_to_dart("v".to_owned())
+                )
+            }
+            _ => format!("{}.into_dart()", obj),
+        }
+    }
+
     fn allocate_funcs(
         &self,
         collector: &mut ExternFuncCollector,
@@ -19,6 +19,23 @@ impl TypeRustGeneratorTrait for TypeOptionalGenerator<'_> {
                 wrapper,
                 inner.self_access("v".to_owned())
             ),
+            None if matches!(*self.ir.inner, IrType::Delegate(IrTypeDelegate::Url)) => {
+                format!("{}.map(|v| v.to_string())", obj)
+            }
+            None if matches!(*self.ir.inner, IrType::Delegate(IrTypeDelegate::CString)) => {
+                format!("{}.map(|v| v.to_string_lossy().into_owned())", obj)
+            }
+            None if matches!(
+                *self.ir.inner,
+                IrType::Delegate(
+                    IrTypeDelegate::ZonedDateTime
+                        | IrTypeDelegate::BTreeMap { .. }
+                        | IrTypeDelegate::Result { .. }
+                )
+            ) || matches!(&*self.ir.inner, IrType::GeneralList(list) if matches!(*list.inner, IrType::Optional(_))) =>
+            {
+                format!("{}.map(|v| {})", obj, inner.convert_to_dart("v".to_owned()))
+            }
             None => obj,
         };
         format!("{}.into_dart()", obj)
@@ -19,20 +19,66 @@ impl TypeRustGeneratorTrait for TypeStructRefGenerator<'_> {
                     } else {
                         String::new()
                     },
-                    field.name.rust_style()
+                    field.name.wire_style()
                 )
             })
             .collect::<Vec<_>>()
             .join(",");
 
         let (left, right) = api_struct.brackets_pair();
-        Some(format!(
-            "{}{}{}{}",
-            self.ir.rust_api_type(),
-            left,
-            fields_str,
-            right
-        ))
+        let ans = if api_struct.skipped_fields.is_empty() {
+            format!("{}{}{}{}", self.ir.rust_api_type(), left, fields_str, right)
+        } else if api_struct.skipped_fields.iter().all(|field| !field.private) {
+            let defaults = api_struct
+                .skipped_fields
+                .iter()
+                .map(|field| format!("{}: Default::default()", field.name));
+            let fields = (!fields_str.is_empty())
+                .then(|| fields_str.clone())
+                .into_iter()
+                .chain(defaults)
+                .collect::<Vec<_>>();
+            format!("{} {{ {} }}", self.ir.rust_api_type(), fields.join(","))
+        } else {
+            // the skipped fields may be private, so they are left to the `Default` of the struct
+            let assignments = api_struct
+                .fields
+                .iter()
+                .map(|field| {
+                    format!(
+                        "ans.{} = self.{}.wire2api();",
+                        field.name.rust_style(),
+                        field.name.wire_style()
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "{{ let mut ans = <{} as Default>::default(); {} ans }}",
+                self.ir.rust_api_type(),
+                assignments.join("\n")
+            )
+        };
+
+        let checks = api_struct
+            .fields
+            .iter()
+            .enumerate()
+            .flat_map(|(i, field)| {
+                let access = if api_struct.is_fields_named {
+                    format!("ans.{}", field.name.rust_style())
+                } else {
+                    format!("ans.{}", i)
+                };
+                field.validations.iter().map(move |validation| {
+                    validation.rust_debug_assertion(field.name.rust_style(), &access, &field.ty)
+                })
+            })
+            .collect::<Vec<_>>();
+        Some(if checks.is_empty() {
+            ans
+        } else {
+            format!("let ans = {};\n{}\nans", ans, checks.join("\n"))
+        })
     }
 
     fn wire_struct_fields(&self) -> Option<Vec<String>> {
@@ -43,7 +89,7 @@ impl TypeRustGeneratorTrait for TypeStructRefGenerator<'_> {
                 .map(|field| {
                     format!(
                         "{}: {}{}",
-                        field.name.rust_style(),
+                        field.name.wire_style(),
                         field.ty.rust_wire_modifier(),
                         field.ty.rust_wire_type()
                     )
@@ -148,7 +194,7 @@ impl TypeRustGeneratorTrait for TypeStructRefGenerator<'_> {
                 .map(|field| {
                     format!(
                         "{}: {},",
-                        field.name.rust_style(),
+                        field.name.wire_style(),
                         if field.ty.rust_wire_is_pointer() {
                             "core::ptr::null_mut()"
                         } else {
@@ -183,3 +229,121 @@ impl TypeRustGeneratorTrait for TypeStructRefGenerator<'_> {
         }
     }
 }
+
+/// Structs nested deeper than this in a sample are not worth it.
+const MAX_SAMPLE_DEPTH: usize = 8;
+
+/// `impl {name} { pub fn sample() -> Self }`, or [None] if some field cannot be made up, or if the
+/// struct is mirrored from another crate and so cannot have inherent methods here.
+pub fn generate_sample_impl(src: &IrStruct, ir_file: &IrFile) -> Option<String> {
+    let value = rust_struct_sample(src, ir_file, 0)?;
+    Some(format!(
+        "impl {} {{
+            /// Placeholder data, e.g. for tests.
+            pub fn sample() -> Self {{
+                {}
+            }}
+        }}
+        ",
+        src.name, value
+    ))
+}
+
+fn rust_struct_sample(src: &IrStruct, ir_file: &IrFile, depth: usize) -> Option<String> {
+    if src.wrapper_name.is_some()
+        || src.skipped_fields.iter().any(|field| field.private)
+        || depth >= MAX_SAMPLE_DEPTH
+    {
+        return None;
+    }
+    let mut fields = src
+        .fields
+        .iter()
+        .map(|field| {
+            let value = rust_sample(&field.ty, ir_file, depth + 1)?;
+            Some(if src.is_fields_named {
+                format!("{}: {}", field.name.rust_style(), value)
+            } else {
+                value
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    fields.extend(
+        src.skipped_fields
+            .iter()
+            .map(|field| format!("{}: Default::default()", field.name)),
+    );
+    let (left, right) = src.brackets_pair();
+    Some(format!(
+        "{}{}{}{}",
+        src.name,
+        left,
+        fields.join(", "),
+        right
+    ))
+}
+
+/// A Rust expression of placeholder data of the type, or [None] if it cannot be made up, e.g. for
+/// closures. Optional values and lists which could hold structs are left empty, so that the
+/// samples of recursive structs end.
+fn rust_sample(ty: &IrType, ir_file: &IrFile, depth: usize) -> Option<String> {
+    Some(match ty {
+        IrType::Primitive(primitive) => match primitive {
+            IrTypePrimitive::Bool => "true".to_owned(),
+            IrTypePrimitive::F32 | IrTypePrimitive::F64 => "1.5".to_owned(),
+            IrTypePrimitive::Unit => "()".to_owned(),
+            _ => "42".to_owned(),
+        },
+        IrType::PrimitiveList(list) => match list.primitive {
+            IrTypePrimitive::F32 | IrTypePrimitive::F64 => "vec![1.5, 2.5]".to_owned(),
+            _ => "vec![1, 2, 3]".to_owned(),
+        },
+        IrType::Optional(optional) if optional.inner.holds_struct(ir_file) => "None".to_owned(),
+        IrType::Optional(optional) => rust_sample(&optional.inner, ir_file, depth)
+            .map(|value| format!("Some({})", value))
+            .unwrap_or_else(|| "None".to_owned()),
+        IrType::Boxed(boxed) => {
+            let value = rust_sample(&boxed.inner, ir_file, depth)?;
+            if boxed.exist_in_real_api {
+                format!("Box::new({})", value)
+            } else {
+                value
+            }
+        }
+        IrType::GeneralList(list) if list.inner.holds_struct(ir_file) => "vec![]".to_owned(),
+        IrType::GeneralList(list) => format!("vec![{}]", rust_sample(&list.inner, ir_file, depth)?),
+        IrType::StructRef(struct_ref) => {
+            let st = struct_ref.get(ir_file);
+            rust_struct_sample(st, ir_file, depth)?;
+            format!("{}::sample()", st.name)
+        }
+        IrType::EnumRef(enum_ref) => {
+            let enu = enum_ref.get(ir_file);
+            if enu.is_struct() {
+                return None;
+            }
+            format!("{}::{}", enu.name, enu.variants().first()?.name)
+        }
+        IrType::Delegate(delegate) => match delegate {
+            IrTypeDelegate::String => "\"frb\".to_owned()".to_owned(),
+            IrTypeDelegate::StringList => {
+                "vec![\"flutter\".to_owned(), \"rust\".to_owned()]".to_owned()
+            }
+            IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) => {
+                rust_sample(&IrType::EnumRef(ir.clone()), ir_file, depth)?
+            }
+            IrTypeDelegate::BTreeMap { value, .. } if value.holds_struct(ir_file) => {
+                "Default::default()".to_owned()
+            }
+            IrTypeDelegate::BTreeMap { key, value } => format!(
+                "std::collections::BTreeMap::from([({}, {})])",
+                rust_sample(key, ir_file, depth)?,
+                rust_sample(value, ir_file, depth)?
+            ),
+            IrTypeDelegate::Transparent(transparent) => {
+                transparent.wrap(&rust_sample(&transparent.inner, ir_file, depth)?)
+            }
+            _ => return None,
+        },
+    })
+}
@@ -6,4 +6,23 @@ pub struct IrField {
     pub name: IrIdent,
     pub is_final: bool,
     pub comments: Vec<IrComment>,
+    pub validations: Vec<IrValidation>,
+    /// How a function input is passed to the Rust function, which takes it by value if `None`.
+    /// It is still transferred by value, and changes made through `&mut` stay on the Rust side.
+    pub borrow: Option<IrBorrow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrBorrow {
+    Shared,
+    Mut,
+}
+
+impl IrBorrow {
+    pub fn rust_prefix(&self) -> &'static str {
+        match self {
+            IrBorrow::Shared => "&",
+            IrBorrow::Mut => "&mut ",
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::error::Error;
 use crate::utils::mod_from_rust_path;
 use crate::{generator, ir::*, Opts};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
 pub type IrStructPool = HashMap<String, IrStruct>;
@@ -11,7 +12,13 @@ pub struct IrFile {
     pub funcs: Vec<IrFunc>,
     pub struct_pool: IrStructPool,
     pub enum_pool: IrEnumPool,
-    pub has_executor: bool,
+    /// Name of the handler provided by the Rust input, if any, used instead of a default one.
+    pub custom_handler: Option<String>,
+    /// Memoized results of [IrFile::distinct_types], keyed by its arguments.
+    /// Must be reset when `funcs` is replaced.
+    pub distinct_types_cache: RefCell<HashMap<(bool, bool), Vec<IrType>>>,
+    /// Functions left out by `--skip-unresolvable`, by path, along with the reason.
+    pub skipped_funcs: Vec<(String, String)>,
 }
 
 impl IrFile {
@@ -31,7 +38,45 @@ impl IrFile {
             if include_func_output {
                 func.output.visit_types(f, self);
             }
+            // the MessagePack buffers actually cross the FFI boundary, see `--codec msgpack`
+            if func.msgpack && (include_func_inputs || include_func_output) {
+                f(&IrType::PrimitiveList(IrTypePrimitiveList {
+                    primitive: IrTypePrimitive::U8,
+                }));
+            }
+        }
+    }
+
+    /// The types of the functions of `--codec msgpack`, for which Dart gets functions converting
+    /// values to and from MessagePack.
+    pub fn msgpack_types(
+        &self,
+        include_func_inputs: bool,
+        include_func_output: bool,
+    ) -> Vec<IrType> {
+        let mut interner = IrTypeInterner::default();
+        for func in self.funcs.iter().filter(|func| func.msgpack) {
+            if include_func_inputs {
+                for field in &func.inputs {
+                    field.ty.visit_types(&mut |ty| interner.intern(ty), self);
+                }
+            }
+            if include_func_output {
+                func.output.visit_types(&mut |ty| interner.intern(ty), self);
+            }
+        }
+        interner.into_types()
+    }
+
+    /// Names of the bridged traits, in order of first appearance.
+    pub fn trait_names(&self) -> Vec<&str> {
+        let mut ans = Vec::new();
+        for name in self.funcs.iter().filter_map(|f| f.trait_impl.as_ref()) {
+            if !ans.contains(&name.trait_name.as_str()) {
+                ans.push(name.trait_name.as_str());
+            }
         }
+        ans
     }
 
     pub fn get_c_struct_names(&self) -> Vec<String> {
@@ -54,34 +99,53 @@ impl IrFile {
         include_func_inputs: bool,
         include_func_output: bool,
     ) -> Vec<IrType> {
-        let mut seen_idents = HashSet::new();
-        let mut ans = Vec::new();
+        let key = (include_func_inputs, include_func_output);
+        if let Some(ans) = self.distinct_types_cache.borrow().get(&key) {
+            return ans.clone();
+        }
+
+        let mut interner = IrTypeInterner::default();
         self.visit_types(
-            &mut |ty| {
-                let ident = ty.safe_ident();
-                let contains = seen_idents.contains(&ident);
-                if !contains {
-                    seen_idents.insert(ident);
-                    ans.push(ty.clone());
-                }
-                contains
-            },
+            &mut |ty| interner.intern(ty),
             include_func_inputs,
             include_func_output,
         );
+        let ans = interner.into_types();
 
-        // make the output change less when input change
-        ans.sort_by_key(|ty| ty.safe_ident());
-
+        self.distinct_types_cache
+            .borrow_mut()
+            .insert(key, ans.clone());
         ans
     }
 
+    /// Names of the structs compared by value in Dart, as they make up the items of a stream marked
+    /// `#[frb(distinct)]`.
+    pub fn distinct_struct_names(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        for func in self.funcs.iter().filter(|func| func.distinct) {
+            func.output.visit_types(
+                &mut |ty| match ty {
+                    IrType::StructRef(st) => !names.insert(st.name.clone()),
+                    _ => false,
+                },
+                self,
+            );
+        }
+        names
+    }
+
+    /// The generated Rust code, whose extern functions include the hand-written ones of
+    /// `--extern-c-input`, so that they are exported and bundled along with the others.
     pub fn generate_rust(&self, config: &Opts) -> generator::rust::Output {
-        generator::rust::generate(
+        let mut output = generator::rust::generate(
             self,
             &mod_from_rust_path(&config.rust_input_path, &config.rust_crate_dir),
-            config.block_index,
-        )
+            config,
+        );
+        output
+            .extern_func_names
+            .extend(config.extern_c_func_names());
+        output
     }
 
     pub fn generate_dart(
@@ -90,25 +154,28 @@ impl IrFile {
     ) -> Result<(generator::dart::Output, bool), anyhow::Error> {
         let (generated_dart, needs_freezed) = generator::dart::generate(
             self,
-            &config.dart_api_class_name(),
-            &config.dart_api_impl_class_name(),
-            &config.dart_wire_class_name(),
+            config,
             config
                 .dart_output_path_name()
                 .ok_or_else(|| Error::str("Invalid dart_output_path_name"))?,
-            config.block_index,
         );
+        if config.pure_dart {
+            generator::dart::check_pure_dart(&generated_dart)?;
+        }
         Ok((generated_dart, needs_freezed))
     }
     /// get all symbols(function names) defined explicitly or implictily
     pub fn get_all_symbols(&self, config: &Opts) -> Vec<String> {
-        let mut generated_rust = self.generate_rust(config);
+        let generated_rust = self.generate_rust(config);
+        let debug_only_func_names = &generated_rust.debug_only_func_names;
 
-        generated_rust.extern_func_names = generated_rust
+        generated_rust
             .extern_func_names
-            .into_iter()
-            .filter(|s| *s != "free_WireSyncReturnStruct")
-            .collect::<Vec<_>>();
-        generated_rust.extern_func_names
+            .iter()
+            .filter(|s| **s != format!("{}free_WireSyncReturnStruct", config.symbol_prefix))
+            // missing from release builds, so not to be exported by the symbol lists
+            .filter(|s| !debug_only_func_names.contains(s))
+            .cloned()
+            .collect()
     }
 }
@@ -1,4 +1,5 @@
 use crate::ir::*;
+use crate::method_utils::FunctionName;
 
 #[derive(Debug, Clone)]
 pub struct IrFunc {
@@ -8,12 +9,298 @@ pub struct IrFunc {
     pub fallible: bool,
     pub mode: IrFuncMode,
     pub comments: Vec<IrComment>,
+    pub trait_impl: Option<IrTraitImpl>,
+    /// Whether the inputs and the output are carried as a single MessagePack buffer each, from
+    /// `--codec msgpack`, see [IrFunc::supports_msgpack].
+    pub msgpack: bool,
+    pub retry: Option<IrFuncRetry>,
+    pub closure: Option<IrFuncClosure>,
+    /// Whether the function returns `Result<T, Box<dyn Error>>`, whose error type is looked up at runtime.
+    pub dyn_error: bool,
+    /// How the method of an opaque struct gets at the value behind the `RustOpaque` it is called
+    /// on, i.e. its first input, from `#[frb(sync_mutex)]`, `#[frb(rwlock)]` or `#[frb(no_lock)]`.
+    pub receiver_lock: Option<IrReceiverLock>,
+    /// A mistake only detected by the parser, such as a `StreamSink` of a borrowed type, reported as a
+    /// `compile_error!` in the generated wire function so that the error names the function.
+    pub compile_error: Option<String>,
+    pub compress: Option<IrFuncCompression>,
+    /// Name of the Dart method dispatching to this function and the others of the same name,
+    /// from `#[frb(dart_name = "..")]`.
+    pub dart_name: Option<String>,
+    /// Whether the function registers the Dart stream of an `#[frb(event)]` type, instead of
+    /// calling a function of the API file.
+    pub event: bool,
+    /// Whether Dart gets a variant of the function returning a handle to cancel the Rust task,
+    /// from `#[frb(cancelable)]`.
+    pub cancelable: bool,
+    /// Whether the method is a Dart getter or setter of its struct, from `#[frb(getter)]` or
+    /// `#[frb(setter)]`.
+    pub accessor: Option<IrFuncAccessor>,
+    /// Whether the function returns a reference, e.g. `&'static Config` or `Option<&str>`, turned
+    /// into the owned value sent to Dart with `ToOwned::to_owned`.
+    pub to_owned_output: Option<IrFuncOwnedOutput>,
+    /// Path of the Rust module defining the function, e.g. `["crate", "api"]`.
+    pub module_path: Vec<String>,
+    /// The `impl Read` and `impl Write` arguments, by their index in the function arguments.
+    pub io_adapters: Vec<(usize, IrFuncIoAdapter)>,
+    /// Number of calls allowed to run at the same time, from `#[frb(max_concurrency = 1)]`.
+    pub max_concurrency: Option<usize>,
+    /// The tokio channel whose receiver the function returns, forwarded to the Dart stream.
+    pub channel: Option<IrFuncChannel>,
+    /// Whether a Dart stream polling the function periodically is generated along with it, from
+    /// `#[frb(poll)]`.
+    pub poll: bool,
+    /// Whether the returned list is decoded item by item as Dart accesses them, from
+    /// `#[frb(lazy_decode)]`.
+    pub lazy_decode: bool,
+    /// Path of the Rust function from the API module, if it is not its name, e.g. `advanced::foo`
+    /// for `advanced_foo` defined in `pub mod advanced { .. }` of the API file.
+    pub rust_call_path: Option<String>,
+    /// Whether the Dart stream skips the items equal to the previous one, from `#[frb(distinct)]`.
+    pub distinct: bool,
+    /// Delay for which calls wait for the next one superseding them, from
+    /// `#[frb(debounce_ms = 250)]`.
+    pub debounce_ms: Option<u64>,
+    /// Whether the wire function is only compiled into debug builds, from `#[frb(debug_only)]`.
+    pub debug_only: bool,
+    /// Whether the stream function returns `Result<StreamSetup>`, so that Dart gets a `Future` of
+    /// the stream, failing with the error of the setup.
+    pub stream_setup: bool,
+    /// Former names of the function, from `#[frb(renamed_from = "old_name")]`, each kept in Dart as
+    /// a deprecated method calling it.
+    pub renamed_from: Vec<String>,
 }
 
 impl IrFunc {
     pub fn wire_func_name(&self) -> String {
         format!("wire_{}", self.name)
     }
+
+    /// The type returned by the Dart method, e.g. `Future<int>`.
+    pub fn dart_return_type(&self) -> String {
+        let return_type = self.mode.dart_return_type(&self.output.dart_api_type());
+        if self.stream_setup {
+            format!("Future<{}>", return_type)
+        } else {
+            return_type
+        }
+    }
+
+    /// Parameterless functions returning `SyncReturn<T>` of a primitive or [String] are
+    /// exposed as Dart getters.
+    pub fn is_dart_getter(&self) -> bool {
+        self.inputs.is_empty()
+            && matches!(self.output, IrType::Delegate(IrTypeDelegate::SyncReturn(_)))
+            && !FunctionName::deserialize(&self.name).is_static_method()
+    }
+
+    /// Whether `--codec msgpack` applies to the function: it returns a `Future` of a value the
+    /// generated Dart code can build from MessagePack, and takes such values only. The other
+    /// functions keep the default wire format.
+    pub fn supports_msgpack(&self, ir_file: &IrFile) -> bool {
+        if self.mode != IrFuncMode::Normal
+            || self.closure.is_some()
+            || self.compile_error.is_some()
+            || self.compress.is_some()
+            || self.event
+            || self.channel.is_some()
+            || self.lazy_decode
+            || !self.io_adapters.is_empty()
+        {
+            return false;
+        }
+        let mut supported = true;
+        for ty in self
+            .inputs
+            .iter()
+            .map(|field| &field.ty)
+            .chain([&self.output])
+        {
+            ty.visit_types(
+                &mut |ty| {
+                    supported &= ty.supports_msgpack(ir_file);
+                    !supported
+                },
+                ir_file,
+            );
+        }
+        supported
+    }
+
+    /// Infallible sync functions taking and returning only primitives pass them as is, since there
+    /// is nothing to allocate or convert. Functions returning `()` are excluded, since Dart checks
+    /// whether the call panicked while returning its value.
+    pub fn is_primitive_fast_path(&self) -> bool {
+        self.mode == IrFuncMode::Sync
+            && !self.fallible
+            && matches!(&self.output, IrType::Delegate(IrTypeDelegate::SyncReturn(inner))
+                if matches!(**inner, IrType::Primitive(_)) && !matches!(**inner, IrType::Primitive(IrTypePrimitive::Unit)))
+            && self
+                .inputs
+                .iter()
+                .all(|input| matches!(input.ty, IrType::Primitive(_)))
+    }
+}
+
+/// Retry policy from `#[frb(retry(times = 3, backoff_ms = 200))]`, applied by the generated Dart code.
+#[derive(Debug, Clone)]
+pub struct IrFuncRetry {
+    /// How many times the call is retried after the first failure.
+    pub times: u32,
+    /// Delay before the first retry, doubled for each subsequent one.
+    pub backoff_ms: u64,
+    /// Prefixes of the error message (the `Debug` output of the Rust error) that are considered
+    /// transient. When empty, every error returned by the function is retried, but never panics.
+    pub on: Vec<String>,
+}
+
+/// Compression from `#[frb(compress = "zstd")]`, applied by the generated Rust code to the output
+/// of the function, and undone by the generated Dart code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrFuncCompression {
+    Zstd,
+}
+
+/// Where the reference returned by a function is, see [IrFunc::to_owned_output].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrFuncOwnedOutput {
+    /// The output itself, e.g. `&str`.
+    Reference,
+    /// The value of an `Option` or `Result`, e.g. `Option<&str>`, copied with `map`.
+    Inner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrFuncAccessor {
+    Getter,
+    Setter,
+}
+
+impl IrFuncAccessor {
+    /// The Dart name of the property of the method [method_name], leaving out the `get_` or
+    /// `set_` prefix if any.
+    pub fn property_name(&self, method_name: &str, naming_policy: NamingPolicy) -> String {
+        let prefix = match self {
+            Self::Getter => "get_",
+            Self::Setter => "set_",
+        };
+        naming_policy.member_name(
+            method_name
+                .strip_prefix(prefix)
+                .filter(|it| !it.is_empty())
+                .unwrap_or(method_name),
+        )
+    }
+}
+
+/// An `impl Read` argument, transferred as a `DartStream<Vec<u8>>`, or an `impl Write` argument,
+/// transferred as a `StreamSink<Vec<u8>>` so that Dart gets a stream of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrFuncIoAdapter {
+    Reader,
+    Writer,
+}
+
+impl IrFuncIoAdapter {
+    /// The argument passed to the function, made from the transferred value `obj`.
+    pub fn wrap(&self, obj: &str) -> String {
+        match self {
+            Self::Reader => format!("support::dart_stream_reader({})", obj),
+            Self::Writer => format!("support::stream_sink_writer({})", obj),
+        }
+    }
+}
+
+/// A returned `tokio::sync::watch::Receiver<T>`, whose current value and then each change is
+/// sent, or `tokio::sync::broadcast::Receiver<T>`, whose messages are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrFuncChannel {
+    Watch,
+    Broadcast,
+}
+
+impl IrFuncChannel {
+    /// The module of the channel in `tokio::sync`.
+    pub fn module(&self) -> &'static str {
+        match self {
+            Self::Watch => "watch",
+            Self::Broadcast => "broadcast",
+        }
+    }
+
+    /// Moves the receiver `obj` to a thread sending its values to `sink`.
+    pub fn forward(&self, obj: &str, sink: &str) -> String {
+        format!("support::forward_{}({}, {})", self.module(), obj, sink)
+    }
+}
+
+/// Set on the functions generated to use or drop the handle of a closure or an iterator returned
+/// by another function, or of a `RustOpaque`.
+#[derive(Debug, Clone)]
+pub enum IrFuncClosure {
+    Call(IrTypeClosure),
+    Drop(IrTypeClosure),
+    IteratorNext(IrTypeIterator),
+    IteratorDrop(IrTypeIterator),
+    OpaqueClone(IrTypeRustOpaque),
+    OpaqueDrop(IrTypeRustOpaque),
+    OpaqueDebug(IrTypeRustOpaque),
+}
+
+/// The locking of a method of an opaque struct `T` around its call, along with how it borrows
+/// `self`, which only the locks can lend mutably.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrReceiverLock {
+    pub kind: IrReceiverLockKind,
+    pub borrow: IrBorrow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrReceiverLockKind {
+    /// Called on a `RustOpaque<Mutex<T>>`, locked for the whole call.
+    SyncMutex,
+    /// Called on a `RustOpaque<RwLock<T>>`, read-locked for `&self` and write-locked for
+    /// `&mut self` for the whole call.
+    RwLock,
+    /// Called on a `RustOpaque<T>` as is, so only with `&self`.
+    NoLock,
+}
+
+impl IrReceiverLockKind {
+    /// The type behind the handle, e.g. `Mutex<T>`, which has to be in scope like `T`.
+    pub fn opaque_inner(&self, struct_name: &str) -> String {
+        match self {
+            Self::SyncMutex => format!("Mutex<{}>", struct_name),
+            Self::RwLock => format!("RwLock<{}>", struct_name),
+            Self::NoLock => struct_name.to_owned(),
+        }
+    }
+}
+
+impl IrReceiverLock {
+    /// The receiver passed to the method, borrowed from the handle `obj` while it is locked.
+    pub fn borrow_receiver(&self, obj: &str) -> String {
+        match (self.kind, self.borrow) {
+            (IrReceiverLockKind::SyncMutex, borrow) => {
+                format!("{}*{}.lock().unwrap()", borrow.rust_prefix(), obj)
+            }
+            (IrReceiverLockKind::RwLock, IrBorrow::Shared) => format!("&*{}.read().unwrap()", obj),
+            (IrReceiverLockKind::RwLock, IrBorrow::Mut) => {
+                format!("&mut *{}.write().unwrap()", obj)
+            }
+            (IrReceiverLockKind::NoLock, borrow) => format!("{}{}", borrow.rust_prefix(), obj),
+        }
+    }
+
+    /// The semantics of the lock, for the documentation of the Dart method.
+    pub fn dart_comment(&self) -> &'static str {
+        match (self.kind, self.borrow) {
+            (IrReceiverLockKind::SyncMutex, _) => "/// Locks the Rust value for the whole call, so that the calls on the handles to it run one at a time.\n",
+            (IrReceiverLockKind::RwLock, IrBorrow::Shared) => "/// Read-locks the Rust value for the whole call, so that it runs alongside other reads, but not alongside writes.\n",
+            (IrReceiverLockKind::RwLock, IrBorrow::Mut) => "/// Write-locks the Rust value for the whole call, so that it runs neither alongside reads nor alongside other writes.\n",
+            (IrReceiverLockKind::NoLock, _) => "/// Does not lock the Rust value, so that it may run alongside any other call on it.\n",
+        }
+    }
 }
 
 /// Represents a function's output type
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use convert_case::{Case, Casing};
 
 #[derive(Debug, Clone)]
@@ -16,11 +18,208 @@ impl IrIdent {
         IrIdent { raw }
     }
 
+    /// The identifier as written in the Rust source, e.g. `r#type`.
     pub fn rust_style(&self) -> &str {
         &self.raw
     }
 
-    pub fn dart_style(&self) -> String {
-        self.raw.to_case(Case::Camel)
+    /// The identifier used in wire structs, which also end up in the C header and in the Dart
+    /// wire classes generated by ffigen, so it has to be plain ASCII and not a keyword of either
+    /// language. Non-ASCII characters become `uXXXX`, keywords get a `_` suffix.
+    pub fn wire_style(&self) -> String {
+        let ans = mangle_non_ascii(self.unraw(), "u");
+        if RUST_KEYWORDS.contains(&ans.as_str()) || DART_RESERVED_WORDS.contains(&ans.as_str()) {
+            ans + "_"
+        } else {
+            ans
+        }
+    }
+
+    /// The identifier used in the Dart API for a member, e.g. a field or a parameter, cased after
+    /// `policy`. Non-ASCII characters become `$XXXX`, reserved words get a `$` suffix; since `$`
+    /// never appears in Rust identifiers, this never collides.
+    pub fn dart_style(&self, policy: NamingPolicy) -> String {
+        dart_mangle(&policy.member_name(self.unraw()))
+    }
+
+    /// Same as [IrIdent::dart_style], for the value of a Dart enum.
+    pub fn dart_enum_value_style(&self, policy: NamingPolicy) -> String {
+        dart_mangle(&policy.enum_values.apply(self.unraw()))
+    }
+
+    /// A comment telling where a Dart identifier comes from, if it had to be mangled.
+    pub fn dart_mapping_comment(&self, policy: NamingPolicy) -> Option<String> {
+        if self.dart_style(policy) == policy.member_name(&self.raw) {
+            None
+        } else {
+            Some(format!("// Rust identifier: `{}`\n", self.raw))
+        }
+    }
+
+    fn unraw(&self) -> &str {
+        self.raw.strip_prefix("r#").unwrap_or(&self.raw)
+    }
+}
+
+/// How Rust identifiers are cased in the Dart API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DartCase {
+    /// As written in Rust, e.g. `get_user` or `Red`, only without leading underscores, which would
+    /// make them private in Dart.
+    Preserve,
+    /// e.g. `getUser` or `red`.
+    Camel,
+    /// e.g. `GET_USER` or `RED`.
+    Screaming,
+}
+
+impl DartCase {
+    pub fn apply(self, rust: &str) -> String {
+        match self {
+            DartCase::Preserve => rust.trim_start_matches('_').to_owned(),
+            DartCase::Camel => rust.to_case(Case::Camel),
+            DartCase::Screaming => rust.to_case(Case::UpperSnake),
+        }
+    }
+}
+
+impl FromStr for DartCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(DartCase::Preserve),
+            "camel" => Ok(DartCase::Camel),
+            "screaming" => Ok(DartCase::Screaming),
+            _ => Err(format!(
+                "unknown case `{}`, expected one of: preserve, camel, screaming",
+                s
+            )),
+        }
+    }
+}
+
+/// How the generated Dart API names what comes from Rust, from `--dart-member-case` and
+/// `--dart-enum-case`, the same for all the blocks so that they name things alike. The names of
+/// classes are always those of Rust, which are already PascalCase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamingPolicy {
+    /// Methods, their parameters, and the fields of classes.
+    pub members: DartCase,
+    /// Values of Dart enums.
+    pub enum_values: DartCase,
+}
+
+impl Default for NamingPolicy {
+    fn default() -> Self {
+        NamingPolicy {
+            members: DartCase::Camel,
+            enum_values: DartCase::Preserve,
+        }
+    }
+}
+
+impl NamingPolicy {
+    /// The Dart name of the method, parameter or field `rust`, before any mangling.
+    pub fn member_name(self, rust: &str) -> String {
+        self.members.apply(rust)
+    }
+}
+
+fn dart_mangle(name: &str) -> String {
+    let ans = mangle_non_ascii(name, "$");
+    if DART_RESERVED_WORDS.contains(&ans.as_str()) {
+        ans + "$"
+    } else {
+        ans
+    }
+}
+
+fn mangle_non_ascii(s: &str, prefix: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                c.to_string()
+            } else {
+                format!("{}{:04x}", prefix, c as u32)
+            }
+        })
+        .collect()
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield",
+];
+
+const DART_RESERVED_WORDS: &[&str] = &[
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+    "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+    "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+    "with",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{DartCase, IrIdent, NamingPolicy};
+
+    fn ident(raw: &str) -> IrIdent {
+        IrIdent::new(raw.to_owned())
+    }
+
+    #[test]
+    fn wire_style_is_plain_ascii_and_not_a_keyword() {
+        assert_eq!(ident("value").wire_style(), "value");
+        assert_eq!(ident("r#type").wire_style(), "type_");
+        assert_eq!(ident("class").wire_style(), "class_");
+        assert_eq!(ident("naïve").wire_style(), "nau00efve");
+    }
+
+    #[test]
+    fn dart_style_mangles_what_dart_does_not_accept() {
+        assert_eq!(
+            ident("user_name").dart_style(NamingPolicy::default()),
+            "userName"
+        );
+        assert_eq!(ident("r#in").dart_style(NamingPolicy::default()), "in$");
+        assert_eq!(
+            ident("naïve").dart_style(NamingPolicy::default()),
+            "na$00efve"
+        );
+    }
+
+    #[test]
+    fn dart_style_follows_the_given_policy() {
+        let preserve = NamingPolicy {
+            members: DartCase::Preserve,
+            enum_values: DartCase::Screaming,
+        };
+        assert_eq!(ident("_user_name").dart_style(preserve), "user_name");
+        assert_eq!(
+            ident("dark_red").dart_enum_value_style(preserve),
+            "DARK_RED"
+        );
+        // not affected by the policies of other configs
+        assert_eq!(
+            ident("user_name").dart_style(NamingPolicy::default()),
+            "userName"
+        );
+    }
+
+    #[test]
+    fn dart_mapping_comment_only_for_mangled_identifiers() {
+        assert_eq!(
+            ident("user_name").dart_mapping_comment(NamingPolicy::default()),
+            None
+        );
+        assert_eq!(
+            ident("r#in")
+                .dart_mapping_comment(NamingPolicy::default())
+                .as_deref(),
+            Some("// Rust identifier: `r#in`\n")
+        );
     }
 }
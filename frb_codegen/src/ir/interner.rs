@@ -0,0 +1,27 @@
+use crate::ir::*;
+use std::collections::BTreeMap;
+
+/// Deduplicates types by structural identity, i.e. by [IrTypeTrait::safe_ident], which is also
+/// what names the generated wire structs and conversion functions. Two instantiations that
+/// would generate the same code are therefore only kept once.
+#[derive(Debug, Clone, Default)]
+pub struct IrTypeInterner {
+    types: BTreeMap<String, IrType>,
+}
+
+impl IrTypeInterner {
+    /// Returns [true] if a structurally identical type was already interned.
+    pub fn intern(&mut self, ty: &IrType) -> bool {
+        let ident = ty.safe_ident();
+        if self.types.contains_key(&ident) {
+            return true;
+        }
+        self.types.insert(ident, ty.clone());
+        false
+    }
+
+    /// The interned types, ordered by identity so the output changes less when the input changes.
+    pub fn into_types(self) -> Vec<IrType> {
+        self.types.into_values().collect()
+    }
+}
@@ -5,6 +5,8 @@ mod file;
 mod func;
 mod ident;
 mod import;
+mod interner;
+mod trait_impl;
 mod ty;
 mod ty_boxed;
 mod ty_delegate;
@@ -14,6 +16,7 @@ mod ty_optional;
 mod ty_primitive;
 mod ty_primitive_list;
 mod ty_struct;
+mod validation;
 
 pub use annotation::*;
 pub use comment::*;
@@ -22,6 +25,8 @@ pub use file::*;
 pub use func::*;
 pub use ident::*;
 pub use import::*;
+pub use interner::*;
+pub use trait_impl::*;
 pub use ty::*;
 pub use ty_boxed::*;
 pub use ty_delegate::*;
@@ -31,3 +36,4 @@ pub use ty_optional::*;
 pub use ty_primitive::*;
 pub use ty_primitive_list::*;
 pub use ty_struct::*;
+pub use validation::*;
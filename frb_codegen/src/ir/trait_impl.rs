@@ -0,0 +1,21 @@
+/// The trait implementation a bridged function comes from, e.g. `impl Api for ApiImpl`.
+#[derive(Debug, Clone)]
+pub struct IrTraitImpl {
+    pub trait_name: String,
+    pub self_ty: String,
+    /// How the receiver is passed, e.g. `&` for `&self`, or [None] for associated functions.
+    /// A receiver is built via `Default::default()` for each call.
+    pub receiver: Option<String>,
+}
+
+impl IrTraitImpl {
+    pub fn call_path(&self, method_name: &str) -> String {
+        format!("<{} as {}>::{}", self.self_ty, self.trait_name, method_name)
+    }
+
+    pub fn receiver_arg(&self) -> Option<String> {
+        self.receiver
+            .as_ref()
+            .map(|modifier| format!("{}{}::default()", modifier, self.self_ty))
+    }
+}
@@ -1,4 +1,5 @@
 use crate::ir::*;
+use crate::method_utils::MethodNamingUtil;
 use enum_dispatch::enum_dispatch;
 use IrType::*;
 
@@ -17,12 +18,34 @@ pub enum IrType {
 }
 
 impl IrType {
+    /// [f] returns [true] if it wants to stop going to the *children* of this subtree
+    ///
+    /// Types are visited in the order of a depth-first recursion, but from a worklist. How deep
+    /// they nest is bounded by the parser anyway, see `--max-type-depth`, which is what the
+    /// generators going down the layers of a type rely on.
     pub fn visit_types<F: FnMut(&IrType) -> bool>(&self, f: &mut F, ir_file: &IrFile) {
-        if f(self) {
-            return;
+        let mut worklist = vec![self.clone()];
+        while let Some(ty) = worklist.pop() {
+            if f(&ty) {
+                continue;
+            }
+            // reversed, so that the first child is visited first
+            worklist.extend(ty.children_types(ir_file).into_iter().rev());
         }
+    }
 
-        self.visit_children_types(f, ir_file);
+    /// The types [Self::visit_types] goes to next. Not generic, as visiting them with a closure
+    /// defined in the generic visitor would instantiate it without end.
+    fn children_types(&self, ir_file: &IrFile) -> Vec<IrType> {
+        let mut children = Vec::new();
+        self.visit_children_types(
+            &mut |child| {
+                children.push(child.clone());
+                true
+            },
+            ir_file,
+        );
+        children
     }
 
     #[inline]
@@ -37,7 +60,15 @@ impl IrType {
     #[inline]
     pub fn rust_ptr_modifier(&self) -> &'static str {
         match self {
-            Optional(_) | Delegate(IrTypeDelegate::String) => "*mut ",
+            Optional(_)
+            | Delegate(
+                IrTypeDelegate::String
+                | IrTypeDelegate::Url
+                | IrTypeDelegate::CString
+                | IrTypeDelegate::ZonedDateTime
+                | IrTypeDelegate::StringEnum(_)
+                | IrTypeDelegate::Result { .. },
+            ) => "*mut ",
             _ => "",
         }
     }
@@ -46,6 +77,7 @@ impl IrType {
     pub fn as_primitive(&self) -> Option<&IrTypePrimitive> {
         match self {
             Primitive(repr) | Delegate(IrTypeDelegate::PrimitiveEnum { repr, .. }) => Some(repr),
+            Delegate(IrTypeDelegate::Transparent(transparent)) => transparent.inner.as_primitive(),
             _ => None,
         }
     }
@@ -55,10 +87,90 @@ impl IrType {
         self.as_primitive().is_some()
     }
 
+    /// Whether this is the output of a function that returns synchronously, i.e. `SyncReturn<T>`.
+    #[inline]
+    pub fn is_sync_return(&self) -> bool {
+        matches!(
+            self,
+            Delegate(IrTypeDelegate::SyncReturnVecU8 | IrTypeDelegate::SyncReturn(_))
+        )
+    }
+
     #[inline]
     pub fn is_struct(&self) -> bool {
         matches!(self, StructRef(_) | EnumRef(_))
     }
+
+    /// Whether the value itself, not counting its children, can be carried in MessagePack by
+    /// `--codec msgpack`, i.e. it has a plain serde representation Dart can build without the
+    /// bridge: not structs with methods, which hold the bridge.
+    /// Transparent structs need to be tuple structs, which serde carries as their field.
+    pub fn supports_msgpack(&self, ir_file: &IrFile) -> bool {
+        match self {
+            Primitive(_) | PrimitiveList(_) | Optional(_) | GeneralList(_) | Boxed(_) => true,
+            StructRef(st) => !MethodNamingUtil::has_methods(&st.name, ir_file),
+            EnumRef(enu) => !enu.get(ir_file).enhanced,
+            Delegate(delegate) => {
+                matches!(
+                    delegate,
+                    IrTypeDelegate::String
+                        | IrTypeDelegate::StringList
+                        | IrTypeDelegate::PrimitiveEnum { .. }
+                        | IrTypeDelegate::StringEnum(_)
+                        | IrTypeDelegate::BTreeMap { .. }
+                ) || matches!(delegate, IrTypeDelegate::Transparent(transparent) if transparent.field_name.is_none())
+            }
+        }
+    }
+
+    /// Whether the type is or contains a struct or an enum with fields, e.g. `Vec<Point>`, which
+    /// may in turn contain values of the type.
+    pub fn holds_struct(&self, ir_file: &IrFile) -> bool {
+        let mut found = false;
+        self.visit_types(
+            &mut |ty| {
+                found |= match ty {
+                    StructRef(_) => true,
+                    EnumRef(enu) => enu.get(ir_file).is_struct(),
+                    _ => false,
+                };
+                found
+            },
+            ir_file,
+        );
+        found
+    }
+
+    /// The type of `Vec<T>` for this type `T`, as the parser would have built it.
+    pub fn list_of(inner: &IrType) -> IrType {
+        match inner {
+            Primitive(primitive) => PrimitiveList(IrTypePrimitiveList {
+                primitive: primitive.clone(),
+            }),
+            Delegate(IrTypeDelegate::String) => Delegate(IrTypeDelegate::StringList),
+            Delegate(IrTypeDelegate::Url) => Delegate(IrTypeDelegate::UrlList),
+            Delegate(IrTypeDelegate::CString) => Delegate(IrTypeDelegate::CStringList),
+            others => GeneralList(IrTypeGeneralList {
+                inner: Box::new(others.clone()),
+            }),
+        }
+    }
+
+    /// The type of `Option<T>` for this type `T`, as the parser would have built it.
+    pub fn optional_of(inner: &IrType) -> IrType {
+        Optional(match inner {
+            Primitive(prim) => IrTypeOptional::new_prim(prim.clone()),
+            ty @ (StructRef(_) | Delegate(IrTypeDelegate::Transparent(_)))
+                if !ty.rust_wire_is_pointer() =>
+            {
+                IrTypeOptional::new_ptr(Boxed(IrTypeBoxed {
+                    inner: Box::new(ty.clone()),
+                    exist_in_real_api: false,
+                }))
+            }
+            other => IrTypeOptional::new_ptr(other.clone()),
+        })
+    }
 }
 
 #[enum_dispatch]
@@ -1,3 +1,5 @@
+use convert_case::{Case, Casing};
+
 use crate::ir::*;
 
 /// types that delegate to another type
@@ -5,13 +7,192 @@ use crate::ir::*;
 pub enum IrTypeDelegate {
     String,
     StringList,
+    /// `url::Url`, carried as a string and parsed on the receiving side
+    Url,
+    UrlList,
+    /// `std::ffi::CString`, carried as a string and checked for nul characters on both sides
+    CString,
+    CStringList,
+    /// `chrono::DateTime<FixedOffset>`, carried as the microseconds since the epoch along with the
+    /// offset in seconds, so that the offset is kept
+    ZonedDateTime,
     SyncReturnVecU8,
+    /// `SyncReturn<T>` where `T` is a primitive or [String], carried as bytes just like [IrTypeDelegate::SyncReturnVecU8]
+    SyncReturn(Box<IrType>),
     ZeroCopyBufferVecPrimitive(IrTypePrimitive),
     PrimitiveEnum {
         ir: IrTypeEnumRef,
         /// Allows for `#[repr]`'s other than [i32]
         repr: IrTypePrimitive,
     },
+    /// An enum without fields marked `#[frb(repr = "string")]`, carried as the name of the variant
+    StringEnum(IrTypeEnumRef),
+    /// A boxed closure, carried as an opaque handle
+    Closure(IrTypeClosure),
+    /// `RustOpaque<T>`, carried as the address of the shared value, which Dart holds until it
+    /// disposes of the handle
+    RustOpaque(IrTypeRustOpaque),
+    /// A boxed iterator, carried as an opaque handle through which Dart reads a page of items at a time
+    Iterator(IrTypeIterator),
+    /// A raw pointer taken or returned by a function marked `#[frb(unsafe_ffi)]`, carried as its
+    /// address and never dereferenced by the bridge
+    UnsafeOpaquePtr(IrTypeUnsafeOpaquePtr),
+    /// `DartStream<T>` parameter, carried as the handle through which Dart pushes the items
+    DartStream(Box<IrType>),
+    /// `BTreeMap<K, V>`, carried as the list of its keys along with the list of its values,
+    /// both in key order
+    BTreeMap {
+        key: Box<IrType>,
+        value: Box<IrType>,
+    },
+    /// `Result<T, E>` anywhere but at the top of a return type, carried as a tag along with the
+    /// optional value and the optional error
+    Result {
+        ok: Box<IrType>,
+        err: Box<IrType>,
+    },
+    /// A struct of one field marked `#[frb(transparent)]` or `#[serde(transparent)]`, carried as
+    /// its field and known to Dart by a typedef
+    Transparent(IrTypeTransparent),
+}
+
+/// `Box<dyn Fn(..) -> T + Send + Sync>` returned by a function, which Dart can call later
+#[derive(Debug, Clone)]
+pub struct IrTypeClosure {
+    /// The name of the function returning the closure
+    pub func_name: String,
+    pub inputs: Vec<IrType>,
+    pub output: Box<IrType>,
+}
+
+impl IrTypeClosure {
+    pub fn dart_class_name(&self) -> String {
+        format!("{}Closure", self.func_name.to_case(Case::Pascal))
+    }
+
+    pub fn call_func_name(&self) -> String {
+        format!("{}_closure_call", self.func_name)
+    }
+
+    pub fn drop_func_name(&self) -> String {
+        format!("{}_closure_drop", self.func_name)
+    }
+
+    /// The type behind the handle, which must be the same wherever the handle is used
+    pub fn rust_dyn_type(&self) -> String {
+        format!(
+            "dyn Fn({}) -> {} + Send + Sync",
+            self.inputs
+                .iter()
+                .map(IrType::rust_api_type)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.output.rust_api_type()
+        )
+    }
+}
+
+/// `RustOpaque<T>` taken or returned by a function, which Dart can clone, compare and dispose of
+#[derive(Debug, Clone)]
+pub struct IrTypeRustOpaque {
+    /// The type behind the handle as written in the signature, e.g. `Mutex<Connection>`
+    pub inner: String,
+    /// Whether `T` derives `Debug`, which Dart can then print with `debugString()`
+    pub debug: bool,
+}
+
+impl IrTypeRustOpaque {
+    pub fn dart_class_name(&self) -> String {
+        self.inner
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+            .to_case(Case::Pascal)
+    }
+
+    fn snake_name(&self) -> String {
+        self.dart_class_name().to_case(Case::Snake)
+    }
+
+    pub fn clone_func_name(&self) -> String {
+        format!("{}_opaque_clone", self.snake_name())
+    }
+
+    pub fn drop_func_name(&self) -> String {
+        format!("{}_opaque_drop", self.snake_name())
+    }
+
+    pub fn debug_func_name(&self) -> String {
+        format!("{}_opaque_debug", self.snake_name())
+    }
+}
+
+/// `Box<dyn Iterator<Item = T> + Send>` returned by a function, which Dart reads lazily
+#[derive(Debug, Clone)]
+pub struct IrTypeIterator {
+    /// The name of the function returning the iterator
+    pub func_name: String,
+    pub item: Box<IrType>,
+}
+
+impl IrTypeIterator {
+    pub fn dart_class_name(&self) -> String {
+        format!("{}Iterator", self.func_name.to_case(Case::Pascal))
+    }
+
+    pub fn next_func_name(&self) -> String {
+        format!("{}_iterator_next", self.func_name)
+    }
+
+    pub fn drop_func_name(&self) -> String {
+        format!("{}_iterator_drop", self.func_name)
+    }
+
+    /// The item type of the handle, which must be the same wherever the handle is used
+    pub fn rust_item_type(&self) -> String {
+        self.item.rust_api_type()
+    }
+}
+
+/// `*mut T` or `*const T`, which Dart holds as a plain address
+#[derive(Debug, Clone)]
+pub struct IrTypeUnsafeOpaquePtr {
+    pub mutable: bool,
+    /// The pointee as written in the signature, e.g. `std::ffi::c_void`
+    pub pointee: String,
+}
+
+impl IrTypeUnsafeOpaquePtr {
+    fn qualifier(&self) -> &'static str {
+        if self.mutable {
+            "mut"
+        } else {
+            "const"
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IrTypeTransparent {
+    pub name: String,
+    pub path: Vec<String>,
+    pub comments: Vec<IrComment>,
+    /// [None] for a tuple struct
+    pub field_name: Option<String>,
+    pub inner: Box<IrType>,
+}
+
+impl IrTypeTransparent {
+    /// `obj` wrapped into the struct
+    pub fn wrap(&self, obj: &str) -> String {
+        match &self.field_name {
+            Some(field_name) => format!("{} {{ {}: {} }}", self.name, field_name, obj),
+            None => format!("{}({})", self.name, obj),
+        }
+    }
+
+    /// The field of `obj`
+    pub fn access(&self, obj: &str) -> String {
+        format!("{}.{}", obj, self.field_name.as_deref().unwrap_or("0"))
+    }
 }
 
 impl IrTypeDelegate {
@@ -20,34 +201,103 @@ impl IrTypeDelegate {
             IrTypeDelegate::String => IrType::PrimitiveList(IrTypePrimitiveList {
                 primitive: IrTypePrimitive::U8,
             }),
-            IrTypeDelegate::SyncReturnVecU8 => IrType::PrimitiveList(IrTypePrimitiveList {
-                primitive: IrTypePrimitive::U8,
-            }),
+            IrTypeDelegate::SyncReturnVecU8 | IrTypeDelegate::SyncReturn(_) => {
+                IrType::PrimitiveList(IrTypePrimitiveList {
+                    primitive: IrTypePrimitive::U8,
+                })
+            }
             IrTypeDelegate::ZeroCopyBufferVecPrimitive(primitive) => {
                 IrType::PrimitiveList(IrTypePrimitiveList {
                     primitive: primitive.clone(),
                 })
             }
-            IrTypeDelegate::StringList => IrType::Delegate(IrTypeDelegate::String),
+            IrTypeDelegate::StringList
+            | IrTypeDelegate::Url
+            | IrTypeDelegate::CString
+            | IrTypeDelegate::StringEnum(_) => IrType::Delegate(IrTypeDelegate::String),
+            IrTypeDelegate::UrlList | IrTypeDelegate::CStringList => {
+                IrType::Delegate(IrTypeDelegate::StringList)
+            }
+            IrTypeDelegate::ZonedDateTime => IrType::PrimitiveList(IrTypePrimitiveList {
+                primitive: IrTypePrimitive::I64,
+            }),
             IrTypeDelegate::PrimitiveEnum { repr, .. } => IrType::Primitive(repr.clone()),
+            IrTypeDelegate::Closure(_)
+            | IrTypeDelegate::Iterator(_)
+            | IrTypeDelegate::RustOpaque(_)
+            | IrTypeDelegate::UnsafeOpaquePtr(_)
+            | IrTypeDelegate::DartStream(_) => IrType::Primitive(IrTypePrimitive::I64),
+            IrTypeDelegate::BTreeMap { key, .. } => IrType::list_of(key),
+            IrTypeDelegate::Result { ok, .. } => IrType::optional_of(ok),
+            IrTypeDelegate::Transparent(transparent) => *transparent.inner.clone(),
         }
     }
 }
 
 impl IrTypeTrait for IrTypeDelegate {
     fn visit_children_types<F: FnMut(&IrType) -> bool>(&self, f: &mut F, ir_file: &IrFile) {
-        self.get_delegate().visit_types(f, ir_file);
+        match self {
+            // the items are converted by the functions pushing them, not through the handle
+            IrTypeDelegate::DartStream(inner) => inner.visit_types(f, ir_file),
+            IrTypeDelegate::BTreeMap { key, value } => {
+                IrType::list_of(key).visit_types(f, ir_file);
+                IrType::list_of(value).visit_types(f, ir_file);
+            }
+            // packed as the bytes of all the strings and the offset after each of them
+            IrTypeDelegate::StringList => {
+                self.get_delegate().visit_types(f, ir_file);
+                IrType::PrimitiveList(IrTypePrimitiveList {
+                    primitive: IrTypePrimitive::I32,
+                })
+                .visit_types(f, ir_file);
+            }
+            // the optional ones are the fields of the wire struct
+            IrTypeDelegate::Result { ok, err } => {
+                for ty in [ok, err] {
+                    ty.visit_types(f, ir_file);
+                    IrType::optional_of(ty).visit_types(f, ir_file);
+                }
+            }
+            _ => self.get_delegate().visit_types(f, ir_file),
+        }
     }
 
     fn safe_ident(&self) -> String {
         match self {
             IrTypeDelegate::String => "String".to_owned(),
             IrTypeDelegate::StringList => "StringList".to_owned(),
+            IrTypeDelegate::Url => "Url".to_owned(),
+            IrTypeDelegate::UrlList => "UrlList".to_owned(),
+            IrTypeDelegate::CString => "CString".to_owned(),
+            IrTypeDelegate::CStringList => "CStringList".to_owned(),
+            IrTypeDelegate::ZonedDateTime => "ZonedDateTime".to_owned(),
             IrTypeDelegate::SyncReturnVecU8 => "SyncReturnVecU8".to_owned(),
+            IrTypeDelegate::SyncReturn(inner) => format!("SyncReturn_{}", inner.safe_ident()),
             IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 "ZeroCopyBuffer_".to_owned() + &self.get_delegate().dart_api_type()
             }
-            IrTypeDelegate::PrimitiveEnum { ir, .. } => ir.safe_ident(),
+            IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) => {
+                ir.safe_ident()
+            }
+            IrTypeDelegate::Closure(closure) => closure.dart_class_name(),
+            IrTypeDelegate::RustOpaque(opaque) => {
+                format!("RustOpaque_{}", opaque.dart_class_name())
+            }
+            IrTypeDelegate::Iterator(iterator) => iterator.dart_class_name(),
+            IrTypeDelegate::UnsafeOpaquePtr(ptr) => format!(
+                "UnsafeOpaquePtr_{}_{}",
+                ptr.qualifier(),
+                ptr.pointee
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+            ),
+            IrTypeDelegate::DartStream(inner) => format!("DartStream_{}", inner.safe_ident()),
+            IrTypeDelegate::BTreeMap { key, value } => {
+                format!("BTreeMap_{}_{}", key.safe_ident(), value.safe_ident())
+            }
+            IrTypeDelegate::Result { ok, err } => {
+                format!("Result_{}_{}", ok.safe_ident(), err.safe_ident())
+            }
+            IrTypeDelegate::Transparent(transparent) => transparent.name.clone(),
         }
     }
 
@@ -55,16 +305,45 @@ impl IrTypeTrait for IrTypeDelegate {
         match self {
             IrTypeDelegate::String => "String".to_string(),
             IrTypeDelegate::StringList => "List<String>".to_owned(),
+            IrTypeDelegate::Url => "Uri".to_owned(),
+            IrTypeDelegate::UrlList => "List<Uri>".to_owned(),
+            IrTypeDelegate::CString => "String".to_owned(),
+            IrTypeDelegate::CStringList => "List<String>".to_owned(),
+            IrTypeDelegate::ZonedDateTime => "ZonedDateTime".to_owned(),
             IrTypeDelegate::SyncReturnVecU8 | IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 self.get_delegate().dart_api_type()
             }
-            IrTypeDelegate::PrimitiveEnum { ir, .. } => ir.dart_api_type(),
+            IrTypeDelegate::SyncReturn(inner) => inner.dart_api_type(),
+            IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) => {
+                ir.dart_api_type()
+            }
+            IrTypeDelegate::Closure(closure) => closure.dart_class_name(),
+            IrTypeDelegate::RustOpaque(opaque) => opaque.dart_class_name(),
+            IrTypeDelegate::Iterator(iterator) => iterator.dart_class_name(),
+            IrTypeDelegate::UnsafeOpaquePtr(_) => "int".to_owned(),
+            IrTypeDelegate::DartStream(inner) => format!("Stream<{}>", inner.dart_api_type()),
+            IrTypeDelegate::BTreeMap { key, value } => {
+                format!("Map<{}, {}>", key.dart_api_type(), value.dart_api_type())
+            }
+            IrTypeDelegate::Result { ok, err } => {
+                format!(
+                    "RustResult<{}, {}>",
+                    ok.dart_api_type(),
+                    err.dart_api_type()
+                )
+            }
+            IrTypeDelegate::Transparent(transparent) => transparent.name.clone(),
         }
     }
 
     fn dart_wire_type(&self) -> String {
         match self {
-            IrTypeDelegate::StringList => "ffi.Pointer<wire_StringList>".to_owned(),
+            IrTypeDelegate::StringList | IrTypeDelegate::UrlList | IrTypeDelegate::CStringList => {
+                "ffi.Pointer<wire_StringList>".to_owned()
+            }
+            IrTypeDelegate::BTreeMap { .. } | IrTypeDelegate::Result { .. } => {
+                format!("ffi.Pointer<{}>", self.rust_wire_type())
+            }
             _ => self.get_delegate().dart_wire_type(),
         }
     }
@@ -73,17 +352,49 @@ impl IrTypeTrait for IrTypeDelegate {
         match self {
             IrTypeDelegate::String => "String".to_owned(),
             IrTypeDelegate::SyncReturnVecU8 => "SyncReturn<Vec<u8>>".to_string(),
+            IrTypeDelegate::SyncReturn(inner) => format!("SyncReturn<{}>", inner.rust_api_type()),
             IrTypeDelegate::StringList => "Vec<String>".to_owned(),
+            IrTypeDelegate::Url => "url::Url".to_owned(),
+            IrTypeDelegate::UrlList => "Vec<url::Url>".to_owned(),
+            IrTypeDelegate::CString => "std::ffi::CString".to_owned(),
+            IrTypeDelegate::CStringList => "Vec<std::ffi::CString>".to_owned(),
+            IrTypeDelegate::ZonedDateTime => "chrono::DateTime<chrono::FixedOffset>".to_owned(),
             IrTypeDelegate::ZeroCopyBufferVecPrimitive(_) => {
                 format!("ZeroCopyBuffer<{}>", self.get_delegate().rust_api_type())
             }
-            IrTypeDelegate::PrimitiveEnum { ir, .. } => ir.rust_api_type(),
+            IrTypeDelegate::PrimitiveEnum { ir, .. } | IrTypeDelegate::StringEnum(ir) => {
+                ir.rust_api_type()
+            }
+            IrTypeDelegate::Closure(closure) => format!("Box<{}>", closure.rust_dyn_type()),
+            IrTypeDelegate::RustOpaque(opaque) => format!("RustOpaque<{}>", opaque.inner),
+            IrTypeDelegate::Iterator(iterator) => format!(
+                "Box<dyn Iterator<Item = {}> + Send>",
+                iterator.rust_item_type()
+            ),
+            IrTypeDelegate::UnsafeOpaquePtr(ptr) => format!("*{} {}", ptr.qualifier(), ptr.pointee),
+            IrTypeDelegate::DartStream(inner) => format!("DartStream<{}>", inner.rust_api_type()),
+            IrTypeDelegate::BTreeMap { key, value } => format!(
+                "std::collections::BTreeMap<{}, {}>",
+                key.rust_api_type(),
+                value.rust_api_type()
+            ),
+            IrTypeDelegate::Result { ok, err } => format!(
+                "std::result::Result<{}, {}>",
+                ok.rust_api_type(),
+                err.rust_api_type()
+            ),
+            IrTypeDelegate::Transparent(transparent) => transparent.name.clone(),
         }
     }
 
     fn rust_wire_type(&self) -> String {
         match self {
-            IrTypeDelegate::StringList => "wire_StringList".to_owned(),
+            IrTypeDelegate::StringList | IrTypeDelegate::UrlList | IrTypeDelegate::CStringList => {
+                "wire_StringList".to_owned()
+            }
+            IrTypeDelegate::BTreeMap { .. } | IrTypeDelegate::Result { .. } => {
+                format!("wire_{}", self.safe_ident())
+            }
             _ => self.get_delegate().rust_wire_type(),
         }
     }
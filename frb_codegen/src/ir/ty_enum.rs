@@ -1,4 +1,4 @@
-use crate::ir::IrType::{EnumRef, StructRef};
+use crate::ir::IrType::{Delegate, EnumRef, Primitive, StructRef};
 use crate::ir::*;
 use convert_case::{Case, Casing};
 
@@ -48,6 +48,11 @@ pub struct IrEnum {
     pub wrapper_name: Option<String>,
     pub path: Vec<String>,
     pub comments: Vec<IrComment>,
+    /// Whether this enum is translated into a Dart enhanced enum, see [IrEnum::enhanced_payload]
+    pub enhanced: bool,
+    /// Whether the variants of this enum without fields are carried by their names rather than
+    /// their indices, see `#[frb(repr = "string")]`
+    pub string_repr: bool,
     _variants: Vec<IrVariant>,
     _is_struct: bool,
 }
@@ -58,6 +63,8 @@ impl IrEnum {
         wrapper_name: Option<String>,
         path: Vec<String>,
         comments: Vec<IrComment>,
+        enhanced: bool,
+        string_repr: bool,
         mut variants: Vec<IrVariant>,
     ) -> Self {
         fn wrap_box(ty: IrType) -> IrType {
@@ -99,6 +106,8 @@ impl IrEnum {
             wrapper_name,
             path,
             comments,
+            enhanced,
+            string_repr,
             _variants: variants,
             _is_struct,
         }
@@ -111,6 +120,43 @@ impl IrEnum {
     pub fn is_struct(&self) -> bool {
         self._is_struct
     }
+
+    /// The single payload field shared by all variants, if every variant carries exactly one
+    /// field of the same primitive or [String] type, e.g. `enum Code { A(String), B(String) }`.
+    pub fn enhanced_payload(&self) -> Option<&IrField> {
+        let mut fields = self.variants().iter().map(|variant| match &variant.kind {
+            IrVariantKind::Struct(st) if st.fields.len() == 1 => Some(&st.fields[0]),
+            _ => None,
+        });
+        let first = fields.next()??;
+        let is_simple = matches!(first.ty, Primitive(_) | Delegate(IrTypeDelegate::String));
+        let all_same = fields.all(
+            |field| matches!(field, Some(field) if field.ty.safe_ident() == first.ty.safe_ident()),
+        );
+        if is_simple && all_same {
+            Some(first)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the payload field in the generated Dart class. Named variants that agree on
+    /// the field name keep it, otherwise it falls back to `value`.
+    pub fn enhanced_payload_name(&self) -> IrIdent {
+        let mut names = self.variants().iter().map(|variant| match &variant.kind {
+            IrVariantKind::Struct(st) if st.is_fields_named => Some(st.fields[0].name.raw.clone()),
+            _ => None,
+        });
+        match names.next().flatten() {
+            Some(name) if names.all(|it| it.as_ref() == Some(&name)) => IrIdent::new(name),
+            _ => IrIdent::new("value".to_owned()),
+        }
+    }
+
+    /// Name of the Dart enhanced enum holding the variant tags.
+    pub fn enhanced_kind_name(&self) -> String {
+        format!("{}Kind", self.name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,22 +21,6 @@ impl IrTypeOptional {
             inner: Box::new(ptr),
         }
     }
-
-    pub fn is_primitive(&self) -> bool {
-        matches!(&*self.inner, Boxed(boxed) if matches!(*boxed.inner, IrType::Primitive(_)))
-    }
-
-    pub fn is_list(&self) -> bool {
-        matches!(&*self.inner, GeneralList(_) | PrimitiveList(_))
-    }
-
-    pub fn is_delegate(&self) -> bool {
-        matches!(&*self.inner, Delegate(_))
-    }
-
-    pub fn needs_initialization(&self) -> bool {
-        !(self.is_primitive() || self.is_delegate())
-    }
 }
 
 impl IrTypeTrait for IrTypeOptional {
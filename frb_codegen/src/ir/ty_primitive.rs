@@ -21,7 +21,7 @@ impl IrTypeTrait for IrTypePrimitive {
     fn visit_children_types<F: FnMut(&IrType) -> bool>(&self, _f: &mut F, _ir_file: &IrFile) {}
 
     fn safe_ident(&self) -> String {
-        self.rust_api_type()
+        self.rust_wire_type()
     }
 
     fn dart_api_type(&self) -> String {
@@ -47,7 +47,10 @@ impl IrTypeTrait for IrTypePrimitive {
     }
 
     fn rust_api_type(&self) -> String {
-        self.rust_wire_type()
+        match self {
+            IrTypePrimitive::Unit => "()".to_owned(),
+            _ => self.rust_wire_type(),
+        }
     }
 
     fn rust_wire_type(&self) -> String {
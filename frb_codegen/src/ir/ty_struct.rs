@@ -49,6 +49,23 @@ pub struct IrStruct {
     pub is_fields_named: bool,
     pub dart_metadata: Vec<IrDartAnnotation>,
     pub comments: Vec<IrComment>,
+    /// Set by `#[frb(skew_tolerant)]`: when decoding in Dart, trailing optional fields missing
+    /// from an older native library become null, and extra fields from a newer one are ignored.
+    pub skew_tolerant: bool,
+    /// Set by `#[frb(dart_builder)]` or `--dart-builder-min-fields`: a `{name}Builder` class is
+    /// generated in Dart along with the struct.
+    pub dart_builder: bool,
+    /// The fields marked `#[frb(skip)]`, which are not bridged and take a default value when the
+    /// struct is built from Dart.
+    pub skipped_fields: Vec<IrSkippedField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrSkippedField {
+    pub name: String,
+    /// Whether the generated code can not set it, so that it is taken from the `Default` of the
+    /// whole struct instead of the `Default` of its own type.
+    pub private: bool,
 }
 
 impl IrStruct {
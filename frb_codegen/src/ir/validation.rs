@@ -0,0 +1,145 @@
+use crate::ir::IrType::*;
+use crate::ir::*;
+
+/// A constraint from `#[frb(validate = "len <= 255")]`, asserted in Dart and debug-asserted in
+/// Rust before the value is used.
+#[derive(Debug, Clone)]
+pub struct IrValidation {
+    pub subject: IrValidationSubject,
+    pub op: String,
+    pub bound: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrValidationSubject {
+    /// The length of a string or list. Strings are measured in UTF-16 code units, as in Dart.
+    Len,
+    /// The number itself
+    Value,
+}
+
+const OPS: [&str; 6] = ["<=", ">=", "==", "!=", "<", ">"];
+
+impl IrValidation {
+    /// Parses `len <op> <number>` or `value <op> <number>`.
+    pub fn parse(source: &str) -> Option<Self> {
+        let source = source.trim();
+        let (subject, rest) = if let Some(rest) = source.strip_prefix("len") {
+            (IrValidationSubject::Len, rest)
+        } else if let Some(rest) = source.strip_prefix("value") {
+            (IrValidationSubject::Value, rest)
+        } else {
+            return None;
+        };
+        let rest = rest.trim_start();
+        let op = OPS.iter().find(|op| rest.starts_with(*op))?;
+        let bound = rest[op.len()..].trim();
+        let valid_bound = match subject {
+            IrValidationSubject::Len => bound.parse::<usize>().is_ok(),
+            IrValidationSubject::Value => bound.parse::<f64>().is_ok(),
+        };
+        valid_bound.then(|| Self {
+            subject,
+            op: op.to_string(),
+            bound: bound.to_owned(),
+        })
+    }
+
+    pub fn source(&self) -> String {
+        let subject = match self.subject {
+            IrValidationSubject::Len => "len",
+            IrValidationSubject::Value => "value",
+        };
+        format!("{} {} {}", subject, self.op, self.bound)
+    }
+
+    /// Whether the constraint can be checked on a value of the given type.
+    pub fn supports(&self, ty: &IrType) -> bool {
+        match (self.subject, ty) {
+            (_, Optional(IrTypeOptional { inner }) | Boxed(IrTypeBoxed { inner, .. })) => {
+                self.supports(inner)
+            }
+            (
+                IrValidationSubject::Len,
+                Delegate(
+                    IrTypeDelegate::String
+                    | IrTypeDelegate::StringList
+                    | IrTypeDelegate::ZeroCopyBufferVecPrimitive(_),
+                )
+                | PrimitiveList(_)
+                | GeneralList(_),
+            ) => true,
+            (
+                IrValidationSubject::Value,
+                Primitive(IrTypePrimitive::F32 | IrTypePrimitive::F64),
+            ) => true,
+            (
+                IrValidationSubject::Value,
+                Primitive(IrTypePrimitive::Bool | IrTypePrimitive::Unit),
+            ) => false,
+            (IrValidationSubject::Value, Primitive(_)) => self.bound.parse::<i64>().is_ok(),
+            _ => false,
+        }
+    }
+
+    pub fn dart_condition(&self, name: &str, ty: &IrType) -> String {
+        let condition = match self.subject {
+            IrValidationSubject::Len => format!("{}.length {} {}", name, self.op, self.bound),
+            IrValidationSubject::Value => format!("{} {} {}", name, self.op, self.bound),
+        };
+        if let Optional(_) = ty {
+            format!("{} == null || {}", name, condition)
+        } else {
+            condition
+        }
+    }
+
+    pub fn dart_assertion(&self, name: &str, ty: &IrType) -> String {
+        format!(
+            "assert({}, '{}: expected {}');",
+            self.dart_condition(name, ty),
+            name.replace('$', "\\$"),
+            self.source(),
+        )
+    }
+
+    pub fn rust_condition(&self, expr: &str, ty: &IrType) -> String {
+        match ty {
+            Optional(IrTypeOptional { inner }) => format!(
+                "{}.as_ref().map_or(true, |it| {})",
+                expr,
+                self.rust_condition("(*it)", inner)
+            ),
+            Boxed(IrTypeBoxed {
+                inner,
+                exist_in_real_api: true,
+            }) => self.rust_condition(&format!("(*{})", expr), inner),
+            Boxed(IrTypeBoxed { inner, .. }) => self.rust_condition(expr, inner),
+            Delegate(IrTypeDelegate::String) => {
+                format!("{}.encode_utf16().count() {} {}", expr, self.op, self.bound)
+            }
+            Delegate(IrTypeDelegate::ZeroCopyBufferVecPrimitive(_)) => {
+                format!("{}.0.len() {} {}", expr, self.op, self.bound)
+            }
+            // `x > 0` does not compile for floats
+            Primitive(IrTypePrimitive::F32 | IrTypePrimitive::F64)
+                if self.bound.parse::<i64>().is_ok() =>
+            {
+                format!("{} {} {}.0", expr, self.op, self.bound)
+            }
+            _ => match self.subject {
+                IrValidationSubject::Len => format!("{}.len() {} {}", expr, self.op, self.bound),
+                IrValidationSubject::Value => format!("{} {} {}", expr, self.op, self.bound),
+            },
+        }
+    }
+
+    pub fn rust_debug_assertion(&self, name: &str, expr: &str, ty: &IrType) -> String {
+        format!(
+            "debug_assert!({}, \"{}: expected {}\");",
+            self.rust_condition(expr, ty),
+            name,
+            self.source(),
+        )
+    }
+}
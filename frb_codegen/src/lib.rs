@@ -3,10 +3,11 @@
 use std::fs;
 use std::path::Path;
 
-use log::info;
+use log::{info, warn};
 use pathdiff::diff_paths;
 
-use crate::commands::BindgenRustToDartArg;
+use crate::commands::{BindgenRustToDartArg, StandaloneFfigenArg};
+use crate::manifest::OutputFiles;
 use crate::others::*;
 use crate::utils::*;
 
@@ -14,20 +15,30 @@ mod config;
 mod tools;
 
 pub use crate::commands::ensure_tools_available;
+pub use crate::compat::check_compat;
 pub use crate::config::parse as config_parse;
 pub use crate::config::Opts;
 pub use crate::config::RawOpts;
+pub use crate::config::{CheckCompatOpts, CleanOpts, Command, ScaffoldTestsOpts};
+pub use crate::manifest::clean;
+pub use crate::scaffold::scaffold_tests;
 pub use crate::utils::get_symbols_if_no_duplicates;
 
 mod commands;
+mod compat;
 mod error;
 mod generator;
 mod ir;
+mod manifest;
 mod markers;
 mod method_utils;
 mod others;
 mod parser;
+mod scaffold;
+mod size_report;
 mod source_graph;
+mod templates;
+pub mod testing;
 mod transformer;
 mod utils;
 use error::*;
@@ -41,24 +52,56 @@ pub fn frb_codegen(config: &config::Opts, all_symbols: &[String]) -> anyhow::Res
     let rust_output_dir = Path::new(&config.rust_output_path).parent().unwrap();
     let dart_output_dir = Path::new(&config.dart_output_path).parent().unwrap();
 
+    let mut outputs = OutputFiles::default();
+
     info!("Phase: Parse source code to AST, then to IR");
     let raw_ir_file = config.get_ir_file();
+    if !raw_ir_file.skipped_funcs.is_empty() {
+        warn!(
+            "Skipped {} functions of {} which cannot be bridged:",
+            raw_ir_file.skipped_funcs.len(),
+            config.rust_input_path
+        );
+        for (name, reason) in &raw_ir_file.skipped_funcs {
+            warn!("    {}: {}", name, reason);
+        }
+    }
 
     info!("Phase: Transform IR");
     let ir_file = transformer::transform(raw_ir_file);
 
     info!("Phase: Generate Rust code");
-    let generated_rust = ir_file.generate_rust(config);
+    let mut generated_rust = ir_file.generate_rust(config);
     let exclude_symbols = generated_rust.get_exclude_symbols(all_symbols);
     fs::create_dir_all(&rust_output_dir)?;
-    fs::write(&config.rust_output_path, generated_rust.code)?;
+    outputs.write(&config.rust_output_path, &generated_rust.code)?;
+
+    let size_report = &mut generated_rust.size_report;
+    if let Some(symbol_budget) = config.symbol_budget {
+        size_report.check_symbol_budget(symbol_budget);
+    }
+    if config.size_report {
+        size_report.log();
+    }
+    size_report.log_warnings();
+    if let Some(size_report_output_path) = &config.size_report_output_path {
+        outputs.write(
+            size_report_output_path,
+            serde_json::to_string_pretty(size_report)?,
+        )?;
+    }
 
     info!("Phase: Generate Dart code");
     let (generated_dart, needs_freezed) = ir_file.generate_dart(config)?;
 
     info!("Phase: Other things");
 
-    commands::format_rust(&config.rust_output_path)?;
+    if config.rustfmt {
+        commands::format_rust(
+            &config.rust_output_path,
+            config.rustfmt_config_path.as_deref(),
+        )?;
+    }
 
     if !config.skip_add_mod_to_lib {
         others::try_add_mod_to_lib(&config.rust_crate_dir, &config.rust_output_path);
@@ -70,79 +113,173 @@ pub fn frb_codegen(config: &config::Opts, all_symbols: &[String]) -> anyhow::Res
         &config.rust_output_path,
         DUMMY_WIRE_CODE_FOR_BINDGEN,
         || {
-            commands::bindgen_rust_to_dart(
-                BindgenRustToDartArg {
-                    rust_crate_dir: &config.rust_crate_dir,
-                    c_output_path: temp_bindgen_c_output_file
-                        .path()
-                        .as_os_str()
-                        .to_str()
-                        .unwrap(),
-                    dart_output_path: temp_dart_wire_file.path().as_os_str().to_str().unwrap(),
-                    dart_class_name: &config.dart_wire_class_name(),
-                    c_struct_names: ir_file.get_c_struct_names(),
-                    exclude_symbols,
-                    llvm_install_path: &config.llvm_path[..],
-                    llvm_compiler_opts: &config.llvm_compiler_opts,
-                },
-                &dart_root,
-            )
+            let arg = BindgenRustToDartArg {
+                rust_crate_dir: &config.rust_crate_dir,
+                c_output_path: temp_bindgen_c_output_file
+                    .path()
+                    .as_os_str()
+                    .to_str()
+                    .unwrap(),
+                dart_output_path: temp_dart_wire_file.path().as_os_str().to_str().unwrap(),
+                dart_class_name: &config.dart_wire_class_name(),
+                c_struct_names: ir_file.get_c_struct_names(),
+                exclude_symbols,
+                llvm_install_path: &config.llvm_path[..],
+                llvm_compiler_opts: &config.llvm_compiler_opts,
+            };
+            if config.ffigen_config_path.is_some() {
+                commands::bindgen_rust_to_c(arg)
+            } else {
+                commands::bindgen_rust_to_dart(arg, &dart_root)
+            }
         },
     )?;
 
+    // the dummy code and the symbol lists must not refer to what release builds leave out
+    let debug_only_func_names = &generated_rust.debug_only_func_names;
     let effective_func_names = [
         generated_rust.extern_func_names,
         EXTRA_EXTERN_FUNC_NAMES.to_vec(),
     ]
-    .concat();
+    .concat()
+    .into_iter()
+    .filter(|name| !debug_only_func_names.contains(name))
+    .collect::<Vec<_>>();
     let c_dummy_code = generator::c::generate_dummy(&effective_func_names);
+    let mut c_header = fs::read_to_string(&temp_bindgen_c_output_file)?;
+    if !config.symbol_prefix.is_empty() {
+        c_header = prefix_c_struct_names(&c_header, &config.symbol_prefix);
+    }
     for output in &config.c_output_path {
         fs::create_dir_all(Path::new(output).parent().unwrap())?;
-        fs::write(
-            &output,
-            fs::read_to_string(&temp_bindgen_c_output_file)? + "\n" + &c_dummy_code,
-        )?;
+        outputs.write(&output, c_header.clone() + "\n" + &c_dummy_code)?;
     }
 
-    fs::create_dir_all(&dart_output_dir)?;
-    let generated_dart_wire_code_raw = fs::read_to_string(temp_dart_wire_file)?;
-    let generated_dart_wire = extract_dart_wire_content(&modify_dart_wire_content(
-        &generated_dart_wire_code_raw,
-        &config.dart_wire_class_name(),
-    ));
+    // of all the blocks, which are linked into the same library
+    if config.block_index == BlockIndex::PRIMARY {
+        let mut exported_symbols = [all_symbols, &effective_func_names[..]].concat();
+        exported_symbols.sort();
+        exported_symbols.dedup();
+        if let Some(export_def_path) = &config.export_def_path {
+            outputs.write(
+                export_def_path,
+                generator::c::generate_module_definition(&exported_symbols),
+            )?;
+        }
+        if let Some(export_version_script_path) = &config.export_version_script_path {
+            outputs.write(
+                export_version_script_path,
+                generator::c::generate_version_script(&exported_symbols),
+            )?;
+        }
+    }
 
-    sanity_check(&generated_dart_wire.body, &config.dart_wire_class_name())?;
+    fs::create_dir_all(&dart_output_dir)?;
+    let relative_to_dart_output = |path: &str| {
+        diff_paths(path, dart_output_dir)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned()
+    };
+    let generated_dart_wire = if let Some(ffigen_config_path) = &config.ffigen_config_path {
+        let ffigen_output_path = config
+            .dart_ffigen_output_path()
+            .ok_or_else(|| Error::str("Invalid dart_output_path"))?;
+        // ffigen resolves the paths of its config from the directory of the config
+        let ffigen_config_dir = Path::new(ffigen_config_path).parent().unwrap();
+        let relative_to_ffigen_config = |path: &str| {
+            diff_paths(path, ffigen_config_dir)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_owned()
+        };
+        let ffigen_config = commands::standalone_ffigen_config(&StandaloneFfigenArg {
+            c_path: &relative_to_ffigen_config(&config.c_output_path[0]),
+            dart_path: &relative_to_ffigen_config(&ffigen_output_path),
+            dart_class_name: &format!("{}Bindings", config.dart_wire_class_name()),
+            symbol_prefix: &config.symbol_prefix,
+            llvm_install_path: &config.llvm_path[..],
+            llvm_compiler_opts: &config.llvm_compiler_opts,
+        })?;
+        fs::create_dir_all(ffigen_config_dir)?;
+        outputs.write(ffigen_config_path, ffigen_config)?;
+        generator::dart::generate_ffigen_wire_code(
+            &config.dart_wire_class_name(),
+            &relative_to_dart_output(&ffigen_output_path),
+        )
+    } else {
+        let mut generated_dart_wire_code_raw = fs::read_to_string(temp_dart_wire_file)?;
+        if !config.symbol_prefix.is_empty() {
+            generated_dart_wire_code_raw =
+                strip_dart_wire_symbol_prefix(&generated_dart_wire_code_raw, &config.symbol_prefix);
+        }
+        let generated_dart_wire = extract_dart_wire_content(&modify_dart_wire_content(
+            &generated_dart_wire_code_raw,
+            &config.dart_wire_class_name(),
+        ));
+        sanity_check(&generated_dart_wire.body, &config.dart_wire_class_name())?;
+        generated_dart_wire
+    };
 
     let generated_dart_decl_all = generated_dart.decl_code;
-    let generated_dart_impl_all = &generated_dart.impl_code + &generated_dart_wire;
+    let mut generated_dart_impl_all = &generated_dart.impl_code + &generated_dart_wire;
+    if config.dart_private_wire {
+        generated_dart_impl_all.body =
+            make_dart_wire_private(&generated_dart_impl_all.body, &generated_dart_wire.body);
+    }
+    if config.dart_minify {
+        generated_dart_impl_all.body = minify_dart(&generated_dart_impl_all.body);
+    }
+    let import_decl = |dart_decl_path: &str| DartBasicCode {
+        import: format!("import \"{}\";", relative_to_dart_output(dart_decl_path)),
+        part: String::new(),
+        body: String::new(),
+    };
     if let Some(dart_decl_output_path) = &config.dart_decl_output_path {
-        let impl_import_decl = DartBasicCode {
-            import: format!(
-                "import \"{}\";",
-                diff_paths(dart_decl_output_path, dart_output_dir)
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-            ),
-            part: String::new(),
-            body: String::new(),
-        };
-        fs::write(
+        outputs.write_keeping_protected_regions(
             &dart_decl_output_path,
-            (&generated_dart.file_prelude + &generated_dart_decl_all).to_text(),
+            (&generated_dart.decl_prelude + &generated_dart_decl_all).to_text(),
         )?;
-        fs::write(
+        outputs.write_keeping_protected_regions(
             &config.dart_output_path,
-            (&generated_dart.file_prelude + &impl_import_decl + &generated_dart_impl_all).to_text(),
+            (&generated_dart.file_prelude
+                + &import_decl(dart_decl_output_path)
+                + &generated_dart_impl_all)
+                .to_text(),
         )?;
     } else {
-        fs::write(
+        outputs.write_keeping_protected_regions(
             &config.dart_output_path,
             (&generated_dart.file_prelude + &generated_dart_decl_all + &generated_dart_impl_all)
                 .to_text(),
         )?;
     }
 
+    // of all the blocks, which are created from the same library
+    if config.block_index == BlockIndex::PRIMARY {
+        if let Some(dart_group) = &config.dart_group {
+            let dart_group_dir = Path::new(&dart_group.output_path).parent().unwrap();
+            let members = dart_group
+                .members
+                .iter()
+                .map(|(class_name, dart_output_path)| {
+                    let import_path = diff_paths(dart_output_path, dart_group_dir).unwrap();
+                    (class_name.clone(), import_path.to_str().unwrap().to_owned())
+                })
+                .collect::<Vec<_>>();
+            fs::create_dir_all(dart_group_dir)?;
+            outputs.write(
+                &dart_group.output_path,
+                (&generated_dart.decl_prelude
+                    + &generator::dart::generate_group_code(&dart_group.class_name(), &members))
+                    .to_text(),
+            )?;
+            commands::format_dart(&dart_group.output_path, config.dart_format_line_length)?;
+        }
+    }
+
     if needs_freezed && config.build_runner {
         let dart_root = config.dart_root.as_ref().ok_or_else(|| {
             Error::str(
@@ -151,12 +288,11 @@ pub fn frb_codegen(config: &config::Opts, all_symbols: &[String]) -> anyhow::Res
             )
         })?;
         commands::build_runner(dart_root)?;
-        commands::format_dart(
-            &config
-                .dart_output_freezed_path()
-                .ok_or_else(|| Error::str("Invalid freezed file path"))?,
-            config.dart_format_line_length,
-        )?;
+        let dart_output_freezed_path = config
+            .dart_output_freezed_path()
+            .ok_or_else(|| Error::str("Invalid freezed file path"))?;
+        commands::format_dart(&dart_output_freezed_path, config.dart_format_line_length)?;
+        outputs.record(dart_output_freezed_path);
     }
 
     commands::format_dart(&config.dart_output_path, config.dart_format_line_length)?;
@@ -164,6 +300,8 @@ pub fn frb_codegen(config: &config::Opts, all_symbols: &[String]) -> anyhow::Res
         commands::format_dart(dart_decl_output_path, config.dart_format_line_length)?;
     }
 
+    outputs.save(&config.rust_crate_dir, &config.rust_input_path)?;
+
     info!("Success!");
     Ok(())
 }
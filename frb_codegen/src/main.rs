@@ -1,13 +1,14 @@
 use env_logger::Env;
 use lib_flutter_rust_bridge_codegen::{
-    config_parse, frb_codegen, get_symbols_if_no_duplicates, RawOpts,
+    check_compat, clean, config_parse, frb_codegen, get_symbols_if_no_duplicates, scaffold_tests,
+    Command, RawOpts,
 };
 use log::{debug, info};
 use structopt::StructOpt;
 
 fn main() -> anyhow::Result<()> {
     //  get valiable options from user input command
-    let raw_opts = RawOpts::from_args();
+    let mut raw_opts = RawOpts::from_args();
     env_logger::Builder::from_env(Env::default().default_filter_or(if raw_opts.verbose {
         "debug"
     } else {
@@ -15,6 +16,17 @@ fn main() -> anyhow::Result<()> {
     }))
     .init();
 
+    match raw_opts.command.take() {
+        Some(Command::Clean(opts)) => return clean(&opts.rust_crate_dir, opts.all),
+        Some(Command::ScaffoldTests(opts)) => {
+            return scaffold_tests(&config_parse(raw_opts), &opts);
+        }
+        Some(Command::CheckCompat(opts)) => {
+            return check_compat(&config_parse(raw_opts), &opts);
+        }
+        None => {}
+    }
+
     let configs = config_parse(raw_opts);
     debug!("configs={:?}", configs);
 
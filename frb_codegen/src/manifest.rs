@@ -0,0 +1,198 @@
+//! The files written by the codegen, recorded in `.flutter_rust_bridge_outputs.json` at the root of
+//! the Rust crate, so that those left behind when an output is renamed or removed, and which would
+//! otherwise cause duplicate symbols or analyzer errors, can be reported and deleted with `clean`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use pathdiff::diff_paths;
+use serde::{Deserialize, Serialize};
+
+use crate::others::keep_protected_regions;
+
+const MANIFEST_FILE_NAME: &str = ".flutter_rust_bridge_outputs.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    /// Files written by the last run for each Rust input, relative to the crate.
+    outputs: BTreeMap<String, BTreeSet<String>>,
+    /// Files written by earlier runs which no run writes anymore, relative to the crate.
+    stale: BTreeSet<String>,
+}
+
+impl Manifest {
+    fn load(crate_dir: &Path) -> Result<Self> {
+        let path = crate_dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    fn save(&self, crate_dir: &Path) -> Result<()> {
+        fs::write(
+            crate_dir.join(MANIFEST_FILE_NAME),
+            serde_json::to_string_pretty(self)? + "\n",
+        )?;
+        Ok(())
+    }
+}
+
+/// The files written while generating the code of one Rust input.
+#[derive(Default)]
+pub struct OutputFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl OutputFiles {
+    pub fn write(&mut self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+        fs::write(path.as_ref(), contents)?;
+        self.record(path);
+        Ok(())
+    }
+
+    /// Like [Self::write], but keeps what the user wrote in the protected regions of the existing
+    /// file, see [crate::others::keep_protected_regions].
+    pub fn write_keeping_protected_regions(
+        &mut self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<str>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(old) => {
+                let contents = keep_protected_regions(&old, contents.as_ref())
+                    .with_context(|| format!("cannot regenerate {}", path.display()))?;
+                self.write(path, contents)
+            }
+            Err(_) => self.write(path, contents.as_ref()),
+        }
+    }
+
+    /// Records a file written by another tool on behalf of the codegen, e.g. by `build_runner`.
+    pub fn record(&mut self, path: impl AsRef<Path>) {
+        self.paths.push(path.as_ref().to_owned());
+    }
+
+    /// Replaces the files of `rust_input` in the manifest of `crate_dir`, and warns about the files
+    /// of earlier runs which are not generated anymore but still exist.
+    pub fn save(&self, crate_dir: &str, rust_input: &str) -> Result<()> {
+        let crate_dir = Path::new(crate_dir);
+        let relative = |path: &Path| {
+            diff_paths(path, crate_dir)
+                .unwrap_or_else(|| path.to_owned())
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let mut manifest = Manifest::load(crate_dir)?;
+        let outputs = self.paths.iter().map(|path| relative(path)).collect();
+        if let Some(previous) = manifest
+            .outputs
+            .insert(relative(Path::new(rust_input)), outputs)
+        {
+            manifest.stale.extend(previous);
+        }
+        let current = manifest.outputs.values().flatten().collect::<BTreeSet<_>>();
+        manifest.stale = manifest
+            .stale
+            .iter()
+            .filter(|path| !current.contains(path) && crate_dir.join(path).exists())
+            .cloned()
+            .collect();
+        manifest.save(crate_dir)?;
+
+        if !manifest.stale.is_empty() {
+            warn!(
+                "These files were generated by an earlier run, but are not anymore, and may conflict with the new ones: {}. \
+                Remove them with `flutter_rust_bridge_codegen clean`",
+                manifest.stale.iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Deletes the stale files recorded in the manifest of `crate_dir`, and also the current outputs
+/// if `all` is set.
+pub fn clean(crate_dir: &str, all: bool) -> Result<()> {
+    let crate_dir = Path::new(crate_dir);
+    let mut manifest = Manifest::load(crate_dir)?;
+    let mut removed = std::mem::take(&mut manifest.stale);
+    if all {
+        removed.extend(
+            std::mem::take(&mut manifest.outputs)
+                .into_values()
+                .flatten(),
+        );
+    }
+    for path in &removed {
+        let path = crate_dir.join(path);
+        if path.exists() {
+            info!("Removing {}", path.display());
+            fs::remove_file(&path).with_context(|| format!("fail to remove {}", path.display()))?;
+        }
+    }
+    if manifest.outputs.is_empty() {
+        let path = crate_dir.join(MANIFEST_FILE_NAME);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    } else {
+        manifest.save(crate_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `names` in `crate_dir` as the outputs of `api.rs`.
+    fn generate(crate_dir: &Path, names: &[&str]) {
+        let mut files = OutputFiles::default();
+        for name in names {
+            files.write(crate_dir.join(name), "// generated").unwrap();
+        }
+        files
+            .save(
+                crate_dir.to_str().unwrap(),
+                crate_dir.join("api.rs").to_str().unwrap(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn files_not_generated_anymore_become_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        generate(dir.path(), &["old.rs", "kept.rs"]);
+        generate(dir.path(), &["new.rs", "kept.rs"]);
+
+        let manifest = Manifest::load(dir.path()).unwrap();
+        assert_eq!(
+            manifest.outputs["api.rs"],
+            BTreeSet::from(["kept.rs".to_owned(), "new.rs".to_owned()])
+        );
+        assert_eq!(manifest.stale, BTreeSet::from(["old.rs".to_owned()]));
+    }
+
+    #[test]
+    fn clean_removes_the_stale_files_or_all_of_them() {
+        let dir = tempfile::tempdir().unwrap();
+        generate(dir.path(), &["old.rs"]);
+        generate(dir.path(), &["new.rs"]);
+
+        clean(dir.path().to_str().unwrap(), false).unwrap();
+        assert!(!dir.path().join("old.rs").exists());
+        assert!(dir.path().join("new.rs").exists());
+        assert!(Manifest::load(dir.path()).unwrap().stale.is_empty());
+
+        clean(dir.path().to_str().unwrap(), true).unwrap();
+        assert!(!dir.path().join("new.rs").exists());
+        assert!(!dir.path().join(MANIFEST_FILE_NAME).exists());
+    }
+}
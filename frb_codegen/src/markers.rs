@@ -1,5 +1,7 @@
 use syn::*;
 
+use crate::ir::{IrFuncAccessor, IrFuncCompression, IrFuncRetry, IrReceiverLockKind, IrValidation};
+
 /// Extract a path from marker `#[frb(mirror(path), ..)]`
 pub fn extract_mirror_marker(attrs: &[Attribute]) -> Option<Path> {
     attrs
@@ -23,17 +25,345 @@ pub fn extract_mirror_marker(attrs: &[Attribute]) -> Option<Path> {
         })
 }
 
-/// Checks if the `#[frb(non_final)]` attribute is present.
-pub fn has_non_final(attrs: &[Attribute]) -> bool {
+/// Extract a retry policy from marker `#[frb(retry(times = 3, backoff_ms = 200, on = "Timeout"), ..)]`.
+/// `on` may be repeated to list several transient errors.
+pub fn extract_retry_marker(attrs: &[Attribute]) -> Option<IrFuncRetry> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::List(MetaList { path, nested, .. }))
+                    if path.is_ident("retry") =>
+                {
+                    let mut retry = IrFuncRetry {
+                        times: 0,
+                        backoff_ms: 0,
+                        on: vec![],
+                    };
+                    for option in nested {
+                        match option {
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                path,
+                                lit: Lit::Int(lit),
+                                ..
+                            })) if path.is_ident("times") => {
+                                retry.times = lit.base10_parse().expect("invalid retry times")
+                            }
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                path,
+                                lit: Lit::Int(lit),
+                                ..
+                            })) if path.is_ident("backoff_ms") => {
+                                retry.backoff_ms =
+                                    lit.base10_parse().expect("invalid retry backoff_ms")
+                            }
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                                path,
+                                lit: Lit::Str(lit),
+                                ..
+                            })) if path.is_ident("on") => retry.on.push(lit.value()),
+                            _ => panic!(
+                                "unsupported option in #[frb(retry(..))], expected `times`, `backoff_ms` or `on`"
+                            ),
+                        }
+                    }
+                    Some(retry)
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+}
+
+/// Extract the compression from marker `#[frb(compress = "zstd"), ..]` on a function.
+pub fn extract_compress_marker(attrs: &[Attribute]) -> Option<IrFuncCompression> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("compress") => match lit.value().as_str() {
+                    "zstd" => Some(IrFuncCompression::Zstd),
+                    other => panic!(
+                        "unsupported #[frb(compress = \"{}\")], expected \"zstd\"",
+                        other
+                    ),
+                },
+                _ => None,
+            }),
+            _ => None,
+        })
+}
+
+/// Extract the Dart name from marker `#[frb(dart_name = "draw"), ..]` on a function, under which
+/// several functions are merged into one Dart method.
+pub fn extract_dart_name_marker(attrs: &[Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("dart_name") => Some(lit.value()),
+                _ => None,
+            }),
+            _ => None,
+        })
+}
+
+/// Extract the limit from marker `#[frb(max_concurrency = 1), ..]` on a function.
+pub fn extract_max_concurrency_marker(attrs: &[Attribute]) -> Option<usize> {
     attrs
         .iter()
         .filter(|attr| attr.path.is_ident("frb"))
-        .any(|attr| {
-            match attr.parse_meta() {
-            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().any(|meta| {
-                matches!(meta, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("non_final"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Int(lit),
+                    ..
+                })) if path.is_ident("max_concurrency") => {
+                    match lit.base10_parse().expect("invalid max_concurrency") {
+                        0 => panic!("#[frb(max_concurrency = 0)] would never run the function"),
+                        max => Some(max),
+                    }
+                }
+                _ => None,
             }),
+            _ => None,
+        })
+}
+
+/// Extract the delay from marker `#[frb(debounce_ms = 250), ..]` on a function.
+pub fn extract_debounce_marker(attrs: &[Attribute]) -> Option<u64> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Int(lit),
+                    ..
+                })) if path.is_ident("debounce_ms") => {
+                    match lit.base10_parse().expect("invalid debounce_ms") {
+                        0 => panic!("#[frb(debounce_ms = 0)] would not debounce anything"),
+                        delay => Some(delay),
+                    }
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+}
+
+/// Checks if the variants of an enum are carried by their names, i.e. `#[frb(repr = "string")]`,
+/// rather than by their indices, i.e. `#[frb(repr = "index")]` or no marker.
+pub fn has_string_repr(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().find_map(|meta| match meta {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("repr") => match lit.value().as_str() {
+                    "string" => Some(true),
+                    "index" => Some(false),
+                    other => panic!(
+                        "invalid #[frb(repr = {:?})], expected \"string\" or \"index\"",
+                        other
+                    ),
+                },
+                _ => None,
+            }),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+/// Extract the constraints from markers `#[frb(validate = "len <= 255"), ..]` on a parameter or field.
+pub fn extract_validations(attrs: &[Attribute]) -> Vec<IrValidation> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .flat_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested
+                .into_iter()
+                .filter_map(|meta| match meta {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(lit),
+                        ..
+                    })) if path.is_ident("validate") => Some(
+                        IrValidation::parse(&lit.value()).unwrap_or_else(|| {
+                            panic!(
+                                "invalid #[frb(validate = \"{}\")], expected e.g. `len <= 255` or `value > 0`",
+                                lit.value()
+                            )
+                        }),
+                    ),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Extract the former names from markers `#[frb(renamed_from = "old_name"), ..]` on a function,
+/// which may be repeated.
+pub fn extract_renamed_from_markers(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("frb"))
+        .flat_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested
+                .into_iter()
+                .filter_map(|meta| match meta {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(lit),
+                        ..
+                    })) if path.is_ident("renamed_from") => Some(lit.value()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Checks if the `#[frb(non_final)]` attribute is present, either on a field or a whole struct.
+pub fn has_non_final(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "non_final")
+}
+
+/// Checks if the `#[frb(skew_tolerant)]` attribute is present on a struct.
+pub fn has_skew_tolerant(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "skew_tolerant")
+}
+
+/// Checks if the `#[frb(skip)]` attribute is present on a field.
+pub fn has_skip(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "skip")
+}
+
+/// Checks if the `#[frb(dart_builder)]` attribute is present on a struct.
+pub fn has_dart_builder(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "dart_builder")
+}
+
+/// Checks if the `#[frb(handler)]` attribute is present on a static.
+pub fn has_handler(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "handler")
+}
+
+/// Checks if the `#[frb(enhanced_enum)]` attribute is present.
+pub fn has_enhanced_enum(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "enhanced_enum")
+}
+
+/// Extracts `#[frb(sync_mutex)]`, `#[frb(rwlock)]` or `#[frb(no_lock)]` from the attributes of a
+/// method of an opaque struct.
+pub fn extract_receiver_lock_marker(attrs: &[Attribute]) -> Option<IrReceiverLockKind> {
+    let locks = [
+        ("sync_mutex", IrReceiverLockKind::SyncMutex),
+        ("rwlock", IrReceiverLockKind::RwLock),
+        ("no_lock", IrReceiverLockKind::NoLock),
+    ]
+    .iter()
+    .filter(|(flag, _)| has_flag(attrs, flag))
+    .map(|(_, lock)| *lock)
+    .collect::<Vec<_>>();
+    match locks[..] {
+        [] => None,
+        [lock] => Some(lock),
+        _ => panic!(
+            "a method can only have one of #[frb(sync_mutex)], #[frb(rwlock)] and #[frb(no_lock)]"
+        ),
+    }
+}
+
+/// Checks if the `#[frb(event)]` attribute is present on a struct or an enum.
+pub fn has_event(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "event")
+}
+
+/// Checks if the `#[frb(cancelable)]` attribute is present on a function.
+pub fn has_cancelable(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "cancelable")
+}
+
+/// Checks if the `#[frb(poll)]` attribute is present on a function.
+pub fn has_poll(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "poll")
+}
+
+/// Checks if the `#[frb(distinct)]` attribute is present on a function.
+pub fn has_distinct(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "distinct")
+}
+
+/// Checks if the `#[frb(debug_only)]` attribute is present on a function.
+pub fn has_debug_only(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "debug_only")
+}
+
+/// Checks if the `#[frb(lazy_decode)]` attribute is present on a function.
+pub fn has_lazy_decode(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "lazy_decode")
+}
+
+/// Checks if the `#[frb(unsafe_ffi)]` attribute is present on a function.
+pub fn has_unsafe_ffi(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "unsafe_ffi")
+}
+
+/// Extracts `#[frb(getter)]` or `#[frb(setter)]` from the attributes of a method.
+pub fn extract_accessor_marker(attrs: &[Attribute]) -> Option<IrFuncAccessor> {
+    match (has_flag(attrs, "getter"), has_flag(attrs, "setter")) {
+        (true, true) => panic!("a method cannot be both #[frb(getter)] and #[frb(setter)]"),
+        (true, false) => Some(IrFuncAccessor::Getter),
+        (false, true) => Some(IrFuncAccessor::Setter),
+        (false, false) => None,
+    }
+}
+
+/// Checks if the `#[frb(transparent)]` or `#[serde(transparent)]` attribute is present on a struct.
+pub fn has_transparent(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "transparent") || has_flag_of(attrs, "serde", "transparent")
+}
+
+/// Checks if `#[derive(name)]` is present, e.g. `#[derive(Debug)]`.
+pub fn has_derive(attrs: &[Attribute], name: &str) -> bool {
+    has_flag_of(attrs, "derive", name)
+}
+
+/// Checks if a bare flag such as `#[frb(flag)]` is present.
+fn has_flag(attrs: &[Attribute], flag: &str) -> bool {
+    has_flag_of(attrs, "frb", flag)
+}
+
+/// Checks if a bare flag of another attribute, such as `#[serde(flag)]`, is present.
+fn has_flag_of(attrs: &[Attribute], attr_name: &str, flag: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(attr_name))
+        .any(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => nested.iter().any(
+                |meta| matches!(meta, NestedMeta::Meta(Meta::Path(path)) if path.is_ident(flag)),
+            ),
             _ => false,
-        }
         })
 }
@@ -6,7 +6,7 @@ use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
 use log::{info, warn};
 use pathdiff::diff_paths;
-use regex::RegexBuilder;
+use regex::{Regex, RegexBuilder};
 
 // NOTE [DartPostCObjectFnType] was originally [*mut DartCObject] but I changed it to [*mut c_void]
 // because cannot automatically generate things related to [DartCObject]. Anyway this works fine.
@@ -24,7 +24,7 @@ pub const DUMMY_WIRE_CODE_FOR_BINDGEN: &str = r#"
     pub struct WireSyncReturnStruct {
         pub ptr: *mut u8,
         pub len: i32,
-        pub success: bool,
+        pub kind: i32,
     }
 
     // ---------------------------------------------
@@ -57,6 +57,209 @@ pub fn modify_dart_wire_content(content_raw: &str, dart_wire_class_name: &str) -
     content.to_string()
 }
 
+/// Prefixes the classes and typedefs declared by the ffigen output with `_`, and renames all
+/// their usages in `code`, so that only the high-level API stays visible outside the library.
+pub fn make_dart_wire_private(code: &str, dart_wire_code: &str) -> String {
+    let names = Regex::new(r"(?m)^(?:class|typedef) ([A-Za-z]\w*)")
+        .unwrap()
+        .captures_iter(dart_wire_code)
+        .map(|cap| cap[1].to_string())
+        .collect::<Vec<_>>();
+    names.iter().fold(code.to_string(), |code, name| {
+        Regex::new(&format!(r"\b{}\b", name))
+            .unwrap()
+            .replace_all(&code, format!("_{}", name).as_str())
+            .to_string()
+    })
+}
+
+/// Prefixes the names of the structs declared in the C header, like the functions already are,
+/// see `--symbol-prefix`.
+pub fn prefix_c_struct_names(header: &str, prefix: &str) -> String {
+    let names = Regex::new(r"typedef struct (\w+) \{")
+        .unwrap()
+        .captures_iter(header)
+        .map(|cap| cap[1].to_string())
+        .collect::<Vec<_>>();
+    names.iter().fold(header.to_string(), |header, name| {
+        Regex::new(&format!(r"\b{}\b", name))
+            .unwrap()
+            .replace_all(&header, format!("{}{}", prefix, name).as_str())
+            .to_string()
+    })
+}
+
+/// Removes `--symbol-prefix` from the members of the wire class generated by ffigen, so that
+/// the Dart code calls them by their usual names, while they keep looking up the prefixed symbols.
+pub fn strip_dart_wire_symbol_prefix(dart_wire_code: &str, prefix: &str) -> String {
+    // symbols are looked up by name in quotes, which must stay prefixed
+    Regex::new(&format!(r"(^|[^'\w])(_?){}", regex::escape(prefix)))
+        .unwrap()
+        .replace_all(dart_wire_code, "$1$2")
+        .to_string()
+}
+
+/// Shrinks the generated implementation code for `--dart-minify`: drops the comments, merges the
+/// `_wire2api_*` functions identical but for their names, and shortens the names of the
+/// private helpers.
+pub fn minify_dart(code: &str) -> String {
+    let code = code
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line.starts_with("//") || line.starts_with("// ignore")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let code = merge_identical_dart_wire2api_funcs(&code);
+
+    let mut counts = [("_w", 0), ("_a", 0), ("_f", 0)];
+    let mut short_names = std::collections::HashMap::new();
+    Regex::new(r"\b_(wire2api|api2wire|api_fill_to_wire)_\w+")
+        .unwrap()
+        .replace_all(&code, |cap: &regex::Captures| {
+            short_names
+                .entry(cap[0].to_string())
+                .or_insert_with(|| {
+                    let (prefix, count) = match &cap[1] {
+                        "wire2api" => &mut counts[0],
+                        "api2wire" => &mut counts[1],
+                        _ => &mut counts[2],
+                    };
+                    *count += 1;
+                    format!("{}{}", prefix, *count - 1)
+                })
+                .clone()
+        })
+        .to_string()
+}
+
+/// Keeps only the first of the `_wire2api_*` functions with the same signature and body, e.g. the
+/// ones of all integer types, and points the usages of the others to it.
+fn merge_identical_dart_wire2api_funcs(code: &str) -> String {
+    let header = Regex::new(r"(?m)^[ \t]*([^\n]*?) (_wire2api_\w+)\(([^)\n]*)\) \{").unwrap();
+    let mut canonical_names: std::collections::HashMap<(String, String, String), String> =
+        std::collections::HashMap::new();
+    let mut renames: Vec<(String, String)> = Vec::new();
+    let mut duplicates = Vec::new();
+    for cap in header.captures_iter(code) {
+        let whole = cap.get(0).unwrap();
+        let end = match dart_block_end(code, whole.end()) {
+            Some(end) => end,
+            None => continue,
+        };
+        let body = code[whole.end()..end]
+            .split_whitespace()
+            .collect::<Vec<_>>();
+        let key = (cap[1].to_string(), cap[3].to_string(), body.join(" "));
+        match canonical_names.get(&key) {
+            Some(canonical_name) => {
+                renames.push((cap[2].to_string(), canonical_name.clone()));
+                duplicates.push(whole.start()..end);
+            }
+            None => {
+                canonical_names.insert(key, cap[2].to_string());
+            }
+        }
+    }
+
+    let mut merged = code.to_string();
+    for range in duplicates.into_iter().rev() {
+        merged.replace_range(range, "");
+    }
+    renames.iter().fold(merged, |code, (name, canonical_name)| {
+        Regex::new(&format!(r"\b{}\b", name))
+            .unwrap()
+            .replace_all(&code, canonical_name.as_str())
+            .to_string()
+    })
+}
+
+/// Position right after the `}` closing the block whose content starts at `start`.
+fn dart_block_end(code: &str, start: usize) -> Option<usize> {
+    let mut depth = 1;
+    for (i, c) in code[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+const PROTECTED_REGION_BEGIN: &str = "// BEGIN PROTECTED REGION";
+const PROTECTED_REGION_END: &str = "// END PROTECTED REGION";
+
+/// The (empty) protected region `name`, whose content is kept by [keep_protected_regions]
+/// across regenerations.
+pub fn protected_region(name: &str) -> String {
+    format!(
+        "{} {}\n{} {}\n",
+        PROTECTED_REGION_BEGIN, name, PROTECTED_REGION_END, name
+    )
+}
+
+/// Moves what the user wrote in the protected regions of the `old` file into the regions of the
+/// same name of the `new` one. Fails rather than dropping a non-empty region which is not
+/// generated anymore.
+pub fn keep_protected_regions(old: &str, new: &str) -> Result<String> {
+    let mut kept = std::collections::HashMap::new();
+    let mut lines = old.lines();
+    while let Some(line) = lines.next() {
+        if let Some(name) = line.trim().strip_prefix(PROTECTED_REGION_BEGIN) {
+            let name = name.trim();
+            let end = format!("{} {}", PROTECTED_REGION_END, name);
+            let content = lines
+                .by_ref()
+                .take_while(|line| line.trim() != end)
+                .collect::<Vec<_>>();
+            kept.insert(name.to_owned(), content);
+        }
+    }
+
+    let mut merged = Vec::new();
+    let mut lines = new.lines();
+    while let Some(line) = lines.next() {
+        merged.push(line.to_owned());
+        if let Some(name) = line.trim().strip_prefix(PROTECTED_REGION_BEGIN) {
+            let name = name.trim();
+            let end = format!("{} {}", PROTECTED_REGION_END, name);
+            let generated = lines
+                .by_ref()
+                .take_while(|line| line.trim() != end)
+                .collect::<Vec<_>>();
+            let indent = &line[..line.len() - line.trim_start().len()];
+            merged.extend(
+                kept.remove(name)
+                    .unwrap_or(generated)
+                    .into_iter()
+                    .map(str::to_owned),
+            );
+            merged.push(format!("{}{}", indent, end));
+        }
+    }
+
+    let lost = kept
+        .into_iter()
+        .filter(|(_, content)| content.iter().any(|line| !line.trim().is_empty()))
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    if !lost.is_empty() {
+        return Err(anyhow!(
+            "the protected regions {:?} are not generated anymore, \
+            move their content elsewhere and remove them before regenerating",
+            lost
+        ));
+    }
+    Ok(merged.join("\n") + "\n")
+}
+
 #[derive(Default)]
 pub struct DartBasicCode {
     pub import: String,
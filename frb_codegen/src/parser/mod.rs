@@ -1,7 +1,9 @@
 mod ty;
 
+use std::panic::{self, AssertUnwindSafe};
 use std::string::String;
 
+use convert_case::{Case, Casing};
 use log::debug;
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
@@ -12,40 +14,252 @@ use syn::*;
 use crate::ir::*;
 
 use crate::generator::rust::HANDLER_NAME;
+use crate::markers;
 use crate::method_utils::FunctionName;
 use crate::parser::ty::TypeParser;
-use crate::source_graph::Crate;
+use crate::source_graph::{flatten_use_tree, Crate};
 
 const STREAM_SINK_IDENT: &str = "StreamSink";
+const DART_STREAM_IDENT: &str = "DartStream";
 const RESULT_IDENT: &str = "Result";
+const STREAM_SETUP_IDENT: &str = "StreamSetup";
+const ERROR_IDENT: &str = "Error";
+const SYNC_RETURN_IDENT: &str = "SyncReturn";
+const BOX_IDENT: &str = "Box";
+const FN_IDENT: &str = "Fn";
+const ITERATOR_IDENT: &str = "Iterator";
 
-pub fn parse(source_rust_content: &str, file: File, manifest_path: &str) -> IrFile {
+/// Default of `--max-type-depth`.
+pub const DEFAULT_MAX_TYPE_DEPTH: usize = 64;
+
+pub fn parse(
+    file: File,
+    manifest_path: &str,
+    rust_input_path: &str,
+    force_custom_handler: bool,
+    skip_unresolvable: bool,
+    max_type_depth: usize,
+) -> IrFile {
     let crate_map = Crate::new(manifest_path);
+    let rust_input_path = std::fs::canonicalize(rust_input_path)
+        .unwrap_or_else(|_| std::path::PathBuf::from(rust_input_path));
+    let api_module_path = crate_map
+        .root_module
+        .find_module_by_file(&rust_input_path)
+        .unwrap_or_else(|| {
+            panic!(
+                "{}",
+                unreachable_api_file_message(&crate_map, &rust_input_path)
+            )
+        })
+        .module_path
+        .clone();
 
-    let mut src_fns = extract_fns_from_file(&file);
-    src_fns.extend(extract_methods_from_file(&file));
+    let mut src_fns = extract_fns_from_file(&file)
+        .into_iter()
+        .chain(extract_methods_from_file(&file))
+        .map(|f| (f, api_module_path.clone()))
+        .collect::<Vec<_>>();
+    src_fns.extend(extract_fns_from_use(&file, &crate_map, &api_module_path));
+    let src_inline_mod_fns = extract_fns_from_inline_mods(&file.items, &[]);
+    let src_trait_fns = extract_trait_methods_from_file(&file);
+    let src_event_types = extract_event_types_from_file(&file);
     let src_structs = crate_map.root_module.collect_structs_to_vec();
     let src_enums = crate_map.root_module.collect_enums_to_vec();
+    let src_clone_impls = crate_map.root_module.collect_trait_impls_to_set("Clone");
+
+    let custom_handler = find_custom_handler(&file).or_else(|| {
+        if force_custom_handler {
+            Some(HANDLER_NAME.to_owned())
+        } else {
+            None
+        }
+    });
+
+    let parser = Parser::new(
+        TypeParser::new(src_structs, src_enums, src_clone_impls, max_type_depth),
+        api_module_path,
+        skip_unresolvable,
+    );
+    parser.parse(
+        src_fns,
+        src_inline_mod_fns,
+        src_trait_fns,
+        src_event_types,
+        custom_handler,
+    )
+}
+
+/// The errors of all the functions which cannot be parsed, so that they can be fixed in one go
+/// rather than one run at a time.
+fn unresolvable_funcs_report(skipped_funcs: &[(String, String)]) -> String {
+    let (plural, pronoun) = match skipped_funcs.len() {
+        1 => ("", "it"),
+        _ => ("s", "them"),
+    };
+    format!(
+        "{} function{} of the API cannot be bridged:\n{}\nFix {}, or leave {} out for now with --skip-unresolvable",
+        skipped_funcs.len(),
+        plural,
+        skipped_funcs
+            .iter()
+            .map(|(path, reason)| format!("    {}: {}", path, reason))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        pronoun,
+        pronoun,
+    )
+}
+
+/// Names of the `#[no_mangle] extern "C"` functions declared by `items`, including those of inline
+/// modules.
+pub fn extern_c_func_names(items: &[Item]) -> Vec<String> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Item::Fn(ItemFn { attrs, sig, .. })
+                if attrs.iter().any(|attr| attr.path.is_ident("no_mangle"))
+                    && matches!(&sig.abi, Some(Abi { name: Some(name), .. }) if name.value() == "C") =>
+            {
+                vec![sig.ident.to_string()]
+            }
+            Item::Mod(ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => extern_c_func_names(items),
+            _ => vec![],
+        })
+        .collect()
+}
 
-    let parser = Parser::new(TypeParser::new(src_structs, src_enums));
-    parser.parse(source_rust_content, src_fns)
+/// Explains that the API file is not a module of the crate, which would otherwise only show up as
+/// missing symbols when Dart calls the functions, and how to declare it.
+fn unreachable_api_file_message(crate_map: &Crate, rust_input_path: &std::path::Path) -> String {
+    let src_dir = crate_map.root_src_file.parent().unwrap();
+    let fix = match rust_input_path.strip_prefix(src_dir) {
+        Ok(relative) => {
+            let mut module_path = relative
+                .with_extension("")
+                .iter()
+                .map(|it| it.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            if module_path.last().map(String::as_str) == Some("mod") {
+                module_path.pop();
+            }
+            let name = module_path.pop().unwrap_or_default();
+            if module_path.is_empty() {
+                format!(
+                    "Declare it with `mod {};` in {}.",
+                    name,
+                    crate_map.root_src_file.display()
+                )
+            } else {
+                format!(
+                    "Declare it with `mod {};` in the module `crate::{}`, itself declared up to {}.",
+                    name,
+                    module_path.join("::"),
+                    crate_map.root_src_file.display()
+                )
+            }
+        }
+        Err(_) => format!("Move it into {}, and declare it there.", src_dir.display()),
+    };
+    format!(
+        "{} is not a module of the crate `{}`, so its functions would not be compiled into the library, and calling them from Dart would fail with missing symbols. {}",
+        rust_input_path.display(),
+        crate_map.name,
+        fix
+    )
 }
 
 struct Parser<'a> {
     type_parser: TypeParser<'a>,
+    /// Path of the module of the API file, e.g. `["crate", "api"]`.
+    api_module_path: Vec<String>,
+    /// Whether the functions which cannot be parsed are skipped, see [Parser::try_parse_function].
+    skip_unresolvable: bool,
+    /// The functions which cannot be parsed, by path, along with the reason.
+    skipped_funcs: Vec<(String, String)>,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(type_parser: TypeParser<'a>) -> Self {
-        Parser { type_parser }
+    pub fn new(
+        type_parser: TypeParser<'a>,
+        api_module_path: Vec<String>,
+        skip_unresolvable: bool,
+    ) -> Self {
+        Parser {
+            type_parser,
+            api_module_path,
+            skip_unresolvable,
+            skipped_funcs: Vec::new(),
+        }
     }
 }
 
 impl<'a> Parser<'a> {
-    fn parse(mut self, source_rust_content: &str, src_fns: Vec<ItemFn>) -> IrFile {
-        let funcs = src_fns.iter().map(|f| self.parse_function(f)).collect();
+    fn parse(
+        mut self,
+        src_fns: Vec<(ItemFn, Vec<String>)>,
+        src_inline_mod_fns: Vec<(ItemFn, Vec<String>)>,
+        src_trait_fns: Vec<(ItemFn, IrTraitImpl)>,
+        src_event_types: Vec<Ident>,
+        custom_handler: Option<String>,
+    ) -> IrFile {
+        let mut funcs: Vec<_> = src_fns
+            .iter()
+            .filter_map(|(f, module_path)| {
+                Some(IrFunc {
+                    module_path: module_path.clone(),
+                    ..self.try_parse_function(f, module_path)?
+                })
+            })
+            .collect();
+        // prefixed with their modules, which Dart has no equivalent of
+        for (f, mods) in src_inline_mod_fns {
+            let module_path = [self.api_module_path.clone(), mods.clone()].concat();
+            if let Some(func) = self.try_parse_function(&f, &module_path) {
+                funcs.push(IrFunc {
+                    name: format!("{}_{}", mods.join("_"), func.name),
+                    rust_call_path: Some(format!("{}::{}", mods.join("::"), func.name)),
+                    module_path,
+                    ..func
+                });
+            }
+        }
+        for (f, trait_impl) in src_trait_fns {
+            let module_path = self.api_module_path.clone();
+            if let Some(func) = self.try_parse_function(&f, &module_path) {
+                funcs.push(IrFunc {
+                    trait_impl: Some(trait_impl),
+                    ..func
+                });
+            }
+        }
+        if !self.skip_unresolvable && !self.skipped_funcs.is_empty() {
+            panic!("{}", unresolvable_funcs_report(&self.skipped_funcs));
+        }
 
-        let has_executor = source_rust_content.contains(HANDLER_NAME);
+        let closure_funcs = funcs
+            .iter()
+            .flat_map(|f| match &f.output {
+                IrType::Delegate(IrTypeDelegate::Closure(closure)) => {
+                    closure_handle_funcs(closure, &f.module_path)
+                }
+                IrType::Delegate(IrTypeDelegate::Iterator(iterator)) => {
+                    iterator_handle_funcs(iterator, &f.module_path)
+                }
+                _ => vec![],
+            })
+            .collect::<Vec<_>>();
+        funcs.extend(closure_funcs);
+        for ident in src_event_types {
+            let ty = self.type_parser.parse_type(&parse_quote!(#ident));
+            funcs.push(event_func(ty, &self.api_module_path));
+        }
+        for opaque in self.type_parser.opaque_types() {
+            funcs.extend(opaque_handle_funcs(opaque, &self.api_module_path));
+        }
 
         let (struct_pool, enum_pool) = self.type_parser.consume();
 
@@ -53,13 +267,62 @@ impl<'a> Parser<'a> {
             funcs,
             struct_pool,
             enum_pool,
-            has_executor,
+            custom_handler,
+            distinct_types_cache: Default::default(),
+            skipped_funcs: self.skipped_funcs,
+        }
+    }
+
+    /// Parses `func`, or records why it cannot be parsed instead of failing, so that the other
+    /// functions are parsed as well and all the errors are reported at once. With
+    /// `--skip-unresolvable`, the types parsed on the way are dropped, so that nothing of the
+    /// function makes it into the output.
+    fn try_parse_function(&mut self, func: &ItemFn, module_path: &[String]) -> Option<IrFunc> {
+        let type_parser = self.skip_unresolvable.then(|| self.type_parser.clone());
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| self.parse_function(func)));
+        panic::set_hook(default_hook);
+
+        match result {
+            Ok(func) => Some(func),
+            Err(payload) => {
+                if let Some(type_parser) = type_parser {
+                    self.type_parser = type_parser;
+                }
+                let reason = match payload.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(payload) => payload
+                        .downcast_ref::<&str>()
+                        .map_or_else(|| "unknown error".to_owned(), |it| (*it).to_owned()),
+                };
+                self.skipped_funcs.push((
+                    format!("{}::{}", module_path.join("::"), func.sig.ident),
+                    reason,
+                ));
+                None
+            }
         }
     }
 
     /// Attempts to parse the type from the return part of a function signature. There is a special
-    /// case for top-level `Result` types.
-    pub fn try_parse_fn_output_type(&mut self, ty: &syn::Type) -> Option<IrFuncOutput> {
+    /// case for top-level `Result` types, as well as `SyncReturn<Result<T>>`, closures and iterators.
+    pub fn try_parse_fn_output_type(
+        &mut self,
+        func_name: &str,
+        ty: &syn::Type,
+    ) -> Option<IrFuncOutput> {
+        if let Some(output) = self.try_parse_closure_output_type(func_name, ty) {
+            return Some(output);
+        }
+        if let Some(output) = self.try_parse_iterator_output_type(func_name, ty) {
+            return Some(output);
+        }
+        if let Some(output) = try_parse_unsafe_opaque_ptr_output_type(ty) {
+            return Some(output);
+        }
+
+        self.type_parser.check_type_depth(ty);
         let inner = ty::SupportedInnerType::try_from_syn_type(ty)?;
 
         match inner {
@@ -69,14 +332,154 @@ impl<'a> Parser<'a> {
             }) if ident == RESULT_IDENT => Some(IrFuncOutput::ResultType(
                 self.type_parser.convert_to_ir_type(*generic)?,
             )),
+            ty::SupportedInnerType::Result(ok, _) => Some(IrFuncOutput::ResultType(
+                self.type_parser.convert_to_ir_type(*ok)?,
+            )),
+            // `SyncReturn<Result<T>>` is as fallible as `Result<SyncReturn<T>>`
+            ty::SupportedInnerType::Path(ty::SupportedPathType {
+                ident,
+                generic: Some(generic),
+            }) if ident == SYNC_RETURN_IDENT && is_result_type(&generic) => {
+                let result_generic = match *generic {
+                    ty::SupportedInnerType::Path(ty::SupportedPathType { generic, .. }) => generic,
+                    ty::SupportedInnerType::Result(ok, _) => Some(ok),
+                    _ => unreachable!(),
+                };
+                Some(IrFuncOutput::ResultType(
+                    self.type_parser
+                        .convert_to_ir_type(ty::SupportedInnerType::Path(
+                            ty::SupportedPathType {
+                                ident,
+                                generic: result_generic,
+                            },
+                        ))?,
+                ))
+            }
             _ => Some(IrFuncOutput::Type(
                 self.type_parser.convert_to_ir_type(inner)?,
             )),
         }
     }
 
-    /// Attempts to parse the type from an argument of a function signature. There is a special
-    /// case for top-level `StreamSink` types.
+    /// Parses `Box<dyn Fn(..) -> T + Send + Sync>`, optionally wrapped in a `Result`.
+    fn try_parse_closure_output_type(
+        &mut self,
+        func_name: &str,
+        ty: &syn::Type,
+    ) -> Option<IrFuncOutput> {
+        let (ty, fallible) = match unwrap_generic_type(ty, RESULT_IDENT) {
+            Some(inner) => (inner, true),
+            None => (ty, false),
+        };
+        let bounds = match unwrap_generic_type(ty, BOX_IDENT)? {
+            Type::TraitObject(TypeTraitObject { bounds, .. }) => bounds,
+            _ => return None,
+        };
+        let fn_args = bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(TraitBound { path, .. }) => {
+                let last_segment = path.segments.last()?;
+                match &last_segment.arguments {
+                    PathArguments::Parenthesized(args) if last_segment.ident == FN_IDENT => {
+                        Some(args)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })?;
+        let has_bound = |name: &str| {
+            bounds.iter().any(|bound| {
+                matches!(bound, TypeParamBound::Trait(TraitBound { path, .. })
+                    if path.segments.last().is_some_and(|it| it.ident == name))
+            })
+        };
+        if !has_bound("Send") || !has_bound("Sync") {
+            panic!(
+                "the closure returned by `{}` should be `Send + Sync`, since Dart may call it from any thread",
+                func_name
+            );
+        }
+
+        let closure = IrTypeClosure {
+            func_name: func_name.to_owned(),
+            inputs: fn_args
+                .inputs
+                .iter()
+                .map(|ty| self.type_parser.parse_type(ty))
+                .collect(),
+            output: Box::new(match &fn_args.output {
+                ReturnType::Type(_, ty) => self.type_parser.parse_type(ty),
+                ReturnType::Default => IrType::Primitive(IrTypePrimitive::Unit),
+            }),
+        };
+        let ty = IrType::Delegate(IrTypeDelegate::Closure(closure));
+        Some(if fallible {
+            IrFuncOutput::ResultType(ty)
+        } else {
+            IrFuncOutput::Type(ty)
+        })
+    }
+
+    /// Parses `Box<dyn Iterator<Item = T> + Send>`, optionally wrapped in a `Result`.
+    fn try_parse_iterator_output_type(
+        &mut self,
+        func_name: &str,
+        ty: &syn::Type,
+    ) -> Option<IrFuncOutput> {
+        let (ty, fallible) = match unwrap_generic_type(ty, RESULT_IDENT) {
+            Some(inner) => (inner, true),
+            None => (ty, false),
+        };
+        let bounds = match unwrap_generic_type(ty, BOX_IDENT)? {
+            Type::TraitObject(TypeTraitObject { bounds, .. }) => bounds,
+            _ => return None,
+        };
+        let item = bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(TraitBound { path, .. }) => {
+                let last_segment = path.segments.last()?;
+                match &last_segment.arguments {
+                    PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                        args, ..
+                    }) if last_segment.ident == ITERATOR_IDENT => {
+                        args.iter().find_map(|arg| match arg {
+                            GenericArgument::Binding(Binding { ident, ty, .. })
+                                if ident == "Item" =>
+                            {
+                                Some(ty)
+                            }
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })?;
+        let is_send = bounds.iter().any(|bound| {
+            matches!(bound, TypeParamBound::Trait(TraitBound { path, .. })
+                if path.segments.last().is_some_and(|it| it.ident == "Send"))
+        });
+        if !is_send {
+            panic!(
+                "the iterator returned by `{}` should be `Send`, since Dart reads it from the worker threads",
+                func_name
+            );
+        }
+
+        let iterator = IrTypeIterator {
+            func_name: func_name.to_owned(),
+            item: Box::new(self.type_parser.parse_type(item)),
+        };
+        let ty = IrType::Delegate(IrTypeDelegate::Iterator(iterator));
+        Some(if fallible {
+            IrFuncOutput::ResultType(ty)
+        } else {
+            IrFuncOutput::Type(ty)
+        })
+    }
+
+    /// Attempts to parse the type from an argument of a function signature. There are special
+    /// cases for top-level `StreamSink` and `DartStream` types.
     pub fn try_parse_fn_arg_type(&mut self, ty: &syn::Type) -> Option<IrFuncArg> {
         match ty {
             syn::Type::Path(syn::TypePath { path, .. }) => {
@@ -96,6 +499,12 @@ impl<'a> Parser<'a> {
                         }
                         _ => None,
                     }
+                } else if last_segment.ident == DART_STREAM_IDENT {
+                    unwrap_generic_type(ty, DART_STREAM_IDENT).map(|t| {
+                        IrFuncArg::Type(IrType::Delegate(IrTypeDelegate::DartStream(Box::new(
+                            self.type_parser.parse_type(t),
+                        ))))
+                    })
                 } else {
                     Some(IrFuncArg::Type(self.type_parser.parse_type(ty)))
                 }
@@ -114,6 +523,9 @@ impl<'a> Parser<'a> {
         let mut output = None;
         let mut mode: Option<IrFuncMode> = None;
         let mut fallible = true;
+        let mut compile_error = None;
+        let mut to_owned_output = None;
+        let mut io_adapters = Vec::new();
 
         for (i, sig_input) in sig.inputs.iter().enumerate() {
             if let FnArg::Typed(ref pat_type) = sig_input {
@@ -122,22 +534,101 @@ impl<'a> Parser<'a> {
                 } else {
                     panic!("unexpected pat_type={:?}", pat_type)
                 };
-                match self.try_parse_fn_arg_type(&pat_type.ty).unwrap_or_else(|| {
+                if let Some(item) = borrowed_stream_sink_item(&pat_type.ty) {
+                    compile_error = Some(format!(
+                        "the items of `StreamSink<{}>` in `{}` must be `'static + Send`, since the sink may be kept after `{}` returns; use an owned type instead",
+                        type_to_string(item),
+                        func_name,
+                        func_name,
+                    ));
+                    output = Some(IrType::Primitive(IrTypePrimitive::Unit));
+                    mode = Some(IrFuncMode::Stream { argument_index: i });
+                    continue;
+                }
+                if let Some(adapter) = io_adapter(&func_name, &pat_type.ty) {
+                    let bytes = IrType::PrimitiveList(IrTypePrimitiveList {
+                        primitive: IrTypePrimitive::U8,
+                    });
+                    match adapter {
+                        IrFuncIoAdapter::Reader => inputs.push(IrField {
+                            name: IrIdent::new(name),
+                            ty: IrType::Delegate(IrTypeDelegate::DartStream(Box::new(bytes))),
+                            is_final: true,
+                            comments: extract_comments(&pat_type.attrs),
+                            validations: vec![],
+                            borrow: None,
+                        }),
+                        IrFuncIoAdapter::Writer => {
+                            check_single_stream_sink(&func_name, &mode);
+                            output = Some(bytes);
+                            mode = Some(IrFuncMode::Stream { argument_index: i });
+                        }
+                    }
+                    io_adapters.push((i, adapter));
+                    continue;
+                }
+                if let Some(ty) = unsafe_opaque_ptr_type(&pat_type.ty) {
+                    inputs.push(IrField {
+                        name: IrIdent::new(name),
+                        ty,
+                        is_final: true,
+                        comments: extract_comments(&pat_type.attrs),
+                        validations: vec![],
+                        borrow: None,
+                    });
+                    continue;
+                }
+                // `&T` and `&mut T` are transferred like `T`, then borrowed when calling the function
+                let (arg_ty, borrow) = match &*pat_type.ty {
+                    syn::Type::Reference(syn::TypeReference {
+                        mutability, elem, ..
+                    }) => (
+                        &**elem,
+                        Some(if mutability.is_some() {
+                            IrBorrow::Mut
+                        } else {
+                            IrBorrow::Shared
+                        }),
+                    ),
+                    ty => (ty, None),
+                };
+                match self.try_parse_fn_arg_type(arg_ty).unwrap_or_else(|| {
                     panic!(
                         "Failed to parse function argument type `{}`",
                         type_to_string(&pat_type.ty)
                     )
                 }) {
+                    IrFuncArg::StreamSinkType(_) if borrow.is_some() => panic!(
+                        "`{}` of `{}` must take the `StreamSink` by value, not `{}`",
+                        name,
+                        func_name,
+                        type_to_string(&pat_type.ty)
+                    ),
+                    IrFuncArg::Type(IrType::Delegate(IrTypeDelegate::DartStream(_)))
+                        if borrow.is_some() =>
+                    {
+                        panic!(
+                            "`{}` of `{}` must take the `DartStream` by value, not `{}`",
+                            name,
+                            func_name,
+                            type_to_string(&pat_type.ty)
+                        )
+                    }
                     IrFuncArg::StreamSinkType(ty) => {
+                        check_single_stream_sink(&func_name, &mode);
                         output = Some(ty);
                         mode = Some(IrFuncMode::Stream { argument_index: i });
                     }
                     IrFuncArg::Type(ty) => {
+                        let validations = markers::extract_validations(&pat_type.attrs);
+                        check_validations(&name, &ty, &validations);
                         inputs.push(IrField {
                             name: IrIdent::new(name),
                             ty,
                             is_final: true,
                             comments: extract_comments(&pat_type.attrs),
+                            validations,
+                            borrow,
                         });
                     }
                 }
@@ -146,15 +637,56 @@ impl<'a> Parser<'a> {
             }
         }
 
+        let channel = match &sig.output {
+            ReturnType::Type(_, ty) => channel_receiver(ty),
+            ReturnType::Default => None,
+        };
+        if let Some((channel, ty)) = &channel {
+            if let Some(IrFuncMode::Stream { .. }) = mode {
+                panic!(
+                    "`{}` returns a `{}::Receiver`, which becomes the Dart stream it returns, so it cannot take a `StreamSink` or `impl Write` as well",
+                    func_name,
+                    channel.module()
+                );
+            }
+            let (item, channel_fallible) = match unwrap_generic_type(ty, RESULT_IDENT) {
+                Some(receiver) => (unwrap_generic_type(receiver, "Receiver").unwrap(), true),
+                None => (unwrap_generic_type(ty, "Receiver").unwrap(), false),
+            };
+            output = Some(self.type_parser.parse_type(item));
+            fallible = channel_fallible;
+            mode = Some(IrFuncMode::Stream {
+                argument_index: sig.inputs.len(),
+            });
+        }
+
+        let stream_setup =
+            matches!(&sig.output, ReturnType::Type(_, ty) if is_stream_setup_result(ty));
+        if stream_setup
+            && (!matches!(mode, Some(IrFuncMode::Stream { .. }))
+                || channel.is_some()
+                || !io_adapters.is_empty())
+        {
+            panic!(
+                "`{}` returns a `StreamSetup`, which only applies to functions taking a `StreamSink`",
+                func_name
+            );
+        }
+
         if output.is_none() {
             output = Some(match &sig.output {
                 ReturnType::Type(_, ty) => {
-                    match self.try_parse_fn_output_type(ty).unwrap_or_else(|| {
-                        panic!(
-                            "Failed to parse function output type `{}`",
-                            type_to_string(ty)
-                        )
-                    }) {
+                    let owned_ty = owned_output_type(&func_name, ty, &self.type_parser);
+                    to_owned_output = owned_ty.as_ref().map(|(_, owned_output)| *owned_output);
+                    let ty = owned_ty.as_ref().map_or(&**ty, |(ty, _)| ty);
+                    match self
+                        .try_parse_fn_output_type(&func_name, ty)
+                        .unwrap_or_else(|| {
+                            panic!(
+                                "Failed to parse function output type `{}`",
+                                type_to_string(ty)
+                            )
+                        }) {
                         IrFuncOutput::ResultType(ty) => ty,
                         IrFuncOutput::Type(ty) => {
                             fallible = false;
@@ -167,15 +699,152 @@ impl<'a> Parser<'a> {
                     IrType::Primitive(IrTypePrimitive::Unit)
                 }
             });
-            mode = Some(
-                if let Some(IrType::Delegate(IrTypeDelegate::SyncReturnVecU8)) = output {
-                    IrFuncMode::Sync
-                } else {
-                    IrFuncMode::Normal
-                },
+            mode = Some(if matches!(&output, Some(ty) if ty.is_sync_return()) {
+                IrFuncMode::Sync
+            } else {
+                IrFuncMode::Normal
+            });
+        }
+
+        if matches!(&output, Some(ty) if !matches!(ty, IrType::Delegate(IrTypeDelegate::RustOpaque(_))) && contains_rust_opaque(ty))
+        {
+            panic!(
+                "`{}` can only return a `RustOpaque` as is, not inside another type, since Dart needs the API class to build the handle",
+                func_name
+            );
+        }
+
+        // the borrow of the receiver is up to the lock, see `opaque_receiver`
+        let receiver_lock = markers::extract_receiver_lock_marker(&func.attrs).map(|kind| {
+            let that = inputs
+                .first_mut()
+                .filter(|_| FunctionName::deserialize(&func_name).is_non_static_method())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "#[frb(sync_mutex)], #[frb(rwlock)] and #[frb(no_lock)] on `{}` are only supported on methods taking `&self` or `&mut self`",
+                        func_name
+                    )
+                });
+            IrReceiverLock {
+                kind,
+                borrow: that.borrow.take().unwrap(),
+            }
+        });
+
+        let is_unsafe_opaque_ptr =
+            |ty: &IrType| matches!(ty, IrType::Delegate(IrTypeDelegate::UnsafeOpaquePtr(_)));
+        if !markers::has_unsafe_ffi(&func.attrs)
+            && (inputs.iter().any(|field| is_unsafe_opaque_ptr(&field.ty))
+                || output.as_ref().is_some_and(is_unsafe_opaque_ptr))
+        {
+            panic!(
+                "`{}` takes or returns a raw pointer, which Dart cannot check in any way; mark it with #[frb(unsafe_ffi)] to pass the pointer as a plain address",
+                func_name
+            );
+        }
+
+        let retry = markers::extract_retry_marker(&func.attrs);
+        if retry.is_some() && mode != Some(IrFuncMode::Normal) {
+            panic!(
+                "#[frb(retry(..))] on `{}` is only supported for functions returning a Future in Dart",
+                func_name
+            );
+        }
+
+        let distinct = markers::has_distinct(&func.attrs);
+        if distinct && !matches!(mode, Some(IrFuncMode::Stream { .. })) {
+            panic!(
+                "#[frb(distinct)] on `{}` is only supported for functions returning a Stream in Dart",
+                func_name
+            );
+        }
+
+        let lazy_decode = markers::has_lazy_decode(&func.attrs);
+        if lazy_decode
+            && (mode != Some(IrFuncMode::Normal)
+                || !matches!(&output, Some(IrType::GeneralList(_))))
+        {
+            panic!(
+                "#[frb(lazy_decode)] on `{}` is only supported for functions returning a Future of a list of non-primitive items in Dart",
+                func_name
+            );
+        }
+
+        let cancelable = markers::has_cancelable(&func.attrs);
+        if cancelable && (mode != Some(IrFuncMode::Normal) || retry.is_some()) {
+            panic!(
+                "#[frb(cancelable)] on `{}` is only supported for functions returning a Future in Dart, without #[frb(retry(..))]",
+                func_name
+            );
+        }
+
+        let debounce_ms = markers::extract_debounce_marker(&func.attrs);
+        if debounce_ms.is_some()
+            && (mode != Some(IrFuncMode::Normal) || retry.is_some() || cancelable)
+        {
+            panic!(
+                "#[frb(debounce_ms = ..)] on `{}` is only supported for functions returning a Future in Dart, without #[frb(retry(..))] or #[frb(cancelable)]",
+                func_name
             );
         }
 
+        let poll = markers::has_poll(&func.attrs);
+        if poll && (!inputs.is_empty() || matches!(mode, Some(IrFuncMode::Stream { .. }))) {
+            panic!(
+                "#[frb(poll)] on `{}` is only supported for functions taking no argument and returning a single value",
+                func_name
+            );
+        }
+
+        let accessor = markers::extract_accessor_marker(&func.attrs);
+        if let Some(accessor) = accessor {
+            let name = FunctionName::deserialize(&func_name);
+            let (attr, expected) = match accessor {
+                IrFuncAccessor::Getter => ("getter", "taking no argument but `&self`"),
+                IrFuncAccessor::Setter => (
+                    "setter",
+                    "taking one argument besides `&self`, and returning `SyncReturn<()>` since a Dart setter cannot be awaited",
+                ),
+            };
+            let supported = name.is_non_static_method()
+                && match accessor {
+                    IrFuncAccessor::Getter => {
+                        inputs.len() == 1 && !matches!(mode, Some(IrFuncMode::Stream { .. }))
+                    }
+                    IrFuncAccessor::Setter => {
+                        inputs.len() == 2
+                            && matches!(&output, Some(IrType::Delegate(IrTypeDelegate::SyncReturn(inner))) if matches!(**inner, IrType::Primitive(IrTypePrimitive::Unit)))
+                    }
+                };
+            if !supported {
+                panic!(
+                    "#[frb({})] on `{}` is only supported on methods {}",
+                    attr,
+                    name.method_name(),
+                    expected
+                );
+            }
+        }
+
+        let compress = markers::extract_compress_marker(&func.attrs);
+        if compress.is_some() {
+            let supported_output = matches!(
+                &output,
+                Some(
+                    IrType::Delegate(IrTypeDelegate::String)
+                        | IrType::PrimitiveList(IrTypePrimitiveList {
+                            primitive: IrTypePrimitive::U8
+                        })
+                )
+            );
+            if !supported_output || mode != Some(IrFuncMode::Normal) {
+                panic!(
+                    "#[frb(compress = ..)] on `{}` is only supported for functions returning a Future of `String` or `Vec<u8>` in Dart",
+                    func_name
+                );
+            }
+        }
+
         IrFunc {
             name: func_name,
             inputs,
@@ -183,10 +852,430 @@ impl<'a> Parser<'a> {
             fallible,
             mode: mode.expect("missing mode"),
             comments: extract_comments(&func.attrs),
+            trait_impl: None,
+            msgpack: false,
+            retry,
+            closure: None,
+            dyn_error: matches!(&sig.output, ReturnType::Type(_, ty) if is_dyn_error_result(ty)),
+            receiver_lock,
+            compile_error,
+            compress,
+            dart_name: markers::extract_dart_name_marker(&func.attrs),
+            event: false,
+            cancelable,
+            accessor,
+            to_owned_output,
+            module_path: self.api_module_path.clone(),
+            io_adapters,
+            max_concurrency: markers::extract_max_concurrency_marker(&func.attrs),
+            channel: channel.map(|(channel, _)| channel),
+            poll,
+            lazy_decode,
+            rust_call_path: None,
+            distinct,
+            debounce_ms,
+            debug_only: markers::has_debug_only(&func.attrs),
+            stream_setup,
+            renamed_from: markers::extract_renamed_from_markers(&func.attrs),
+        }
+    }
+}
+
+/// Whether `ty` is or holds a `RustOpaque`, leaving out the fields of structs and enums, where
+/// the type parser does not allow them.
+fn contains_rust_opaque(ty: &IrType) -> bool {
+    match ty {
+        IrType::Delegate(IrTypeDelegate::RustOpaque(_)) => true,
+        IrType::Optional(IrTypeOptional { inner })
+        | IrType::Boxed(IrTypeBoxed { inner, .. })
+        | IrType::GeneralList(IrTypeGeneralList { inner })
+        | IrType::Delegate(IrTypeDelegate::SyncReturn(inner)) => contains_rust_opaque(inner),
+        IrType::Delegate(IrTypeDelegate::BTreeMap { key, value }) => {
+            contains_rust_opaque(key) || contains_rust_opaque(value)
         }
+        IrType::Delegate(IrTypeDelegate::Result { ok, err }) => {
+            contains_rust_opaque(ok) || contains_rust_opaque(err)
+        }
+        _ => false,
+    }
+}
+
+/// The functions through which Dart clones, prints and releases the handles of a `RustOpaque`.
+fn opaque_handle_funcs(opaque: &IrTypeRustOpaque, module_path: &[String]) -> Vec<IrFunc> {
+    let handle = IrField {
+        name: IrIdent::new("handle".to_owned()),
+        ty: IrType::Primitive(IrTypePrimitive::I64),
+        is_final: true,
+        comments: vec![],
+        validations: vec![],
+        borrow: None,
+    };
+    let sync_func = |name: String, output: IrType, closure: IrFuncClosure| IrFunc {
+        name,
+        inputs: vec![handle.clone()],
+        output: IrType::Delegate(IrTypeDelegate::SyncReturn(Box::new(output))),
+        fallible: false,
+        mode: IrFuncMode::Sync,
+        comments: vec![],
+        trait_impl: None,
+        msgpack: false,
+        retry: None,
+        closure: Some(closure),
+        dyn_error: false,
+        receiver_lock: None,
+        compile_error: None,
+        compress: None,
+        dart_name: None,
+        event: false,
+        cancelable: false,
+        module_path: module_path.to_vec(),
+        accessor: None,
+        to_owned_output: None,
+        io_adapters: vec![],
+        max_concurrency: None,
+        channel: None,
+        poll: false,
+        lazy_decode: false,
+        rust_call_path: None,
+        distinct: false,
+        debounce_ms: None,
+        debug_only: false,
+        stream_setup: false,
+        renamed_from: vec![],
+    };
+    let unit = IrType::Primitive(IrTypePrimitive::Unit);
+
+    let mut funcs = vec![
+        sync_func(
+            opaque.clone_func_name(),
+            unit.clone(),
+            IrFuncClosure::OpaqueClone(opaque.clone()),
+        ),
+        sync_func(
+            opaque.drop_func_name(),
+            unit,
+            IrFuncClosure::OpaqueDrop(opaque.clone()),
+        ),
+    ];
+    if opaque.debug {
+        funcs.push(sync_func(
+            opaque.debug_func_name(),
+            IrType::Delegate(IrTypeDelegate::String),
+            IrFuncClosure::OpaqueDebug(opaque.clone()),
+        ));
+    }
+    funcs
+}
+
+/// `impl Read` or `impl Write`, possibly along with auto traits such as `Send`.
+fn io_adapter(func_name: &str, ty: &Type) -> Option<IrFuncIoAdapter> {
+    let bounds = match ty {
+        Type::ImplTrait(TypeImplTrait { bounds, .. }) => bounds,
+        _ => return None,
+    };
+    let traits = bounds
+        .iter()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(TraitBound { path, .. }) => {
+                path.segments.last().map(|it| it.ident.to_string())
+            }
+            _ => None,
+        })
+        .filter(|name| !matches!(name.as_str(), "Send" | "Sync" | "Unpin"))
+        .collect::<Vec<_>>();
+    match traits.as_slice() {
+        [name] if name == "Read" => Some(IrFuncIoAdapter::Reader),
+        [name] if name == "Write" => Some(IrFuncIoAdapter::Writer),
+        _ => panic!(
+            "`{}` takes `{}`, while only `impl Read` and `impl Write` are supported as arguments",
+            func_name,
+            quote!(#ty)
+        ),
+    }
+}
+
+/// `Result<StreamSetup, E>`, returned by the functions taking a `StreamSink` which fail before
+/// streaming anything.
+fn is_stream_setup_result(ty: &Type) -> bool {
+    matches!(
+        unwrap_generic_type(ty, RESULT_IDENT),
+        Some(Type::Path(TypePath { path, .. }))
+            if path.segments.last().is_some_and(|segment| segment.ident == STREAM_SETUP_IDENT)
+    )
+}
+
+/// The output of a function is the stream of its `StreamSink` or `impl Write`, so it can only
+/// take one of them.
+fn check_single_stream_sink(func_name: &str, mode: &Option<IrFuncMode>) {
+    if let Some(IrFuncMode::Stream { .. }) = mode {
+        panic!(
+            "`{}` can only take one `StreamSink` or `impl Write`, which becomes the Dart stream it returns",
+            func_name
+        );
+    }
+}
+
+/// `*mut T` or `*const T`, which is only allowed as a whole argument or return type.
+fn unsafe_opaque_ptr_type(ty: &Type) -> Option<IrType> {
+    match ty {
+        Type::Ptr(TypePtr {
+            mutability, elem, ..
+        }) => Some(IrType::Delegate(IrTypeDelegate::UnsafeOpaquePtr(
+            IrTypeUnsafeOpaquePtr {
+                mutable: mutability.is_some(),
+                // keeps the spaces of e.g. `*mut u8`, unlike `type_to_string`
+                pointee: quote!(#elem).to_string().replace(" :: ", "::"),
+            },
+        ))),
+        _ => None,
+    }
+}
+
+/// Parses `*mut T` or `*const T`, optionally wrapped in a `Result`.
+fn try_parse_unsafe_opaque_ptr_output_type(ty: &Type) -> Option<IrFuncOutput> {
+    match unwrap_generic_type(ty, RESULT_IDENT) {
+        Some(inner) => unsafe_opaque_ptr_type(inner).map(IrFuncOutput::ResultType),
+        None => unsafe_opaque_ptr_type(ty).map(IrFuncOutput::Type),
+    }
+}
+
+/// The functions through which Dart calls and releases a closure returned by another function.
+fn closure_handle_funcs(closure: &IrTypeClosure, module_path: &[String]) -> Vec<IrFunc> {
+    let handle = IrField {
+        name: IrIdent::new("handle".to_owned()),
+        ty: IrType::Primitive(IrTypePrimitive::I64),
+        is_final: true,
+        comments: vec![],
+        validations: vec![],
+        borrow: None,
+    };
+    let args = closure.inputs.iter().enumerate().map(|(i, ty)| IrField {
+        name: IrIdent::new(format!("arg{}", i)),
+        ty: ty.clone(),
+        is_final: true,
+        comments: vec![],
+        validations: vec![],
+        borrow: None,
+    });
+
+    vec![
+        IrFunc {
+            name: closure.call_func_name(),
+            inputs: std::iter::once(handle.clone()).chain(args).collect(),
+            output: (*closure.output).clone(),
+            fallible: false,
+            mode: IrFuncMode::Normal,
+            comments: vec![],
+            trait_impl: None,
+            msgpack: false,
+            retry: None,
+            closure: Some(IrFuncClosure::Call(closure.clone())),
+            dyn_error: false,
+            receiver_lock: None,
+            compile_error: None,
+            compress: None,
+            dart_name: None,
+            event: false,
+            cancelable: false,
+            accessor: None,
+            to_owned_output: None,
+            io_adapters: vec![],
+            max_concurrency: None,
+            channel: None,
+            poll: false,
+            lazy_decode: false,
+            rust_call_path: None,
+            distinct: false,
+            debounce_ms: None,
+            debug_only: false,
+            stream_setup: false,
+            renamed_from: vec![],
+            module_path: module_path.to_vec(),
+        },
+        IrFunc {
+            name: closure.drop_func_name(),
+            inputs: vec![handle],
+            output: IrType::Delegate(IrTypeDelegate::SyncReturn(Box::new(IrType::Primitive(
+                IrTypePrimitive::Unit,
+            )))),
+            fallible: false,
+            mode: IrFuncMode::Sync,
+            comments: vec![],
+            trait_impl: None,
+            msgpack: false,
+            retry: None,
+            closure: Some(IrFuncClosure::Drop(closure.clone())),
+            dyn_error: false,
+            receiver_lock: None,
+            compile_error: None,
+            compress: None,
+            dart_name: None,
+            event: false,
+            cancelable: false,
+            accessor: None,
+            to_owned_output: None,
+            io_adapters: vec![],
+            max_concurrency: None,
+            channel: None,
+            poll: false,
+            lazy_decode: false,
+            rust_call_path: None,
+            distinct: false,
+            debounce_ms: None,
+            debug_only: false,
+            stream_setup: false,
+            renamed_from: vec![],
+            module_path: module_path.to_vec(),
+        },
+    ]
+}
+
+/// The functions through which Dart reads and releases an iterator returned by another function.
+fn iterator_handle_funcs(iterator: &IrTypeIterator, module_path: &[String]) -> Vec<IrFunc> {
+    let field = |name: &str, ty| IrField {
+        name: IrIdent::new(name.to_owned()),
+        ty,
+        is_final: true,
+        comments: vec![],
+        validations: vec![],
+        borrow: None,
+    };
+    let handle = field("handle", IrType::Primitive(IrTypePrimitive::I64));
+
+    vec![
+        IrFunc {
+            name: iterator.next_func_name(),
+            inputs: vec![
+                handle.clone(),
+                field("count", IrType::Primitive(IrTypePrimitive::U32)),
+            ],
+            output: IrType::list_of(&iterator.item),
+            fallible: false,
+            mode: IrFuncMode::Normal,
+            comments: vec![],
+            trait_impl: None,
+            msgpack: false,
+            retry: None,
+            closure: Some(IrFuncClosure::IteratorNext(iterator.clone())),
+            dyn_error: false,
+            receiver_lock: None,
+            compile_error: None,
+            compress: None,
+            dart_name: None,
+            event: false,
+            cancelable: false,
+            accessor: None,
+            to_owned_output: None,
+            io_adapters: vec![],
+            max_concurrency: None,
+            channel: None,
+            poll: false,
+            lazy_decode: false,
+            rust_call_path: None,
+            distinct: false,
+            debounce_ms: None,
+            debug_only: false,
+            stream_setup: false,
+            renamed_from: vec![],
+            module_path: module_path.to_vec(),
+        },
+        IrFunc {
+            name: iterator.drop_func_name(),
+            inputs: vec![handle],
+            output: IrType::Delegate(IrTypeDelegate::SyncReturn(Box::new(IrType::Primitive(
+                IrTypePrimitive::Unit,
+            )))),
+            fallible: false,
+            mode: IrFuncMode::Sync,
+            comments: vec![],
+            trait_impl: None,
+            msgpack: false,
+            retry: None,
+            closure: Some(IrFuncClosure::IteratorDrop(iterator.clone())),
+            dyn_error: false,
+            receiver_lock: None,
+            compile_error: None,
+            compress: None,
+            dart_name: None,
+            event: false,
+            cancelable: false,
+            accessor: None,
+            to_owned_output: None,
+            io_adapters: vec![],
+            max_concurrency: None,
+            channel: None,
+            poll: false,
+            lazy_decode: false,
+            rust_call_path: None,
+            distinct: false,
+            debounce_ms: None,
+            debug_only: false,
+            stream_setup: false,
+            renamed_from: vec![],
+            module_path: module_path.to_vec(),
+        },
+    ]
+}
+
+/// The function through which Dart listens to the values of an `#[frb(event)]` type.
+fn event_func(ty: IrType, module_path: &[String]) -> IrFunc {
+    IrFunc {
+        name: format!("{}_events", ty.safe_ident().to_case(Case::Snake)),
+        inputs: vec![],
+        output: ty.clone(),
+        fallible: false,
+        mode: IrFuncMode::Stream { argument_index: 0 },
+        comments: vec![IrComment::from(
+            format!(
+                " The `{}` events sent by `Event::emit` on the Rust side.",
+                ty.rust_api_type()
+            )
+            .as_str(),
+        )],
+        trait_impl: None,
+        msgpack: false,
+        retry: None,
+        closure: None,
+        dyn_error: false,
+        receiver_lock: None,
+        compile_error: None,
+        compress: None,
+        dart_name: None,
+        event: true,
+        cancelable: false,
+        accessor: None,
+        to_owned_output: None,
+        io_adapters: vec![],
+        max_concurrency: None,
+        channel: None,
+        poll: false,
+        lazy_decode: false,
+        rust_call_path: None,
+        distinct: false,
+        debounce_ms: None,
+        debug_only: false,
+        stream_setup: false,
+        renamed_from: vec![],
+        module_path: module_path.to_vec(),
     }
 }
 
+/// Finds the structs and enums marked with `#[frb(event)]` in the API file.
+fn extract_event_types_from_file(file: &File) -> Vec<Ident> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(item_struct) if markers::has_event(&item_struct.attrs) => {
+                Some(item_struct.ident.clone())
+            }
+            Item::Enum(item_enum) if markers::has_event(&item_enum.attrs) => {
+                Some(item_enum.ident.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 fn extract_fns_from_file(file: &File) -> Vec<ItemFn> {
     let mut src_fns = Vec::new();
 
@@ -201,6 +1290,137 @@ fn extract_fns_from_file(file: &File) -> Vec<ItemFn> {
     src_fns
 }
 
+/// The public functions of the `pub mod` blocks of the API file, along with the path of their
+/// module from the API module, e.g. `["advanced"]`.
+fn extract_fns_from_inline_mods(items: &[Item], mods: &[String]) -> Vec<(ItemFn, Vec<String>)> {
+    let mut src_fns = Vec::new();
+
+    for item in items {
+        if let Item::Mod(ItemMod {
+            vis: Visibility::Public(_),
+            ident,
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            let mods = [mods, &[ident.to_string()]].concat();
+            for item in items {
+                if let Item::Fn(item_fn) = item {
+                    if let Visibility::Public(_) = &item_fn.vis {
+                        src_fns.push((item_fn.clone(), mods.clone()));
+                    }
+                }
+            }
+            src_fns.extend(extract_fns_from_inline_mods(items, &mods));
+        }
+    }
+
+    src_fns
+}
+
+/// Finds the handler provided by the API file: a static named `FLUTTER_RUST_BRIDGE_HANDLER` or marked
+/// with `#[frb(handler)]`, either as a plain `static` or inside `lazy_static!`.
+fn find_custom_handler(file: &File) -> Option<String> {
+    file.items.iter().find_map(|item| match item {
+        Item::Static(item_static)
+            if item_static.ident == HANDLER_NAME || markers::has_handler(&item_static.attrs) =>
+        {
+            Some(item_static.ident.to_string())
+        }
+        Item::Macro(ItemMacro { mac, .. })
+            if mac
+                .path
+                .segments
+                .last()
+                .is_some_and(|it| it.ident == "lazy_static") =>
+        {
+            find_custom_handler_in_lazy_static(mac)
+        }
+        _ => None,
+    })
+}
+
+/// Looks for `static ref NAME` in the body of `lazy_static!`, which syn does not parse as items.
+fn find_custom_handler_in_lazy_static(mac: &Macro) -> Option<String> {
+    mac.parse_body_with(|input: ParseStream<'_>| {
+        let mut attrs = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Token![#]) {
+                attrs.extend(input.call(Attribute::parse_outer)?);
+            } else if input.peek(Token![static]) && input.peek2(Token![ref]) {
+                input.parse::<Token![static]>()?;
+                input.parse::<Token![ref]>()?;
+                let name: Ident = input.parse()?;
+                if name == HANDLER_NAME || markers::has_handler(&attrs) {
+                    return Ok(Some(name.to_string()));
+                }
+                attrs.clear();
+            } else {
+                if input.peek(Token![;]) {
+                    attrs.clear();
+                }
+                input.step(|cursor| {
+                    cursor
+                        .token_tree()
+                        .map(|(_, rest)| ((), rest))
+                        .ok_or_else(|| cursor.error("unexpected end of input"))
+                })?;
+            }
+        }
+        Ok(None)
+    })
+    .ok()
+    .flatten()
+}
+
+/// Functions re-exported by the API file, e.g. `pub use crate::imp::do_work;` or `pub use imp::*;`,
+/// resolved through the modules of the crate. Renamed imports are not supported.
+/// Each function comes with the path of the module defining it.
+fn extract_fns_from_use(
+    file: &File,
+    crate_map: &Crate,
+    api_module_path: &[String],
+) -> Vec<(ItemFn, Vec<String>)> {
+    let mut src_fns = Vec::new();
+    for item in file.items.iter() {
+        let item_use = match item {
+            Item::Use(item_use) if matches!(item_use.vis, Visibility::Public(_)) => item_use,
+            _ => continue,
+        };
+        for use_path in flatten_use_tree(&item_use.tree) {
+            let (name, module_path) = match use_path.split_last() {
+                Some((name, module_path)) if !module_path.is_empty() => (name, module_path),
+                _ => continue,
+            };
+            let mut absolute_path = if module_path[0] == "crate" {
+                vec![]
+            } else {
+                api_module_path.to_vec()
+            };
+            for segment in module_path {
+                match segment.as_str() {
+                    "self" => {}
+                    "super" => {
+                        absolute_path.pop();
+                    }
+                    _ => absolute_path.push(segment.clone()),
+                }
+            }
+            if let Some(module) = crate_map.root_module.find_module(&absolute_path) {
+                src_fns.extend(
+                    module
+                        .public_fns()
+                        .into_iter()
+                        .filter(|f| name == "*" || f.sig.ident == name)
+                        .map(|f| (f.clone(), module.module_path.clone())),
+                );
+            }
+        }
+    }
+
+    src_fns
+}
+
 fn extract_methods_from_file(file: &File) -> Vec<ItemFn> {
     let mut src_fns = Vec::new();
 
@@ -221,11 +1441,103 @@ fn extract_methods_from_file(file: &File) -> Vec<ItemFn> {
     src_fns
 }
 
+/// Collects the methods of `impl Trait for Type` blocks, where `Trait` is a `pub trait` declared
+/// in the same file. Doc comments are taken from the trait declaration, since it is the contract.
+fn extract_trait_methods_from_file(file: &File) -> Vec<(ItemFn, IrTraitImpl)> {
+    let mut src_fns = Vec::new();
+
+    for item in file.items.iter() {
+        if let Item::Trait(item_trait) = item {
+            if !matches!(item_trait.vis, Visibility::Public(_)) {
+                continue;
+            }
+            let impls = file
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Impl(
+                        item_impl @ ItemImpl {
+                            trait_: Some((_, path, _)),
+                            ..
+                        },
+                    ) if path.is_ident(&item_trait.ident) => Some(item_impl),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let item_impl = match impls.as_slice() {
+                [item_impl] => item_impl,
+                [] => continue,
+                _ => panic!(
+                    "trait `{}` should be implemented exactly once in the API file to be bridged",
+                    item_trait.ident
+                ),
+            };
+            let self_ty = match item_impl.self_ty.as_ref() {
+                Type::Path(p) => p.path.segments.last().unwrap().ident.to_string(),
+                other => panic!("unsupported trait impl target: {}", type_to_string(other)),
+            };
+
+            for item in &item_impl.items {
+                if let ImplItem::Method(item_method) = item {
+                    let trait_attrs = item_trait.items.iter().find_map(|it| match it {
+                        TraitItem::Method(m) if m.sig.ident == item_method.sig.ident => {
+                            Some(m.attrs.clone())
+                        }
+                        _ => None,
+                    });
+                    let receiver = item_method.sig.inputs.iter().find_map(|input| match input {
+                        FnArg::Receiver(Receiver {
+                            reference,
+                            mutability,
+                            ..
+                        // `mut self` only makes the binding mutable, the receiver is still moved
+                        }) => Some(
+                            match (reference, mutability) {
+                                (None, _) => "",
+                                (Some(_), None) => "&",
+                                (Some(_), Some(_)) => "&mut ",
+                            }
+                            .to_owned(),
+                        ),
+                        _ => None,
+                    });
+                    let f = ItemFn {
+                        attrs: trait_attrs.unwrap_or_else(|| item_method.attrs.clone()),
+                        vis: Visibility::Inherited,
+                        sig: Signature {
+                            inputs: item_method
+                                .sig
+                                .inputs
+                                .iter()
+                                .filter(|input| matches!(input, FnArg::Typed(_)))
+                                .cloned()
+                                .collect(),
+                            ..item_method.sig.clone()
+                        },
+                        block: Box::new(item_method.block.clone()),
+                    };
+                    src_fns.push((
+                        f,
+                        IrTraitImpl {
+                            trait_name: item_trait.ident.to_string(),
+                            self_ty: self_ty.clone(),
+                            receiver,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    src_fns
+}
+
 // Converts an item implementation (something like fn(&self, ...)) into a function where `&self` is a named parameter to `&Self`
 fn item_method_to_function(item_impl: &ItemImpl, item_method: &ImplItemMethod) -> Option<ItemFn> {
     if let Type::Path(p) = item_impl.self_ty.as_ref() {
         let struct_name = p.path.segments.first().unwrap().ident.to_string();
         let span = item_method.sig.ident.span();
+        let receiver_lock = markers::extract_receiver_lock_marker(&item_method.attrs);
         let is_static_method = {
             let Signature { inputs, .. } = &item_method.sig;
             {
@@ -273,7 +1585,13 @@ fn item_method_to_function(item_impl: &ItemImpl, item_method: &ImplItemMethod) -
         };
 
         Some(ItemFn {
-            attrs: vec![],
+            // only ours, e.g. `#[frb(getter)]`
+            attrs: item_method
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("frb"))
+                .cloned()
+                .collect(),
             vis: item_method.vis.clone(),
             sig: Signature {
                 constness: None,
@@ -289,6 +1607,14 @@ fn item_method_to_function(item_impl: &ItemImpl, item_method: &ImplItemMethod) -
                     .inputs
                     .iter()
                     .map(|input| {
+                        if let (Some(lock), FnArg::Receiver(receiver)) = (receiver_lock, input) {
+                            return opaque_receiver(
+                                lock,
+                                &struct_name,
+                                &item_method.sig.ident,
+                                receiver,
+                            );
+                        }
                         if let FnArg::Receiver(Receiver { mutability, .. }) = input {
                             let mut segments = Punctuated::new();
                             segments.push(PathSegment {
@@ -331,6 +1657,51 @@ fn item_method_to_function(item_impl: &ItemImpl, item_method: &ImplItemMethod) -
     }
 }
 
+/// The receiver of a method marked with a lock, e.g. `&mut self` of `#[frb(sync_mutex)]`, taken as
+/// `that: &mut RustOpaque<Mutex<Struct>>`, whose borrow then tells how to lock it.
+fn opaque_receiver(
+    lock: IrReceiverLockKind,
+    struct_name: &str,
+    method_name: &Ident,
+    receiver: &Receiver,
+) -> FnArg {
+    if receiver.reference.is_none() {
+        panic!(
+            "`{}` must take `&self` or `&mut self` to be called on a `RustOpaque`, which shares the value",
+            method_name
+        );
+    }
+    if lock == IrReceiverLockKind::NoLock && receiver.mutability.is_some() {
+        panic!(
+            "#[frb(no_lock)] on `{}` only supports `&self`, since the calls on the handles to the value may run at the same time; use #[frb(sync_mutex)] or #[frb(rwlock)] for `&mut self`",
+            method_name
+        );
+    }
+    let ty = format!(
+        "{}RustOpaque<{}>",
+        if receiver.mutability.is_some() {
+            "&mut "
+        } else {
+            "&"
+        },
+        lock.opaque_inner(struct_name)
+    );
+    FnArg::Typed(PatType {
+        attrs: vec![],
+        pat: Box::new(Pat::Ident(PatIdent {
+            attrs: vec![],
+            by_ref: None,
+            mutability: None,
+            ident: Ident::new("that", method_name.span()),
+            subpat: None,
+        })),
+        colon_token: Colon {
+            spans: [method_name.span()],
+        },
+        ty: Box::new(syn::parse_str(&ty).unwrap()),
+    })
+}
+
 fn extract_comments(attrs: &[Attribute]) -> Vec<IrComment> {
     attrs
         .iter()
@@ -477,6 +1848,161 @@ fn extract_metadata(attrs: &[Attribute]) -> Vec<IrDartAnnotation> {
         .collect()
 }
 
+/// Returns `T` of `Ident<T>`, e.g. for `Box<T>`.
+fn unwrap_generic_type<'a>(ty: &'a Type, ident: &str) -> Option<&'a Type> {
+    match ty {
+        Type::Path(TypePath { path, .. }) => {
+            let last_segment = path.segments.last()?;
+            match &last_segment.arguments {
+                PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. })
+                    if last_segment.ident == ident =>
+                {
+                    args.iter().find_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `watch::Receiver<T>` or `broadcast::Receiver<T>` of `tokio::sync`, possibly in a `Result`,
+/// along with the type itself. The module must be part of the path, to tell them from the
+/// receivers of other channels.
+fn channel_receiver(ty: &Type) -> Option<(IrFuncChannel, &Type)> {
+    let receiver = unwrap_generic_type(ty, RESULT_IDENT).unwrap_or(ty);
+    let segments = match receiver {
+        Type::Path(TypePath { path, .. }) => &path.segments,
+        _ => return None,
+    };
+    let mut modules = segments.iter().rev();
+    if modules.next()?.ident != "Receiver" {
+        return None;
+    }
+    let channel = match modules.next()?.ident.to_string().as_str() {
+        "watch" => IrFuncChannel::Watch,
+        "broadcast" => IrFuncChannel::Broadcast,
+        _ => return None,
+    };
+    unwrap_generic_type(receiver, "Receiver")?;
+    Some((channel, ty))
+}
+
+/// The owned type sent to Dart for a function returning a reference, e.g. `&'static Config`: the
+/// referenced type, `String` for `str` and `Vec<T>` for `[T]`, as given by `ToOwned`.
+fn owned_type_of_reference(func_name: &str, ty: &Type, type_parser: &TypeParser) -> Option<Type> {
+    let reference = match ty {
+        Type::Reference(reference) => reference,
+        _ => return None,
+    };
+    if reference.mutability.is_some() {
+        panic!(
+            "`{}` returns `{}`, but only shared references can be returned, which are copied to Dart",
+            func_name,
+            type_to_string(ty)
+        );
+    }
+    Some(match &*reference.elem {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => {
+            parse_quote!(String)
+        }
+        Type::Slice(TypeSlice { elem, .. }) => parse_quote!(Vec<#elem>),
+        elem if !type_parser.is_clone(elem) => panic!(
+            "`{}` returns `{}`, which is copied to Dart with `Clone`: derive or implement `Clone` for `{}`, or return an owned value",
+            func_name,
+            type_to_string(ty),
+            type_to_string(elem)
+        ),
+        elem => elem.clone(),
+    })
+}
+
+/// The owned type of a returned reference, either the output itself, e.g. `&str`, or the value
+/// of an `Option` or `Result`, e.g. `Option<&str>` of an accessor.
+fn owned_output_type(
+    func_name: &str,
+    ty: &Type,
+    type_parser: &TypeParser,
+) -> Option<(Type, IrFuncOwnedOutput)> {
+    if let Some(owned) = owned_type_of_reference(func_name, ty, type_parser) {
+        return Some((owned, IrFuncOwnedOutput::Reference));
+    }
+    let mut ty = ty.clone();
+    let segment = match &mut ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last_mut()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" && segment.ident != "Result" {
+        return None;
+    }
+    let inner = match &mut segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first_mut()? {
+            GenericArgument::Type(inner) => inner,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    *inner = owned_type_of_reference(func_name, inner, type_parser)?;
+    Some((ty, IrFuncOwnedOutput::Inner))
+}
+
+/// The item type of a `StreamSink` argument, if it borrows, e.g. `StreamSink<&'a str>`.
+fn borrowed_stream_sink_item(ty: &Type) -> Option<&Type> {
+    unwrap_generic_type(ty, STREAM_SINK_IDENT).filter(|item| {
+        let item = type_to_string(item);
+        item.contains('&') || item.contains('\'')
+    })
+}
+
+/// Whether the type is `Result<T, Box<dyn Error ..>>`.
+fn is_dyn_error_result(ty: &Type) -> bool {
+    let error_ty = match ty {
+        Type::Path(TypePath { path, .. }) => match path.segments.last() {
+            Some(PathSegment {
+                ident,
+                arguments:
+                    PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }),
+            }) if ident == RESULT_IDENT => args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .nth(1),
+            _ => None,
+        },
+        _ => None,
+    };
+    match error_ty.and_then(|ty| unwrap_generic_type(ty, BOX_IDENT)) {
+        Some(Type::TraitObject(TypeTraitObject { bounds, .. })) => bounds.iter().any(|bound| {
+            matches!(bound, TypeParamBound::Trait(TraitBound { path, .. })
+                if path.segments.last().is_some_and(|it| it.ident == ERROR_IDENT))
+        }),
+        _ => false,
+    }
+}
+
+fn check_validations(name: &str, ty: &IrType, validations: &[IrValidation]) {
+    for validation in validations {
+        if !validation.supports(ty) {
+            panic!(
+                "#[frb(validate = \"{}\")] cannot be checked on `{}` of type `{}`",
+                validation.source(),
+                name,
+                ty.rust_api_type()
+            );
+        }
+    }
+}
+
+fn is_result_type(ty: &ty::SupportedInnerType) -> bool {
+    matches!(ty, ty::SupportedInnerType::Path(ty::SupportedPathType { ident, generic: Some(_) }) if ident == RESULT_IDENT)
+        || matches!(ty, ty::SupportedInnerType::Result(..))
+}
+
 /// syn -> string https://github.com/dtolnay/syn/issues/294
 fn type_to_string(ty: &Type) -> String {
     quote!(#ty).to_string().replace(' ', "")
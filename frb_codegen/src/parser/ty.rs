@@ -10,34 +10,55 @@ use crate::markers;
 
 use crate::source_graph::{Enum, Struct};
 
-use crate::parser::{extract_comments, extract_metadata, type_to_string};
+use crate::parser::{check_validations, extract_comments, extract_metadata, type_to_string};
 
+#[derive(Clone)]
 pub struct TypeParser<'a> {
     src_structs: HashMap<String, &'a Struct>,
     src_enums: HashMap<String, &'a Enum>,
+    /// The types of the crate implementing `Clone` by hand, see [TypeParser::is_clone].
+    src_clone_impls: HashSet<String>,
 
     parsing_or_parsed_struct_names: HashSet<String>,
     struct_pool: IrStructPool,
 
     parsed_enums: HashSet<String>,
     enum_pool: IrEnumPool,
+
+    opaque_types: Vec<IrTypeRustOpaque>,
+    /// How many structs or enums are having their fields parsed, which may not hold a `RustOpaque`
+    parsing_fields: usize,
+    /// See [TypeParser::check_type_depth].
+    max_type_depth: usize,
 }
 
 impl<'a> TypeParser<'a> {
     pub fn new(
         src_structs: HashMap<String, &'a Struct>,
         src_enums: HashMap<String, &'a Enum>,
+        src_clone_impls: HashSet<String>,
+        max_type_depth: usize,
     ) -> Self {
         TypeParser {
             src_structs,
             src_enums,
+            src_clone_impls,
             struct_pool: HashMap::new(),
             enum_pool: HashMap::new(),
             parsing_or_parsed_struct_names: HashSet::new(),
             parsed_enums: HashSet::new(),
+            opaque_types: Vec::new(),
+            parsing_fields: 0,
+            max_type_depth,
         }
     }
 
+    /// The distinct `RustOpaque` types found so far, each getting the functions through which
+    /// Dart clones and disposes of its handles.
+    pub fn opaque_types(&self) -> &[IrTypeRustOpaque] {
+        &self.opaque_types
+    }
+
     pub fn consume(self) -> (IrStructPool, IrEnumPool) {
         (self.struct_pool, self.enum_pool)
     }
@@ -52,6 +73,11 @@ pub enum SupportedInnerType {
     Path(SupportedPathType),
     /// Array type
     Array(Box<Self>, usize),
+    /// `BTreeMap<K, V>`, one of the two supported path types with 2 generic type arguments
+    BTreeMap(Box<Self>, Box<Self>),
+    /// `Result<T, E>`, the other one. `Result<T>` is a `Path`, since it is an alias whose error
+    /// type is unknown.
+    Result(Box<Self>, Box<Self>),
     /// The unit type `()`.
     Unit,
 }
@@ -61,6 +87,8 @@ impl std::fmt::Display for SupportedInnerType {
         match self {
             Self::Path(p) => write!(f, "{}", p),
             Self::Array(u, len) => write!(f, "[{}; {}]", u, len),
+            Self::BTreeMap(key, value) => write!(f, "BTreeMap<{}, {}>", key, value),
+            Self::Result(ok, err) => write!(f, "Result<{}, {}>", ok, err),
             Self::Unit => write!(f, "()"),
         }
     }
@@ -96,6 +124,37 @@ impl SupportedInnerType {
                         ident: last_segment.ident,
                         generic: None,
                     })),
+                    syn::PathArguments::AngleBracketed(a) if last_segment.ident == "BTreeMap" => {
+                        let mut types = a.args.iter().filter_map(|arg| match arg {
+                            syn::GenericArgument::Type(t) => Some(t),
+                            _ => None,
+                        });
+                        let key = SupportedInnerType::try_from_syn_type(types.next()?)?;
+                        let value = SupportedInnerType::try_from_syn_type(types.next()?)?;
+                        Some(SupportedInnerType::BTreeMap(Box::new(key), Box::new(value)))
+                    }
+                    // an unsupported error type, e.g. `Box<dyn Error>`, only matters below the
+                    // top of a return type, so it is left out here and reported by the conversion
+                    syn::PathArguments::AngleBracketed(ref a)
+                        if last_segment.ident == "Result" && a.args.len() == 2 =>
+                    {
+                        let mut types = a.args.iter().filter_map(|arg| match arg {
+                            syn::GenericArgument::Type(t) => Some(t),
+                            _ => None,
+                        });
+                        let ok = SupportedInnerType::try_from_syn_type(types.next()?)?;
+                        Some(
+                            match types.next().and_then(SupportedInnerType::try_from_syn_type) {
+                                Some(err) => {
+                                    SupportedInnerType::Result(Box::new(ok), Box::new(err))
+                                }
+                                None => SupportedInnerType::Path(SupportedPathType {
+                                    ident: last_segment.ident,
+                                    generic: Some(Box::new(ok)),
+                                }),
+                            },
+                        )
+                    }
                     syn::PathArguments::AngleBracketed(a) => {
                         let generic = match a.args.into_iter().next() {
                             Some(syn::GenericArgument::Type(t)) => {
@@ -135,6 +194,7 @@ impl SupportedInnerType {
 
 impl<'a> TypeParser<'a> {
     pub fn parse_type(&mut self, ty: &syn::Type) -> IrType {
+        self.check_type_depth(ty);
         let supported_type = SupportedInnerType::try_from_syn_type(ty)
             .unwrap_or_else(|| panic!("Unsupported type `{}`", type_to_string(ty)));
 
@@ -142,11 +202,49 @@ impl<'a> TypeParser<'a> {
             .unwrap_or_else(|| panic!("parse_type failed for ty={}", type_to_string(ty)))
     }
 
+    /// Fails like an unsupported type when `ty` nests more layers than `--max-type-depth`, e.g. 3
+    /// for `Option<Vec<u8>>`. This bounds the recursion of the conversion below, as well as that
+    /// of the generators, which go down the resulting types one layer at a time. Layers are
+    /// counted from a worklist, and start over inside structs and enums, whose fields are parsed
+    /// on their own.
+    pub fn check_type_depth(&self, ty: &syn::Type) {
+        let mut worklist = vec![(ty, 1)];
+        while let Some((ty, depth)) = worklist.pop() {
+            if depth > self.max_type_depth {
+                panic!(
+                    "a type nests more than {} layers of types, e.g. `Option<Vec<Option<..>>>`; wrap some layers in a struct, or raise --max-type-depth",
+                    self.max_type_depth
+                );
+            }
+            worklist.extend(
+                inner_syn_types(ty)
+                    .into_iter()
+                    .map(|inner| (inner, depth + 1)),
+            );
+        }
+    }
+
     /// Converts an inner type into an `IrType` if possible.
     pub fn convert_to_ir_type(&mut self, ty: SupportedInnerType) -> Option<IrType> {
         match ty {
             SupportedInnerType::Path(p) => self.convert_path_to_ir_type(p),
             SupportedInnerType::Array(p, len) => self.convert_array_to_ir_type(*p, len),
+            SupportedInnerType::BTreeMap(key, value) => {
+                let key = self.convert_to_ir_type(*key)?;
+                let value = self.convert_to_ir_type(*value)?;
+                Some(IrType::Delegate(IrTypeDelegate::BTreeMap {
+                    key: Box::new(key),
+                    value: Box::new(value),
+                }))
+            }
+            SupportedInnerType::Result(ok, err) => {
+                let ok = self.convert_to_ir_type(*ok)?;
+                let err = self.convert_to_ir_type(*err)?;
+                Some(IrType::Delegate(IrTypeDelegate::Result {
+                    ok: Box::new(ok),
+                    err: Box::new(err),
+                }))
+            }
             SupportedInnerType::Unit => Some(IrType::Primitive(IrTypePrimitive::Unit)),
         }
     }
@@ -165,6 +263,20 @@ impl<'a> TypeParser<'a> {
         })
     }
 
+    /// `url::Url`, unless the API file declares its own type of the same name.
+    fn is_url(&self, ident: &str) -> bool {
+        ident == "Url"
+            && !self.src_structs.contains_key(ident)
+            && !self.src_enums.contains_key(ident)
+    }
+
+    /// `std::ffi::CString`, unless the API file declares its own type of the same name.
+    fn is_c_string(&self, ident: &str) -> bool {
+        ident == "CString"
+            && !self.src_structs.contains_key(ident)
+            && !self.src_enums.contains_key(ident)
+    }
+
     /// Converts a path type into an `IrType` if possible.
     pub fn convert_path_to_ir_type(&mut self, p: SupportedPathType) -> Option<IrType> {
         let p_as_str = format!("{}", &p);
@@ -172,9 +284,20 @@ impl<'a> TypeParser<'a> {
         if let Some(generic) = p.generic {
             match ident_string.as_str() {
                 "SyncReturn" => {
-                    // Special-case SyncReturn<Vec<u8>>. SyncReturn for any other type is not
-                    // supported.
+                    // Special-case SyncReturn<Vec<u8>>. Other than that, only primitives and
+                    // String are supported, optionally wrapped in an Option.
                     match *generic {
+                        SupportedInnerType::Path(SupportedPathType {
+                            ident,
+                            generic: Some(generic),
+                        }) if ident == "Option" => match self.convert_to_ir_type(*generic)? {
+                            inner @ (Primitive(_) | Delegate(IrTypeDelegate::String)) => {
+                                Some(IrType::Delegate(IrTypeDelegate::SyncReturn(Box::new(
+                                    IrType::Optional(IrTypeOptional::new_ptr(inner)),
+                                ))))
+                            }
+                            _ => None,
+                        },
                         SupportedInnerType::Path(SupportedPathType {
                             ident,
                             generic: Some(generic),
@@ -187,7 +310,12 @@ impl<'a> TypeParser<'a> {
                             }
                             _ => None,
                         },
-                        _ => None,
+                        other => match self.convert_to_ir_type(other)? {
+                            inner @ (Primitive(_) | Delegate(IrTypeDelegate::String)) => Some(
+                                IrType::Delegate(IrTypeDelegate::SyncReturn(Box::new(inner))),
+                            ),
+                            _ => None,
+                        },
                     }
                 }
                 "Vec" => {
@@ -195,11 +323,21 @@ impl<'a> TypeParser<'a> {
                     if matches!(*generic, SupportedInnerType::Path(SupportedPathType { ref ident, .. }) if ident == "String")
                     {
                         Some(IrType::Delegate(IrTypeDelegate::StringList))
+                    } else if matches!(*generic, SupportedInnerType::Path(SupportedPathType { ref ident, .. }) if self.is_url(&ident.to_string()))
+                    {
+                        Some(IrType::Delegate(IrTypeDelegate::UrlList))
+                    } else if matches!(*generic, SupportedInnerType::Path(SupportedPathType { ref ident, .. }) if self.is_c_string(&ident.to_string()))
+                    {
+                        Some(IrType::Delegate(IrTypeDelegate::CStringList))
                     } else {
                         self.convert_to_ir_type(*generic).map(|inner| match inner {
                             Primitive(primitive) => {
                                 PrimitiveList(IrTypePrimitiveList { primitive })
                             }
+                            Delegate(IrTypeDelegate::Transparent(transparent)) => panic!(
+                                "transparent struct `{}` is not supported in a Vec yet",
+                                transparent.name
+                            ),
                             others => GeneralList(IrTypeGeneralList {
                                 inner: Box::new(others),
                             }),
@@ -216,6 +354,17 @@ impl<'a> TypeParser<'a> {
                         None
                     }
                 }
+                // only the offset kept by the bridge, see `IrTypeDelegate::ZonedDateTime`
+                "DateTime" => match *generic {
+                    SupportedInnerType::Path(SupportedPathType {
+                        ident,
+                        generic: None,
+                    }) if ident == "FixedOffset" => Some(Delegate(IrTypeDelegate::ZonedDateTime)),
+                    _ => None,
+                },
+                "RustOpaque" => Some(Delegate(IrTypeDelegate::RustOpaque(
+                    self.parse_rust_opaque(&generic.to_string()),
+                ))),
                 "Box" => self.convert_to_ir_type(*generic).map(|inner| {
                     Boxed(IrTypeBoxed {
                         exist_in_real_api: true,
@@ -231,16 +380,8 @@ impl<'a> TypeParser<'a> {
                             p_as_str
                         );
                     }
-                    self.convert_to_ir_type(*generic).map(|inner| match inner {
-                        Primitive(prim) => IrType::Optional(IrTypeOptional::new_prim(prim)),
-                        st @ StructRef(_) => {
-                            IrType::Optional(IrTypeOptional::new_ptr(Boxed(IrTypeBoxed {
-                                inner: Box::new(st),
-                                exist_in_real_api: false,
-                            })))
-                        }
-                        other => IrType::Optional(IrTypeOptional::new_ptr(other)),
-                    })
+                    self.convert_to_ir_type(*generic)
+                        .map(|inner| IrType::optional_of(&inner))
                 }
                 _ => None,
             }
@@ -250,11 +391,21 @@ impl<'a> TypeParser<'a> {
                 .or_else(|| {
                     if ident_string == "String" {
                         Some(IrType::Delegate(IrTypeDelegate::String))
+                    } else if self.is_url(ident_string) {
+                        Some(IrType::Delegate(IrTypeDelegate::Url))
+                    } else if self.is_c_string(ident_string) {
+                        Some(IrType::Delegate(IrTypeDelegate::CString))
+                    } else if self.is_transparent_struct(ident_string) {
+                        Some(Delegate(IrTypeDelegate::Transparent(
+                            self.parse_transparent_core(&p.ident),
+                        )))
                     } else if self.src_structs.contains_key(ident_string) {
                         if !self.parsing_or_parsed_struct_names.contains(ident_string) {
                             self.parsing_or_parsed_struct_names
                                 .insert(ident_string.to_owned());
+                            self.parsing_fields += 1;
                             let api_struct = self.parse_struct_core(&p.ident);
+                            self.parsing_fields -= 1;
                             self.struct_pool.insert(ident_string.to_owned(), api_struct);
                         }
 
@@ -268,7 +419,9 @@ impl<'a> TypeParser<'a> {
                         }))
                     } else if self.src_enums.contains_key(ident_string) {
                         if self.parsed_enums.insert(ident_string.to_owned()) {
+                            self.parsing_fields += 1;
                             let enu = self.parse_enum_core(&p.ident);
+                            self.parsing_fields -= 1;
                             self.enum_pool.insert(ident_string.to_owned(), enu);
                         }
 
@@ -277,8 +430,11 @@ impl<'a> TypeParser<'a> {
                         };
                         let enu = self.enum_pool.get(ident_string);
                         let is_struct = enu.map(IrEnum::is_struct).unwrap_or(true);
+                        let string_repr = enu.map(|enu| enu.string_repr).unwrap_or(false);
                         if is_struct {
                             Some(EnumRef(enum_ref))
+                        } else if string_repr {
+                            Some(Delegate(IrTypeDelegate::StringEnum(enum_ref)))
                         } else {
                             Some(Delegate(IrTypeDelegate::PrimitiveEnum {
                                 ir: enum_ref,
@@ -327,6 +483,9 @@ impl<'a> TypeParser<'a> {
                             is_fields_named: field_ident.is_some(),
                             dart_metadata: extract_metadata(attrs),
                             comments: extract_comments(attrs),
+                            skew_tolerant: false,
+                            dart_builder: false,
+                            skipped_fields: vec![],
                             fields: variant
                                 .fields
                                 .iter()
@@ -342,6 +501,8 @@ impl<'a> TypeParser<'a> {
                                     ty: self.parse_type(&field.ty),
                                     is_final: true,
                                     comments: extract_comments(&field.attrs),
+                                    validations: vec![],
+                                    borrow: None,
                                 })
                                 .collect(),
                         })
@@ -349,12 +510,122 @@ impl<'a> TypeParser<'a> {
                 },
             })
             .collect();
-        IrEnum::new(name, wrapper_name, path, comments, variants)
+        let enhanced = markers::has_enhanced_enum(&src_enum.src.attrs);
+        let string_repr = markers::has_string_repr(&src_enum.src.attrs);
+        let enu = IrEnum::new(
+            name,
+            wrapper_name,
+            path,
+            comments,
+            enhanced,
+            string_repr,
+            variants,
+        );
+        if enu.string_repr && enu.is_struct() {
+            panic!(
+                "#[frb(repr = \"string\")] requires the variants of `{}` to have no fields",
+                enu.name
+            );
+        }
+        if enu.enhanced && enu.enhanced_payload().is_none() {
+            panic!(
+                "#[frb(enhanced_enum)] requires every variant of `{}` to carry exactly one field of the same primitive or String type",
+                enu.name
+            );
+        }
+        enu
+    }
+
+    /// Dart needs the API class to build a handle, which the decoders of the fields of structs
+    /// and enums are not given, so a handle can not be one of their fields.
+    fn parse_rust_opaque(&mut self, inner: &str) -> IrTypeRustOpaque {
+        if self.parsing_fields > 0 {
+            panic!(
+                "`RustOpaque<{}>` is not supported in the fields of structs and enums yet",
+                inner
+            );
+        }
+        // e.g. the `Mutex<T>` of the methods marked `#[frb(sync_mutex)]`, `Debug` whenever `T` is
+        let name = ["Mutex<", "RwLock<"]
+            .iter()
+            .find_map(|lock| inner.strip_prefix(lock)?.strip_suffix('>'))
+            .unwrap_or(inner);
+        let attrs = match (self.src_structs.get(name), self.src_enums.get(name)) {
+            (Some(src_struct), _) => &src_struct.src.attrs[..],
+            (None, Some(src_enum)) => &src_enum.src.attrs[..],
+            (None, None) => &[],
+        };
+        let opaque = IrTypeRustOpaque {
+            inner: inner.to_owned(),
+            debug: markers::has_derive(attrs, "Debug"),
+        };
+        if !self.opaque_types.iter().any(|it| it.inner == opaque.inner) {
+            self.opaque_types.push(opaque.clone());
+        }
+        opaque
+    }
+
+    /// Whether `ty` can be cloned, i.e. unless it is a struct or an enum of the crate neither
+    /// deriving nor implementing `Clone`. Other types are left to the compiler.
+    pub fn is_clone(&self, ty: &Type) -> bool {
+        let ident = match ty {
+            Type::Path(TypePath { qself: None, path }) => match path.segments.last() {
+                Some(segment) => segment.ident.to_string(),
+                None => return true,
+            },
+            _ => return true,
+        };
+        let attrs = match (self.src_structs.get(&ident), self.src_enums.get(&ident)) {
+            (Some(src_struct), _) => &src_struct.src.attrs,
+            (None, Some(src_enum)) => &src_enum.src.attrs,
+            (None, None) => return true,
+        };
+        markers::has_derive(attrs, "Clone") || self.src_clone_impls.contains(&ident)
+    }
+
+    fn is_transparent_struct(&self, ident: &str) -> bool {
+        self.src_structs
+            .get(ident)
+            .is_some_and(|src_struct| markers::has_transparent(&src_struct.src.attrs))
+    }
+
+    fn parse_transparent_core(&mut self, ident: &syn::Ident) -> IrTypeTransparent {
+        let src_struct = self.src_structs[&ident.to_string()];
+        let fields = match &src_struct.src.fields {
+            Fields::Named(FieldsNamed { named, .. }) => named,
+            Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => unnamed,
+            Fields::Unit => panic!("transparent struct `{}` has no field", ident),
+        };
+        if fields.len() != 1 {
+            panic!(
+                "transparent struct `{}` should have exactly one field, found {}",
+                ident,
+                fields.len()
+            );
+        }
+        let field = &fields[0];
+
+        let inner = self.parse_type(&field.ty);
+        if matches!(inner, StructRef(_) | EnumRef(_)) {
+            panic!(
+                "transparent struct `{}` cannot wrap the struct or enum `{}`, which Dart already has a class for",
+                ident,
+                inner.rust_api_type()
+            );
+        }
+        IrTypeTransparent {
+            name: ident.to_string(),
+            path: src_struct.path.clone(),
+            comments: extract_comments(&src_struct.src.attrs),
+            field_name: field.ident.as_ref().map(ToString::to_string),
+            inner: Box::new(inner),
+        }
     }
 
     fn parse_struct_core(&mut self, ident: &syn::Ident) -> IrStruct {
         let src_struct = self.src_structs[&ident.to_string()];
         let mut fields = Vec::new();
+        let mut skipped_fields = Vec::new();
 
         let (is_fields_named, struct_fields) = match &src_struct.src.fields {
             Fields::Named(FieldsNamed { named, .. }) => (true, named),
@@ -362,17 +633,41 @@ impl<'a> TypeParser<'a> {
             _ => panic!("unsupported type: {:?}", src_struct.src.fields),
         };
 
+        let struct_non_final = markers::has_non_final(&src_struct.src.attrs);
         for (idx, field) in struct_fields.iter().enumerate() {
             let field_name = field
                 .ident
                 .as_ref()
                 .map_or(format!("field{}", idx), ToString::to_string);
+            if markers::has_skip(&field.attrs) {
+                if !is_fields_named {
+                    panic!(
+                        "#[frb(skip)] on field {} of `{}` is only supported on named fields",
+                        idx, src_struct.ident
+                    );
+                }
+                skipped_fields.push(IrSkippedField {
+                    name: field_name,
+                    private: !is_visible_to_crate(&field.vis),
+                });
+                continue;
+            }
+            if !is_visible_to_crate(&field.vis) {
+                panic!(
+                    "field `{}` of `{}` is private, so the generated code can not read or set it: make it `pub`, or mark it with #[frb(skip)] to leave it out of Dart and take it from the `Default` of the struct",
+                    field_name, src_struct.ident
+                );
+            }
             let field_type = self.parse_type(&field.ty);
+            let validations = markers::extract_validations(&field.attrs);
+            check_validations(&field_name, &field_type, &validations);
             fields.push(IrField {
                 name: IrIdent::new(field_name),
                 ty: field_type,
-                is_final: !markers::has_non_final(&field.attrs),
+                is_final: !struct_non_final && !markers::has_non_final(&field.attrs),
                 comments: extract_comments(&field.attrs),
+                validations,
+                borrow: None,
             });
         }
 
@@ -393,6 +688,51 @@ impl<'a> TypeParser<'a> {
             is_fields_named,
             dart_metadata: metadata,
             comments,
+            skew_tolerant: markers::has_skew_tolerant(&src_struct.src.attrs),
+            dart_builder: markers::has_dart_builder(&src_struct.src.attrs),
+            skipped_fields,
         }
     }
 }
+
+/// Whether the generated code, which lives in another module of the crate, can access an item of
+/// this visibility.
+/// The types written inside `ty`, e.g. its generic arguments.
+fn inner_syn_types(ty: &syn::Type) -> Vec<&syn::Type> {
+    match ty {
+        syn::Type::Path(syn::TypePath { path, .. }) => path
+            .segments
+            .iter()
+            .flat_map(|segment| match &segment.arguments {
+                syn::PathArguments::AngleBracketed(a) => a
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            })
+            .collect(),
+        syn::Type::Array(syn::TypeArray { elem, .. })
+        | syn::Type::Slice(syn::TypeSlice { elem, .. })
+        | syn::Type::Reference(syn::TypeReference { elem, .. })
+        | syn::Type::Paren(syn::TypeParen { elem, .. })
+        | syn::Type::Group(syn::TypeGroup { elem, .. }) => vec![elem],
+        syn::Type::Tuple(syn::TypeTuple { elems, .. }) => elems.iter().collect(),
+        _ => vec![],
+    }
+}
+
+fn is_visible_to_crate(vis: &Visibility) -> bool {
+    match vis {
+        Visibility::Public(_) | Visibility::Crate(_) => true,
+        Visibility::Restricted(VisRestricted {
+            path,
+            in_token: None,
+            ..
+        }) => path.is_ident("crate"),
+        _ => false,
+    }
+}
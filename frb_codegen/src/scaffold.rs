@@ -0,0 +1,646 @@
+//! The `scaffold-tests` subcommand: a Flutter integration test, or a `package:test` test for pure
+//! Dart projects, calling the generated Dart API with sample values, along with Rust functions
+//! echoing each type of the API, so that the test can check that the samples survive the round
+//! trip. Both files are meant to be edited afterwards.
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
+use log::{info, warn};
+use pathdiff::diff_paths;
+
+use crate::config::{Opts, ScaffoldTestsOpts};
+use crate::ir::*;
+use crate::method_utils::{FunctionName, MethodNamingUtil};
+use crate::tools::{DartRepository, DartToolchain};
+use crate::transformer;
+
+/// Nesting from which optional values are null and lists empty, so that the samples of
+/// recursive types end.
+const MAX_SAMPLE_DEPTH: usize = 3;
+
+pub fn scaffold_tests(configs: &[Opts], opts: &ScaffoldTestsOpts) -> anyhow::Result<()> {
+    for (i, config) in configs.iter().enumerate() {
+        let ir_file = transformer::transform(config.get_ir_file());
+        let pure_dart = config.pure_dart || is_pure_dart(config);
+        let echo_path = match opts.echo_output.get(i) {
+            Some(path) => path.clone(),
+            None => sibling_path(&config.rust_input_path, "_echo.rs"),
+        };
+        let test_path = match opts.test_output.get(i) {
+            Some(path) => path.clone(),
+            None => default_test_path(config, pure_dart),
+        };
+
+        let scaffold = Scaffold::new(&ir_file, config.naming_policy);
+        write_scaffold(&echo_path, &scaffold.rust_echo_code(), opts.force)?;
+        write_scaffold(
+            &test_path,
+            &scaffold.dart_test_code(config, &echo_path, &test_path, pure_dart),
+            opts.force,
+        )?;
+    }
+    Ok(())
+}
+
+/// Files already scaffolded have most likely been edited since, so they are only replaced
+/// when forced to.
+fn write_scaffold(path: &str, content: &str, force: bool) -> anyhow::Result<()> {
+    if Path::new(path).exists() && !force {
+        warn!(
+            "Skipping {}, which already exists; use --force to replace it",
+            path
+        );
+        return Ok(());
+    }
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    info!("Writing {}", path);
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// `path` with its extension replaced by `suffix`, e.g. `src/api.rs` to `src/api_echo.rs`.
+fn sibling_path(path: &str, suffix: &str) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().unwrap().to_str().unwrap();
+    path.with_file_name(format!("{}{}", stem, suffix))
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+/// Whether the Dart project does not use Flutter, as guessed from its `pubspec.lock`.
+fn is_pure_dart(config: &Opts) -> bool {
+    config
+        .dart_root
+        .as_deref()
+        .and_then(|dart_root| DartRepository::from_str(dart_root).ok())
+        .is_some_and(|repo| repo.toolchain == DartToolchain::Dart)
+}
+
+/// `integration_test/bridge_generated_test.dart` in the Dart project, or
+/// `test/bridge_generated_test.dart` for pure Dart.
+fn default_test_path(config: &Opts, pure_dart: bool) -> String {
+    let file_name = Path::new(&sibling_path(&config.dart_output_path, "_test.dart"))
+        .file_name()
+        .unwrap()
+        .to_owned();
+    match &config.dart_root {
+        Some(dart_root) => Path::new(dart_root)
+            .join(if pure_dart {
+                "test"
+            } else {
+                "integration_test"
+            })
+            .join(file_name)
+            .to_str()
+            .unwrap()
+            .to_owned(),
+        None => sibling_path(&config.dart_output_path, "_test.dart"),
+    }
+}
+
+struct Scaffold<'a> {
+    ir_file: &'a IrFile,
+    naming_policy: NamingPolicy,
+    /// The API functions which the test can call with samples for all their arguments.
+    funcs: Vec<&'a IrFunc>,
+    /// The types of the arguments of the API functions, which have a sample value.
+    echo_types: Vec<IrType>,
+}
+
+impl<'a> Scaffold<'a> {
+    fn new(ir_file: &'a IrFile, naming_policy: NamingPolicy) -> Self {
+        let mut scaffold = Self {
+            ir_file,
+            naming_policy,
+            funcs: vec![],
+            echo_types: vec![],
+        };
+        let api_funcs = ir_file.funcs.iter().filter(|func| {
+            let name = FunctionName::deserialize(&func.name);
+            func.closure.is_none()
+                && !func.event
+                && func.dart_name.is_none()
+                && func.accessor.is_none()
+                && !name.is_non_static_method()
+                && !name.is_static_method()
+                // the echo functions of an earlier run, once bridged
+                && !func.name.starts_with("echo_")
+        });
+        for func in api_funcs {
+            for input in &func.inputs {
+                let ident = input.ty.safe_ident();
+                if scaffold.dart_sample(&input.ty, 0).is_some()
+                    && !scaffold
+                        .echo_types
+                        .iter()
+                        .any(|ty| ty.safe_ident() == ident)
+                {
+                    scaffold.echo_types.push(input.ty.clone());
+                }
+            }
+            let callable = matches!(func.mode, IrFuncMode::Normal | IrFuncMode::Sync)
+                && func
+                    .inputs
+                    .iter()
+                    .all(|input| scaffold.dart_sample(&input.ty, 0).is_some());
+            if callable {
+                scaffold.funcs.push(func);
+            }
+        }
+        scaffold
+    }
+
+    fn rust_echo_code(&self) -> String {
+        let api_module_path = self
+            .funcs
+            .first()
+            .map_or_else(|| "crate".to_owned(), |func| func.module_path.join("::"));
+        let echo_funcs = self
+            .echo_types
+            .iter()
+            .map(|ty| {
+                format!(
+                    "pub fn {}(value: {ty}) -> {ty} {{\n    value\n}}\n",
+                    echo_func_name(ty),
+                    ty = ty.rust_api_type()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "//! Functions returning their argument as is, for the integration test scaffolded by
+//! `flutter_rust_bridge_codegen scaffold-tests` to check the round trip of each type of the API.
+//! Declare this module in `lib.rs`, re-export it from the API file with `pub use`, and run the
+//! code generator again so that they are bridged.
+
+#[allow(unused_imports)]
+use {}::*;
+
+{}",
+            api_module_path, echo_funcs
+        )
+    }
+
+    fn dart_test_code(
+        &self,
+        config: &Opts,
+        echo_path: &str,
+        test_path: &str,
+        pure_dart: bool,
+    ) -> String {
+        let test_dir = Path::new(test_path).parent().unwrap();
+        let bridge_import = package_import(config).unwrap_or_else(|| {
+            diff_paths(&config.dart_output_path, test_dir)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .replace('\\', "/")
+        });
+        // the Flutter tests run on a device, and the pure Dart ones in the Dart VM
+        let (test_func, test_params) = if pure_dart {
+            ("test", "()")
+        } else {
+            ("testWidgets", "(tester)")
+        };
+        let call_tests = self
+            .funcs
+            .iter()
+            .map(|func| {
+                let args = func
+                    .inputs
+                    .iter()
+                    .map(|input| {
+                        format!(
+                            "{}: {}",
+                            input.name.dart_style(self.naming_policy),
+                            self.dart_sample(&input.ty, 0).unwrap()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let call = if func.is_dart_getter() {
+                    format!("api.{}", self.naming_policy.member_name(&func.name))
+                } else {
+                    format!(
+                        "api.{}({})",
+                        self.naming_policy.member_name(&func.name),
+                        args.join(", ")
+                    )
+                };
+                format!(
+                    "{}('{}', {} async {{
+      {}{};
+    }});",
+                    test_func,
+                    func.name,
+                    test_params,
+                    if func.mode == IrFuncMode::Normal {
+                        "await "
+                    } else {
+                        ""
+                    },
+                    call
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n    ");
+        let round_trip_tests = self
+            .echo_types
+            .iter()
+            .map(|ty| {
+                format!(
+                    "{}('{}', {} async {{
+      final expected = {};
+      final actual = await api.{}(value: expected);
+      {}
+    }});",
+                    test_func,
+                    ty.rust_api_type(),
+                    test_params,
+                    self.dart_sample(ty, 0).unwrap(),
+                    self.naming_policy.member_name(&echo_func_name(ty)),
+                    self.dart_checks(ty, "actual", "expected", 0)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n    ");
+
+        let (kind, test_imports, load_dylib, set_up) = if pure_dart {
+            (
+                "Test",
+                "import 'package:test/test.dart';".to_owned(),
+                format!(
+                    "  // built by `cargo build`, relative to the root of the Dart package
+  const dir = '{}';
+  final dylib = Platform.isMacOS
+      ? DynamicLibrary.open('$dir/lib$name.dylib')
+      : Platform.isWindows
+          ? DynamicLibrary.open('$dir/$name.dll')
+          : DynamicLibrary.open('$dir/lib$name.so');",
+                    native_lib_dir(config)
+                ),
+                "",
+            )
+        } else {
+            (
+                "Integration test",
+                "import 'package:flutter_test/flutter_test.dart';
+import 'package:integration_test/integration_test.dart';"
+                    .to_owned(),
+                "  final dylib = Platform.isIOS
+      ? DynamicLibrary.process()
+      : Platform.isMacOS
+          ? DynamicLibrary.open('$name.framework/$name')
+          : Platform.isWindows
+              ? DynamicLibrary.open('$name.dll')
+              : DynamicLibrary.open('lib$name.so');"
+                    .to_owned(),
+                "\n  IntegrationTestWidgetsFlutterBinding.ensureInitialized();",
+            )
+        };
+
+        format!(
+            "// {kind} scaffolded by `flutter_rust_bridge_codegen scaffold-tests`, meant to be
+// edited: the sample arguments may need to be adjusted for the functions to succeed. The round
+// trips call the functions of `{echo_file}`, which must be bridged first.
+
+import 'dart:ffi';
+import 'dart:io';
+import 'dart:typed_data';
+
+{test_imports}
+
+import '{bridge_import}';
+
+{api_class} createApi() {{
+  const name = '{lib_name}';
+{load_dylib}
+  return {api_impl_class}(dylib);
+}}
+
+void main() {{{set_up}
+  final api = createApi();
+
+  group('calls', () {{
+    {call_tests}
+  }});
+
+  group('round trips', () {{
+    {round_trip_tests}
+  }});
+}}
+",
+            kind = kind,
+            test_imports = test_imports,
+            load_dylib = load_dylib,
+            set_up = set_up,
+            echo_file = Path::new(echo_path).file_name().unwrap().to_str().unwrap(),
+            bridge_import = bridge_import,
+            api_class = config.dart_api_class_name(),
+            api_impl_class = config.dart_api_impl_class_name(),
+            lib_name = rust_lib_name(&config.manifest_path),
+            call_tests = call_tests,
+            round_trip_tests = round_trip_tests,
+        )
+    }
+
+    /// A Dart expression of the type, or [None] for the types which cannot be made up, such as
+    /// closures or streams.
+    fn dart_sample(&self, ty: &IrType, depth: usize) -> Option<String> {
+        Some(match ty {
+            IrType::Primitive(primitive) => match primitive {
+                IrTypePrimitive::Bool => "true".to_owned(),
+                IrTypePrimitive::F32 | IrTypePrimitive::F64 => "1.5".to_owned(),
+                IrTypePrimitive::Unit => return None,
+                _ => "42".to_owned(),
+            },
+            IrType::PrimitiveList(list) => {
+                let items = match list.primitive {
+                    IrTypePrimitive::F32 | IrTypePrimitive::F64 => "[1.5, 2.5]",
+                    _ => "[1, 2, 3]",
+                };
+                format!("{}.fromList({})", ty.dart_api_type(), items)
+            }
+            IrType::Optional(optional) if depth < MAX_SAMPLE_DEPTH => self
+                .dart_sample(&optional.inner, depth + 1)
+                .unwrap_or_else(|| "null".to_owned()),
+            IrType::Optional(_) => "null".to_owned(),
+            IrType::Boxed(boxed) => self.dart_sample(&boxed.inner, depth)?,
+            // a null item too, the one the Rust side is most likely to get wrong
+            IrType::GeneralList(list) if depth < MAX_SAMPLE_DEPTH => match &*list.inner {
+                IrType::Optional(_) => {
+                    format!("[{}, null]", self.dart_sample(&list.inner, depth + 1)?)
+                }
+                inner => format!("[{}]", self.dart_sample(inner, depth + 1)?),
+            },
+            IrType::GeneralList(list) => format!("<{}>[]", list.inner.dart_api_type()),
+            IrType::StructRef(struct_ref) => {
+                let st = struct_ref.get(self.ir_file);
+                let mut args = st
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        Some(format!(
+                            "{}: {}",
+                            field.name.dart_style(self.naming_policy),
+                            self.dart_sample(&field.ty, depth + 1)?
+                        ))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if MethodNamingUtil::has_methods(&st.name, self.ir_file) {
+                    args.insert(0, "bridge: api".to_owned());
+                }
+                format!("{}({})", ty.dart_api_type(), args.join(", "))
+            }
+            IrType::EnumRef(enum_ref) if !enum_ref.get(self.ir_file).is_struct() => {
+                format!("{}.values.first", ty.dart_api_type())
+            }
+            IrType::EnumRef(_) => return None,
+            IrType::Delegate(delegate) => match delegate {
+                IrTypeDelegate::String | IrTypeDelegate::CString => "'frb'".to_owned(),
+                IrTypeDelegate::StringList | IrTypeDelegate::CStringList => {
+                    "['flutter', 'rust']".to_owned()
+                }
+                IrTypeDelegate::Url => "Uri.parse('https://example.com/')".to_owned(),
+                IrTypeDelegate::UrlList => "[Uri.parse('https://example.com/')]".to_owned(),
+                IrTypeDelegate::ZonedDateTime => {
+                    "ZonedDateTime(DateTime.utc(2022), Duration.zero)".to_owned()
+                }
+                IrTypeDelegate::PrimitiveEnum { .. } | IrTypeDelegate::StringEnum(_) => {
+                    format!("{}.values.first", ty.dart_api_type())
+                }
+                IrTypeDelegate::BTreeMap { key, value } => format!(
+                    "{{{}: {}}}",
+                    self.dart_sample(key, depth + 1)?,
+                    self.dart_sample(value, depth + 1)?
+                ),
+                IrTypeDelegate::Transparent(transparent) => {
+                    self.dart_sample(&transparent.inner, depth)?
+                }
+                _ => return None,
+            },
+        })
+    }
+
+    /// Dart statements checking that `actual` is the same as `expected`, comparing the structs
+    /// without `==` field by field.
+    fn dart_checks(&self, ty: &IrType, actual: &str, expected: &str, depth: usize) -> String {
+        match ty {
+            _ if self.has_dart_equality(ty) => {
+                format!("expect({}, equals({}));", actual, expected)
+            }
+            IrType::Optional(optional) => format!(
+                "expect({actual} == null, equals({expected} == null));
+                if ({expected} != null) {{
+                    {}
+                }}",
+                self.dart_checks(
+                    &optional.inner,
+                    &format!("{}!", actual),
+                    &format!("{}!", expected),
+                    depth
+                ),
+                actual = actual,
+                expected = expected,
+            ),
+            IrType::Boxed(boxed) => self.dart_checks(&boxed.inner, actual, expected, depth),
+            IrType::GeneralList(list) => format!(
+                "expect({actual}.length, equals({expected}.length));
+                for (var i{depth} = 0; i{depth} < {expected}.length; i{depth}++) {{
+                    {}
+                }}",
+                self.dart_checks(
+                    &list.inner,
+                    &format!("{}[i{}]", actual, depth),
+                    &format!("{}[i{}]", expected, depth),
+                    depth + 1
+                ),
+                actual = actual,
+                expected = expected,
+                depth = depth,
+            ),
+            IrType::StructRef(struct_ref) => struct_ref
+                .get(self.ir_file)
+                .fields
+                .iter()
+                .map(|field| {
+                    let name = field.name.dart_style(self.naming_policy);
+                    self.dart_checks(
+                        &field.ty,
+                        &format!("{}.{}", actual, name),
+                        &format!("{}.{}", expected, name),
+                        depth,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            IrType::Delegate(IrTypeDelegate::Transparent(transparent)) => {
+                self.dart_checks(&transparent.inner, actual, expected, depth)
+            }
+            _ => format!("expect({}, equals({}));", actual, expected),
+        }
+    }
+
+    /// Whether `equals` compares the Dart values of the type by value, which is not the case of
+    /// the structs generated without freezed.
+    fn has_dart_equality(&self, ty: &IrType) -> bool {
+        match ty {
+            IrType::Primitive(_) | IrType::PrimitiveList(_) | IrType::EnumRef(_) => true,
+            IrType::Optional(IrTypeOptional { inner })
+            | IrType::Boxed(IrTypeBoxed { inner, .. })
+            | IrType::GeneralList(IrTypeGeneralList { inner }) => self.has_dart_equality(inner),
+            IrType::StructRef(struct_ref) => struct_ref.get(self.ir_file).using_freezed(),
+            IrType::Delegate(IrTypeDelegate::BTreeMap { key, value }) => {
+                self.has_dart_equality(key) && self.has_dart_equality(value)
+            }
+            IrType::Delegate(IrTypeDelegate::Transparent(transparent)) => {
+                self.has_dart_equality(&transparent.inner)
+            }
+            IrType::Delegate(_) => true,
+        }
+    }
+}
+
+fn echo_func_name(ty: &IrType) -> String {
+    format!("echo_{}", ty.safe_ident()).to_case(Case::Snake)
+}
+
+/// `package:my_app/bridge_generated.dart` when the Dart output is in the `lib` directory of the
+/// Dart project.
+fn package_import(config: &Opts) -> Option<String> {
+    let dart_root = Path::new(config.dart_root.as_ref()?);
+    let pubspec = fs::read_to_string(dart_root.join("pubspec.yaml")).ok()?;
+    let pubspec = serde_yaml::from_str::<serde_yaml::Value>(&pubspec).ok()?;
+    let package_name = pubspec.get("name")?.as_str()?;
+    let path = Path::new(&config.dart_output_path)
+        .strip_prefix(dart_root.join("lib"))
+        .ok()?;
+    Some(format!(
+        "package:{}/{}",
+        package_name,
+        path.to_str()?.replace('\\', "/")
+    ))
+}
+
+/// The directory of the debug build of the crate, relative to the Dart project if possible.
+fn native_lib_dir(config: &Opts) -> String {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    let lib_dir = canonical(Path::new(&config.rust_crate_dir))
+        .join("target")
+        .join("debug");
+    let dart_root = canonical(Path::new(&config.dart_root_or_default()));
+    diff_paths(&lib_dir, dart_root)
+        .unwrap_or(lib_dir)
+        .to_str()
+        .unwrap()
+        .replace('\\', "/")
+}
+
+/// The name of the library built from the crate, e.g. `my_crate` for `libmy_crate.so`.
+fn rust_lib_name(manifest_path: &str) -> String {
+    let manifest = fs::read_to_string(manifest_path)
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Value>().ok());
+    let name_of = |table: &str| {
+        manifest
+            .as_ref()?
+            .get(table)?
+            .get("name")?
+            .as_str()
+            .map(ToOwned::to_owned)
+    };
+    name_of("lib")
+        .or_else(|| name_of("package"))
+        .unwrap_or_default()
+        .replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn empty_ir_file() -> IrFile {
+        IrFile {
+            funcs: vec![],
+            struct_pool: HashMap::new(),
+            enum_pool: HashMap::new(),
+            custom_handler: None,
+            distinct_types_cache: RefCell::new(HashMap::new()),
+            skipped_funcs: vec![],
+        }
+    }
+
+    fn list(inner: IrType) -> IrType {
+        IrType::GeneralList(IrTypeGeneralList {
+            inner: Box::new(inner),
+        })
+    }
+
+    fn optional(inner: IrType) -> IrType {
+        IrType::Optional(IrTypeOptional {
+            inner: Box::new(inner),
+        })
+    }
+
+    #[test]
+    fn sibling_path_replaces_the_extension() {
+        assert_eq!(sibling_path("src/api.rs", "_echo.rs"), "src/api_echo.rs");
+    }
+
+    #[test]
+    fn rust_lib_name_prefers_the_name_of_the_lib() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("Cargo.toml");
+        let manifest_path = manifest_path.to_str().unwrap();
+
+        fs::write(manifest_path, "[package]\nname = \"my-crate\"\n").unwrap();
+        assert_eq!(rust_lib_name(manifest_path), "my_crate");
+        fs::write(
+            manifest_path,
+            "[package]\nname = \"my-crate\"\n\n[lib]\nname = \"native\"\n",
+        )
+        .unwrap();
+        assert_eq!(rust_lib_name(manifest_path), "native");
+    }
+
+    #[test]
+    fn existing_scaffolds_are_only_replaced_when_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test").join("api_test.dart");
+        let path = path.to_str().unwrap();
+
+        write_scaffold(path, "first", false).unwrap();
+        write_scaffold(path, "second", false).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "first");
+        write_scaffold(path, "third", true).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "third");
+    }
+
+    #[test]
+    fn samples_of_lists_hold_a_null_item_and_end() {
+        let ir_file = empty_ir_file();
+        let scaffold = Scaffold::new(&ir_file, NamingPolicy::default());
+        let int = IrType::Primitive(IrTypePrimitive::I32);
+
+        assert_eq!(
+            scaffold.dart_sample(&list(optional(int.clone())), 0),
+            Some("[42, null]".to_owned())
+        );
+        assert_eq!(
+            scaffold.dart_sample(&list(list(list(list(int)))), 0),
+            Some("[[[<int>[]]]]".to_owned())
+        );
+        assert_eq!(
+            scaffold.dart_sample(&IrType::Primitive(IrTypePrimitive::Unit), 0),
+            None
+        );
+    }
+}
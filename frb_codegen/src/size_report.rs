@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::ir::*;
+
+/// Generic types nested deeper than this are reported, since every layer generates its own
+/// wire struct and functions.
+const MAX_TYPE_NESTING: usize = 6;
+
+/// Breakdown of the generated Rust code of one block, per function and per type.
+#[derive(Debug, Default, Serialize)]
+pub struct SizeReport {
+    pub total_lines: usize,
+    pub total_extern_symbols: usize,
+    pub funcs: Vec<SizeEntry>,
+    pub types: Vec<SizeEntry>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizeEntry {
+    pub name: String,
+    pub lines: usize,
+    pub extern_symbols: Vec<String>,
+}
+
+pub enum SizeKey<'a> {
+    Func(&'a IrFunc),
+    Type(&'a IrType),
+}
+
+/// Code generated for a [SizeKey], possibly nothing.
+pub trait GeneratedCode {
+    fn line_count(&self) -> usize;
+}
+
+impl GeneratedCode for String {
+    fn line_count(&self) -> usize {
+        self.lines().filter(|line| !line.trim().is_empty()).count()
+    }
+}
+
+impl GeneratedCode for Option<String> {
+    fn line_count(&self) -> usize {
+        self.as_ref().map_or(0, |code| code.line_count())
+    }
+}
+
+impl SizeReport {
+    pub fn add(&mut self, key: SizeKey, lines: usize, extern_symbols: &[String]) {
+        let (entries, name) = match key {
+            SizeKey::Func(func) => (&mut self.funcs, func.name.clone()),
+            SizeKey::Type(ty) => (&mut self.types, ty.rust_api_type()),
+        };
+        match entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) => {
+                entry.lines += lines;
+                entry.extern_symbols.extend_from_slice(extern_symbols);
+            }
+            None => entries.push(SizeEntry {
+                name,
+                lines,
+                extern_symbols: extern_symbols.to_vec(),
+            }),
+        }
+    }
+
+    /// Sorts the entries largest first and checks for types out of proportion.
+    pub fn finish(&mut self, code: &str, extern_func_names: &[String], ir_file: &IrFile) {
+        self.total_lines = code.to_string().line_count();
+        self.total_extern_symbols = extern_func_names.len();
+        for entries in [&mut self.funcs, &mut self.types].iter_mut() {
+            entries.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+        }
+
+        self.warnings
+            .extend(deeply_nested_types(ir_file).into_iter().map(|(ty, depth)| {
+                format!(
+                    "`{}` nests {} levels of types, each generating its own wire code; \
+                consider wrapping some of them in a struct",
+                    ty.rust_api_type(),
+                    depth
+                )
+            }));
+    }
+
+    pub fn check_symbol_budget(&mut self, budget: usize) {
+        if self.total_extern_symbols > budget {
+            self.warnings.push(format!(
+                "{} extern symbols are generated, more than the budget of {}",
+                self.total_extern_symbols, budget
+            ));
+        }
+    }
+
+    pub fn log_warnings(&self) {
+        for warning in &self.warnings {
+            warn!("{}", warning);
+        }
+    }
+
+    pub fn log(&self) {
+        info!(
+            "Generated Rust code size: {} lines, {} extern symbols",
+            self.total_lines, self.total_extern_symbols
+        );
+        for (title, entries) in [("Functions", &self.funcs), ("Types", &self.types)].iter() {
+            info!("{} (largest first):", title);
+            for entry in entries.iter() {
+                info!(
+                    "    {}: {} lines, {} extern symbols",
+                    entry.name,
+                    entry.lines,
+                    entry.extern_symbols.len()
+                );
+            }
+        }
+    }
+}
+
+/// Types nested deeper than [MAX_TYPE_NESTING], leaving out the ones only reported as part of
+/// an outer type.
+fn deeply_nested_types(ir_file: &IrFile) -> Vec<(IrType, usize)> {
+    let mut distinct_types = ir_file.distinct_types(true, false);
+    distinct_types.extend(ir_file.distinct_types(false, true));
+
+    let mut seen = HashSet::new();
+    let offenders = distinct_types
+        .into_iter()
+        .filter(|ty| seen.insert(ty.safe_ident()))
+        .map(|ty| {
+            let depth = nesting_depth(&ty, ir_file);
+            (ty, depth)
+        })
+        .filter(|(_, depth)| *depth > MAX_TYPE_NESTING)
+        .collect::<Vec<_>>();
+
+    let mut inner_offenders = HashSet::new();
+    for (ty, _) in &offenders {
+        ty.visit_children_types(
+            &mut |child| {
+                inner_offenders.insert(child.safe_ident());
+                true
+            },
+            ir_file,
+        );
+    }
+    offenders
+        .into_iter()
+        .filter(|(ty, _)| !inner_offenders.contains(&ty.safe_ident()))
+        .collect()
+}
+
+/// Number of generic layers down to a primitive, struct or enum.
+fn nesting_depth(ty: &IrType, ir_file: &IrFile) -> usize {
+    if ty.is_struct() {
+        return 1;
+    }
+    let mut depth = 0;
+    ty.visit_children_types(
+        &mut |child| {
+            depth = depth.max(nesting_depth(child, ir_file));
+            true
+        },
+        ir_file,
+    );
+    depth + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn empty_ir_file() -> IrFile {
+        IrFile {
+            funcs: vec![],
+            struct_pool: HashMap::new(),
+            enum_pool: HashMap::new(),
+            custom_handler: None,
+            distinct_types_cache: RefCell::new(HashMap::new()),
+            skipped_funcs: vec![],
+        }
+    }
+
+    fn optional(inner: IrType) -> IrType {
+        IrType::Optional(IrTypeOptional {
+            inner: Box::new(inner),
+        })
+    }
+
+    #[test]
+    fn line_count_skips_blank_lines() {
+        assert_eq!("a\n\n  \nb\n".to_owned().line_count(), 2);
+        assert_eq!(None::<String>.line_count(), 0);
+    }
+
+    #[test]
+    fn entries_of_the_same_type_are_merged_and_sorted() {
+        let int = IrType::Primitive(IrTypePrimitive::I32);
+        let string = IrType::Delegate(IrTypeDelegate::String);
+        let mut report = SizeReport::default();
+        report.add(SizeKey::Type(&int), 3, &["a".to_owned()]);
+        report.add(SizeKey::Type(&string), 5, &[]);
+        report.add(SizeKey::Type(&int), 4, &["b".to_owned()]);
+        report.finish(
+            "x\ny\n",
+            &["a".to_owned(), "b".to_owned()],
+            &empty_ir_file(),
+        );
+
+        assert_eq!(report.total_lines, 2);
+        assert_eq!(report.total_extern_symbols, 2);
+        let types = report
+            .types
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.lines, entry.extern_symbols.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(types, [("i32", 7, 2), ("String", 5, 0)]);
+    }
+
+    #[test]
+    fn symbol_budget_warns_only_when_exceeded() {
+        let mut report = SizeReport {
+            total_extern_symbols: 10,
+            ..Default::default()
+        };
+        report.check_symbol_budget(10);
+        assert!(report.warnings.is_empty());
+        report.check_symbol_budget(9);
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn nesting_depth_counts_generic_layers() {
+        let ir_file = empty_ir_file();
+        let int = IrType::Primitive(IrTypePrimitive::I32);
+        assert_eq!(nesting_depth(&int, &ir_file), 1);
+        assert_eq!(nesting_depth(&optional(optional(int)), &ir_file), 3);
+    }
+}
@@ -7,11 +7,16 @@
         - Imports that start with two colons (use ::a::b) - these are also silently ignored
 */
 
-use std::{collections::HashMap, fmt::Debug, fs, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use cargo_metadata::MetadataCommand;
 use log::{debug, warn};
-use syn::{Attribute, Ident, ItemEnum, ItemStruct, UseTree};
+use syn::{Attribute, Ident, ItemEnum, ItemFn, ItemStruct, UseTree};
 
 use crate::markers;
 
@@ -182,6 +187,20 @@ impl Debug for Module {
     }
 }
 
+/// The file given by `#[path = "..."]`, relative to the directory of the declaring file.
+fn path_attribute(attrs: &[Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("path"))
+        .find_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(path),
+                ..
+            })) => Some(path.value()),
+            _ => None,
+        })
+}
+
 /// Get a struct or enum ident, possibly remapped by a mirror marker
 fn get_ident(ident: &Ident, attrs: &[Attribute]) -> (Ident, bool) {
     markers::extract_mirror_marker(attrs)
@@ -259,18 +278,20 @@ impl Module {
                             child_module
                         }
                         None => {
-                            let folder_path =
-                                self.file_path.parent().unwrap().join(ident.to_string());
-                            let folder_exists = folder_path.exists();
-
-                            let file_path = if folder_exists {
-                                folder_path.join("mod.rs")
-                            } else {
-                                self.file_path
-                                    .parent()
-                                    .unwrap()
-                                    .join(ident.to_string() + ".rs")
+                            let file_path = match path_attribute(&item_mod.attrs) {
+                                Some(path) => self.file_path.parent().unwrap().join(path),
+                                None => {
+                                    let dir = self.child_module_dir();
+                                    let file_path = dir.join(ident.to_string() + ".rs");
+                                    if file_path.exists() {
+                                        file_path
+                                    } else {
+                                        dir.join(ident.to_string()).join("mod.rs")
+                                    }
+                                }
                             };
+                            // so that it matches the canonical path of the API file
+                            let file_path = fs::canonicalize(&file_path).unwrap_or(file_path);
 
                             let file_exists = file_path.exists();
 
@@ -321,6 +342,17 @@ impl Module {
         });
     }
 
+    /// The directory of the files of the modules declared in this one: its own for the crate root
+    /// and `mod.rs`, and the one named after it otherwise, e.g. `src/api/` for `src/api.rs`.
+    fn child_module_dir(&self) -> PathBuf {
+        let dir = self.file_path.parent().unwrap();
+        if self.module_path.len() == 1 || self.file_path.file_name().unwrap() == "mod.rs" {
+            dir.to_owned()
+        } else {
+            dir.join(self.file_path.file_stem().unwrap())
+        }
+    }
+
     #[allow(dead_code)]
     fn resolve_imports(&mut self) {
         let imports = &mut self.scope.as_mut().unwrap().imports;
@@ -344,6 +376,53 @@ impl Module {
         }
     }
 
+    /// Finds a module by its full path, e.g. `["crate", "api"]`.
+    pub fn find_module(&self, module_path: &[String]) -> Option<&Module> {
+        if module_path == self.module_path.as_slice() {
+            return Some(self);
+        }
+        if !module_path.starts_with(&self.module_path) {
+            return None;
+        }
+        self.scope
+            .as_ref()?
+            .modules
+            .iter()
+            .find_map(|module| module.find_module(module_path))
+    }
+
+    /// Finds the module stored in the given file, excluding modules declared inline in it.
+    pub fn find_module_by_file(&self, file_path: &Path) -> Option<&Module> {
+        if let Some(ModuleSource::File(_)) = &self.source {
+            if self.file_path == file_path {
+                return Some(self);
+            }
+        }
+        self.scope
+            .as_ref()?
+            .modules
+            .iter()
+            .find_map(|module| module.find_module_by_file(file_path))
+    }
+
+    /// Public functions defined directly in this module.
+    pub fn public_fns(&self) -> Vec<&ItemFn> {
+        let items = match &self.source {
+            Some(ModuleSource::File(file)) => &file.items,
+            Some(ModuleSource::ModuleInFile(items)) => items,
+            None => return vec![],
+        };
+        items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Fn(item_fn) if matches!(item_fn.vis, syn::Visibility::Public(_)) => {
+                    Some(item_fn)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn collect_structs<'a>(&'a self, container: &mut HashMap<String, &'a Struct>) {
         let scope = self.scope.as_ref().unwrap();
         for scope_struct in &scope.structs {
@@ -375,6 +454,45 @@ impl Module {
         self.collect_enums(&mut ans);
         ans
     }
+
+    /// Names of the types implementing `trait_name` by hand, e.g. `Config` for
+    /// `impl Clone for Config`, in this module or its submodules.
+    pub fn collect_trait_impls(&self, trait_name: &str, container: &mut HashSet<String>) {
+        let items = match &self.source {
+            Some(ModuleSource::File(file)) => &file.items[..],
+            Some(ModuleSource::ModuleInFile(items)) => &items[..],
+            None => &[],
+        };
+        for item in items {
+            if let syn::Item::Impl(syn::ItemImpl {
+                trait_: Some((None, trait_path, _)),
+                self_ty,
+                ..
+            }) = item
+            {
+                if let syn::Type::Path(self_ty) = &**self_ty {
+                    if trait_path
+                        .segments
+                        .last()
+                        .is_some_and(|it| it.ident == trait_name)
+                    {
+                        if let Some(segment) = self_ty.path.segments.last() {
+                            container.insert(segment.ident.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        for scope_module in &self.scope.as_ref().unwrap().modules {
+            scope_module.collect_trait_impls(trait_name, container);
+        }
+    }
+
+    pub fn collect_trait_impls_to_set(&self, trait_name: &str) -> HashSet<String> {
+        let mut ans = HashSet::new();
+        self.collect_trait_impls(trait_name, &mut ans);
+        ans
+    }
 }
 
 fn flatten_use_tree_rename_abort_warning(use_tree: &UseTree) {
@@ -395,7 +513,7 @@ fn flatten_use_tree_rename_abort_warning(use_tree: &UseTree) {
 ///
 /// Warning: As of writing, import renames (import a::b as c) are silently
 /// ignored.
-fn flatten_use_tree(use_tree: &UseTree) -> Vec<Vec<String>> {
+pub fn flatten_use_tree(use_tree: &UseTree) -> Vec<Vec<String>> {
     // Vec<(path, is_complete)>
     let mut result = vec![(vec![], false)];
 
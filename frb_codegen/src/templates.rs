@@ -0,0 +1,187 @@
+//! Handlebars templates given with `--templates`, overriding parts of the generated code without
+//! forking the generator. Each file of that directory is named after the part it replaces:
+//!
+//! * `wire_func.rs.hbs`: every `extern "C"` function of the Rust output
+//! * `dart_method.dart.hbs`: the implementation of each Rust API function in the Dart output
+//! * `dart_api_class.dart.hbs`: the abstract Dart API class
+//!
+//! Parts without a template are generated as usual. Every template also receives the code it
+//! replaces as `default`, so that it can wrap it instead of starting from scratch.
+
+use std::fs;
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+const WIRE_FUNC: &str = "wire_func";
+const DART_METHOD: &str = "dart_method";
+const DART_API_CLASS: &str = "dart_api_class";
+
+/// Template names and their file names.
+const TEMPLATE_FILES: [(&str, &str); 3] = [
+    (WIRE_FUNC, "wire_func.rs.hbs"),
+    (DART_METHOD, "dart_method.dart.hbs"),
+    (DART_API_CLASS, "dart_api_class.dart.hbs"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Templates {
+    registry: Handlebars<'static>,
+}
+
+impl Templates {
+    /// Loads the templates of `dir`, or none at all.
+    pub fn load(dir: Option<&str>) -> Self {
+        let mut registry = Handlebars::new();
+        // the output is code, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+        // a misspelled variable would otherwise silently render as nothing
+        registry.set_strict_mode(true);
+
+        if let Some(dir) = dir {
+            let entries = fs::read_dir(dir).unwrap_or_else(|err| {
+                panic!("fail to read templates directory `{}`: {}", dir, err)
+            });
+            for entry in entries {
+                let path = entry.unwrap().path();
+                let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+                if !file_name.ends_with(".hbs") {
+                    continue;
+                }
+                let (name, _) = TEMPLATE_FILES
+                    .iter()
+                    .find(|(_, it)| *it == file_name)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "unknown template `{}`, expected one of: {}",
+                            path.display(),
+                            TEMPLATE_FILES
+                                .iter()
+                                .map(|(_, it)| *it)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    });
+                registry
+                    .register_template_file(name, &path)
+                    .unwrap_or_else(|err| panic!("invalid template `{}`: {}", path.display(), err));
+            }
+        }
+
+        Templates { registry }
+    }
+
+    /// Renders the template `name` if the user provided it.
+    fn render(&self, name: &str, data: &impl Serialize) -> Option<String> {
+        if !self.registry.has_template(name) {
+            return None;
+        }
+        Some(
+            self.registry
+                .render(name, data)
+                .unwrap_or_else(|err| panic!("fail to render template `{}`: {}", name, err)),
+        )
+    }
+
+    pub fn wire_func(&self, data: &WireFuncData) -> Option<String> {
+        self.render(WIRE_FUNC, data)
+    }
+
+    pub fn dart_method(&self, data: &DartMethodData) -> Option<String> {
+        self.render(DART_METHOD, data)
+    }
+
+    pub fn dart_api_class(&self, data: &DartApiClassData) -> Option<String> {
+        self.render(DART_API_CLASS, data)
+    }
+}
+
+#[derive(Serialize)]
+pub struct WireFuncData<'a> {
+    /// Exported name, including the `--symbol-prefix`.
+    pub name: &'a str,
+    /// The Rust API function it calls, or [None] for the helpers, e.g. allocating wire structs.
+    pub func: Option<&'a str>,
+    /// `Normal`, `Sync` or `Stream` for API functions.
+    pub mode: Option<&'a str>,
+    pub params: &'a [&'a str],
+    pub return_type: Option<&'a str>,
+    pub body: &'a str,
+    pub default: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct DartMethodData<'a> {
+    /// Name of the Rust API function.
+    pub func: &'a str,
+    /// `Normal`, `Sync` or `Stream`.
+    pub mode: &'a str,
+    pub comments: &'a str,
+    pub signature: &'a str,
+    pub default: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct DartApiClassData<'a> {
+    pub name: &'a str,
+    /// Dart classes of the Rust traits implemented by the API.
+    pub implements: &'a [&'a str],
+    /// Declarations of the class, from the API methods to the utilities, e.g. `captureRustOutput`.
+    pub body: &'a str,
+    pub default: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn load(files: &[(&str, &str)]) -> Templates {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        Templates::load(Some(dir.path().to_str().unwrap()))
+    }
+
+    fn dart_method(templates: &Templates) -> Option<String> {
+        templates.dart_method(&DartMethodData {
+            func: "greet",
+            mode: "Normal",
+            comments: "",
+            signature: "Future<String> greet();",
+            default: "Future<String> greet() => inner.greet();",
+        })
+    }
+
+    #[test]
+    fn parts_without_a_template_are_not_rendered() {
+        assert_eq!(dart_method(&Templates::load(None)), None);
+        assert_eq!(dart_method(&load(&[("README.md", "not a template")])), None);
+    }
+
+    #[test]
+    fn renders_the_data_without_escaping() {
+        let templates = load(&[(
+            "dart_method.dart.hbs",
+            "// {{func}} ({{mode}})\n{{default}}",
+        )]);
+        assert_eq!(
+            dart_method(&templates).as_deref(),
+            Some("// greet (Normal)\nFuture<String> greet() => inner.greet();")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown template")]
+    fn rejects_unknown_templates() {
+        load(&[("dart_function.dart.hbs", "{{default}}")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "fail to render template `dart_method`")]
+    fn rejects_misspelled_variables() {
+        dart_method(&load(&[("dart_method.dart.hbs", "{{defualt}}")]));
+    }
+}
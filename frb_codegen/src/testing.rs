@@ -0,0 +1,241 @@
+//! Snapshot tests of the generated code, for the projects using flutter_rust_bridge: called from
+//! their own tests, [assert_generated_matches] fails whenever the generated code changes, be it
+//! because of a change of their API or of a new version of the codegen.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Opts;
+use crate::transformer;
+
+/// Environment variable which, when set, makes [assert_generated_matches] write the snapshots
+/// instead of comparing with them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "FRB_UPDATE_SNAPSHOTS";
+
+/// Generates the Rust and the Dart code of `config`, without writing its outputs nor running any
+/// tool, and panics unless they are the same as the snapshots in `snapshot_dir`, named after the
+/// outputs, e.g. `bridge_generated.rs.snap`. Missing snapshots are written instead, and so are all
+/// of them when `FRB_UPDATE_SNAPSHOTS` is set.
+///
+/// The code is compared as generated, i.e. before formatting, and without the wire code of ffigen.
+pub fn assert_generated_matches(config: &Opts, snapshot_dir: impl AsRef<Path>) {
+    let ir_file = transformer::transform(config.get_ir_file());
+    let rust = ir_file.generate_rust(config).code;
+    let (dart, _) = ir_file
+        .generate_dart(config)
+        .unwrap_or_else(|err| panic!("cannot generate the Dart code: {}", err));
+    let dart = (&dart.file_prelude + &dart.decl_code + &dart.impl_code).to_text();
+
+    for (output_path, generated) in [
+        (&config.rust_output_path, rust),
+        (&config.dart_output_path, dart),
+    ] {
+        let file_name = Path::new(output_path)
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        let snapshot_path = snapshot_dir.as_ref().join(format!("{}.snap", file_name));
+        assert_matches_snapshot(&snapshot_path, &generated);
+    }
+}
+
+fn assert_matches_snapshot(snapshot_path: &Path, generated: &str) {
+    let snapshot = match fs::read_to_string(snapshot_path) {
+        Ok(snapshot) if env::var_os(UPDATE_SNAPSHOTS_ENV).is_none() => snapshot,
+        _ => {
+            if let Some(dir) = snapshot_path.parent() {
+                fs::create_dir_all(dir).unwrap();
+            }
+            fs::write(snapshot_path, generated).unwrap();
+            return;
+        }
+    };
+
+    let expected = snapshot.lines().collect::<Vec<_>>();
+    let actual = generated.lines().collect::<Vec<_>>();
+    if expected != actual {
+        let line = expected
+            .iter()
+            .zip(&actual)
+            .position(|(expected, actual)| expected != actual)
+            .unwrap_or_else(|| expected.len().min(actual.len()));
+        panic!(
+            "the generated code differs from {} from line {}:\n-{}\n+{}\nrerun with {}=1 to update the snapshot if the change is expected",
+            snapshot_path.display(),
+            line + 1,
+            expected.get(line).unwrap_or(&" <end of file>"),
+            actual.get(line).unwrap_or(&" <end of file>"),
+            UPDATE_SNAPSHOTS_ENV
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::*;
+    use crate::config::{parse, RawOpts};
+
+    /// API using the types whose conversions to and from the wire are only checked by the
+    /// compiler, e.g. because they call traits of `flutter_rust_bridge` or of other crates.
+    const FIXTURE_API: &str = "test_fixtures/compile/api.rs";
+
+    /// Needs the dependencies of the fixture, e.g. `chrono` and `url`, from crates.io or from the
+    /// local cache, so it only runs with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "fetches the dependencies of the fixture"]
+    fn generated_rust_code_compiles() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let crate_dir = tempfile::tempdir().unwrap();
+        let crate_dir = crate_dir.path();
+        fs::create_dir_all(crate_dir.join("src")).unwrap();
+        fs::write(
+            crate_dir.join("Cargo.toml"),
+            format!(
+                r#"[package]
+name = "frb_compile_test"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+anyhow = "1.0"
+chrono = "0.4"
+flutter_rust_bridge = {{ path = {:?} }}
+lazy_static = "1.4"
+url = "2"
+"#,
+                manifest_dir.join("..").join("frb_rust")
+            ),
+        )
+        .unwrap();
+        fs::write(
+            crate_dir.join("src").join("lib.rs"),
+            "mod api;\nmod bridge_generated;\n",
+        )
+        .unwrap();
+        fs::copy(
+            manifest_dir.join(FIXTURE_API),
+            crate_dir.join("src").join("api.rs"),
+        )
+        .unwrap();
+
+        let path = |name: &str| crate_dir.join(name).to_string_lossy().into_owned();
+        let config = parse(RawOpts {
+            rust_input: vec![path("src/api.rs")],
+            dart_output: vec![path("bridge_generated.dart")],
+            rust_output: Some(vec![path("src/bridge_generated.rs")]),
+            c_output: Some(vec![path("bridge_generated.h")]),
+            ..Default::default()
+        })
+        .remove(0);
+        let ir_file = transformer::transform(config.get_ir_file());
+        fs::write(
+            &config.rust_output_path,
+            ir_file.generate_rust(&config).code,
+        )
+        .unwrap();
+
+        let output = Command::new(env!("CARGO"))
+            .args(["check", "--quiet"])
+            .current_dir(crate_dir)
+            // next to the test binary, i.e. in `<target dir>/<profile>/deps`, and shared between
+            // the runs, so that the dependencies are only built once
+            .env(
+                "CARGO_TARGET_DIR",
+                env::current_exe()
+                    .unwrap()
+                    .ancestors()
+                    .nth(3)
+                    .unwrap()
+                    .join("compile_test"),
+            )
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "the generated code does not compile:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// A crate whose `src/api.rs` is `api`, for the codegen to parse.
+    fn temp_crate(api: &str) -> tempfile::TempDir {
+        let crate_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(crate_dir.path().join("src")).unwrap();
+        fs::write(
+            crate_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"frb_codegen_test\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(crate_dir.path().join("src").join("lib.rs"), "mod api;\n").unwrap();
+        fs::write(crate_dir.path().join("src").join("api.rs"), api).unwrap();
+        crate_dir
+    }
+
+    #[test]
+    fn pure_dart_output_does_not_import_flutter() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let crate_dir = temp_crate(&fs::read_to_string(manifest_dir.join(FIXTURE_API)).unwrap());
+        let crate_dir = crate_dir.path();
+
+        let path = |name: &str| crate_dir.join(name).to_string_lossy().into_owned();
+        let generate_dart = |dart_import: Option<Vec<String>>| {
+            let config = parse(RawOpts {
+                rust_input: vec![path("src/api.rs")],
+                dart_output: vec![path("bridge_generated.dart")],
+                rust_output: Some(vec![path("src/bridge_generated.rs")]),
+                c_output: Some(vec![path("bridge_generated.h")]),
+                dart_import,
+                pure_dart: true,
+                ..Default::default()
+            })
+            .remove(0);
+            transformer::transform(config.get_ir_file())
+                .generate_dart(&config)
+                .map(|(dart, _)| (&dart.file_prelude + &dart.decl_code + &dart.impl_code).to_text())
+        };
+
+        let dart = generate_dart(None).unwrap();
+        assert!(dart.contains("import 'package:flutter_rust_bridge/flutter_rust_bridge.dart';"));
+        assert!(!dart.contains("package:flutter/"));
+        assert!(!dart.contains("dart:ui"));
+
+        // e.g. a type of the app mapped to a Flutter library by mistake
+        let err = generate_dart(Some(vec![
+            "Counter=package:flutter/foundation.dart".to_owned()
+        ]))
+        .unwrap_err();
+        assert!(err.to_string().contains("package:flutter/foundation.dart"));
+    }
+
+    #[test]
+    fn too_deeply_nested_types_are_parser_errors() {
+        let crate_dir = temp_crate(&format!(
+            "pub fn nested(value: {}u8{}) {{}}\n",
+            "Vec<".repeat(8),
+            ">".repeat(8)
+        ));
+        let path = |name: &str| crate_dir.path().join(name).to_string_lossy().into_owned();
+        let config = |max_type_depth| {
+            parse(RawOpts {
+                rust_input: vec![path("src/api.rs")],
+                dart_output: vec![path("bridge_generated.dart")],
+                rust_output: Some(vec![path("src/bridge_generated.rs")]),
+                c_output: Some(vec![path("bridge_generated.h")]),
+                max_type_depth: Some(max_type_depth),
+                ..Default::default()
+            })
+            .remove(0)
+        };
+
+        // `Vec<..>` eight times, then `u8`
+        assert_eq!(config(9).get_ir_file().funcs.len(), 1);
+        let err = std::panic::catch_unwind(|| config(8).get_ir_file()).unwrap_err();
+        let message = err.downcast::<String>().unwrap();
+        assert!(message.contains("::nested: a type nests more than 8 layers"));
+        assert!(message.contains("--max-type-depth"));
+    }
+}
@@ -19,6 +19,7 @@ pub fn transform(src: IrFile) -> IrFile {
 
     IrFile {
         funcs: dst_funcs,
+        distinct_types_cache: Default::default(),
         ..src
     }
 }
@@ -36,6 +37,18 @@ fn transform_func_input_add_boxed(input: IrField) -> IrField {
             }),
             ..input
         }
+    } else if let Delegate(IrTypeDelegate::DartStream(inner)) = &input.ty {
+        // the items are passed to Rust one by one, so structs are boxed just like other inputs
+        if !inner.is_struct() {
+            return input;
+        }
+        IrField {
+            ty: Delegate(IrTypeDelegate::DartStream(Box::new(Boxed(IrTypeBoxed {
+                exist_in_real_api: false,
+                inner: inner.clone(),
+            })))),
+            ..input
+        }
     } else {
         input
     }
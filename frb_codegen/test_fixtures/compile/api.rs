@@ -0,0 +1,125 @@
+//! Functions whose generated code is only checked by compiling it, see `testing::tests`.
+
+use std::collections::BTreeMap;
+pub use std::sync::Mutex;
+
+use flutter_rust_bridge::{frb, RustOpaque, SyncReturn};
+
+pub fn parse_url(url: String) -> url::Url {
+    url::Url::parse(&url).unwrap()
+}
+
+pub fn make_adder(by: i32) -> Box<dyn Fn(i32) -> i32 + Send + Sync> {
+    Box::new(move |x| x + by)
+}
+
+pub struct Counter {
+    pub count: u32,
+}
+
+impl Counter {
+    #[frb(sync_mutex)]
+    pub fn increment(&mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+}
+
+pub fn new_counter() -> RustOpaque<Mutex<Counter>> {
+    RustOpaque::new(Mutex::new(Counter { count: 0 }))
+}
+
+#[frb]
+pub fn rename(#[frb(validate = "len >= 1")] name: String) -> bool {
+    !name.is_empty()
+}
+
+pub fn scores(by_name: BTreeMap<String, i32>) -> BTreeMap<String, i32> {
+    by_name
+}
+
+pub fn check_all(ids: Vec<u64>) -> Vec<Result<u64, String>> {
+    ids.into_iter()
+        .map(|id| if id > 0 { Ok(id) } else { Err("zero".to_owned()) })
+        .collect()
+}
+
+pub fn count_up(to: u32) -> Box<dyn Iterator<Item = u32> + Send> {
+    Box::new(0..to)
+}
+
+#[derive(Clone)]
+pub struct Config {
+    pub max_retries: u32,
+}
+
+static CONFIG: Config = Config { max_retries: 3 };
+
+pub fn config() -> &'static Config {
+    &CONFIG
+}
+
+pub fn join(names: Vec<String>) -> String {
+    names.join(", ")
+}
+
+pub fn next_day(
+    at: chrono::DateTime<chrono::FixedOffset>,
+) -> chrono::DateTime<chrono::FixedOffset> {
+    at + chrono::Duration::days(1)
+}
+
+pub fn c_names(names: Vec<std::ffi::CString>) -> Vec<std::ffi::CString> {
+    names
+}
+
+pub fn c_name(name: std::ffi::CString) -> std::ffi::CString {
+    name
+}
+
+pub fn fill_ids(ids: Vec<Option<i64>>) -> Vec<Option<i64>> {
+    ids.into_iter().map(|id| id.or(Some(0))).collect()
+}
+
+pub fn fill_names(names: Vec<Option<String>>) -> Vec<Option<String>> {
+    names
+}
+
+pub trait Tally {
+    fn peek(&self) -> u32;
+    fn bump(&mut self, by: u32) -> u32;
+    fn finish(self, by: u32) -> u32;
+}
+
+#[derive(Default)]
+pub struct TallyImpl {
+    count: u32,
+}
+
+impl Tally for TallyImpl {
+    fn peek(&self) -> u32 {
+        self.count
+    }
+
+    fn bump(&mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+
+    fn finish(mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+}
+
+pub fn divide(a: i32, b: i32) -> SyncReturn<i32> {
+    SyncReturn(a / b)
+}
+
+pub fn greet_sync(name: String) -> SyncReturn<String> {
+    SyncReturn(format!("Hello, {}", name))
+}
+
+pub fn find_sync(names: Vec<String>, prefix: String) -> SyncReturn<Option<String>> {
+    SyncReturn(names.into_iter().find(|name| name.starts_with(&prefix)))
+}
@@ -1,3 +1,5 @@
 export 'src/flutter_rust_bridge_io.dart'
     if (dart.library.html) 'flutter_rust_bridge_web.dart';
+export 'src/msgpack.dart';
+export 'src/compression.dart';
 export 'src/platform_independent.dart';
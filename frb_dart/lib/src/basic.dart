@@ -11,6 +11,10 @@ import 'package:meta/meta.dart';
 
 final _instances = <Type>{};
 
+/// Identifies this run of the isolate. Like every Dart static, it is initialized again after a
+/// hot restart, while the Rust library keeps its state.
+final _sessionToken = DateTime.now().microsecondsSinceEpoch;
+
 /// Base class for generated bindings of Flutter Rust Bridge.
 /// Normally, users do not extend this class manually. Instead,
 /// users should directly use the generated class.
@@ -23,6 +27,12 @@ abstract class FlutterRustBridgeBase<T extends FlutterRustBridgeWireBase> {
   @protected
   final T inner;
 
+  /// Turns the errors of Rust, otherwise thrown as [FfiException], into the error types of the app,
+  /// so that call sites catch those instead of wrapping every call. Errors are thrown as is when null.
+  Object Function(FfiException error)? errorMapper;
+
+  Object _mapError(FfiException error) => errorMapper?.call(error) ?? error;
+
   void _sanityCheckSingleton() {
     if (_instances.contains(runtimeType)) {
       throw Exception(
@@ -33,60 +43,303 @@ abstract class FlutterRustBridgeBase<T extends FlutterRustBridgeWireBase> {
 
   void _setUpRustToDartComm() {
     inner.store_dart_post_cobject(NativeApi.postCObject.cast());
+    // after a hot restart, tears down the Rust state of the previous run before any other call
+    inner.wire_reinit_bridge(_sessionToken);
   }
 
   /// Execute a normal ffi call. Usually called by generated code instead of manually called.
   @protected
-  Future<S> executeNormal<S>(FlutterRustBridgeTask<S> task) {
+  Future<S> executeNormal<S>(FlutterRustBridgeTask<S> task) =>
+      _executeNormal(task, mapError: true);
+
+  Future<S> _executeNormal<S>(FlutterRustBridgeTask<S> task,
+      {required bool mapError}) {
     final completer = Completer<dynamic>();
     final sendPort = singleCompletePort(completer);
     task.callFfi(sendPort.nativePort);
-    return completer.future.then((dynamic raw) =>
-        _transformRust2DartMessage(raw, task.parseSuccessData));
+    return completer.future.then((dynamic raw) => _transformRust2DartMessage(
+        raw, task.parseSuccessData,
+        mapError: mapError));
+  }
+
+  /// Similar to [executeNormal], except that this also returns a handle to cancel the Rust task.
+  @protected
+  RustTaskHandle<S> executeNormalWithHandle<S>(FlutterRustBridgeTask<S> task) {
+    final completer = Completer<dynamic>();
+    final sendPort = singleCompletePort(completer);
+    final port = sendPort.nativePort;
+    task.callFfi(port);
+    return RustTaskHandle(
+      future: completer.future.then((dynamic raw) =>
+          _transformRust2DartMessage(raw, task.parseSuccessData)),
+      cancel: () => inner.wire_cancel_task(port),
+    );
+  }
+
+  /// Similar to [executeNormal], except that errors returned by Rust are retried up to [times] times,
+  /// waiting [backoff] before the first retry and doubling it for each subsequent one.
+  /// If [retryOn] is not empty, only errors whose message starts with one of its entries are retried.
+  /// Panics are never retried. Usually called by generated code for `#[frb(retry(..))]`.
+  @protected
+  Future<S> executeNormalWithRetry<S>(
+    FlutterRustBridgeTask<S> task, {
+    required int times,
+    required Duration backoff,
+    List<String> retryOn = const [],
+  }) async {
+    for (var attempt = 0;; attempt++) {
+      try {
+        // mapped only once given up, since retrying depends on the original error
+        return await _executeNormal(task, mapError: false);
+      } on FfiException catch (e) {
+        final transient = e.code == 'RESULT_ERROR' &&
+            (retryOn.isEmpty || retryOn.any(e.message.startsWith));
+        if (!transient || attempt >= times) {
+          if (errorMapper == null) rethrow;
+          throw _mapError(e);
+        }
+        await Future<void>.delayed(backoff * (1 << attempt));
+      }
+    }
+  }
+
+  final _debouncedCalls = <String, _DebouncedCall<dynamic>>{};
+
+  /// Similar to [executeNormal], except that the call waits [delay] before reaching Rust, and is
+  /// superseded by the next call of the same [key] during that time or while it runs, in which case
+  /// Rust is asked to cancel it as with [RustTaskHandle.cancel]. The futures of superseded calls
+  /// complete with the result of the latest one. Usually called by generated code for
+  /// `#[frb(debounce_ms = ..)]`.
+  @protected
+  Future<S> executeNormalDebounced<S>(
+    FlutterRustBridgeTask<S> task, {
+    required String key,
+    required Duration delay,
+  }) {
+    final previous = _debouncedCalls[key] as _DebouncedCall<S>?;
+    final call = _DebouncedCall<S>([...?previous?.completers, Completer<S>()]);
+    if (previous != null) {
+      previous.timer?.cancel();
+      final port = previous.port;
+      if (port != null) inner.wire_cancel_task(port);
+    }
+    _debouncedCalls[key] = call;
+
+    call.timer = Timer(delay, () {
+      call.timer = null;
+      final completer = Completer<dynamic>();
+      final sendPort = singleCompletePort(completer);
+      final port = call.port = sendPort.nativePort;
+      task.callFfi(port);
+      completer.future
+          .then((dynamic raw) =>
+              _transformRust2DartMessage(raw, task.parseSuccessData))
+          .then((value) {
+        if (_debouncedCalls[key] != call) return;
+        _debouncedCalls.remove(key);
+        for (final completer in call.completers) {
+          completer.complete(value);
+        }
+      }, onError: (Object error, StackTrace stackTrace) {
+        if (_debouncedCalls[key] != call) return;
+        _debouncedCalls.remove(key);
+        for (final completer in call.completers) {
+          completer.completeError(error, stackTrace);
+        }
+      });
+    });
+    return call.completers.last.future;
+  }
+
+  /// Calls [poll] once listened to, then [interval] after each call completes, and emits its results
+  /// which differ from the previous one according to `==`. Errors are emitted as well, without
+  /// stopping the polling. Nothing is called while the subscription is paused, nor once cancelled.
+  /// Usually called by generated code for `#[frb(poll)]`.
+  @protected
+  Stream<S> executePoll<S>(FutureOr<S> Function() poll, Duration interval) {
+    late final StreamController<S> controller;
+    Timer? timer;
+    var polling = false;
+    var hasLast = false;
+    S? last;
+
+    Future<void> tick() async {
+      timer = null;
+      polling = true;
+      try {
+        final value = await poll();
+        if (!hasLast || value != last) {
+          hasLast = true;
+          last = value;
+          controller.add(value);
+        }
+      } catch (e, s) {
+        controller.addError(e, s);
+      }
+      polling = false;
+      if (controller.hasListener && !controller.isPaused) {
+        timer = Timer(interval, tick);
+      }
+    }
+
+    void start() {
+      if (!polling && timer == null) tick();
+    }
+
+    void stop() {
+      timer?.cancel();
+      timer = null;
+    }
+
+    controller = StreamController<S>(
+      onListen: start,
+      onPause: stop,
+      onResume: start,
+      onCancel: stop,
+    );
+    return controller.stream;
   }
 
-  /// Similar to [executeNormal], except that this will return synchronously
+  /// Similar to [executeNormal], except that this will return synchronously.
+  /// Returns null if the Rust function returned `None`, and throws if it returned an error or panicked.
+  @protected
+  Uint8List? executeSync(FlutterRustBridgeSyncTask task) =>
+      _transformSyncReturn(task.callFfi());
+
+  /// Where the Rust functions taking and returning primitives write whether they panicked, to be
+  /// passed to them. Shared by all the calls, since they are synchronous.
   @protected
-  Uint8List executeSync(FlutterRustBridgeSyncTask task) {
-    final raw = task.callFfi();
+  late final ffi.Pointer<WireSyncReturnStruct> primitiveSyncOutcome =
+      inner.new_sync_outcome();
 
+  /// Similar to [executeSync], for the Rust functions taking and returning primitives, which
+  /// return [value] as is. Throws if the call which gave [value] panicked.
+  @protected
+  S executePrimitiveSync<S>(S value) {
+    final outcome = primitiveSyncOutcome.ref;
+    if (outcome.kind == WireSyncReturnStruct.kError) {
+      _transformSyncReturn(outcome);
+    }
+    return value;
+  }
+
+  Uint8List? _transformSyncReturn(WireSyncReturnStruct raw) {
     final bytes = Uint8List.fromList(raw.ptr.asTypedList(raw.len));
-    final success = raw.success > 0;
+    final kind = raw.kind;
 
     inner.free_WireSyncReturnStruct(raw);
 
-    if (success) {
-      return bytes;
-    } else {
-      throw FfiException('EXECUTE_SYNC', utf8.decode(bytes), null);
+    switch (kind) {
+      case WireSyncReturnStruct.kSuccess:
+        return bytes;
+      case WireSyncReturnStruct.kNull:
+        return null;
+      case WireSyncReturnStruct.kError:
+        throw _mapError(
+            FfiException('EXECUTE_SYNC', utf8.decode(bytes), null));
+      default:
+        throw Exception('Unsupported sync return kind=$kind');
     }
   }
 
   /// Similar to [executeNormal], except that this will return a [Stream] instead of a [Future].
   @protected
   Stream<S> executeStream<S>(FlutterRustBridgeTask<S> task) async* {
+    yield* _executeStreamOnPort(ReceivePort(), task);
+  }
+
+  /// Similar to [executeStream], except that this also returns a handle to control the Rust producer.
+  /// The Rust function is called once the stream of the handle is listened to.
+  @protected
+  RustStreamHandle<S> executeStreamWithHandle<S>(
+      FlutterRustBridgeTask<S> task) {
+    final receivePort = ReceivePort();
+    final port = receivePort.sendPort.nativePort;
+    return RustStreamHandle(
+      stream: _executeStreamOnPort(receivePort, task),
+      control: (command, value) =>
+          inner.wire_stream_control(port, command, value),
+    );
+  }
+
+  /// Similar to [executeStream], for the Rust functions returning `Result<StreamSetup>`: the
+  /// returned future fails with the error of the setup, before the stream yields anything.
+  @protected
+  Future<Stream<S>> executeStreamAfterSetup<S>(
+      FlutterRustBridgeTask<S> task) async {
     final receivePort = ReceivePort();
+    final messages = StreamIterator<dynamic>(receivePort);
     task.callFfi(receivePort.sendPort.nativePort);
 
-    await for (final raw in receivePort) {
-      try {
-        yield _transformRust2DartMessage(raw, task.parseSuccessData);
-      } on _CloseStreamException {
-        receivePort.close();
+    await messages.moveNext();
+    final first = messages.current;
+    if (first[0] == _RUST2DART_ACTION_ERROR) {
+      receivePort.close();
+      // throws the error of the setup
+      _transformRust2DartMessage(first, task.parseSuccessData);
+    }
+    return _streamAfterSetup(receivePort, messages, first, task);
+  }
+
+  Stream<S> _streamAfterSetup<S>(
+      ReceivePort receivePort,
+      StreamIterator<dynamic> messages,
+      dynamic first,
+      FlutterRustBridgeTask<S> task) async* {
+    final port = receivePort.sendPort.nativePort;
+    try {
+      var raw = first;
+      while (true) {
+        // items added by another thread may arrive before the notice of the setup
+        if (raw[0] != _RUST2DART_ACTION_STREAM_READY) {
+          try {
+            yield _transformRust2DartMessage(raw, task.parseSuccessData);
+          } on _CloseStreamException {
+            break;
+          }
+        }
+        if (!await messages.moveNext()) break;
+        raw = messages.current;
+      }
+    } finally {
+      receivePort.close();
+      // never leave the Rust producer paused or throttled once nobody listens
+      inner.wire_stream_control(port, RustStreamHandle._kDispose, 0);
+    }
+  }
+
+  Stream<S> _executeStreamOnPort<S>(
+      ReceivePort receivePort, FlutterRustBridgeTask<S> task) async* {
+    final port = receivePort.sendPort.nativePort;
+    task.callFfi(port);
+
+    try {
+      await for (final raw in receivePort) {
+        try {
+          yield _transformRust2DartMessage(raw, task.parseSuccessData);
+        } on _CloseStreamException {
+          receivePort.close();
+        }
       }
+    } finally {
+      receivePort.close();
+      // never leave the Rust producer paused or throttled once nobody listens
+      inner.wire_stream_control(port, RustStreamHandle._kDispose, 0);
     }
   }
 
   S _transformRust2DartMessage<S>(
-      dynamic raw, S Function(dynamic) parseSuccessData) {
+      dynamic raw, S Function(dynamic) parseSuccessData,
+      {bool mapError = true}) {
     final action = raw[0];
     switch (action) {
       case _RUST2DART_ACTION_SUCCESS:
         assert(raw.length == 2);
         return parseSuccessData(raw[1]);
       case _RUST2DART_ACTION_ERROR:
-        assert(raw.length == 4);
-        throw FfiException(raw[1], raw[2], raw[3]);
+        assert(raw.length == 5);
+        final error = FfiException(raw[1], raw[2], raw[3], raw[4]);
+        throw mapError ? _mapError(error) : error;
       case _RUST2DART_ACTION_CLOSE_STREAM:
         assert(raw.length == 1);
         throw _CloseStreamException();
@@ -103,6 +356,9 @@ abstract class FlutterRustBridgeBase<T extends FlutterRustBridgeWireBase> {
 
   // ignore: constant_identifier_names
   static const _RUST2DART_ACTION_CLOSE_STREAM = 2;
+
+  // ignore: constant_identifier_names
+  static const _RUST2DART_ACTION_STREAM_READY = 3;
 }
 
 /// A task to call FFI function.
@@ -160,6 +416,96 @@ abstract class FlutterRustBridgeWireBase {
   /// Not to be used by normal users, but has to be public for generated code
   // ignore: non_constant_identifier_names
   void free_WireSyncReturnStruct(WireSyncReturnStruct val);
+
+  /// Not to be used by normal users, but has to be public for generated code
+  // ignore: non_constant_identifier_names
+  ffi.Pointer<WireSyncReturnStruct> new_sync_outcome();
+
+  /// Not to be used by normal users, but has to be public for generated code
+  // ignore: non_constant_identifier_names
+  void wire_stream_control(int port_, int command, int value);
+
+  /// Not to be used by normal users, but has to be public for generated code
+  // ignore: non_constant_identifier_names
+  void wire_reinit_bridge(int token);
+
+  /// Not to be used by normal users, but has to be public for generated code
+  // ignore: non_constant_identifier_names
+  void wire_cancel_task(int port_);
+}
+
+/// A call of [FlutterRustBridgeBase.executeNormalDebounced] waiting for its delay or running, with
+/// the calls it superseded.
+class _DebouncedCall<S> {
+  /// Completed with the result of this call, for it and for the calls it superseded.
+  final List<Completer<S>> completers;
+
+  /// Until the call reaches Rust.
+  Timer? timer;
+
+  /// Once the call reaches Rust, to cancel it.
+  int? port;
+
+  _DebouncedCall(this.completers);
+}
+
+/// The result of a Rust task, together with a way to cancel it.
+///
+/// A task cancelled before it starts is dropped, and [future] completes with an [FfiException]
+/// of code `CANCELLED`. Once running, the Rust code has to check `is_cancelled()` itself.
+/// To use it with `package:async`, wrap it as
+/// `CancelableOperation.fromFuture(handle.future, onCancel: handle.cancel)`.
+class RustTaskHandle<S> {
+  /// The value returned by Rust.
+  final Future<S> future;
+
+  final void Function() _cancel;
+
+  /// Not to be used by normal users, but has to be public for generated code
+  RustTaskHandle({
+    required this.future,
+    required void Function() cancel,
+  }) : _cancel = cancel;
+
+  /// Asks Rust to drop the task if it has not started yet, or else to stop it early.
+  void cancel() => _cancel();
+}
+
+/// A stream produced by Rust, together with controls over the Rust producer.
+///
+/// While paused or throttled, the Rust side blocks when adding to its `StreamSink`,
+/// so the producer is slowed down instead of buffering values.
+class RustStreamHandle<S> {
+  static const _kPause = 0;
+  static const _kResume = 1;
+  static const _kThrottle = 2;
+  static const _kDispose = 3;
+
+  /// The values sent by Rust.
+  final Stream<S> stream;
+
+  final void Function(int command, int value) _control;
+
+  /// Not to be used by normal users, but has to be public for generated code
+  RustStreamHandle({
+    required this.stream,
+    required void Function(int command, int value) control,
+  }) : _control = control;
+
+  /// Same handle, but with [stream] replaced, e.g. to wait for something before listening.
+  RustStreamHandle<S> withStream(Stream<S> stream) =>
+      RustStreamHandle(stream: stream, control: _control);
+
+  /// Blocks the Rust producer the next time it adds a value, until [resume] is called.
+  void pause() => _control(_kPause, 0);
+
+  /// Lets a paused Rust producer continue.
+  void resume() => _control(_kResume, 0);
+
+  /// Makes the Rust producer wait at least [minInterval] between two values.
+  /// Pass [Duration.zero] to disable throttling.
+  void throttle(Duration minInterval) =>
+      _control(_kThrottle, minInterval.inMilliseconds);
 }
 
 class _CloseStreamException {}
@@ -175,6 +521,15 @@ class WireSyncReturnStruct extends ffi.Struct {
   external int len;
 
   /// Not to be used by normal users, but has to be public for generated code
-  @ffi.Uint8()
-  external int success;
+  @ffi.Int32()
+  external int kind;
+
+  /// Values of [kind], in sync with `SyncReturnKind` in Rust
+  static const kSuccess = 0;
+
+  /// Values of [kind], in sync with `SyncReturnKind` in Rust
+  static const kError = 1;
+
+  /// Values of [kind], in sync with `SyncReturnKind` in Rust
+  static const kNull = 2;
 }
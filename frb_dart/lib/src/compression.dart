@@ -0,0 +1,573 @@
+import 'dart:typed_data';
+
+// NOTE for maintainer: Please manually keep in sync with `support::compress_zstd` in Rust
+const _kUncompressed = 0;
+const _kZstd = 1;
+
+/// Decodes the output of a function marked with `#[frb(compress = "zstd")]`.
+/// Not to be used by normal users, but has to be public for generated code
+Uint8List decompressPayload(dynamic raw) {
+  final bytes = raw as Uint8List;
+  final body = Uint8List.sublistView(bytes, 1);
+  switch (bytes[0]) {
+    case _kUncompressed:
+      return body;
+    case _kZstd:
+      return zstdDecompress(body);
+    default:
+      throw Exception('Unsupported payload compression ${bytes[0]}');
+  }
+}
+
+/// Decompresses data in the [Zstandard format](https://www.rfc-editor.org/rfc/rfc8878),
+/// which may consist of several frames. Dictionaries are not supported.
+Uint8List zstdDecompress(Uint8List input) => _ZstdDecoder(input).decompress();
+
+const _kZstdMagic = 0xFD2FB528;
+const _kSkippableMagicMask = 0xFFFFFFF0;
+const _kSkippableMagic = 0x184D2A50;
+
+const _kLiteralLengthBase = [
+  0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 20, 22, 24,
+  28, 32, 40, 48, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384, 32768,
+  65536,
+];
+const _kLiteralLengthBits = [
+  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3,
+  4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+];
+const _kMatchLengthBase = [
+  3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22,
+  23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 37, 39, 41, 43, 47, 51,
+  59, 67, 83, 99, 131, 259, 515, 1027, 2051, 4099, 8195, 16387, 32771, 65539,
+];
+const _kMatchLengthBits = [
+  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+  0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 4, 4, 5, 7, 8, 9, 10, 11,
+  12, 13, 14, 15, 16,
+];
+
+const _kLiteralLengthDefault = [
+  4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2,
+  2, 3, 2, 1, 1, 1, 1, 1, -1, -1, -1, -1,
+];
+const _kMatchLengthDefault = [
+  1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+  1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1,
+  -1, -1, -1, -1, -1,
+];
+const _kOffsetDefault = [
+  1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+  -1, -1, -1, -1, -1,
+];
+
+class _ZstdDecoder {
+  final Uint8List input;
+  int pos = 0;
+  final output = _Output();
+
+  // State shared by the blocks of a frame
+  _HuffmanTable? huffman;
+  _FseTable? literalLengths;
+  _FseTable? offsets;
+  _FseTable? matchLengths;
+  final repeatedOffsets = [1, 4, 8];
+  int frameStart = 0;
+
+  _ZstdDecoder(this.input);
+
+  Uint8List decompress() {
+    while (pos < input.length) {
+      final magic = _readLittleEndian(4);
+      if (magic & _kSkippableMagicMask == _kSkippableMagic) {
+        final size = _readLittleEndian(4);
+        pos += size;
+      } else if (magic == _kZstdMagic) {
+        _decodeFrame();
+      } else {
+        throw const FormatException('Not a Zstandard frame');
+      }
+    }
+    return Uint8List.sublistView(output.bytes, 0, output.length);
+  }
+
+  int _readLittleEndian(int byteCount) {
+    var value = 0;
+    for (var i = 0; i < byteCount; i++) {
+      value |= input[pos + i] << (8 * i);
+    }
+    pos += byteCount;
+    return value;
+  }
+
+  void _decodeFrame() {
+    final descriptor = input[pos++];
+    final singleSegment = (descriptor >> 5) & 1;
+    final hasChecksum = (descriptor >> 2) & 1 == 1;
+    if (singleSegment == 0) pos++; // window descriptor
+    pos += const [0, 1, 2, 4][descriptor & 3]; // dictionary id
+    pos += [singleSegment, 2, 4, 8][descriptor >> 6]; // frame content size
+
+    huffman = null;
+    literalLengths = null;
+    offsets = null;
+    matchLengths = null;
+    repeatedOffsets.setAll(0, const [1, 4, 8]);
+    frameStart = output.length;
+
+    var lastBlock = false;
+    while (!lastBlock) {
+      final header = _readLittleEndian(3);
+      lastBlock = header & 1 == 1;
+      final size = header >> 3;
+      switch ((header >> 1) & 3) {
+        case 0:
+          output.addRange(input, pos, pos + size);
+          pos += size;
+          break;
+        case 1:
+          output.addRepeated(input[pos++], size);
+          break;
+        case 2:
+          final end = pos + size;
+          _decodeCompressedBlock(end);
+          pos = end;
+          break;
+        default:
+          throw const FormatException('Reserved Zstandard block type');
+      }
+    }
+    if (hasChecksum) pos += 4;
+  }
+
+  void _decodeCompressedBlock(int end) {
+    final literals = _decodeLiterals();
+
+    var sequenceCount = input[pos++];
+    if (sequenceCount == 255) {
+      sequenceCount = input[pos] + (input[pos + 1] << 8) + 0x7F00;
+      pos += 2;
+    } else if (sequenceCount >= 128) {
+      sequenceCount = ((sequenceCount - 128) << 8) + input[pos++];
+    }
+    if (sequenceCount == 0) {
+      output.addRange(literals, 0, literals.length);
+      return;
+    }
+
+    final modes = input[pos++];
+    final literalLengths = this.literalLengths = _readSequenceTable(
+        (modes >> 6) & 3, this.literalLengths, _kLiteralLengthDefault, 6, 35, 9);
+    final offsets = this.offsets = _readSequenceTable(
+        (modes >> 4) & 3, this.offsets, _kOffsetDefault, 5, 31, 8);
+    final matchLengths = this.matchLengths = _readSequenceTable(
+        (modes >> 2) & 3, this.matchLengths, _kMatchLengthDefault, 6, 52, 9);
+
+    final bits = _BackwardBits(input, pos, end);
+    var literalLengthState = bits.read(literalLengths.accuracyLog);
+    var offsetState = bits.read(offsets.accuracyLog);
+    var matchLengthState = bits.read(matchLengths.accuracyLog);
+    var literalPos = 0;
+    for (var i = 0; i < sequenceCount; i++) {
+      final offsetCode = offsets.symbols[offsetState];
+      final literalLengthCode = literalLengths.symbols[literalLengthState];
+      final matchLengthCode = matchLengths.symbols[matchLengthState];
+
+      final offsetValue = (1 << offsetCode) + bits.read(offsetCode);
+      final matchLength = _kMatchLengthBase[matchLengthCode] +
+          bits.read(_kMatchLengthBits[matchLengthCode]);
+      final literalLength = _kLiteralLengthBase[literalLengthCode] +
+          bits.read(_kLiteralLengthBits[literalLengthCode]);
+
+      if (i != sequenceCount - 1) {
+        literalLengthState = literalLengths.update(literalLengthState, bits);
+        matchLengthState = matchLengths.update(matchLengthState, bits);
+        offsetState = offsets.update(offsetState, bits);
+      }
+
+      final offset = _resolveOffset(offsetValue, literalLength);
+      output.addRange(literals, literalPos, literalPos + literalLength);
+      literalPos += literalLength;
+      if (offset > output.length - frameStart) {
+        throw const FormatException('Invalid Zstandard match offset');
+      }
+      output.copyMatch(offset, matchLength);
+    }
+    output.addRange(literals, literalPos, literals.length);
+  }
+
+  int _resolveOffset(int offsetValue, int literalLength) {
+    final repeated = repeatedOffsets;
+    if (offsetValue > 3) {
+      repeated[2] = repeated[1];
+      repeated[1] = repeated[0];
+      return repeated[0] = offsetValue - 3;
+    }
+    final index = literalLength == 0 ? offsetValue : offsetValue - 1;
+    if (index == 0) return repeated[0];
+    final offset = index < 3 ? repeated[index] : repeated[0] - 1;
+    if (index > 1) repeated[2] = repeated[1];
+    repeated[1] = repeated[0];
+    return repeated[0] = offset;
+  }
+
+  _FseTable _readSequenceTable(int mode, _FseTable? previous,
+      List<int> defaultDistribution, int defaultAccuracyLog, int maxSymbol,
+      int maxAccuracyLog) {
+    switch (mode) {
+      case 0:
+        return _FseTable(defaultDistribution, defaultAccuracyLog);
+      case 1:
+        return _FseTable.rle(input[pos++]);
+      case 2:
+        return _readFseTable(maxSymbol, maxAccuracyLog);
+      default:
+        if (previous == null) {
+          throw const FormatException('Missing Zstandard table to repeat');
+        }
+        return previous;
+    }
+  }
+
+  Uint8List _decodeLiterals() {
+    final header = input[pos];
+    final type = header & 3;
+    final sizeFormat = (header >> 2) & 3;
+
+    if (type < 2) {
+      final int size;
+      if (sizeFormat == 1) {
+        size = (header >> 4) + (input[pos + 1] << 4);
+        pos += 2;
+      } else if (sizeFormat == 3) {
+        size = (header >> 4) + (input[pos + 1] << 4) + (input[pos + 2] << 12);
+        pos += 3;
+      } else {
+        size = header >> 3;
+        pos += 1;
+      }
+      if (type == 0) {
+        final literals = Uint8List.sublistView(input, pos, pos + size);
+        pos += size;
+        return literals;
+      }
+      return Uint8List(size)..fillRange(0, size, input[pos++]);
+    }
+
+    final headerSize = const [3, 3, 4, 5][sizeFormat];
+    final sizeBits = const [10, 10, 14, 18][sizeFormat];
+    final sizes = _readLittleEndian(headerSize);
+    final mask = (1 << sizeBits) - 1;
+    final regeneratedSize = (sizes >> 4) & mask;
+    final end = pos + ((sizes >> (4 + sizeBits)) & mask);
+
+    if (type == 2) huffman = _readHuffmanTable();
+    final table = huffman;
+    if (table == null) {
+      throw const FormatException('Missing Zstandard Huffman table');
+    }
+
+    final literals = Uint8List(regeneratedSize);
+    if (sizeFormat == 0) {
+      table.decodeStream(input, pos, end, literals, 0, regeneratedSize);
+    } else {
+      final streamSizes = [
+        input[pos] | (input[pos + 1] << 8),
+        input[pos + 2] | (input[pos + 3] << 8),
+        input[pos + 4] | (input[pos + 5] << 8),
+      ];
+      streamSizes.add(end - pos - 6 - streamSizes.reduce((a, b) => a + b));
+      final quarter = (regeneratedSize + 3) ~/ 4;
+      var streamStart = pos + 6;
+      for (var i = 0; i < 4; i++) {
+        final count = i < 3 ? quarter : regeneratedSize - 3 * quarter;
+        table.decodeStream(input, streamStart, streamStart + streamSizes[i],
+            literals, i * quarter, count);
+        streamStart += streamSizes[i];
+      }
+    }
+    pos = end;
+    return literals;
+  }
+
+  _HuffmanTable _readHuffmanTable() {
+    final header = input[pos++];
+    final weights = <int>[];
+    if (header >= 128) {
+      final count = header - 127;
+      for (var i = 0; i < count; i++) {
+        final byte = input[pos + i ~/ 2];
+        weights.add(i.isEven ? byte >> 4 : byte & 15);
+      }
+      pos += (count + 1) ~/ 2;
+      return _HuffmanTable(weights);
+    }
+
+    final end = pos + header;
+    final table = _readFseTable(255, 6);
+    final bits = _BackwardBits(input, pos, end);
+    var state1 = bits.read(table.accuracyLog);
+    var state2 = bits.read(table.accuracyLog);
+    while (true) {
+      weights.add(table.symbols[state1]);
+      state1 = table.update(state1, bits);
+      if (bits.isOverflowed) {
+        weights.add(table.symbols[state2]);
+        break;
+      }
+      weights.add(table.symbols[state2]);
+      state2 = table.update(state2, bits);
+      if (bits.isOverflowed) {
+        weights.add(table.symbols[state1]);
+        break;
+      }
+      if (weights.length > 255) {
+        throw const FormatException('Invalid Zstandard Huffman weights');
+      }
+    }
+    pos = end;
+    return _HuffmanTable(weights);
+  }
+
+  /// Reads a table description, see https://www.rfc-editor.org/rfc/rfc8878#section-4.1.1
+  _FseTable _readFseTable(int maxSymbol, int maxAccuracyLog) {
+    final bits = _ForwardBits(input, pos);
+    final accuracyLog = bits.read(4) + 5;
+    if (accuracyLog > maxAccuracyLog) {
+      throw const FormatException('Invalid Zstandard table accuracy');
+    }
+    var remaining = 1 << accuracyLog;
+    final frequencies = <int>[];
+    while (remaining > 0 && frequencies.length <= maxSymbol) {
+      final bitCount = (remaining + 1).bitLength;
+      var value = bits.read(bitCount);
+      final lowerMask = (1 << (bitCount - 1)) - 1;
+      final threshold = (1 << bitCount) - 1 - (remaining + 1);
+      if (value & lowerMask < threshold) {
+        bits.bit--;
+        value &= lowerMask;
+      } else if (value > lowerMask) {
+        value -= threshold;
+      }
+      final probability = value - 1;
+      remaining -= probability.abs();
+      frequencies.add(probability);
+      if (probability == 0) {
+        var repeat = bits.read(2);
+        while (true) {
+          frequencies.addAll(List.filled(repeat, 0));
+          if (repeat != 3) break;
+          repeat = bits.read(2);
+        }
+      }
+    }
+    if (remaining != 0 || frequencies.length > maxSymbol + 1) {
+      throw const FormatException('Invalid Zstandard table description');
+    }
+    pos = bits.alignedPos;
+    return _FseTable(frequencies, accuracyLog);
+  }
+}
+
+/// Decoding table of finite state entropy, see https://www.rfc-editor.org/rfc/rfc8878#section-4.1
+class _FseTable {
+  final int accuracyLog;
+  final Uint8List symbols;
+  final Uint8List numBits;
+  final Uint16List baselines;
+
+  _FseTable._(this.accuracyLog, this.symbols, this.numBits, this.baselines);
+
+  factory _FseTable.rle(int symbol) => _FseTable._(
+      0, Uint8List.fromList([symbol]), Uint8List(1), Uint16List(1));
+
+  factory _FseTable(List<int> frequencies, int accuracyLog) {
+    final size = 1 << accuracyLog;
+    final symbols = Uint8List(size);
+    final numBits = Uint8List(size);
+    final baselines = Uint16List(size);
+    final next = List.filled(frequencies.length, 0);
+
+    // symbols with a "less than 1" probability take the last cells
+    var highThreshold = size;
+    for (var s = 0; s < frequencies.length; s++) {
+      if (frequencies[s] == -1) {
+        symbols[--highThreshold] = s;
+        next[s] = 1;
+      }
+    }
+
+    final step = (size >> 1) + (size >> 3) + 3;
+    final mask = size - 1;
+    var position = 0;
+    for (var s = 0; s < frequencies.length; s++) {
+      if (frequencies[s] <= 0) continue;
+      next[s] = frequencies[s];
+      for (var i = 0; i < frequencies[s]; i++) {
+        symbols[position] = s;
+        do {
+          position = (position + step) & mask;
+        } while (position >= highThreshold);
+      }
+    }
+
+    for (var i = 0; i < size; i++) {
+      final state = next[symbols[i]]++;
+      numBits[i] = accuracyLog - (state.bitLength - 1);
+      baselines[i] = (state << numBits[i]) - size;
+    }
+    return _FseTable._(accuracyLog, symbols, numBits, baselines);
+  }
+
+  int update(int state, _BackwardBits bits) =>
+      baselines[state] + bits.read(numBits[state]);
+}
+
+/// Decoding table of the literals, see https://www.rfc-editor.org/rfc/rfc8878#section-4.2
+class _HuffmanTable {
+  final int maxBits;
+  final Uint8List symbols;
+  final Uint8List numBits;
+
+  _HuffmanTable._(this.maxBits, this.symbols, this.numBits);
+
+  /// The weight of the last symbol is implied by the others.
+  factory _HuffmanTable(List<int> weights) {
+    var total = 0;
+    for (final weight in weights) {
+      if (weight > 0) total += 1 << (weight - 1);
+    }
+    if (total == 0) throw const FormatException('Invalid Zstandard weights');
+    final maxBits = total.bitLength;
+    final left = (1 << maxBits) - total;
+    if (left & (left - 1) != 0) {
+      throw const FormatException('Invalid Zstandard weights');
+    }
+    final bitsOfSymbol = [...weights, left.bitLength]
+        .map((weight) => weight > 0 ? maxBits + 1 - weight : 0)
+        .toList();
+
+    final rankCount = List.filled(maxBits + 2, 0);
+    for (final bits in bitsOfSymbol) {
+      if (bits > 0) rankCount[bits]++;
+    }
+    final rankIndex = List.filled(maxBits + 2, 0);
+    for (var bits = maxBits; bits >= 1; bits--) {
+      rankIndex[bits - 1] =
+          rankIndex[bits] + rankCount[bits] * (1 << (maxBits - bits));
+    }
+
+    final size = 1 << maxBits;
+    final symbols = Uint8List(size);
+    final numBits = Uint8List(size);
+    for (var s = 0; s < bitsOfSymbol.length; s++) {
+      final bits = bitsOfSymbol[s];
+      if (bits == 0) continue;
+      final start = rankIndex[bits];
+      final end = start + (1 << (maxBits - bits));
+      symbols.fillRange(start, end, s);
+      numBits.fillRange(start, end, bits);
+      rankIndex[bits] = end;
+    }
+    return _HuffmanTable._(maxBits, symbols, numBits);
+  }
+
+  void decodeStream(Uint8List input, int start, int end, Uint8List output,
+      int outputStart, int count) {
+    final bits = _BackwardBits(input, start, end);
+    final mask = (1 << maxBits) - 1;
+    var state = bits.read(maxBits);
+    for (var i = outputStart; i < outputStart + count; i++) {
+      output[i] = symbols[state];
+      final n = numBits[state];
+      state = ((state << n) | bits.read(n)) & mask;
+    }
+  }
+}
+
+/// Reads [count] (at most 32) bits, starting at bit [bit] of [data], least significant first.
+int _bitsAt(Uint8List data, int bit, int count) {
+  if (count == 0) return 0;
+  final pos = bit >> 3;
+  final shift = bit & 7;
+  var value = 0;
+  for (var i = 0; i < (shift + count + 7) >> 3; i++) {
+    value |= data[pos + i] << (8 * i);
+  }
+  return (value >> shift) & ((1 << count) - 1);
+}
+
+class _ForwardBits {
+  final Uint8List data;
+  int bit;
+
+  _ForwardBits(this.data, int pos) : bit = pos * 8;
+
+  int read(int count) {
+    final value = _bitsAt(data, bit, count);
+    bit += count;
+    return value;
+  }
+
+  int get alignedPos => (bit + 7) >> 3;
+}
+
+/// A bitstream read from its end, which is marked by the highest set bit of the last byte.
+class _BackwardBits {
+  final Uint8List data;
+  final int start;
+
+  /// Number of bits left, relative to [start]. Reading past the beginning yields zeros.
+  int offset;
+
+  _BackwardBits(this.data, this.start, int end)
+      : offset = (end - start) * 8 - 9 + data[end - 1].bitLength {
+    if (data[end - 1] == 0) {
+      throw const FormatException('Invalid Zstandard bitstream');
+    }
+  }
+
+  bool get isOverflowed => offset < 0;
+
+  int read(int count) {
+    offset -= count;
+    if (offset >= 0) return _bitsAt(data, start * 8 + offset, count);
+    final available = count + offset;
+    if (available <= 0) return 0;
+    return _bitsAt(data, start * 8, available) << -offset;
+  }
+}
+
+class _Output {
+  var bytes = Uint8List(1024);
+  var length = 0;
+
+  void _reserve(int extra) {
+    if (length + extra <= bytes.length) return;
+    var capacity = bytes.length * 2;
+    while (capacity < length + extra) {
+      capacity *= 2;
+    }
+    bytes = Uint8List(capacity)..setRange(0, length, bytes);
+  }
+
+  void addRange(Uint8List source, int start, int end) {
+    _reserve(end - start);
+    bytes.setRange(length, length + end - start, source, start);
+    length += end - start;
+  }
+
+  void addRepeated(int byte, int count) {
+    _reserve(count);
+    bytes.fillRange(length, length + count, byte);
+    length += count;
+  }
+
+  void copyMatch(int offset, int count) {
+    _reserve(count);
+    var source = length - offset;
+    for (var i = 0; i < count; i++) {
+      bytes[length++] = bytes[source++];
+    }
+  }
+}
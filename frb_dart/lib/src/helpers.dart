@@ -43,6 +43,23 @@ mixin FlutterRustBridgeSetupMixin<T extends FlutterRustBridgeWireBase>
     yield* super.executeStream(task);
   }
 
+  @override
+  Future<Stream<S>> executeStreamAfterSetup<S>(
+      FlutterRustBridgeTask<S> task) async {
+    await _beforeExecute(task);
+    return await super.executeStreamAfterSetup(task);
+  }
+
+  @override
+  RustStreamHandle<S> executeStreamWithHandle<S>(
+      FlutterRustBridgeTask<S> task) {
+    final handle = super.executeStreamWithHandle(task);
+    return handle.withStream(() async* {
+      await _beforeExecute(task);
+      yield* handle.stream;
+    }());
+  }
+
   Future<void> _beforeExecute<S>(FlutterRustBridgeTask<S> task) async {
     if (!_setupCompleter.isCompleted &&
         task.hint is! _FlutterRustBridgeSetupMixinSkipWaitHint) {
@@ -0,0 +1,256 @@
+import 'dart:convert';
+import 'dart:typed_data';
+
+// NOTE for maintainer: Please manually keep in sync with `support::msgpack_decode` and
+// `support::msgpack_encode` in Rust, and with `generator::dart::msgpack` in the codegen
+
+/// Encodes the arguments of a function generated with `--codec msgpack` in the
+/// [MessagePack format](https://github.com/msgpack/msgpack/blob/master/spec.md).
+/// Lists become arrays and maps become maps, integers take the smallest encoding and doubles
+/// are always written as float 64.
+/// Not to be used by normal users, but has to be public for generated code
+Uint8List msgpackEncode(Object? value) => (_MsgPackWriter()..write(value)).takeBytes();
+
+/// Decodes the output of a function generated with `--codec msgpack`.
+/// Arrays become lists, maps become maps keeping the order of their entries, and binaries
+/// become [Uint8List]s. Extension types are not supported.
+/// Not to be used by normal users, but has to be public for generated code
+Object? msgpackDecode(Uint8List bytes) {
+  final reader = _MsgPackReader(bytes);
+  final value = reader.read();
+  if (reader.offset != bytes.length) {
+    throw FormatException('Trailing bytes after MessagePack value', bytes, reader.offset);
+  }
+  return value;
+}
+
+class _MsgPackWriter {
+  var _buffer = Uint8List(256);
+  late var _data = ByteData.sublistView(_buffer);
+  var _length = 0;
+
+  Uint8List takeBytes() => Uint8List.sublistView(_buffer, 0, _length);
+
+  void write(Object? value) {
+    if (value == null) {
+      _writeUint8(0xc0);
+    } else if (value is bool) {
+      _writeUint8(value ? 0xc3 : 0xc2);
+    } else if (value is int) {
+      _writeInt(value);
+    } else if (value is double) {
+      _writeUint8(0xcb);
+      _reserve(8);
+      _data.setFloat64(_length, value);
+      _length += 8;
+    } else if (value is String) {
+      _writeString(value);
+    } else if (value is List) {
+      _writeHeader(value.length, 0x90, 0x0f, 0xdc, 0xdd);
+      for (final item in value) {
+        write(item);
+      }
+    } else if (value is Map) {
+      _writeHeader(value.length, 0x80, 0x0f, 0xde, 0xdf);
+      value.forEach((key, item) {
+        write(key);
+        write(item);
+      });
+    } else {
+      throw ArgumentError.value(value, 'value', 'Cannot be encoded in MessagePack');
+    }
+  }
+
+  void _writeInt(int value) {
+    if (value >= 0) {
+      if (value <= 0x7f) {
+        _writeUint8(value);
+      } else if (value <= 0xff) {
+        _writeUint8(0xcc);
+        _writeUint8(value);
+      } else if (value <= 0xffff) {
+        _writeUint8(0xcd);
+        _writeUint16(value);
+      } else if (value <= 0xffffffff) {
+        _writeUint8(0xce);
+        _writeUint32(value);
+      } else {
+        _writeUint8(0xcf);
+        _reserve(8);
+        _data.setUint64(_length, value);
+        _length += 8;
+      }
+    } else if (value >= -32) {
+      _writeUint8(value & 0xff);
+    } else if (value >= -0x80) {
+      _writeUint8(0xd0);
+      _writeUint8(value & 0xff);
+    } else if (value >= -0x8000) {
+      _writeUint8(0xd1);
+      _writeUint16(value & 0xffff);
+    } else if (value >= -0x80000000) {
+      _writeUint8(0xd2);
+      _writeUint32(value & 0xffffffff);
+    } else {
+      _writeUint8(0xd3);
+      _reserve(8);
+      _data.setInt64(_length, value);
+      _length += 8;
+    }
+  }
+
+  void _writeString(String value) {
+    final bytes = utf8.encode(value);
+    if (bytes.length <= 0x1f) {
+      _writeUint8(0xa0 | bytes.length);
+    } else if (bytes.length <= 0xff) {
+      _writeUint8(0xd9);
+      _writeUint8(bytes.length);
+    } else {
+      _writeHeader(bytes.length, 0, 0, 0xda, 0xdb);
+    }
+    _reserve(bytes.length);
+    _buffer.setRange(_length, _length + bytes.length, bytes);
+    _length += bytes.length;
+  }
+
+  /// The length of an array, a map or a long string: within [fixMask] in the marker itself, else
+  /// after the 16 or 32 bit marker.
+  void _writeHeader(int length, int fixMarker, int fixMask, int marker16, int marker32) {
+    if (length <= fixMask) {
+      _writeUint8(fixMarker | length);
+    } else if (length <= 0xffff) {
+      _writeUint8(marker16);
+      _writeUint16(length);
+    } else {
+      _writeUint8(marker32);
+      _writeUint32(length);
+    }
+  }
+
+  void _writeUint8(int value) {
+    _reserve(1);
+    _buffer[_length++] = value;
+  }
+
+  void _writeUint16(int value) {
+    _reserve(2);
+    _data.setUint16(_length, value);
+    _length += 2;
+  }
+
+  void _writeUint32(int value) {
+    _reserve(4);
+    _data.setUint32(_length, value);
+    _length += 4;
+  }
+
+  void _reserve(int extra) {
+    if (_length + extra <= _buffer.length) return;
+    var capacity = _buffer.length * 2;
+    while (capacity < _length + extra) {
+      capacity *= 2;
+    }
+    _buffer = Uint8List(capacity)..setRange(0, _length, _buffer);
+    _data = ByteData.sublistView(_buffer);
+  }
+}
+
+class _MsgPackReader {
+  final Uint8List _bytes;
+  final ByteData _data;
+  var offset = 0;
+
+  _MsgPackReader(this._bytes) : _data = ByteData.sublistView(_bytes);
+
+  Object? read() {
+    final marker = _readUint8();
+    if (marker <= 0x7f) return marker;
+    if (marker >= 0xe0) return marker - 0x100;
+    if (marker & 0xf0 == 0x80) return _readMap(marker & 0x0f);
+    if (marker & 0xf0 == 0x90) return _readArray(marker & 0x0f);
+    if (marker & 0xe0 == 0xa0) return _readString(marker & 0x1f);
+    switch (marker) {
+      case 0xc0:
+        return null;
+      case 0xc2:
+        return false;
+      case 0xc3:
+        return true;
+      case 0xc4:
+        return _readBinary(_readUint8());
+      case 0xc5:
+        return _readBinary(_readUint16());
+      case 0xc6:
+        return _readBinary(_readUint32());
+      case 0xca:
+        return _advance(4, _data.getFloat32(offset));
+      case 0xcb:
+        return _advance(8, _data.getFloat64(offset));
+      case 0xcc:
+        return _readUint8();
+      case 0xcd:
+        return _readUint16();
+      case 0xce:
+        return _readUint32();
+      case 0xcf:
+        return _advance(8, _data.getUint64(offset));
+      case 0xd0:
+        return _advance(1, _data.getInt8(offset));
+      case 0xd1:
+        return _advance(2, _data.getInt16(offset));
+      case 0xd2:
+        return _advance(4, _data.getInt32(offset));
+      case 0xd3:
+        return _advance(8, _data.getInt64(offset));
+      case 0xd9:
+        return _readString(_readUint8());
+      case 0xda:
+        return _readString(_readUint16());
+      case 0xdb:
+        return _readString(_readUint32());
+      case 0xdc:
+        return _readArray(_readUint16());
+      case 0xdd:
+        return _readArray(_readUint32());
+      case 0xde:
+        return _readMap(_readUint16());
+      case 0xdf:
+        return _readMap(_readUint32());
+      default:
+        throw FormatException(
+            'Unsupported MessagePack marker 0x${marker.toRadixString(16)}', _bytes, offset - 1);
+    }
+  }
+
+  List<dynamic> _readArray(int length) => List<dynamic>.generate(length, (_) => read());
+
+  Map<dynamic, dynamic> _readMap(int length) {
+    final map = <dynamic, dynamic>{};
+    for (var i = 0; i < length; i++) {
+      final key = read();
+      map[key] = read();
+    }
+    return map;
+  }
+
+  String _readString(int length) => utf8.decode(_readBinary(length));
+
+  Uint8List _readBinary(int length) {
+    final view = Uint8List.sublistView(_bytes, offset, offset + length);
+    offset += length;
+    return view;
+  }
+
+  int _readUint8() => _advance(1, _data.getUint8(offset));
+
+  int _readUint16() => _advance(2, _data.getUint16(offset));
+
+  int _readUint32() => _advance(4, _data.getUint32(offset));
+
+  /// Moves past the [length] bytes [value] has been read from.
+  T _advance<T>(int length, T value) {
+    offset += length;
+    return value;
+  }
+}
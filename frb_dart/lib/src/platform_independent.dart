@@ -1,3 +1,5 @@
+import 'dart:collection';
+
 import 'package:meta/meta.dart';
 
 /// Base class for various kinds of tasks.
@@ -73,10 +75,23 @@ class FfiException {
   /// The error details of the exception. May not exist.
   final Object? details;
 
-  const FfiException(this.code, this.message, this.details);
+  /// The id of the Rust call which failed, also available to its Rust code as `current_call_id()`,
+  /// so that the error can be matched with the logs of that call. Null for synchronous calls.
+  final int? callId;
+
+  const FfiException(this.code, this.message, this.details, [this.callId]);
+
+  /// The Rust type of the error (e.g. `std::num::ParseIntError`), when it was returned
+  /// as a `Box<dyn Error>` whose type is registered in Rust. Null otherwise.
+  String? get errorType {
+    final details = this.details;
+    return code == 'RESULT_ERROR' && details is String ? details : null;
+  }
 
   @override
-  String toString() => 'FfiException($code, $message, $details)';
+  String toString() => callId == null
+      ? 'FfiException($code, $message, $details)'
+      : 'FfiException($code, $message, $details, call $callId)';
 }
 
 /// Exception when timeout happens using [FlutterRustBridgeTimeoutMixin]
@@ -108,3 +123,335 @@ bool _listEquals<T>(List<T>? a, List<T>? b) {
   }
   return true;
 }
+
+/// A snapshot of the load of the Rust executor, see `getBridgeHealth` of `--bridge-health`.
+@immutable
+class BridgeHealth {
+  /// Number of tasks waiting for a worker.
+  final int queuedCount;
+
+  /// Number of tasks being executed.
+  final int activeCount;
+
+  /// How long the oldest waiting task has been queued, zero if none is waiting.
+  final Duration oldestPendingAge;
+
+  const BridgeHealth({
+    required this.queuedCount,
+    required this.activeCount,
+    required this.oldestPendingAge,
+  });
+
+  /// Not to be used by normal users, but has to be public for generated code
+  factory BridgeHealth.fromWire(dynamic raw) {
+    final list = raw as List<int>;
+    return BridgeHealth(
+      queuedCount: list[0],
+      activeCount: list[1],
+      oldestPendingAge: Duration(milliseconds: list[2]),
+    );
+  }
+
+  @override
+  String toString() =>
+      'BridgeHealth(queuedCount: $queuedCount, activeCount: $activeCount, oldestPendingAge: $oldestPendingAge)';
+}
+
+/// The maximum level of the logs of the Rust `log` crate, see `setLogLevel`. The index of each
+/// value is sent to Rust, so the order must not change.
+enum LogLevel {
+  /// No logs at all.
+  off,
+  error,
+  warn,
+  info,
+  debug,
+  trace,
+}
+
+/// A panic of a Rust function called from Dart, see `rustCrashes`.
+@immutable
+class RustCrash {
+  /// Name of the Rust function.
+  final String function;
+
+  /// The message of the panic.
+  final String message;
+
+  /// Name of the thread which panicked, if it has one.
+  final String? thread;
+
+  /// The file, line and column of the panic, if known.
+  final String? location;
+
+  /// The backtrace of the panic, if the crash stream was listened to when it happened.
+  final String? backtrace;
+
+  const RustCrash({
+    required this.function,
+    required this.message,
+    this.thread,
+    this.location,
+    this.backtrace,
+  });
+
+  /// Not to be used by normal users, but has to be public for generated code
+  factory RustCrash.fromWire(dynamic raw) {
+    final list = raw as List<dynamic>;
+    return RustCrash(
+      function: list[0] as String,
+      message: list[1] as String,
+      thread: list[2] as String?,
+      location: list[3] as String?,
+      backtrace: list[4] as String?,
+    );
+  }
+
+  @override
+  String toString() =>
+      'RustCrash(function: $function, message: $message, thread: $thread, location: $location)';
+}
+
+/// Base of the Dart objects holding a handle to a Rust object, which is released by [dispose].
+///
+/// Caches sharing such objects can check [isDisposed] before using one, or get notified with
+/// [addDisposeListener] when it is released by whoever owns it.
+abstract class RustOpaqueHandle {
+  bool _disposed = false;
+  final _disposeListeners = <void Function()>[];
+
+  /// Whether the Rust object has been released, after which the handle must not be used.
+  bool get isDisposed => _disposed;
+
+  /// Calls [listener] once the Rust object is released, right away if it already is.
+  void addDisposeListener(void Function() listener) {
+    if (_disposed) {
+      listener();
+    } else {
+      _disposeListeners.add(listener);
+    }
+  }
+
+  /// Removes a listener added by [addDisposeListener], if it has not been called yet.
+  void removeDisposeListener(void Function() listener) =>
+      _disposeListeners.remove(listener);
+
+  /// Throws a [StateError] if the Rust object has been released.
+  @protected
+  void checkNotDisposed() {
+    if (_disposed) throw StateError('$runtimeType has been disposed');
+  }
+
+  /// Releases the Rust object. Called once, by the first call to [dispose].
+  @protected
+  void release();
+
+  /// Releases the Rust object, then calls the listeners. Further calls do nothing.
+  void dispose() {
+    if (_disposed) return;
+    _disposed = true;
+    release();
+    final listeners = List.of(_disposeListeners);
+    _disposeListeners.clear();
+    for (final listener in listeners) {
+      listener();
+    }
+  }
+}
+
+/// Whether the fields [a] and [b] of two generated objects are equal, comparing lists and maps
+/// item by item, like the classes generated by freezed.
+///
+/// Not to be used by normal users, but has to be public for generated code
+bool deepFieldsEqual(List<Object?> a, List<Object?> b) => _deepEquals(a, b);
+
+/// A hash code of the fields of a generated object, consistent with [deepFieldsEqual].
+///
+/// Not to be used by normal users, but has to be public for generated code
+int deepFieldsHash(List<Object?> fields) => _deepHash(fields);
+
+bool _deepEquals(Object? a, Object? b) {
+  if (identical(a, b)) return true;
+  if (a is List && b is List) {
+    if (a.length != b.length) return false;
+    for (var i = 0; i < a.length; i++) {
+      if (!_deepEquals(a[i], b[i])) return false;
+    }
+    return true;
+  }
+  if (a is Map && b is Map) {
+    if (a.length != b.length) return false;
+    for (final key in a.keys) {
+      if (!b.containsKey(key) || !_deepEquals(a[key], b[key])) return false;
+    }
+    return true;
+  }
+  return a == b;
+}
+
+int _deepHash(Object? value) {
+  if (value is List) return Object.hashAll(value.map(_deepHash));
+  if (value is Map) {
+    return Object.hashAllUnordered(value.entries
+        .map((e) => Object.hash(_deepHash(e.key), _deepHash(e.value))));
+  }
+  return value.hashCode;
+}
+
+/// A Rust `Result` found inside another type, e.g. in `Vec<Result<T, E>>`, holding either the
+/// [value] of `Ok` or the [error] of `Err`.
+///
+/// A `Result` returned by a function is not one of them: its error is thrown instead.
+@immutable
+class RustResult<T, E> {
+  final bool _isOk;
+  final T? _value;
+  final E? _error;
+
+  const RustResult.ok(T value)
+      : _isOk = true,
+        _value = value,
+        _error = null;
+
+  const RustResult.err(E error)
+      : _isOk = false,
+        _value = null,
+        _error = error;
+
+  /// Whether this is `Ok`.
+  bool get isOk => _isOk;
+
+  /// Whether this is `Err`.
+  bool get isErr => !_isOk;
+
+  /// The value of `Ok`. Throws a [StateError] if this is `Err`.
+  T get value {
+    if (!_isOk) throw StateError('RustResult.value called on Err($_error)');
+    return _value as T;
+  }
+
+  /// The error of `Err`. Throws a [StateError] if this is `Ok`.
+  E get error {
+    if (_isOk) throw StateError('RustResult.error called on Ok($_value)');
+    return _error as E;
+  }
+
+  /// Calls [ok] with the value or [err] with the error, whichever is held.
+  R when<R>({
+    required R Function(T value) ok,
+    required R Function(E error) err,
+  }) =>
+      _isOk ? ok(_value as T) : err(_error as E);
+
+  @override
+  bool operator ==(Object other) =>
+      other is RustResult &&
+      other._isOk == _isOk &&
+      other._value == _value &&
+      other._error == _error;
+
+  @override
+  int get hashCode => Object.hash(_isOk, _value, _error);
+
+  @override
+  String toString() => _isOk ? 'Ok($_value)' : 'Err($_error)';
+}
+
+/// A Rust `chrono::DateTime<FixedOffset>`: a point in time along with the offset from UTC it was
+/// recorded at, which a plain [DateTime] would lose.
+@immutable
+class ZonedDateTime {
+  /// The point in time, in UTC.
+  final DateTime utc;
+
+  /// The offset from UTC of the local time, e.g. two hours for `+02:00`.
+  final Duration offset;
+
+  ZonedDateTime(DateTime dateTime, this.offset) : utc = dateTime.toUtc();
+
+  ZonedDateTime.fromMicrosecondsSinceEpoch(int microseconds, this.offset)
+      : utc = DateTime.fromMicrosecondsSinceEpoch(microseconds, isUtc: true);
+
+  /// The local date and time at [offset]. Dart has no time zone of a fixed offset, so it is
+  /// returned as a UTC [DateTime] whose fields are the local ones.
+  DateTime get local => utc.add(offset);
+
+  @override
+  bool operator ==(Object other) =>
+      other is ZonedDateTime && other.utc == utc && other.offset == offset;
+
+  @override
+  int get hashCode => Object.hash(utc, offset);
+
+  /// In ISO 8601 with the offset, e.g. `2022-10-01T12:00:00.000+02:00`.
+  @override
+  String toString() {
+    final minutes = offset.inMinutes.abs();
+    final sign = offset.isNegative ? '-' : '+';
+    final hh = (minutes ~/ 60).toString().padLeft(2, '0');
+    final mm = (minutes % 60).toString().padLeft(2, '0');
+    final time = local.toIso8601String();
+    return '${time.substring(0, time.length - 1)}$sign$hh:$mm';
+  }
+}
+
+/// Hands out one instance per distinct short string, so that the many copies decoded from a large
+/// list, e.g. the same category name on thousands of rows, can be collected right away instead of
+/// being kept alive by the rows, see `--intern-strings`.
+///
+/// Not to be used by normal users, but has to be public for generated code
+class StringInterner {
+  /// Longer strings are returned as is, since they are rarely repeated.
+  final int maxLength;
+
+  /// The table is cleared when it reaches this many strings, to bound its memory.
+  final int capacity;
+
+  final _strings = <String, String>{};
+
+  StringInterner({required this.maxLength, this.capacity = 4096});
+
+  String intern(String value) {
+    if (value.length > maxLength) return value;
+    final interned = _strings[value];
+    if (interned != null) return interned;
+    if (_strings.length >= capacity) _strings.clear();
+    return _strings[value] = value;
+  }
+}
+
+/// A list returned by a Rust function marked `#[frb(lazy_decode)]`, which decodes each item the
+/// first time it is accessed, instead of all of them when the list is received.
+class LazyDecodedList<T> extends ListBase<T> {
+  static const _notDecoded = Object();
+
+  final List<dynamic> _raw;
+  final T Function(dynamic raw) _decode;
+  final List<Object?> _items;
+
+  /// Not to be used by normal users, but has to be public for generated code
+  LazyDecodedList(this._raw, this._decode)
+      : _items = List.filled(_raw.length, _notDecoded);
+
+  @override
+  int get length => _raw.length;
+
+  @override
+  set length(int newLength) =>
+      throw UnsupportedError('Cannot change the length of a LazyDecodedList');
+
+  @override
+  T operator [](int index) {
+    final item = _items[index];
+    if (!identical(item, _notDecoded)) return item as T;
+    final decoded = _decode(_raw[index]);
+    _items[index] = decoded;
+    // the raw item is no longer needed
+    _raw[index] = null;
+    return decoded;
+  }
+
+  @override
+  void operator []=(int index, T value) =>
+      throw UnsupportedError('Cannot modify a LazyDecodedList');
+}
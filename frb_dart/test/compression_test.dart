@@ -0,0 +1,88 @@
+import 'dart:io';
+import 'dart:typed_data';
+
+import 'package:flutter_rust_bridge/flutter_rust_bridge.dart';
+import 'package:test/test.dart';
+
+/// A vector written by `zstd::bulk::compress`, see `zstd_test_vectors` in
+/// `frb_rust/src/support.rs`.
+Uint8List vector(String name) => File('test/zstd/$name').readAsBytesSync();
+
+int _xorshift(int state) {
+  state ^= (state << 13) & 0xFFFFFFFF;
+  state ^= state >> 17;
+  state ^= (state << 5) & 0xFFFFFFFF;
+  return state;
+}
+
+/// The same as `sample_text` in Rust.
+Uint8List sampleText(int length) {
+  const words = [
+    'flutter',
+    'rust',
+    'bridge',
+    'dart',
+    'frame',
+    'block',
+    'literal',
+    'sequence',
+  ];
+  var state = 0x2545f491;
+  var word = 0;
+  final text = BytesBuilder();
+  while (text.length < length) {
+    state = _xorshift(state);
+    if (state & 3 == 0) word = (state >> 8) % words.length;
+    text.add('${words[word]} '.codeUnits);
+  }
+  return Uint8List.sublistView(text.takeBytes(), 0, length);
+}
+
+/// The same as `sample_noise` in Rust.
+Uint8List sampleNoise(int length) {
+  var state = 0x2545f491;
+  final noise = Uint8List(length);
+  for (var i = 0; i < length; i++) {
+    state = _xorshift(state);
+    noise[i] = state >> 24;
+  }
+  return noise;
+}
+
+void main() {
+  group('zstdDecompress', () {
+    test('copies raw blocks', () {
+      expect(zstdDecompress(vector('raw.zst')), sampleNoise(3000));
+    });
+
+    test('repeats the byte of RLE blocks', () {
+      expect(zstdDecompress(vector('rle.zst')),
+          List.filled(300000, 'a'.codeUnitAt(0)));
+    });
+
+    test('decodes compressed blocks repeating the tables of the previous one',
+        () {
+      expect(zstdDecompress(vector('blocks.zst')), sampleText(200000));
+    });
+
+    test('joins the frames and steps over the skippable ones', () {
+      expect(zstdDecompress(vector('frames.zst')),
+          [...sampleText(2000), ...sampleNoise(100)]);
+    });
+
+    test('throws on data of another format', () {
+      expect(() => zstdDecompress(Uint8List.fromList([1, 2, 3, 4])),
+          throwsFormatException);
+    });
+  });
+
+  group('decompressPayload', () {
+    test('reads the marker written by compress_zstd', () {
+      expect(decompressPayload(Uint8List.fromList([0, 1, 2])), [1, 2]);
+      expect(decompressPayload(Uint8List.fromList([1, ...vector('raw.zst')])),
+          sampleNoise(3000));
+      expect(() => decompressPayload(Uint8List.fromList([2, 1])),
+          throwsException);
+    });
+  });
+}
@@ -0,0 +1,73 @@
+import 'dart:typed_data';
+
+import 'package:flutter_rust_bridge/flutter_rust_bridge.dart';
+import 'package:test/test.dart';
+
+Object? decode(List<int> bytes) => msgpackDecode(Uint8List.fromList(bytes));
+
+void main() {
+  group('msgpackEncode', () {
+    test('writes integers in their smallest encoding', () {
+      expect(msgpackEncode(1), [0x01]);
+      expect(msgpackEncode(-1), [0xff]);
+      expect(msgpackEncode(200), [0xcc, 0xc8]);
+      expect(msgpackEncode(0x10000), [0xce, 0x00, 0x01, 0x00, 0x00]);
+      expect(msgpackEncode(-100), [0xd0, 0x9c]);
+      expect(msgpackEncode(-200), [0xd1, 0xff, 0x38]);
+    });
+
+    test('writes doubles as float 64', () {
+      expect(msgpackEncode(1.0), [0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0]);
+    });
+
+    test('writes strings with their length in utf-8', () {
+      expect(msgpackEncode('é'), [0xa2, 0xc3, 0xa9]);
+      final long = 'a' * 40;
+      expect(msgpackEncode(long), [0xd9, 40, ...long.codeUnits]);
+    });
+
+    test('writes lists as arrays and maps as maps', () {
+      expect(msgpackEncode([1, null, true]), [0x93, 0x01, 0xc0, 0xc3]);
+      expect(msgpackEncode({'a': false}), [0x81, 0xa1, 0x61, 0xc2]);
+      expect(msgpackEncode(List.filled(1000, 0)).sublist(0, 3), [0xdc, 0x03, 0xe8]);
+    });
+
+    test('throws on values without a MessagePack representation', () {
+      expect(() => msgpackEncode(Object()), throwsArgumentError);
+    });
+  });
+
+  group('msgpackDecode', () {
+    test('reads back what msgpackEncode writes', () {
+      final value = [
+        1,
+        -200,
+        0x100000000,
+        1.5,
+        'hello',
+        null,
+        [true, false],
+        {'key': 'value'},
+      ];
+      expect(msgpackDecode(msgpackEncode(value)), value);
+    });
+
+    test('reads floats, binaries and signed integers written by Rust', () {
+      expect(decode([0xca, 0x3f, 0xc0, 0, 0]), 1.5);
+      expect(decode([0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]), -2);
+      final binary = decode([0xc4, 0x02, 0x01, 0x02]);
+      expect(binary, isA<Uint8List>());
+      expect(binary, [1, 2]);
+    });
+
+    test('keeps the order of map entries', () {
+      final map = decode([0x82, 0xa1, 0x62, 0x01, 0xa1, 0x61, 0x02]) as Map;
+      expect(map.keys, ['b', 'a']);
+    });
+
+    test('throws on unsupported markers and trailing bytes', () {
+      expect(() => decode([0xc1]), throwsFormatException);
+      expect(() => decode([0x01, 0x02]), throwsFormatException);
+    });
+  });
+}
@@ -1,7 +1,7 @@
 // AUTO GENERATED FILE, DO NOT EDIT.
 // Generated by `flutter_rust_bridge`.
 
-// ignore_for_file: non_constant_identifier_names, unused_element, duplicate_ignore, directives_ordering, curly_braces_in_flow_control_structures, unnecessary_lambdas, slash_for_doc_comments, prefer_const_literals_to_create_immutables, implicit_dynamic_list_literal, duplicate_import, unused_import, prefer_single_quotes, prefer_const_constructors, use_super_parameters, always_use_package_imports
+// ignore_for_file: non_constant_identifier_names, unused_element, duplicate_ignore, directives_ordering, curly_braces_in_flow_control_structures, unnecessary_lambdas, slash_for_doc_comments, prefer_const_literals_to_create_immutables, implicit_dynamic_list_literal, duplicate_import, unused_import, prefer_single_quotes, prefer_const_constructors, use_super_parameters, always_use_package_imports, depend_on_referenced_packages
 
 import 'dart:convert';
 import 'dart:typed_data';
@@ -15,6 +15,7 @@ import 'dart:ffi' as ffi;
 
 part 'bridge_generated.freezed.dart';
 
+/// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
 abstract class FlutterRustBridgeExampleSingleBlockTest {
   /// Documentation on a simple adder function.
   Future<int> simpleAdder({required int a, required int b, dynamic hint});
@@ -80,12 +81,37 @@ abstract class FlutterRustBridgeExampleSingleBlockTest {
 
   FlutterRustBridgeTaskConstMeta get kHandleSyncReturnConstMeta;
 
+  /// Takes and returns primitives, so it is called without encoding them.
+  int divideSync({required int a, required int b, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kDivideSyncConstMeta;
+
+  int? parseIdSync({required String name, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kParseIdSyncConstMeta;
+
+  @meta.useResult
   Stream<String> handleStream({required String arg, dynamic hint});
 
+  /// Same as [handleStream], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<String> handleStreamWithHandle({required String arg, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamConstMeta;
 
+  /// Counts down from `from` to 0, or fails before streaming if `from` is negative.
+  @meta.useResult
+  Future<Stream<int>> countDown({required int from, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCountDownConstMeta;
+
+  @meta.useResult
   Stream<MyStreamEntry> handleStreamOfStruct({dynamic hint});
 
+  /// Same as [handleStreamOfStruct], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<MyStreamEntry> handleStreamOfStructWithHandle({dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamOfStructConstMeta;
 
   Future<int> returnErr({dynamic hint});
@@ -96,6 +122,36 @@ abstract class FlutterRustBridgeExampleSingleBlockTest {
 
   FlutterRustBridgeTaskConstMeta get kReturnPanicConstMeta;
 
+  /// Fails with the id of its call in the message, which Dart also receives along with the error.
+  Future<int> returnErrWithCallId({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kReturnErrWithCallIdConstMeta;
+
+  /// Counts for up to 10 seconds, and returns how far it got once cancelled from Dart.
+  Future<int> countUntilCancelled({dynamic hint});
+
+  /// Same as [countUntilCancelled], but also returns a handle to cancel the Rust task.
+  @meta.useResult
+  RustTaskHandle<int> countUntilCancelledWithHandle({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCountUntilCancelledConstMeta;
+
+  /// Fails with `error` for the first `failures` attempts of each `key`, then returns the number of
+  /// attempts. Dart retries the errors starting with "Timeout".
+  Future<int> flakyFetch({required String key, required int failures, required String error, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kFlakyFetchConstMeta;
+
+  /// Called by Dart once the query has not changed for 50 ms, e.g. while the user types it.
+  Future<List<String>> searchWords({required String query, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kSearchWordsConstMeta;
+
+  /// How many times `search_words` has reached Rust.
+  Future<int> countSearches({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCountSearchesConstMeta;
+
   Future<double?> handleOptionalReturn({required double left, required double right, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kHandleOptionalReturnConstMeta;
@@ -180,8 +236,13 @@ abstract class FlutterRustBridgeExampleSingleBlockTest {
 
   FlutterRustBridgeTaskConstMeta get kNextUserIdConstMeta;
 
+  @meta.useResult
   Stream<Event> registerEventListener({dynamic hint});
 
+  /// Same as [registerEventListener], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Event> registerEventListenerWithHandle({dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kRegisterEventListenerConstMeta;
 
   Future<void> closeEventListener({dynamic hint});
@@ -192,16 +253,74 @@ abstract class FlutterRustBridgeExampleSingleBlockTest {
 
   FlutterRustBridgeTaskConstMeta get kCreateEventConstMeta;
 
+  /// Ticks every 10 ms from another thread, until nobody listens anymore.
+  @meta.useResult
+  Stream<int> tick({dynamic hint});
+
+  /// Same as [tick], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<int> tickWithHandle({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kTickConstMeta;
+
+  /// How many streams of `tick` are still producing.
+  Future<int> countRunningTickers({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCountRunningTickersConstMeta;
+
+  /// A level which Rust cannot push to Dart, e.g. because it reads it from a C library on demand.
+  int get polledLevel;
+
+  /// Calls [polledLevel] every [interval], and emits its value whenever it changes.
+  @meta.useResult
+  Stream<int> watchPolledLevel({Duration interval = const Duration(seconds: 1)});
+
+  FlutterRustBridgeTaskConstMeta get kPolledLevelConstMeta;
+
+  Future<void> setPolledLevel({required int level, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kSetPolledLevelConstMeta;
+
+  /// Ticks every 10 ms from another thread, until the Dart side is restarted.
+  @meta.useResult
+  Stream<int> tickUntilReinit({dynamic hint});
+
+  /// Same as [tickUntilReinit], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<int> tickUntilReinitWithHandle({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kTickUntilReinitConstMeta;
+
+  /// How many times the Dart side has been restarted since `tick_until_reinit` was first called.
+  Future<int> countReinits({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCountReinitsConstMeta;
+
+  @meta.useResult
   Stream<Log> handleStreamSinkAt1({required int key, required int max, dynamic hint});
 
+  /// Same as [handleStreamSinkAt1], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Log> handleStreamSinkAt1WithHandle({required int key, required int max, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt1ConstMeta;
 
+  @meta.useResult
   Stream<Log> handleStreamSinkAt2({required int key, required int max, dynamic hint});
 
+  /// Same as [handleStreamSinkAt2], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Log> handleStreamSinkAt2WithHandle({required int key, required int max, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt2ConstMeta;
 
+  @meta.useResult
   Stream<Log> handleStreamSinkAt3({required int key, required int max, dynamic hint});
 
+  /// Same as [handleStreamSinkAt3], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Log> handleStreamSinkAt3WithHandle({required int key, required int max, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt3ConstMeta;
 
   Future<SumWith> getSumStruct({dynamic hint});
@@ -224,23 +343,50 @@ abstract class FlutterRustBridgeExampleSingleBlockTest {
 
   FlutterRustBridgeTaskConstMeta get kConcatenateStaticStaticMethodConcatenateWithConstMeta;
 
+  @meta.useResult
   Stream<Log2> handleSomeStreamSinkMethodConcatenateWith(
       {required ConcatenateWith that, required int key, required int max, dynamic hint});
 
+  /// Same as [handleSomeStreamSinkMethodConcatenateWith], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Log2> handleSomeStreamSinkMethodConcatenateWithWithHandle(
+      {required ConcatenateWith that, required int key, required int max, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStreamSinkMethodConcatenateWithConstMeta;
 
+  @meta.useResult
   Stream<int> handleSomeStreamSinkAt1MethodConcatenateWith({required ConcatenateWith that, dynamic hint});
 
+  /// Same as [handleSomeStreamSinkAt1MethodConcatenateWith], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<int> handleSomeStreamSinkAt1MethodConcatenateWithWithHandle(
+      {required ConcatenateWith that, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStreamSinkAt1MethodConcatenateWithConstMeta;
 
+  @meta.useResult
   Stream<Log2> handleSomeStaticStreamSinkStaticMethodConcatenateWith(
       {required int key, required int max, dynamic hint});
 
+  /// Same as [handleSomeStaticStreamSinkStaticMethodConcatenateWith], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<Log2> handleSomeStaticStreamSinkStaticMethodConcatenateWithWithHandle(
+      {required int key, required int max, dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStaticStreamSinkStaticMethodConcatenateWithConstMeta;
 
+  @meta.useResult
   Stream<int> handleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWith({dynamic hint});
 
+  /// Same as [handleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWith], but also returns a handle to pause, resume or throttle the Rust producer.
+  @meta.useResult
+  RustStreamHandle<int> handleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWithWithHandle({dynamic hint});
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWithConstMeta;
+
+  /// Reports the load of the Rust executor, e.g. to detect when its workers are saturated.
+  /// The request does not go through the executor, so it completes even when all workers are busy.
+  Future<BridgeHealth> getBridgeHealth();
 }
 
 class ApplicationEnv {
@@ -816,18 +962,40 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         argNames: ["s"],
       );
 
-  Uint8List handleSyncReturn({required String mode, dynamic hint}) => executeSync(FlutterRustBridgeSyncTask(
+  Uint8List handleSyncReturn({required String mode, dynamic hint}) =>
+      _wire2api_SyncReturnVecU8(executeSync(FlutterRustBridgeSyncTask(
         callFfi: () => inner.wire_handle_sync_return(_api2wire_String(mode)),
         constMeta: kHandleSyncReturnConstMeta,
         argValues: [mode],
         hint: hint,
-      ));
+      )));
 
   FlutterRustBridgeTaskConstMeta get kHandleSyncReturnConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_sync_return",
         argNames: ["mode"],
       );
 
+  int divideSync({required int a, required int b, dynamic hint}) =>
+      executePrimitiveSync(inner.wire_divide_sync(_api2wire_i32(a), _api2wire_i32(b), primitiveSyncOutcome));
+
+  FlutterRustBridgeTaskConstMeta get kDivideSyncConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "divide_sync",
+        argNames: ["a", "b"],
+      );
+
+  int? parseIdSync({required String name, dynamic hint}) =>
+      _wire2api_SyncReturn_opt_i32(executeSync(FlutterRustBridgeSyncTask(
+        callFfi: () => inner.wire_parse_id_sync(_api2wire_String(name)),
+        constMeta: kParseIdSyncConstMeta,
+        argValues: [name],
+        hint: hint,
+      )));
+
+  FlutterRustBridgeTaskConstMeta get kParseIdSyncConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "parse_id_sync",
+        argNames: ["name"],
+      );
+
   Stream<String> handleStream({required String arg, dynamic hint}) => executeStream(FlutterRustBridgeTask(
         callFfi: (port_) => inner.wire_handle_stream(port_, _api2wire_String(arg)),
         parseSuccessData: _wire2api_String,
@@ -836,11 +1004,33 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<String> handleStreamWithHandle({required String arg, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_stream(port_, _api2wire_String(arg)),
+        parseSuccessData: _wire2api_String,
+        constMeta: kHandleStreamConstMeta,
+        argValues: [arg],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_stream",
         argNames: ["arg"],
       );
 
+  Future<Stream<int>> countDown({required int from, dynamic hint}) => executeStreamAfterSetup(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_down(port_, _api2wire_i32(from)),
+        parseSuccessData: _wire2api_i32,
+        constMeta: kCountDownConstMeta,
+        argValues: [from],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kCountDownConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "count_down",
+        argNames: ["from"],
+      );
+
   Stream<MyStreamEntry> handleStreamOfStruct({dynamic hint}) => executeStream(FlutterRustBridgeTask(
         callFfi: (port_) => inner.wire_handle_stream_of_struct(port_),
         parseSuccessData: _wire2api_my_stream_entry,
@@ -849,6 +1039,15 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<MyStreamEntry> handleStreamOfStructWithHandle({dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_stream_of_struct(port_),
+        parseSuccessData: _wire2api_my_stream_entry,
+        constMeta: kHandleStreamOfStructConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamOfStructConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_stream_of_struct",
         argNames: [],
@@ -880,6 +1079,89 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         argNames: [],
       );
 
+  Future<int> returnErrWithCallId({dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_return_err_with_call_id(port_),
+        parseSuccessData: _wire2api_u64,
+        constMeta: kReturnErrWithCallIdConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kReturnErrWithCallIdConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "return_err_with_call_id",
+        argNames: [],
+      );
+
+  Future<int> countUntilCancelled({dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_until_cancelled(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kCountUntilCancelledConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  RustTaskHandle<int> countUntilCancelledWithHandle({dynamic hint}) => executeNormalWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_until_cancelled(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kCountUntilCancelledConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kCountUntilCancelledConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "count_until_cancelled",
+        argNames: [],
+      );
+
+  Future<int> flakyFetch({required String key, required int failures, required String error, dynamic hint}) =>
+      executeNormalWithRetry(
+          FlutterRustBridgeTask(
+            callFfi: (port_) => inner.wire_flaky_fetch(
+                port_, _api2wire_String(key), _api2wire_i32(failures), _api2wire_String(error)),
+            parseSuccessData: _wire2api_i32,
+            constMeta: kFlakyFetchConstMeta,
+            argValues: [key, failures, error],
+            hint: hint,
+          ),
+          times: 3,
+          backoff: const Duration(milliseconds: 10),
+          retryOn: const ["Timeout"]);
+
+  FlutterRustBridgeTaskConstMeta get kFlakyFetchConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "flaky_fetch",
+        argNames: ["key", "failures", "error"],
+      );
+
+  Future<List<String>> searchWords({required String query, dynamic hint}) =>
+      executeNormalDebounced(
+          FlutterRustBridgeTask(
+            callFfi: (port_) => inner.wire_search_words(port_, _api2wire_String(query)),
+            parseSuccessData: _wire2api_StringList,
+            constMeta: kSearchWordsConstMeta,
+            argValues: [query],
+            hint: hint,
+          ),
+          key: 'search_words',
+          delay: const Duration(milliseconds: 50));
+
+  FlutterRustBridgeTaskConstMeta get kSearchWordsConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "search_words",
+        argNames: ["query"],
+      );
+
+  Future<int> countSearches({dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_searches(port_),
+        parseSuccessData: _wire2api_i32,
+        constMeta: kCountSearchesConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kCountSearchesConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "count_searches",
+        argNames: [],
+      );
+
   Future<double?> handleOptionalReturn({required double left, required double right, dynamic hint}) =>
       executeNormal(FlutterRustBridgeTask(
         callFfi: (port_) => inner.wire_handle_optional_return(port_, _api2wire_f64(left), _api2wire_f64(right)),
@@ -1157,6 +1439,15 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Event> registerEventListenerWithHandle({dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_register_event_listener(port_),
+        parseSuccessData: _wire2api_event,
+        constMeta: kRegisterEventListenerConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kRegisterEventListenerConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "register_event_listener",
         argNames: [],
@@ -1188,6 +1479,97 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         argNames: [],
       );
 
+  Stream<int> tick({dynamic hint}) => executeStream(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_tick(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kTickConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  RustStreamHandle<int> tickWithHandle({dynamic hint}) => executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_tick(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kTickConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kTickConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "tick",
+        argNames: [],
+      );
+
+  Future<int> countRunningTickers({dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_running_tickers(port_),
+        parseSuccessData: _wire2api_i32,
+        constMeta: kCountRunningTickersConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kCountRunningTickersConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "count_running_tickers",
+        argNames: [],
+      );
+
+  int get polledLevel => executePrimitiveSync(inner.wire_polled_level(primitiveSyncOutcome));
+
+  Stream<int> watchPolledLevel({Duration interval = const Duration(seconds: 1)}) =>
+      executePoll(() => polledLevel, interval);
+
+  FlutterRustBridgeTaskConstMeta get kPolledLevelConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "polled_level",
+        argNames: [],
+      );
+
+  Future<void> setPolledLevel({required int level, dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_set_polled_level(port_, _api2wire_i32(level)),
+        parseSuccessData: _wire2api_unit,
+        constMeta: kSetPolledLevelConstMeta,
+        argValues: [level],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kSetPolledLevelConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "set_polled_level",
+        argNames: ["level"],
+      );
+
+  Stream<int> tickUntilReinit({dynamic hint}) => executeStream(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_tick_until_reinit(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kTickUntilReinitConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  RustStreamHandle<int> tickUntilReinitWithHandle({dynamic hint}) => executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_tick_until_reinit(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kTickUntilReinitConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kTickUntilReinitConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "tick_until_reinit",
+        argNames: [],
+      );
+
+  Future<int> countReinits({dynamic hint}) => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_count_reinits(port_),
+        parseSuccessData: _wire2api_i32,
+        constMeta: kCountReinitsConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
+  FlutterRustBridgeTaskConstMeta get kCountReinitsConstMeta => const FlutterRustBridgeTaskConstMeta(
+        debugName: "count_reinits",
+        argNames: [],
+      );
+
   Stream<Log> handleStreamSinkAt1({required int key, required int max, dynamic hint}) =>
       executeStream(FlutterRustBridgeTask(
         callFfi: (port_) => inner.wire_handle_stream_sink_at_1(port_, _api2wire_u32(key), _api2wire_u32(max)),
@@ -1197,6 +1579,15 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Log> handleStreamSinkAt1WithHandle({required int key, required int max, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_stream_sink_at_1(port_, _api2wire_u32(key), _api2wire_u32(max)),
+        parseSuccessData: _wire2api_log,
+        constMeta: kHandleStreamSinkAt1ConstMeta,
+        argValues: [key, max],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt1ConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_stream_sink_at_1",
         argNames: ["key", "max"],
@@ -1211,6 +1602,15 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Log> handleStreamSinkAt2WithHandle({required int key, required int max, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_stream_sink_at_2(port_, _api2wire_u32(key), _api2wire_u32(max)),
+        parseSuccessData: _wire2api_log,
+        constMeta: kHandleStreamSinkAt2ConstMeta,
+        argValues: [key, max],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt2ConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_stream_sink_at_2",
         argNames: ["key", "max"],
@@ -1225,6 +1625,15 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Log> handleStreamSinkAt3WithHandle({required int key, required int max, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_stream_sink_at_3(port_, _api2wire_u32(key), _api2wire_u32(max)),
+        parseSuccessData: _wire2api_log,
+        constMeta: kHandleStreamSinkAt3ConstMeta,
+        argValues: [key, max],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleStreamSinkAt3ConstMeta => const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_stream_sink_at_3",
         argNames: ["key", "max"],
@@ -1314,6 +1723,17 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Log2> handleSomeStreamSinkMethodConcatenateWithWithHandle(
+          {required ConcatenateWith that, required int key, required int max, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_some_stream_sink__method__ConcatenateWith(
+            port_, _api2wire_box_autoadd_concatenate_with(that), _api2wire_u32(key), _api2wire_u32(max)),
+        parseSuccessData: _wire2api_log_2,
+        constMeta: kHandleSomeStreamSinkMethodConcatenateWithConstMeta,
+        argValues: [that, key, max],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStreamSinkMethodConcatenateWithConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_some_stream_sink__method__ConcatenateWith",
@@ -1330,6 +1750,17 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<int> handleSomeStreamSinkAt1MethodConcatenateWithWithHandle(
+          {required ConcatenateWith that, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_some_stream_sink_at_1__method__ConcatenateWith(
+            port_, _api2wire_box_autoadd_concatenate_with(that)),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kHandleSomeStreamSinkAt1MethodConcatenateWithConstMeta,
+        argValues: [that],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStreamSinkAt1MethodConcatenateWithConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_some_stream_sink_at_1__method__ConcatenateWith",
@@ -1347,6 +1778,17 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<Log2> handleSomeStaticStreamSinkStaticMethodConcatenateWithWithHandle(
+          {required int key, required int max, dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_some_static_stream_sink__static_method__ConcatenateWith(
+            port_, _api2wire_u32(key), _api2wire_u32(max)),
+        parseSuccessData: _wire2api_log_2,
+        constMeta: kHandleSomeStaticStreamSinkStaticMethodConcatenateWithConstMeta,
+        argValues: [key, max],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStaticStreamSinkStaticMethodConcatenateWithConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_some_static_stream_sink__static_method__ConcatenateWith",
@@ -1362,23 +1804,54 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
         hint: hint,
       ));
 
+  RustStreamHandle<int> handleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWithWithHandle({dynamic hint}) =>
+      executeStreamWithHandle(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith(port_),
+        parseSuccessData: _wire2api_u32,
+        constMeta: kHandleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWithConstMeta,
+        argValues: [],
+        hint: hint,
+      ));
+
   FlutterRustBridgeTaskConstMeta get kHandleSomeStaticStreamSinkSingleArgStaticMethodConcatenateWithConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith",
         argNames: [],
       );
 
+  Future<BridgeHealth> getBridgeHealth() => executeNormal(FlutterRustBridgeTask(
+        callFfi: (port_) => inner.wire_get_bridge_health(port_),
+        parseSuccessData: BridgeHealth.fromWire,
+        constMeta: const FlutterRustBridgeTaskConstMeta(
+          debugName: 'get_bridge_health',
+          argNames: [],
+        ),
+        argValues: [],
+        hint: null,
+      ));
+
   // Section: api2wire
   ffi.Pointer<wire_uint_8_list> _api2wire_String(String raw) {
     return _api2wire_uint_8_list(utf8.encoder.convert(raw));
   }
 
   ffi.Pointer<wire_StringList> _api2wire_StringList(List<String> raw) {
-    final ans = inner.new_StringList_0(raw.length);
-    for (var i = 0; i < raw.length; i++) {
-      ans.ref.ptr[i] = _api2wire_String(raw[i]);
+    final encoded = raw.map(utf8.encoder.convert).toList();
+    final ends = inner.new_int_32_list_0(encoded.length);
+    final endsView = ends.ref.ptr.asTypedList(encoded.length);
+    var total = 0;
+    for (var i = 0; i < encoded.length; i++) {
+      total += encoded[i].length;
+      endsView[i] = total;
     }
-    return ans;
+    final bytes = inner.new_uint_8_list_0(total);
+    final bytesView = bytes.ref.ptr.asTypedList(total);
+    var start = 0;
+    for (final string in encoded) {
+      bytesView.setAll(start, string);
+      start += string.length;
+    }
+    return inner.new_StringList_0(bytes, ends);
   }
 
   ffi.Pointer<wire_uint_8_list> _api2wire_ZeroCopyBuffer_Uint8List(Uint8List raw) {
@@ -1624,7 +2097,7 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
   ffi.Pointer<wire_list_opt_box_autoadd_attribute> _api2wire_list_opt_box_autoadd_attribute(List<Attribute?> raw) {
     final ans = inner.new_list_opt_box_autoadd_attribute_0(raw.length);
     for (var i = 0; i < raw.length; ++i) {
-      _api_fill_to_wire_opt_box_autoadd_attribute(raw[i], ans.ref.ptr[i]);
+      ans.ref.ptr[i] = _api2wire_opt_box_autoadd_attribute(raw[i]);
     }
     return ans;
   }
@@ -1933,23 +2406,6 @@ class FlutterRustBridgeExampleSingleBlockTestImpl
     wireObj.body = _api2wire_String(apiObj.body);
   }
 
-  void _api_fill_to_wire_opt_box_autoadd_attribute(Attribute? apiObj, ffi.Pointer<wire_Attribute> wireObj) {
-    if (apiObj != null) _api_fill_to_wire_box_autoadd_attribute(apiObj, wireObj);
-  }
-
-  void _api_fill_to_wire_opt_box_autoadd_exotic_optionals(
-      ExoticOptionals? apiObj, ffi.Pointer<wire_ExoticOptionals> wireObj) {
-    if (apiObj != null) _api_fill_to_wire_box_autoadd_exotic_optionals(apiObj, wireObj);
-  }
-
-  void _api_fill_to_wire_opt_box_autoadd_new_type_int(NewTypeInt? apiObj, ffi.Pointer<wire_NewTypeInt> wireObj) {
-    if (apiObj != null) _api_fill_to_wire_box_autoadd_new_type_int(apiObj, wireObj);
-  }
-
-  void _api_fill_to_wire_opt_box_exotic_optionals(ExoticOptionals? apiObj, ffi.Pointer<wire_ExoticOptionals> wireObj) {
-    if (apiObj != null) _api_fill_to_wire_box_exotic_optionals(apiObj, wireObj);
-  }
-
   void _api_fill_to_wire_sum_with(SumWith apiObj, wire_SumWith wireObj) {
     wireObj.x = _api2wire_u32(apiObj.x);
   }
@@ -1972,6 +2428,15 @@ Uint8List _wire2api_SyncReturnVecU8(dynamic raw) {
   return raw as Uint8List;
 }
 
+int _wire2api_SyncReturn_i32(dynamic raw) {
+  return ByteData.sublistView(raw as Uint8List).getInt32(0, Endian.little);
+}
+
+int? _wire2api_SyncReturn_opt_i32(dynamic raw) {
+  if (raw == null) return null;
+  return ByteData.sublistView(raw as Uint8List).getInt32(0, Endian.little);
+}
+
 Float32List _wire2api_ZeroCopyBuffer_Float32List(dynamic raw) {
   return raw as Float32List;
 }
@@ -2749,6 +3214,37 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
   late final _wire_handle_sync_return =
       _wire_handle_sync_returnPtr.asFunction<WireSyncReturnStruct Function(ffi.Pointer<wire_uint_8_list>)>();
 
+  int wire_divide_sync(
+    int a,
+    int b,
+    ffi.Pointer<WireSyncReturnStruct> outcome_,
+  ) {
+    return _wire_divide_sync(
+      a,
+      b,
+      outcome_,
+    );
+  }
+
+  late final _wire_divide_syncPtr =
+      _lookup<ffi.NativeFunction<ffi.Int32 Function(ffi.Int32, ffi.Int32, ffi.Pointer<WireSyncReturnStruct>)>>(
+          'wire_divide_sync');
+  late final _wire_divide_sync =
+      _wire_divide_syncPtr.asFunction<int Function(int, int, ffi.Pointer<WireSyncReturnStruct>)>();
+
+  WireSyncReturnStruct wire_parse_id_sync(
+    ffi.Pointer<wire_uint_8_list> name,
+  ) {
+    return _wire_parse_id_sync(
+      name,
+    );
+  }
+
+  late final _wire_parse_id_syncPtr =
+      _lookup<ffi.NativeFunction<WireSyncReturnStruct Function(ffi.Pointer<wire_uint_8_list>)>>('wire_parse_id_sync');
+  late final _wire_parse_id_sync =
+      _wire_parse_id_syncPtr.asFunction<WireSyncReturnStruct Function(ffi.Pointer<wire_uint_8_list>)>();
+
   void wire_handle_stream(
     int port_,
     ffi.Pointer<wire_uint_8_list> arg,
@@ -2764,6 +3260,20 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
   late final _wire_handle_stream =
       _wire_handle_streamPtr.asFunction<void Function(int, ffi.Pointer<wire_uint_8_list>)>();
 
+  void wire_count_down(
+    int port_,
+    int from,
+  ) {
+    return _wire_count_down(
+      port_,
+      from,
+    );
+  }
+
+  late final _wire_count_downPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.Int32)>>('wire_count_down');
+  late final _wire_count_down = _wire_count_downPtr.asFunction<void Function(int, int)>();
+
   void wire_handle_stream_of_struct(
     int port_,
   ) {
@@ -2798,6 +3308,76 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
   late final _wire_return_panicPtr = _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_return_panic');
   late final _wire_return_panic = _wire_return_panicPtr.asFunction<void Function(int)>();
 
+  void wire_return_err_with_call_id(
+    int port_,
+  ) {
+    return _wire_return_err_with_call_id(
+      port_,
+    );
+  }
+
+  late final _wire_return_err_with_call_idPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_return_err_with_call_id');
+  late final _wire_return_err_with_call_id = _wire_return_err_with_call_idPtr.asFunction<void Function(int)>();
+
+  void wire_count_until_cancelled(
+    int port_,
+  ) {
+    return _wire_count_until_cancelled(
+      port_,
+    );
+  }
+
+  late final _wire_count_until_cancelledPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_count_until_cancelled');
+  late final _wire_count_until_cancelled = _wire_count_until_cancelledPtr.asFunction<void Function(int)>();
+
+  void wire_flaky_fetch(
+    int port_,
+    ffi.Pointer<wire_uint_8_list> key,
+    int failures,
+    ffi.Pointer<wire_uint_8_list> error,
+  ) {
+    return _wire_flaky_fetch(
+      port_,
+      key,
+      failures,
+      error,
+    );
+  }
+
+  late final _wire_flaky_fetchPtr = _lookup<
+      ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_uint_8_list>, ffi.Int32,
+              ffi.Pointer<wire_uint_8_list>)>>('wire_flaky_fetch');
+  late final _wire_flaky_fetch = _wire_flaky_fetchPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_uint_8_list>, int, ffi.Pointer<wire_uint_8_list>)>();
+
+  void wire_search_words(
+    int port_,
+    ffi.Pointer<wire_uint_8_list> query,
+  ) {
+    return _wire_search_words(
+      port_,
+      query,
+    );
+  }
+
+  late final _wire_search_wordsPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.Pointer<wire_uint_8_list>)>>('wire_search_words');
+  late final _wire_search_words = _wire_search_wordsPtr.asFunction<void Function(int, ffi.Pointer<wire_uint_8_list>)>();
+
+  void wire_count_searches(
+    int port_,
+  ) {
+    return _wire_count_searches(
+      port_,
+    );
+  }
+
+  late final _wire_count_searchesPtr = _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_count_searches');
+  late final _wire_count_searches = _wire_count_searchesPtr.asFunction<void Function(int)>();
+
   void wire_handle_optional_return(
     int port_,
     double left,
@@ -3130,6 +3710,78 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
   late final _wire_create_eventPtr = _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_create_event');
   late final _wire_create_event = _wire_create_eventPtr.asFunction<void Function(int)>();
 
+  void wire_tick(
+    int port_,
+  ) {
+    return _wire_tick(
+      port_,
+    );
+  }
+
+  late final _wire_tickPtr = _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_tick');
+  late final _wire_tick = _wire_tickPtr.asFunction<void Function(int)>();
+
+  void wire_count_running_tickers(
+    int port_,
+  ) {
+    return _wire_count_running_tickers(
+      port_,
+    );
+  }
+
+  late final _wire_count_running_tickersPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_count_running_tickers');
+  late final _wire_count_running_tickers = _wire_count_running_tickersPtr.asFunction<void Function(int)>();
+
+  int wire_polled_level(
+    ffi.Pointer<WireSyncReturnStruct> outcome_,
+  ) {
+    return _wire_polled_level(
+      outcome_,
+    );
+  }
+
+  late final _wire_polled_levelPtr =
+      _lookup<ffi.NativeFunction<ffi.Int32 Function(ffi.Pointer<WireSyncReturnStruct>)>>('wire_polled_level');
+  late final _wire_polled_level = _wire_polled_levelPtr.asFunction<int Function(ffi.Pointer<WireSyncReturnStruct>)>();
+
+  void wire_set_polled_level(
+    int port_,
+    int level,
+  ) {
+    return _wire_set_polled_level(
+      port_,
+      level,
+    );
+  }
+
+  late final _wire_set_polled_levelPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.Int32)>>('wire_set_polled_level');
+  late final _wire_set_polled_level = _wire_set_polled_levelPtr.asFunction<void Function(int, int)>();
+
+  void wire_tick_until_reinit(
+    int port_,
+  ) {
+    return _wire_tick_until_reinit(
+      port_,
+    );
+  }
+
+  late final _wire_tick_until_reinitPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_tick_until_reinit');
+  late final _wire_tick_until_reinit = _wire_tick_until_reinitPtr.asFunction<void Function(int)>();
+
+  void wire_count_reinits(
+    int port_,
+  ) {
+    return _wire_count_reinits(
+      port_,
+    );
+  }
+
+  late final _wire_count_reinitsPtr = _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_count_reinits');
+  late final _wire_count_reinits = _wire_count_reinitsPtr.asFunction<void Function(int)>();
+
   void wire_handle_stream_sink_at_1(
     int port_,
     int key,
@@ -3340,16 +3992,21 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
           .asFunction<void Function(int)>();
 
   ffi.Pointer<wire_StringList> new_StringList_0(
-    int len,
+    ffi.Pointer<wire_uint_8_list> bytes,
+    ffi.Pointer<wire_int_32_list> ends,
   ) {
     return _new_StringList_0(
-      len,
+      bytes,
+      ends,
     );
   }
 
-  late final _new_StringList_0Ptr =
-      _lookup<ffi.NativeFunction<ffi.Pointer<wire_StringList> Function(ffi.Int32)>>('new_StringList_0');
-  late final _new_StringList_0 = _new_StringList_0Ptr.asFunction<ffi.Pointer<wire_StringList> Function(int)>();
+  late final _new_StringList_0Ptr = _lookup<
+      ffi.NativeFunction<
+          ffi.Pointer<wire_StringList> Function(
+              ffi.Pointer<wire_uint_8_list>, ffi.Pointer<wire_int_32_list>)>>('new_StringList_0');
+  late final _new_StringList_0 = _new_StringList_0Ptr.asFunction<
+      ffi.Pointer<wire_StringList> Function(ffi.Pointer<wire_uint_8_list>, ffi.Pointer<wire_int_32_list>)>();
 
   ffi.Pointer<wire_ApplicationEnv> new_box_application_env_0() {
     return _new_box_application_env_0();
@@ -3829,6 +4486,67 @@ class FlutterRustBridgeExampleSingleBlockTestWire implements FlutterRustBridgeWi
   late final _free_WireSyncReturnStruct =
       _free_WireSyncReturnStructPtr.asFunction<void Function(WireSyncReturnStruct)>();
 
+  ffi.Pointer<WireSyncReturnStruct> new_sync_outcome() {
+    return _new_sync_outcome();
+  }
+
+  late final _new_sync_outcomePtr =
+      _lookup<ffi.NativeFunction<ffi.Pointer<WireSyncReturnStruct> Function()>>('new_sync_outcome');
+  late final _new_sync_outcome =
+      _new_sync_outcomePtr.asFunction<ffi.Pointer<WireSyncReturnStruct> Function()>();
+
+  void wire_get_bridge_health(
+    int port_,
+  ) {
+    return _wire_get_bridge_health(
+      port_,
+    );
+  }
+
+  late final _wire_get_bridge_healthPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_get_bridge_health');
+  late final _wire_get_bridge_health = _wire_get_bridge_healthPtr.asFunction<void Function(int)>();
+
+  void wire_stream_control(
+    int port_,
+    int command,
+    int value,
+  ) {
+    return _wire_stream_control(
+      port_,
+      command,
+      value,
+    );
+  }
+
+  late final _wire_stream_controlPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.Int32, ffi.Int64)>>('wire_stream_control');
+  late final _wire_stream_control = _wire_stream_controlPtr.asFunction<void Function(int, int, int)>();
+
+  void wire_reinit_bridge(
+    int token,
+  ) {
+    return _wire_reinit_bridge(
+      token,
+    );
+  }
+
+  late final _wire_reinit_bridgePtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_reinit_bridge');
+  late final _wire_reinit_bridge = _wire_reinit_bridgePtr.asFunction<void Function(int)>();
+
+  void wire_cancel_task(
+    int port_,
+  ) {
+    return _wire_cancel_task(
+      port_,
+    );
+  }
+
+  late final _wire_cancel_taskPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_cancel_task');
+  late final _wire_cancel_task = _wire_cancel_taskPtr.asFunction<void Function(int)>();
+
   void store_dart_post_cobject(
     DartPostCObjectFnType ptr,
   ) {
@@ -3870,10 +4588,9 @@ class wire_list_my_size extends ffi.Struct {
 }
 
 class wire_StringList extends ffi.Struct {
-  external ffi.Pointer<ffi.Pointer<wire_uint_8_list>> ptr;
+  external ffi.Pointer<wire_uint_8_list> bytes;
 
-  @ffi.Int32()
-  external int len;
+  external ffi.Pointer<wire_int_32_list> ends;
 }
 
 class wire_list_my_tree_node extends ffi.Struct {
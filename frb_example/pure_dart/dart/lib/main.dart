@@ -120,6 +120,31 @@ void main(List<String> args) async {
     }
   });
 
+  test('dart call divide_sync', () {
+    expect(api.divideSync(a: 7, b: 2), 3);
+    expect(() => api.divideSync(a: 7, b: 0), throwsA(isA<FfiException>()));
+    // the panic does not leak into the next call
+    expect(api.divideSync(a: 8, b: 2), 4);
+  });
+
+  test('dart call parse_id_sync', () {
+    expect(api.parseIdSync(name: '42'), 42);
+    expect(api.parseIdSync(name: 'forty-two'), null);
+  });
+
+  test('dart call getBridgeHealth', () async {
+    final idle = await api.getBridgeHealth();
+    expect(idle.queuedCount, 0);
+    expect(idle.oldestPendingAge, Duration.zero);
+
+    // handle_stream keeps its worker busy for a while after the first item
+    final stream = api.handleStream(arg: 'health').asBroadcastStream();
+    await stream.first;
+    final busy = await api.getBridgeHealth();
+    expect(busy.activeCount, greaterThanOrEqualTo(1));
+    await stream.drain();
+  });
+
   test('dart call handle_stream', () async {
     final stream = api.handleStream(arg: 'hello');
     var cnt = 0;
@@ -150,6 +175,57 @@ void main(List<String> args) async {
     _testHandleStream(api.handleStreamSinkAt3);
   });
 
+  test('dart call countDown', () async {
+    final stream = await api.countDown(from: 3);
+    expect(await stream.toList(), [3, 2, 1, 0]);
+    // fails when called, instead of when listened to
+    await expectLater(api.countDown(from: -1), throwsA(isA<FfiException>()));
+  });
+
+  test('dart call tickWithHandle', () async {
+    final handle = api.tickWithHandle();
+    final ticks = <int>[];
+    final subscription = handle.stream.listen(ticks.add);
+    Future<int> ticksAfter(int milliseconds) async {
+      await Future<void>.delayed(Duration(milliseconds: milliseconds));
+      return ticks.length;
+    }
+
+    await ticksAfter(100);
+    handle.pause();
+    // the tick sent right before pausing may still arrive
+    final paused = await ticksAfter(50);
+    expect(await ticksAfter(200), paused);
+
+    handle.resume();
+    expect(await ticksAfter(200), greaterThan(paused + 5));
+
+    handle.throttle(const Duration(milliseconds: 100));
+    final throttled = await ticksAfter(50);
+    expect(await ticksAfter(500), lessThanOrEqualTo(throttled + 6));
+    handle.throttle(Duration.zero);
+
+    // cancelling disposes the handle, which lets the paused producer notice that nobody listens
+    handle.pause();
+    await subscription.cancel();
+    await Future<void>.delayed(const Duration(milliseconds: 100));
+    expect(await api.countRunningTickers(), 0);
+    expect(ticks, List.generate(ticks.length, (i) => i));
+  });
+
+  test('dart call watchPolledLevel', () async {
+    await api.setPolledLevel(level: 1);
+    final levels = <int>[];
+    final subscription = api.watchPolledLevel(interval: const Duration(milliseconds: 10)).listen(levels.add);
+    await Future<void>.delayed(const Duration(milliseconds: 100));
+    await api.setPolledLevel(level: 2);
+    await Future<void>.delayed(const Duration(milliseconds: 100));
+    await subscription.cancel();
+    // polled many times, but only emitted when it changes
+    expect(levels, [1, 2]);
+    expect(api.polledLevel, 2);
+  });
+
   test('dart call returnErr', () async {
     try {
       await api.returnErr();
@@ -170,6 +246,49 @@ void main(List<String> args) async {
     }
   });
 
+  test('dart call returnErrWithCallId', () async {
+    try {
+      await api.returnErrWithCallId();
+      fail("exception not thrown");
+    } on FfiException catch (e) {
+      expect(e.callId, isNotNull);
+      expect(e.message, contains('call ${e.callId} '));
+    }
+  });
+
+  test('dart call countUntilCancelledWithHandle', () async {
+    final handle = api.countUntilCancelledWithHandle();
+    await Future<void>.delayed(const Duration(milliseconds: 200));
+    handle.cancel();
+    // stopped early by checking is_cancelled()
+    expect(await handle.future, lessThan(1000));
+  });
+
+  test('dart call flakyFetch', () async {
+    Matcher failsWith(String message) =>
+        throwsA(isA<FfiException>().having((e) => e.message, 'message', startsWith(message)));
+
+    expect(await api.flakyFetch(key: 'recovers', failures: 2, error: 'Timeout'), 3);
+    // gives up after 3 retries
+    await expectLater(
+        api.flakyFetch(key: 'gives up', failures: 10, error: 'Timeout'), failsWith('Timeout at attempt 4'));
+    // only retries the errors listed in `on`
+    await expectLater(
+        api.flakyFetch(key: 'not transient', failures: 1, error: 'Invalid'), failsWith('Invalid at attempt 1'));
+  });
+
+  test('dart call searchWords', () async {
+    final searches = await api.countSearches();
+    // typed faster than the delay: only the last query reaches Rust, and every call gets its result
+    final results = await Future.wait([
+      api.searchWords(query: 'b'),
+      api.searchWords(query: 'br'),
+      api.searchWords(query: 'bri'),
+    ]);
+    expect(results, List.filled(3, ['bridge']));
+    expect(await api.countSearches(), searches + 1);
+  });
+
   test('dart call handleOptionalReturn', () async {
     expect((await api.handleOptionalReturn(left: 1, right: 1))!, 1);
     expect(await api.handleOptionalReturn(left: 2, right: 0), null);
@@ -446,6 +565,26 @@ void main(List<String> args) async {
     expect(cnt, 5);
   });
 
+  // keep it last, since it tears down the state of the previous tests in Rust
+  test('dart call wire_reinit_bridge', () async {
+    final ticks = <int>[];
+    final subscription = api.tickUntilReinit().listen(ticks.add);
+    await Future<void>.delayed(const Duration(milliseconds: 100));
+    final reinits = await api.countReinits();
+
+    // what the API class of a hot-restarted isolate does, with the token of its new session
+    // ignore: invalid_use_of_protected_member
+    api.inner.wire_reinit_bridge(DateTime.now().microsecondsSinceEpoch);
+    expect(await api.countReinits(), reinits + 1);
+
+    // the stream of the previous session gets nothing anymore
+    final received = ticks.length;
+    expect(received, greaterThan(0));
+    await Future<void>.delayed(const Duration(milliseconds: 100));
+    expect(ticks.length, received);
+    await subscription.cancel();
+  });
+
   print('flutter_rust_bridge example program end');
 }
 
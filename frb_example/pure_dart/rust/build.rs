@@ -16,6 +16,10 @@ fn main() {
         rust_input: vec![RUST_INPUT.to_string()],
         // Path of output generated Dart code
         dart_output: vec![DART_OUTPUT.to_string()],
+        // Generate `getBridgeHealth()`
+        bridge_health: true,
+        // The Dart project does not use Flutter
+        pure_dart: true,
         // for other options use defaults
         ..Default::default()
     };
@@ -1,7 +1,8 @@
 #![allow(unused_variables)]
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicI32, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Once};
 use std::thread;
 use std::time::Duration;
 
@@ -170,6 +171,18 @@ pub fn handle_sync_return(mode: String) -> Result<SyncReturn<Vec<u8>>> {
     }
 }
 
+/// Takes and returns primitives, so it is called without encoding them.
+pub fn divide_sync(a: i32, b: i32) -> SyncReturn<i32> {
+    if b == 0 {
+        panic!("deliberate panic in divide_sync");
+    }
+    SyncReturn(a / b)
+}
+
+pub fn parse_id_sync(name: String) -> SyncReturn<Option<i32>> {
+    SyncReturn(name.parse().ok())
+}
+
 pub fn handle_stream(sink: StreamSink<String>, arg: String) -> Result<()> {
     println!("handle_stream arg={}", arg);
 
@@ -200,6 +213,21 @@ pub fn handle_stream(sink: StreamSink<String>, arg: String) -> Result<()> {
     Ok(())
 }
 
+/// Counts down from `from` to 0, or fails before streaming if `from` is negative.
+pub fn count_down(from: i32, sink: StreamSink<i32>) -> Result<StreamSetup> {
+    if from < 0 {
+        return Err(anyhow!("cannot count down from {}", from));
+    }
+    Ok(StreamSetup::produce(move || {
+        for i in (0..=from).rev() {
+            if !sink.add(i) {
+                return;
+            }
+        }
+        sink.close();
+    }))
+}
+
 pub struct MyStreamEntry {
     pub hello: String,
 }
@@ -219,6 +247,60 @@ pub fn return_panic() -> i32 {
     panic!("return_panic() is called, thus deliberately panic")
 }
 
+/// Fails with the id of its call in the message, which Dart also receives along with the error.
+pub fn return_err_with_call_id() -> Result<u64> {
+    let call_id = current_call_id().ok_or_else(|| anyhow!("not called from Dart"))?;
+    Err(anyhow!("call {} deliberately returns Err", call_id))
+}
+
+/// Counts for up to 10 seconds, and returns how far it got once cancelled from Dart.
+#[frb(cancelable)]
+pub fn count_until_cancelled() -> u32 {
+    for i in 0..1000 {
+        if is_cancelled() {
+            return i;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    1000
+}
+
+lazy_static! {
+    static ref FLAKY_ATTEMPTS: Mutex<HashMap<String, i32>> = Default::default();
+}
+
+/// Fails with `error` for the first `failures` attempts of each `key`, then returns the number of
+/// attempts. Dart retries the errors starting with "Timeout".
+#[frb(retry(times = 3, backoff_ms = 10, on = "Timeout"))]
+pub fn flaky_fetch(key: String, failures: i32, error: String) -> Result<i32> {
+    let mut attempts = FLAKY_ATTEMPTS.lock().unwrap();
+    let attempt = attempts.entry(key).or_default();
+    *attempt += 1;
+    if *attempt <= failures {
+        return Err(anyhow!("{} at attempt {}", error, attempt));
+    }
+    Ok(*attempt)
+}
+
+// debouncing test
+static SEARCHES: AtomicI32 = AtomicI32::new(0);
+
+/// Called by Dart once the query has not changed for 50 ms, e.g. while the user types it.
+#[frb(debounce_ms = 50)]
+pub fn search_words(query: String) -> Vec<String> {
+    SEARCHES.fetch_add(1, Ordering::SeqCst);
+    ["flutter", "rust", "bridge"]
+        .iter()
+        .filter(|word| word.starts_with(&query))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// How many times `search_words` has reached Rust.
+pub fn count_searches() -> i32 {
+    SEARCHES.load(Ordering::SeqCst)
+}
+
 pub fn handle_optional_return(left: f64, right: f64) -> Option<f64> {
     if right == 0. {
         None
@@ -596,6 +678,67 @@ pub fn create_event() {
     }
 }
 
+// stream control test
+static RUNNING_TICKERS: AtomicI32 = AtomicI32::new(0);
+
+/// Ticks every 10 ms from another thread, until nobody listens anymore.
+pub fn tick(sink: StreamSink<u32>) -> Result<()> {
+    RUNNING_TICKERS.fetch_add(1, Ordering::SeqCst);
+    thread::spawn(move || {
+        let mut tick = 0;
+        while sink.add(tick) {
+            tick += 1;
+            thread::sleep(Duration::from_millis(10));
+        }
+        RUNNING_TICKERS.fetch_sub(1, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// How many streams of `tick` are still producing.
+pub fn count_running_tickers() -> i32 {
+    RUNNING_TICKERS.load(Ordering::SeqCst)
+}
+
+// polling test
+static POLLED_LEVEL: AtomicI32 = AtomicI32::new(0);
+
+/// A level which Rust cannot push to Dart, e.g. because it reads it from a C library on demand.
+#[frb(poll)]
+pub fn polled_level() -> SyncReturn<i32> {
+    SyncReturn(POLLED_LEVEL.load(Ordering::SeqCst))
+}
+
+pub fn set_polled_level(level: i32) {
+    POLLED_LEVEL.store(level, Ordering::SeqCst);
+}
+
+// hot restart test
+static REINITS: AtomicI32 = AtomicI32::new(0);
+static REINIT_HOOK: Once = Once::new();
+
+/// Ticks every 10 ms from another thread, until the Dart side is restarted.
+pub fn tick_until_reinit(sink: StreamSink<u32>) -> Result<()> {
+    REINIT_HOOK.call_once(|| {
+        on_reinit(|| {
+            REINITS.fetch_add(1, Ordering::SeqCst);
+        })
+    });
+    thread::spawn(move || {
+        let mut tick = 0;
+        while sink.add(tick) {
+            tick += 1;
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+    Ok(())
+}
+
+/// How many times the Dart side has been restarted since `tick_until_reinit` was first called.
+pub fn count_reinits() -> i32 {
+    REINITS.load(Ordering::SeqCst)
+}
+
 #[derive(Debug, Clone)]
 pub struct Log {
     pub key: u32,
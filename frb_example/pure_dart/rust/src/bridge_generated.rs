@@ -11,6 +11,7 @@
 // Generated by `flutter_rust_bridge`.
 
 use crate::api::*;
+use flutter_rust_bridge::support::IntoDart;
 use flutter_rust_bridge::*;
 
 // Section: imports
@@ -23,11 +24,7 @@ use crate::data::MyStruct;
 #[no_mangle]
 pub extern "C" fn wire_simple_adder(port_: i64, a: i32, b: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "simple_adder",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("simple_adder", Some(port_), FfiCallMode::Normal),
         move || {
             let api_a = a.wire2api();
             let api_b = b.wire2api();
@@ -45,11 +42,7 @@ pub extern "C" fn wire_primitive_types(
     my_bool: bool,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "primitive_types",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("primitive_types", Some(port_), FfiCallMode::Normal),
         move || {
             let api_my_i32 = my_i32.wire2api();
             let api_my_i64 = my_i64.wire2api();
@@ -70,11 +63,7 @@ pub extern "C" fn wire_primitive_types(
 #[no_mangle]
 pub extern "C" fn wire_primitive_u32(port_: i64, my_u32: u32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "primitive_u32",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("primitive_u32", Some(port_), FfiCallMode::Normal),
         move || {
             let api_my_u32 = my_u32.wire2api();
             move |task_callback| Ok(primitive_u32(api_my_u32))
@@ -85,11 +74,7 @@ pub extern "C" fn wire_primitive_u32(port_: i64, my_u32: u32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_string(port_: i64, s: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_string",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_string", Some(port_), FfiCallMode::Normal),
         move || {
             let api_s = s.wire2api();
             move |task_callback| Ok(handle_string(api_s))
@@ -100,11 +85,7 @@ pub extern "C" fn wire_handle_string(port_: i64, s: *mut wire_uint_8_list) {
 #[no_mangle]
 pub extern "C" fn wire_handle_return_unit(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_return_unit",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_return_unit", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(handle_return_unit()),
     )
 }
@@ -112,11 +93,7 @@ pub extern "C" fn wire_handle_return_unit(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_handle_vec_u8(port_: i64, v: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_vec_u8",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_vec_u8", Some(port_), FfiCallMode::Normal),
         move || {
             let api_v = v.wire2api();
             move |task_callback| Ok(handle_vec_u8(api_v))
@@ -127,11 +104,7 @@ pub extern "C" fn wire_handle_vec_u8(port_: i64, v: *mut wire_uint_8_list) {
 #[no_mangle]
 pub extern "C" fn wire_handle_vec_of_primitive(port_: i64, n: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_vec_of_primitive",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_vec_of_primitive", Some(port_), FfiCallMode::Normal),
         move || {
             let api_n = n.wire2api();
             move |task_callback| Ok(handle_vec_of_primitive(api_n))
@@ -142,11 +115,11 @@ pub extern "C" fn wire_handle_vec_of_primitive(port_: i64, n: i32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_zero_copy_vec_of_primitive(port_: i64, n: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_zero_copy_vec_of_primitive",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "handle_zero_copy_vec_of_primitive",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_n = n.wire2api();
             move |task_callback| Ok(handle_zero_copy_vec_of_primitive(api_n))
@@ -157,11 +130,7 @@ pub extern "C" fn wire_handle_zero_copy_vec_of_primitive(port_: i64, n: i32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_struct(port_: i64, arg: *mut wire_MySize, boxed: *mut wire_MySize) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_arg = arg.wire2api();
             let api_boxed = boxed.wire2api();
@@ -173,11 +142,7 @@ pub extern "C" fn wire_handle_struct(port_: i64, arg: *mut wire_MySize, boxed: *
 #[no_mangle]
 pub extern "C" fn wire_handle_newtype(port_: i64, arg: *mut wire_NewTypeInt) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_newtype",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_newtype", Some(port_), FfiCallMode::Normal),
         move || {
             let api_arg = arg.wire2api();
             move |task_callback| Ok(handle_newtype(api_arg))
@@ -188,11 +153,7 @@ pub extern "C" fn wire_handle_newtype(port_: i64, arg: *mut wire_NewTypeInt) {
 #[no_mangle]
 pub extern "C" fn wire_handle_list_of_struct(port_: i64, l: *mut wire_list_my_size) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_list_of_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_list_of_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_l = l.wire2api();
             move |task_callback| Ok(handle_list_of_struct(api_l))
@@ -203,11 +164,7 @@ pub extern "C" fn wire_handle_list_of_struct(port_: i64, l: *mut wire_list_my_si
 #[no_mangle]
 pub extern "C" fn wire_handle_string_list(port_: i64, names: *mut wire_StringList) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_string_list",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_string_list", Some(port_), FfiCallMode::Normal),
         move || {
             let api_names = names.wire2api();
             move |task_callback| Ok(handle_string_list(api_names))
@@ -218,11 +175,7 @@ pub extern "C" fn wire_handle_string_list(port_: i64, names: *mut wire_StringLis
 #[no_mangle]
 pub extern "C" fn wire_handle_complex_struct(port_: i64, s: *mut wire_MyTreeNode) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_complex_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_complex_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_s = s.wire2api();
             move |task_callback| Ok(handle_complex_struct(api_s))
@@ -235,14 +188,44 @@ pub extern "C" fn wire_handle_sync_return(
     mode: *mut wire_uint_8_list,
 ) -> support::WireSyncReturnStruct {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync(
-        WrapInfo {
-            debug_name: "handle_sync_return",
-            port: None,
-            mode: FfiCallMode::Sync,
-        },
+        WrapInfo::new("handle_sync_return", None, FfiCallMode::Sync),
         move || {
             let api_mode = mode.wire2api();
-            handle_sync_return(api_mode)
+            support::IntoSyncReturnResult::into_sync_return_result(handle_sync_return(api_mode))
+        },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_divide_sync(
+    a: i32,
+    b: i32,
+    outcome_: *mut support::WireSyncReturnStruct,
+) -> i32 {
+    let (value, outcome) = FLUTTER_RUST_BRIDGE_HANDLER.wrap_primitive_sync(
+        WrapInfo::new("divide_sync", None, FfiCallMode::Sync),
+        move || {
+            let api_a = a.wire2api();
+            let api_b = b.wire2api();
+            divide_sync(api_a, api_b).0
+        },
+    );
+    unsafe { *outcome_ = outcome };
+    value
+}
+
+#[no_mangle]
+pub extern "C" fn wire_parse_id_sync(name: *mut wire_uint_8_list) -> support::WireSyncReturnStruct {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync_nullable(
+        WrapInfo::new("parse_id_sync", None, FfiCallMode::Sync),
+        move || {
+            let api_name = name.wire2api();
+            Ok(parse_id_sync(api_name)).map(|ret| {
+                SyncReturn(
+                    ret.0
+                        .map(support::IntoSyncReturnBytes::into_sync_return_bytes),
+                )
+            })
         },
     )
 }
@@ -250,11 +233,7 @@ pub extern "C" fn wire_handle_sync_return(
 #[no_mangle]
 pub extern "C" fn wire_handle_stream(port_: i64, arg: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_stream",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new("handle_stream", Some(port_), FfiCallMode::Stream),
         move || {
             let api_arg = arg.wire2api();
             move |task_callback| handle_stream(task_callback.stream_sink(), api_arg)
@@ -263,13 +242,23 @@ pub extern "C" fn wire_handle_stream(port_: i64, arg: *mut wire_uint_8_list) {
 }
 
 #[no_mangle]
-pub extern "C" fn wire_handle_stream_of_struct(port_: i64) {
+pub extern "C" fn wire_count_down(port_: i64, from: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_stream_of_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
+        WrapInfo::new("count_down", Some(port_), FfiCallMode::Stream),
+        move || {
+            let api_from = from.wire2api();
+            move |task_callback| {
+                count_down(api_from, task_callback.stream_sink())
+                    .map(|setup| task_callback.start_stream(setup))
+            }
         },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_handle_stream_of_struct(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("handle_stream_of_struct", Some(port_), FfiCallMode::Stream),
         move || move |task_callback| handle_stream_of_struct(task_callback.stream_sink()),
     )
 }
@@ -277,11 +266,7 @@ pub extern "C" fn wire_handle_stream_of_struct(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_return_err(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "return_err",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("return_err", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| return_err(),
     )
 }
@@ -289,23 +274,68 @@ pub extern "C" fn wire_return_err(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_return_panic(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "return_panic",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("return_panic", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(return_panic()),
     )
 }
 
 #[no_mangle]
-pub extern "C" fn wire_handle_optional_return(port_: i64, left: f64, right: f64) {
+pub extern "C" fn wire_return_err_with_call_id(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("return_err_with_call_id", Some(port_), FfiCallMode::Normal),
+        move || move |task_callback| return_err_with_call_id(),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_count_until_cancelled(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_optional_return",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
+        WrapInfo::new("count_until_cancelled", Some(port_), FfiCallMode::Normal),
+        move || move |task_callback| Ok(count_until_cancelled()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_flaky_fetch(
+    port_: i64,
+    key: *mut wire_uint_8_list,
+    failures: i32,
+    error: *mut wire_uint_8_list,
+) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("flaky_fetch", Some(port_), FfiCallMode::Normal),
+        move || {
+            let api_key = key.wire2api();
+            let api_failures = failures.wire2api();
+            let api_error = error.wire2api();
+            move |task_callback| flaky_fetch(api_key, api_failures, api_error)
         },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_search_words(port_: i64, query: *mut wire_uint_8_list) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("search_words", Some(port_), FfiCallMode::Normal),
+        move || {
+            let api_query = query.wire2api();
+            move |task_callback| Ok(search_words(api_query))
+        },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_count_searches(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("count_searches", Some(port_), FfiCallMode::Normal),
+        move || move |task_callback| Ok(count_searches()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_handle_optional_return(port_: i64, left: f64, right: f64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("handle_optional_return", Some(port_), FfiCallMode::Normal),
         move || {
             let api_left = left.wire2api();
             let api_right = right.wire2api();
@@ -317,11 +347,7 @@ pub extern "C" fn wire_handle_optional_return(port_: i64, left: f64, right: f64)
 #[no_mangle]
 pub extern "C" fn wire_handle_optional_struct(port_: i64, document: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_optional_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_optional_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_document = document.wire2api();
             move |task_callback| Ok(handle_optional_struct(api_document))
@@ -332,11 +358,11 @@ pub extern "C" fn wire_handle_optional_struct(port_: i64, document: *mut wire_ui
 #[no_mangle]
 pub extern "C" fn wire_handle_optional_increment(port_: i64, opt: *mut wire_ExoticOptionals) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_optional_increment",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "handle_optional_increment",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_opt = opt.wire2api();
             move |task_callback| Ok(handle_optional_increment(api_opt))
@@ -347,11 +373,11 @@ pub extern "C" fn wire_handle_optional_increment(port_: i64, opt: *mut wire_Exot
 #[no_mangle]
 pub extern "C" fn wire_handle_increment_boxed_optional(port_: i64, opt: *mut f64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_increment_boxed_optional",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "handle_increment_boxed_optional",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_opt = opt.wire2api();
             move |task_callback| Ok(handle_increment_boxed_optional(api_opt))
@@ -371,11 +397,11 @@ pub extern "C" fn wire_handle_option_box_arguments(
     structbox: *mut wire_ExoticOptionals,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_option_box_arguments",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "handle_option_box_arguments",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_i8box = i8box.wire2api();
             let api_u8box = u8box.wire2api();
@@ -402,11 +428,7 @@ pub extern "C" fn wire_handle_option_box_arguments(
 #[no_mangle]
 pub extern "C" fn wire_print_note(port_: i64, note: *mut wire_Note) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "print_note",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("print_note", Some(port_), FfiCallMode::Normal),
         move || {
             let api_note = note.wire2api();
             move |task_callback| Ok(print_note(api_note))
@@ -417,11 +439,7 @@ pub extern "C" fn wire_print_note(port_: i64, note: *mut wire_Note) {
 #[no_mangle]
 pub extern "C" fn wire_handle_return_enum(port_: i64, input: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_return_enum",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_return_enum", Some(port_), FfiCallMode::Normal),
         move || {
             let api_input = input.wire2api();
             move |task_callback| Ok(handle_return_enum(api_input))
@@ -432,11 +450,7 @@ pub extern "C" fn wire_handle_return_enum(port_: i64, input: *mut wire_uint_8_li
 #[no_mangle]
 pub extern "C" fn wire_handle_enum_parameter(port_: i64, weekday: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_enum_parameter",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_enum_parameter", Some(port_), FfiCallMode::Normal),
         move || {
             let api_weekday = weekday.wire2api();
             move |task_callback| Ok(handle_enum_parameter(api_weekday))
@@ -447,11 +461,7 @@ pub extern "C" fn wire_handle_enum_parameter(port_: i64, weekday: i32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_customized_struct(port_: i64, val: *mut wire_Customized) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_customized_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_customized_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_val = val.wire2api();
             move |task_callback| Ok(handle_customized_struct(api_val))
@@ -462,11 +472,7 @@ pub extern "C" fn wire_handle_customized_struct(port_: i64, val: *mut wire_Custo
 #[no_mangle]
 pub extern "C" fn wire_handle_enum_struct(port_: i64, val: *mut wire_KitchenSink) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_enum_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("handle_enum_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_val = val.wire2api();
             move |task_callback| Ok(handle_enum_struct(api_val))
@@ -477,11 +483,7 @@ pub extern "C" fn wire_handle_enum_struct(port_: i64, val: *mut wire_KitchenSink
 #[no_mangle]
 pub extern "C" fn wire_use_imported_struct(port_: i64, my_struct: *mut wire_MyStruct) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "use_imported_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("use_imported_struct", Some(port_), FfiCallMode::Normal),
         move || {
             let api_my_struct = my_struct.wire2api();
             move |task_callback| Ok(use_imported_struct(api_my_struct))
@@ -492,11 +494,7 @@ pub extern "C" fn wire_use_imported_struct(port_: i64, my_struct: *mut wire_MySt
 #[no_mangle]
 pub extern "C" fn wire_use_imported_enum(port_: i64, my_enum: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "use_imported_enum",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("use_imported_enum", Some(port_), FfiCallMode::Normal),
         move || {
             let api_my_enum = my_enum.wire2api();
             move |task_callback| Ok(use_imported_enum(api_my_enum))
@@ -507,11 +505,7 @@ pub extern "C" fn wire_use_imported_enum(port_: i64, my_enum: i32) {
 #[no_mangle]
 pub extern "C" fn wire_get_app_settings(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_app_settings",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_app_settings", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(mirror_ApplicationSettings(get_app_settings())),
     )
 }
@@ -519,11 +513,7 @@ pub extern "C" fn wire_get_app_settings(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_is_app_embedded(port_: i64, app_settings: *mut wire_ApplicationSettings) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "is_app_embedded",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("is_app_embedded", Some(port_), FfiCallMode::Normal),
         move || {
             let api_app_settings = app_settings.wire2api();
             move |task_callback| Ok(is_app_embedded(api_app_settings))
@@ -534,11 +524,7 @@ pub extern "C" fn wire_is_app_embedded(port_: i64, app_settings: *mut wire_Appli
 #[no_mangle]
 pub extern "C" fn wire_get_message(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_message",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_message", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(mirror_ApplicationMessage(get_message())),
     )
 }
@@ -546,11 +532,7 @@ pub extern "C" fn wire_get_message(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_get_array(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_array",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_array", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(get_array()),
     )
 }
@@ -558,11 +540,7 @@ pub extern "C" fn wire_get_array(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_get_complex_array(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_complex_array",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_complex_array", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(get_complex_array()),
     )
 }
@@ -570,11 +548,7 @@ pub extern "C" fn wire_get_complex_array(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_get_usize(port_: i64, u: usize) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_usize",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_usize", Some(port_), FfiCallMode::Normal),
         move || {
             let api_u = u.wire2api();
             move |task_callback| Ok(get_usize(api_u))
@@ -585,11 +559,7 @@ pub extern "C" fn wire_get_usize(port_: i64, u: usize) {
 #[no_mangle]
 pub extern "C" fn wire_next_user_id(port_: i64, user_id: *mut wire_UserId) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "next_user_id",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("next_user_id", Some(port_), FfiCallMode::Normal),
         move || {
             let api_user_id = user_id.wire2api();
             move |task_callback| Ok(next_user_id(api_user_id))
@@ -600,11 +570,7 @@ pub extern "C" fn wire_next_user_id(port_: i64, user_id: *mut wire_UserId) {
 #[no_mangle]
 pub extern "C" fn wire_register_event_listener(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "register_event_listener",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new("register_event_listener", Some(port_), FfiCallMode::Stream),
         move || move |task_callback| register_event_listener(task_callback.stream_sink()),
     )
 }
@@ -612,11 +578,7 @@ pub extern "C" fn wire_register_event_listener(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_close_event_listener(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "close_event_listener",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("close_event_listener", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(close_event_listener()),
     )
 }
@@ -624,23 +586,68 @@ pub extern "C" fn wire_close_event_listener(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_create_event(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "create_event",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("create_event", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(create_event()),
     )
 }
 
 #[no_mangle]
-pub extern "C" fn wire_handle_stream_sink_at_1(port_: i64, key: u32, max: u32) {
+pub extern "C" fn wire_tick(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_stream_sink_at_1",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
+        WrapInfo::new("tick", Some(port_), FfiCallMode::Stream),
+        move || move |task_callback| tick(task_callback.stream_sink()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_count_running_tickers(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("count_running_tickers", Some(port_), FfiCallMode::Normal),
+        move || move |task_callback| Ok(count_running_tickers()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_polled_level(outcome_: *mut support::WireSyncReturnStruct) -> i32 {
+    let (value, outcome) = FLUTTER_RUST_BRIDGE_HANDLER.wrap_primitive_sync(
+        WrapInfo::new("polled_level", None, FfiCallMode::Sync),
+        move || polled_level().0,
+    );
+    unsafe { *outcome_ = outcome };
+    value
+}
+
+#[no_mangle]
+pub extern "C" fn wire_set_polled_level(port_: i64, level: i32) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("set_polled_level", Some(port_), FfiCallMode::Normal),
+        move || {
+            let api_level = level.wire2api();
+            move |task_callback| Ok(set_polled_level(api_level))
         },
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_tick_until_reinit(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("tick_until_reinit", Some(port_), FfiCallMode::Stream),
+        move || move |task_callback| tick_until_reinit(task_callback.stream_sink()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_count_reinits(port_: i64) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("count_reinits", Some(port_), FfiCallMode::Normal),
+        move || move |task_callback| Ok(count_reinits()),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wire_handle_stream_sink_at_1(port_: i64, key: u32, max: u32) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap(
+        WrapInfo::new("handle_stream_sink_at_1", Some(port_), FfiCallMode::Stream),
         move || {
             let api_key = key.wire2api();
             let api_max = max.wire2api();
@@ -654,11 +661,7 @@ pub extern "C" fn wire_handle_stream_sink_at_1(port_: i64, key: u32, max: u32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_stream_sink_at_2(port_: i64, key: u32, max: u32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_stream_sink_at_2",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new("handle_stream_sink_at_2", Some(port_), FfiCallMode::Stream),
         move || {
             let api_key = key.wire2api();
             let api_max = max.wire2api();
@@ -672,11 +675,7 @@ pub extern "C" fn wire_handle_stream_sink_at_2(port_: i64, key: u32, max: u32) {
 #[no_mangle]
 pub extern "C" fn wire_handle_stream_sink_at_3(port_: i64, key: u32, max: u32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_stream_sink_at_3",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new("handle_stream_sink_at_3", Some(port_), FfiCallMode::Stream),
         move || {
             let api_key = key.wire2api();
             let api_max = max.wire2api();
@@ -690,11 +689,7 @@ pub extern "C" fn wire_handle_stream_sink_at_3(port_: i64, key: u32, max: u32) {
 #[no_mangle]
 pub extern "C" fn wire_get_sum_struct(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "get_sum_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("get_sum_struct", Some(port_), FfiCallMode::Normal),
         move || move |task_callback| Ok(get_sum_struct()),
     )
 }
@@ -702,11 +697,7 @@ pub extern "C" fn wire_get_sum_struct(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_sum__method__SumWith(port_: i64, that: *mut wire_SumWith, y: u32, z: u32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "sum__method__SumWith",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("sum__method__SumWith", Some(port_), FfiCallMode::Normal),
         move || {
             let api_that = that.wire2api();
             let api_y = y.wire2api();
@@ -719,11 +710,11 @@ pub extern "C" fn wire_sum__method__SumWith(port_: i64, that: *mut wire_SumWith,
 #[no_mangle]
 pub extern "C" fn wire_new__static_method__ConcatenateWith(port_: i64, a: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "new__static_method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "new__static_method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_a = a.wire2api();
             move |task_callback| Ok(ConcatenateWith::new(api_a))
@@ -738,11 +729,11 @@ pub extern "C" fn wire_concatenate__method__ConcatenateWith(
     b: *mut wire_uint_8_list,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "concatenate__method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "concatenate__method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_that = that.wire2api();
             let api_b = b.wire2api();
@@ -758,11 +749,11 @@ pub extern "C" fn wire_concatenate_static__static_method__ConcatenateWith(
     b: *mut wire_uint_8_list,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "concatenate_static__static_method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "concatenate_static__static_method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_a = a.wire2api();
             let api_b = b.wire2api();
@@ -779,11 +770,11 @@ pub extern "C" fn wire_handle_some_stream_sink__method__ConcatenateWith(
     max: u32,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_some_stream_sink__method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new(
+            "handle_some_stream_sink__method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Stream,
+        ),
         move || {
             let api_that = that.wire2api();
             let api_key = key.wire2api();
@@ -806,11 +797,11 @@ pub extern "C" fn wire_handle_some_stream_sink_at_1__method__ConcatenateWith(
     that: *mut wire_ConcatenateWith,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_some_stream_sink_at_1__method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new(
+            "handle_some_stream_sink_at_1__method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Stream,
+        ),
         move || {
             let api_that = that.wire2api();
             move |task_callback| {
@@ -830,11 +821,11 @@ pub extern "C" fn wire_handle_some_static_stream_sink__static_method__Concatenat
     max: u32,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_some_static_stream_sink__static_method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new(
+            "handle_some_static_stream_sink__static_method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Stream,
+        ),
         move || {
             let api_key = key.wire2api();
             let api_max = max.wire2api();
@@ -854,11 +845,11 @@ pub extern "C" fn wire_handle_some_static_stream_sink_single_arg__static_method_
     port_: i64,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith",
-            port: Some(port_),
-            mode: FfiCallMode::Stream,
-        },
+        WrapInfo::new(
+            "handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith",
+            Some(port_),
+            FfiCallMode::Stream,
+        ),
         move || {
             move |task_callback| {
                 ConcatenateWith::handle_some_static_stream_sink_single_arg(
@@ -874,8 +865,8 @@ pub extern "C" fn wire_handle_some_static_stream_sink_single_arg__static_method_
 #[repr(C)]
 #[derive(Clone)]
 pub struct wire_StringList {
-    ptr: *mut *mut wire_uint_8_list,
-    len: i32,
+    bytes: *mut wire_uint_8_list,
+    ends: *mut wire_int_32_list,
 }
 
 #[repr(C)]
@@ -1169,16 +1160,83 @@ const _: fn() = || {
         let _: ApplicationMode = ApplicationSettings.mode;
         let _: Box<ApplicationEnv> = ApplicationSettings.env;
     }
+    {
+        fn handle_stream_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        handle_stream_stream_sink_must_be_send_and_static::<StreamSink<String>>();
+    }
+    {
+        fn count_down_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        count_down_stream_sink_must_be_send_and_static::<StreamSink<i32>>();
+    }
+    {
+        fn handle_stream_of_struct_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        handle_stream_of_struct_stream_sink_must_be_send_and_static::<StreamSink<MyStreamEntry>>();
+    }
+    {
+        fn register_event_listener_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        register_event_listener_stream_sink_must_be_send_and_static::<StreamSink<Event>>();
+    }
+    {
+        fn tick_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        tick_stream_sink_must_be_send_and_static::<StreamSink<u32>>();
+    }
+    {
+        fn tick_until_reinit_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        tick_until_reinit_stream_sink_must_be_send_and_static::<StreamSink<u32>>();
+    }
+    {
+        fn handle_stream_sink_at_1_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        handle_stream_sink_at_1_stream_sink_must_be_send_and_static::<StreamSink<Log>>();
+    }
+    {
+        fn handle_stream_sink_at_2_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        handle_stream_sink_at_2_stream_sink_must_be_send_and_static::<StreamSink<Log>>();
+    }
+    {
+        fn handle_stream_sink_at_3_stream_sink_must_be_send_and_static<T: Send + 'static>() {}
+        handle_stream_sink_at_3_stream_sink_must_be_send_and_static::<StreamSink<Log>>();
+    }
+    {
+        fn handle_some_stream_sink__method__ConcatenateWith_stream_sink_must_be_send_and_static<
+            T: Send + 'static,
+        >() {
+        }
+        handle_some_stream_sink__method__ConcatenateWith_stream_sink_must_be_send_and_static::<
+            StreamSink<Log2>,
+        >();
+    }
+    {
+        fn handle_some_stream_sink_at_1__method__ConcatenateWith_stream_sink_must_be_send_and_static<
+            T: Send + 'static,
+        >() {
+        }
+        handle_some_stream_sink_at_1__method__ConcatenateWith_stream_sink_must_be_send_and_static::<
+            StreamSink<u32>,
+        >();
+    }
+    {
+        fn handle_some_static_stream_sink__static_method__ConcatenateWith_stream_sink_must_be_send_and_static<
+            T: Send + 'static,
+        >() {
+        }
+        handle_some_static_stream_sink__static_method__ConcatenateWith_stream_sink_must_be_send_and_static::<StreamSink<Log2>>();
+    }
+    {
+        fn handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith_stream_sink_must_be_send_and_static<
+            T: Send + 'static,
+        >() {
+        }
+        handle_some_static_stream_sink_single_arg__static_method__ConcatenateWith_stream_sink_must_be_send_and_static::<StreamSink<u32>>();
+    }
 };
 // Section: allocate functions
 
 #[no_mangle]
-pub extern "C" fn new_StringList_0(len: i32) -> *mut wire_StringList {
-    let wrap = wire_StringList {
-        ptr: support::new_leak_vec_ptr(<*mut wire_uint_8_list>::new_with_null_ptr(), len),
-        len,
-    };
-    support::new_leak_box_ptr(wrap)
+pub extern "C" fn new_StringList_0(
+    bytes: *mut wire_uint_8_list,
+    ends: *mut wire_int_32_list,
+) -> *mut wire_StringList {
+    support::new_leak_box_ptr(wire_StringList { bytes, ends })
 }
 
 #[no_mangle]
@@ -1450,11 +1508,10 @@ impl Wire2Api<String> for *mut wire_uint_8_list {
 
 impl Wire2Api<Vec<String>> for *mut wire_StringList {
     fn wire2api(self) -> Vec<String> {
-        let vec = unsafe {
-            let wrap = support::box_from_leak_ptr(self);
-            support::vec_from_leak_ptr(wrap.ptr, wrap.len)
-        };
-        vec.into_iter().map(Wire2Api::wire2api).collect()
+        let wrap = unsafe { support::box_from_leak_ptr(self) };
+        let bytes: Vec<u8> = wrap.bytes.wire2api();
+        let ends: Vec<i32> = wrap.ends.wire2api();
+        support::unpack_strings(&bytes, &ends).expect("invalid packed strings")
     }
 }
 
@@ -2327,8 +2384,19 @@ impl support::IntoDart for ExoticOptionals {
             self.float32list.into_dart(),
             self.float64list.into_dart(),
             self.attributes.into_dart(),
-            self.attributes_nullable.into_dart(),
-            self.nullable_attributes.into_dart(),
+            self.attributes_nullable
+                .into_iter()
+                .map(|v| v.into_dart())
+                .collect::<Vec<_>>()
+                .into_dart(),
+            self.nullable_attributes
+                .map(|v| {
+                    v.into_iter()
+                        .map(|v| v.into_dart())
+                        .collect::<Vec<_>>()
+                        .into_dart()
+                })
+                .into_dart(),
             self.newtypeint.into_dart(),
         ]
         .into_dart()
@@ -2488,6 +2556,7 @@ impl support::IntoDartExceptPrimitive for ZeroCopyVecOfPrimitivePack {}
 // Section: executor
 
 support::lazy_static! {
+    /// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
     pub static ref FLUTTER_RUST_BRIDGE_HANDLER: support::DefaultHandler = Default::default();
 }
 
@@ -2499,3 +2568,36 @@ pub extern "C" fn free_WireSyncReturnStruct(val: support::WireSyncReturnStruct)
         let _ = support::vec_from_leak_ptr(val.ptr, val.len);
     }
 }
+
+#[no_mangle]
+pub extern "C" fn new_sync_outcome() -> *mut support::WireSyncReturnStruct {
+    support::new_sync_outcome()
+}
+
+// Section: health
+
+#[no_mangle]
+pub extern "C" fn wire_get_bridge_health(port_: i64) {
+    support::report_health(port_, || FLUTTER_RUST_BRIDGE_HANDLER.health())
+}
+
+// Section: stream control
+
+#[no_mangle]
+pub extern "C" fn wire_stream_control(port_: i64, command: i32, value: i64) {
+    support::stream_control(port_, command, value)
+}
+
+// Section: reinit
+
+#[no_mangle]
+pub extern "C" fn wire_reinit_bridge(token: i64) {
+    support::reinit(token)
+}
+
+// Section: cancel
+
+#[no_mangle]
+pub extern "C" fn wire_cancel_task(port_: i64) {
+    support::cancel_task(port_)
+}
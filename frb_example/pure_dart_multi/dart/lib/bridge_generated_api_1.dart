@@ -1,7 +1,7 @@
 // AUTO GENERATED FILE, DO NOT EDIT.
 // Generated by `flutter_rust_bridge`.
 
-// ignore_for_file: non_constant_identifier_names, unused_element, duplicate_ignore, directives_ordering, curly_braces_in_flow_control_structures, unnecessary_lambdas, slash_for_doc_comments, prefer_const_literals_to_create_immutables, implicit_dynamic_list_literal, duplicate_import, unused_import, prefer_single_quotes, prefer_const_constructors, use_super_parameters, always_use_package_imports
+// ignore_for_file: non_constant_identifier_names, unused_element, duplicate_ignore, directives_ordering, curly_braces_in_flow_control_structures, unnecessary_lambdas, slash_for_doc_comments, prefer_const_literals_to_create_immutables, implicit_dynamic_list_literal, duplicate_import, unused_import, prefer_single_quotes, prefer_const_constructors, use_super_parameters, always_use_package_imports, depend_on_referenced_packages
 
 import 'dart:convert';
 import 'dart:typed_data';
@@ -11,6 +11,7 @@ import 'dart:typed_data';
 import 'package:flutter_rust_bridge/flutter_rust_bridge.dart';
 import 'dart:ffi' as ffi;
 
+/// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
 abstract class ApiClass1 {
   /// Documentation on a simple adder function.
   Future<int> simpleAdder1({required int a, required int b, dynamic hint});
@@ -97,6 +98,55 @@ class ApiClass1Wire implements FlutterRustBridgeWireBase {
   late final _free_WireSyncReturnStruct =
       _free_WireSyncReturnStructPtr.asFunction<void Function(WireSyncReturnStruct)>();
 
+  ffi.Pointer<WireSyncReturnStruct> new_sync_outcome() {
+    return _new_sync_outcome();
+  }
+
+  late final _new_sync_outcomePtr =
+      _lookup<ffi.NativeFunction<ffi.Pointer<WireSyncReturnStruct> Function()>>('new_sync_outcome');
+  late final _new_sync_outcome =
+      _new_sync_outcomePtr.asFunction<ffi.Pointer<WireSyncReturnStruct> Function()>();
+
+  void wire_stream_control(
+    int port_,
+    int command,
+    int value,
+  ) {
+    return _wire_stream_control(
+      port_,
+      command,
+      value,
+    );
+  }
+
+  late final _wire_stream_controlPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.Int32, ffi.Int64)>>('wire_stream_control');
+  late final _wire_stream_control = _wire_stream_controlPtr.asFunction<void Function(int, int, int)>();
+
+  void wire_reinit_bridge(
+    int token,
+  ) {
+    return _wire_reinit_bridge(
+      token,
+    );
+  }
+
+  late final _wire_reinit_bridgePtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_reinit_bridge');
+  late final _wire_reinit_bridge = _wire_reinit_bridgePtr.asFunction<void Function(int)>();
+
+  void wire_cancel_task(
+    int port_,
+  ) {
+    return _wire_cancel_task(
+      port_,
+    );
+  }
+
+  late final _wire_cancel_taskPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_cancel_task');
+  late final _wire_cancel_task = _wire_cancel_taskPtr.asFunction<void Function(int)>();
+
   void store_dart_post_cobject(
     DartPostCObjectFnType ptr,
   ) {
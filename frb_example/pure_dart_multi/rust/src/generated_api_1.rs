@@ -11,6 +11,7 @@
 // Generated by `flutter_rust_bridge`.
 
 use crate::api_1::*;
+use flutter_rust_bridge::support::IntoDart;
 use flutter_rust_bridge::*;
 
 // Section: imports
@@ -20,11 +21,7 @@ use flutter_rust_bridge::*;
 #[no_mangle]
 pub extern "C" fn wire_simple_adder_1(port_: i64, a: i32, b: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "simple_adder_1",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("simple_adder_1", Some(port_), FfiCallMode::Normal),
         move || {
             let api_a = a.wire2api();
             let api_b = b.wire2api();
@@ -83,6 +80,7 @@ impl<T> NewWithNullPtr for *mut T {
 // Section: executor
 
 support::lazy_static! {
+    /// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
     pub static ref FLUTTER_RUST_BRIDGE_HANDLER: support::DefaultHandler = Default::default();
 }
 
@@ -94,3 +92,29 @@ pub extern "C" fn free_WireSyncReturnStruct(val: support::WireSyncReturnStruct)
         let _ = support::vec_from_leak_ptr(val.ptr, val.len);
     }
 }
+
+#[no_mangle]
+pub extern "C" fn new_sync_outcome() -> *mut support::WireSyncReturnStruct {
+    support::new_sync_outcome()
+}
+
+// Section: stream control
+
+#[no_mangle]
+pub extern "C" fn wire_stream_control(port_: i64, command: i32, value: i64) {
+    support::stream_control(port_, command, value)
+}
+
+// Section: reinit
+
+#[no_mangle]
+pub extern "C" fn wire_reinit_bridge(token: i64) {
+    support::reinit(token)
+}
+
+// Section: cancel
+
+#[no_mangle]
+pub extern "C" fn wire_cancel_task(port_: i64) {
+    support::cancel_task(port_)
+}
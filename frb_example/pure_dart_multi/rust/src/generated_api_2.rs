@@ -20,11 +20,7 @@ use flutter_rust_bridge::*;
 #[no_mangle]
 pub extern "C" fn wire_simple_adder_2(port_: i64, a: i32, b: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "simple_adder_2",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("simple_adder_2", Some(port_), FfiCallMode::Normal),
         move || {
             let api_a = a.wire2api();
             let api_b = b.wire2api();
@@ -83,5 +79,6 @@ impl<T> NewWithNullPtr for *mut T {
 // Section: executor
 
 support::lazy_static! {
+    /// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
     pub static ref FLUTTER_RUST_BRIDGE_HANDLER: support::DefaultHandler = Default::default();
 }
@@ -35,7 +35,7 @@ typedef struct wire_list_size {
 typedef struct WireSyncReturnStruct {
   uint8_t *ptr;
   int32_t len;
-  bool success;
+  int32_t kind;
 } WireSyncReturnStruct;
 
 typedef int64_t DartPort;
@@ -84,6 +84,15 @@ struct wire_uint_8_list *new_uint_8_list_0(int32_t len);
 
 void free_WireSyncReturnStruct(struct WireSyncReturnStruct val);
 
+struct WireSyncReturnStruct *new_sync_outcome(void);
+
+
+void wire_stream_control(int64_t port_, int32_t command, int64_t value);
+
+void wire_reinit_bridge(int64_t token);
+
+void wire_cancel_task(int64_t port_);
+
 void store_dart_post_cobject(DartPostCObjectFnType ptr);
 
 static int64_t dummy_method_to_enforce_bundling(void) {
@@ -107,6 +116,10 @@ static int64_t dummy_method_to_enforce_bundling(void) {
     dummy_var ^= ((int64_t) (void*) new_list_tree_node_0);
     dummy_var ^= ((int64_t) (void*) new_uint_8_list_0);
     dummy_var ^= ((int64_t) (void*) free_WireSyncReturnStruct);
+    dummy_var ^= ((int64_t) (void*) new_sync_outcome);
+    dummy_var ^= ((int64_t) (void*) wire_stream_control);
+    dummy_var ^= ((int64_t) (void*) wire_reinit_bridge);
+    dummy_var ^= ((int64_t) (void*) wire_cancel_task);
     dummy_var ^= ((int64_t) (void*) store_dart_post_cobject);
     return dummy_var;
 }
\ No newline at end of file
@@ -11,6 +11,7 @@
 // Generated by `flutter_rust_bridge`.
 
 use crate::api::*;
+use flutter_rust_bridge::support::IntoDart;
 use flutter_rust_bridge::*;
 
 // Section: imports
@@ -26,11 +27,7 @@ pub extern "C" fn wire_draw_mandelbrot(
     num_threads: i32,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "draw_mandelbrot",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("draw_mandelbrot", Some(port_), FfiCallMode::Normal),
         move || {
             let api_image_size = image_size.wire2api();
             let api_zoom_point = zoom_point.wire2api();
@@ -46,11 +43,7 @@ pub extern "C" fn wire_draw_mandelbrot(
 #[no_mangle]
 pub extern "C" fn wire_passing_complex_structs(port_: i64, root: *mut wire_TreeNode) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "passing_complex_structs",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new("passing_complex_structs", Some(port_), FfiCallMode::Normal),
         move || {
             let api_root = root.wire2api();
             move |task_callback| Ok(passing_complex_structs(api_root))
@@ -61,11 +54,11 @@ pub extern "C" fn wire_passing_complex_structs(port_: i64, root: *mut wire_TreeN
 #[no_mangle]
 pub extern "C" fn wire_returning_structs_with_boxed_fields(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "returning_structs_with_boxed_fields",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "returning_structs_with_boxed_fields",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || move |task_callback| Ok(returning_structs_with_boxed_fields()),
     )
 }
@@ -73,11 +66,11 @@ pub extern "C" fn wire_returning_structs_with_boxed_fields(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_off_topic_memory_test_input_array(port_: i64, input: *mut wire_uint_8_list) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_input_array",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_input_array",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_input = input.wire2api();
             move |task_callback| Ok(off_topic_memory_test_input_array(api_input))
@@ -88,11 +81,11 @@ pub extern "C" fn wire_off_topic_memory_test_input_array(port_: i64, input: *mut
 #[no_mangle]
 pub extern "C" fn wire_off_topic_memory_test_output_zero_copy_buffer(port_: i64, len: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_output_zero_copy_buffer",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_output_zero_copy_buffer",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_len = len.wire2api();
             move |task_callback| Ok(off_topic_memory_test_output_zero_copy_buffer(api_len))
@@ -103,11 +96,11 @@ pub extern "C" fn wire_off_topic_memory_test_output_zero_copy_buffer(port_: i64,
 #[no_mangle]
 pub extern "C" fn wire_off_topic_memory_test_output_vec_u8(port_: i64, len: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_output_vec_u8",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_output_vec_u8",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_len = len.wire2api();
             move |task_callback| Ok(off_topic_memory_test_output_vec_u8(api_len))
@@ -121,11 +114,11 @@ pub extern "C" fn wire_off_topic_memory_test_input_vec_of_object(
     input: *mut wire_list_size,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_input_vec_of_object",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_input_vec_of_object",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_input = input.wire2api();
             move |task_callback| Ok(off_topic_memory_test_input_vec_of_object(api_input))
@@ -136,11 +129,11 @@ pub extern "C" fn wire_off_topic_memory_test_input_vec_of_object(
 #[no_mangle]
 pub extern "C" fn wire_off_topic_memory_test_output_vec_of_object(port_: i64, len: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_output_vec_of_object",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_output_vec_of_object",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_len = len.wire2api();
             move |task_callback| Ok(off_topic_memory_test_output_vec_of_object(api_len))
@@ -154,11 +147,11 @@ pub extern "C" fn wire_off_topic_memory_test_input_complex_struct(
     input: *mut wire_TreeNode,
 ) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_input_complex_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_input_complex_struct",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_input = input.wire2api();
             move |task_callback| Ok(off_topic_memory_test_input_complex_struct(api_input))
@@ -169,11 +162,11 @@ pub extern "C" fn wire_off_topic_memory_test_input_complex_struct(
 #[no_mangle]
 pub extern "C" fn wire_off_topic_memory_test_output_complex_struct(port_: i64, len: i32) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_memory_test_output_complex_struct",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_memory_test_output_complex_struct",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || {
             let api_len = len.wire2api();
             move |task_callback| Ok(off_topic_memory_test_output_complex_struct(api_len))
@@ -184,11 +177,11 @@ pub extern "C" fn wire_off_topic_memory_test_output_complex_struct(port_: i64, l
 #[no_mangle]
 pub extern "C" fn wire_off_topic_deliberately_return_error(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_deliberately_return_error",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_deliberately_return_error",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || move |task_callback| off_topic_deliberately_return_error(),
     )
 }
@@ -196,11 +189,11 @@ pub extern "C" fn wire_off_topic_deliberately_return_error(port_: i64) {
 #[no_mangle]
 pub extern "C" fn wire_off_topic_deliberately_panic(port_: i64) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap(
-        WrapInfo {
-            debug_name: "off_topic_deliberately_panic",
-            port: Some(port_),
-            mode: FfiCallMode::Normal,
-        },
+        WrapInfo::new(
+            "off_topic_deliberately_panic",
+            Some(port_),
+            FfiCallMode::Normal,
+        ),
         move || move |task_callback| Ok(off_topic_deliberately_panic()),
     )
 }
@@ -490,6 +483,7 @@ impl support::IntoDartExceptPrimitive for TreeNode {}
 // Section: executor
 
 support::lazy_static! {
+    /// A Rust function panicking on a worker fails its call with a `PANIC_ERROR`, and the worker goes on with the next calls.
     pub static ref FLUTTER_RUST_BRIDGE_HANDLER: support::DefaultHandler = Default::default();
 }
 
@@ -501,3 +495,29 @@ pub extern "C" fn free_WireSyncReturnStruct(val: support::WireSyncReturnStruct)
         let _ = support::vec_from_leak_ptr(val.ptr, val.len);
     }
 }
+
+#[no_mangle]
+pub extern "C" fn new_sync_outcome() -> *mut support::WireSyncReturnStruct {
+    support::new_sync_outcome()
+}
+
+// Section: stream control
+
+#[no_mangle]
+pub extern "C" fn wire_stream_control(port_: i64, command: i32, value: i64) {
+    support::stream_control(port_, command, value)
+}
+
+// Section: reinit
+
+#[no_mangle]
+pub extern "C" fn wire_reinit_bridge(token: i64) {
+    support::reinit(token)
+}
+
+// Section: cancel
+
+#[no_mangle]
+pub extern "C" fn wire_cancel_task(port_: i64) {
+    support::cancel_task(port_)
+}
@@ -0,0 +1,64 @@
+//! Cancellation of the tasks whose Dart `Future` is not awaited anymore, requested through
+//! `RustTaskHandle.cancel` on the Dart side.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+lazy_static! {
+    /// Whether cancellation has been requested, for each pending or running task by port.
+    static ref TASKS: Mutex<HashMap<i64, bool>> = Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// The port of the task running on this thread.
+    static CURRENT_TASK: Cell<Option<i64>> = const { Cell::new(None) };
+}
+
+/// Whether Dart has cancelled the task running on the current thread. Long-running functions
+/// may check it from time to time, and return early when it becomes true.
+pub fn is_cancelled() -> bool {
+    matches!(
+        CURRENT_TASK.with(Cell::get),
+        Some(port) if TASKS.lock().get(&port) == Some(&true)
+    )
+}
+
+/// Starts tracking the task answering to `port`, before it is queued.
+pub fn register(port: i64) {
+    TASKS.lock().insert(port, false);
+}
+
+/// Requests the cancellation of the task answering to `port`, unless it has already finished.
+pub fn cancel(port: i64) {
+    if let Some(cancelled) = TASKS.lock().get_mut(&port) {
+        *cancelled = true;
+    }
+}
+
+/// Marks the task answering to `port` as running on the current thread until the returned guard
+/// is dropped. Returns [None] if it has been cancelled while queued, so that it should be dropped.
+pub fn start(port: i64) -> Option<RunningTask> {
+    let mut tasks = TASKS.lock();
+    if tasks.get(&port) == Some(&true) {
+        tasks.remove(&port);
+        return None;
+    }
+    drop(tasks);
+    CURRENT_TASK.with(|current| current.set(Some(port)));
+    Some(RunningTask { port })
+}
+
+/// See [start].
+pub struct RunningTask {
+    port: i64,
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        CURRENT_TASK.with(|current| current.set(None));
+        TASKS.lock().remove(&self.port);
+    }
+}
@@ -0,0 +1,45 @@
+//! Limit on the concurrent calls of a function marked `#[frb(max_concurrency = N)]`, e.g. to wrap a
+//! C library which is not thread-safe.
+
+use parking_lot::{Condvar, Mutex};
+
+/// A counting semaphore, declared by the generated code as one `static` per limited function.
+pub struct ConcurrencyLimit {
+    max: usize,
+    running: Mutex<usize>,
+    released: Condvar,
+}
+
+impl ConcurrencyLimit {
+    pub const fn new(max: usize) -> Self {
+        Self {
+            max,
+            running: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Calls `f` once fewer than `max` other calls are running, blocking the current thread
+    /// until then.
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let mut running = self.running.lock();
+        while *running >= self.max {
+            self.released.wait(&mut running);
+        }
+        *running += 1;
+        drop(running);
+
+        // Released even if `f` panics, since the handler catches the panic and goes on.
+        let _permit = Permit(self);
+        f()
+    }
+}
+
+struct Permit<'a>(&'a ConcurrencyLimit);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.0.running.lock() -= 1;
+        self.0.released.notify_one();
+    }
+}
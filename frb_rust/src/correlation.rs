@@ -0,0 +1,42 @@
+//! Correlation ids of the calls from Dart, so that the logs and errors of concurrent calls can be
+//! told apart, and matched with the Dart calls which caused them through `FfiException.callId`.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// The id of the call running on this thread.
+    static CURRENT_CALL: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// A new id for the [WrapInfo](crate::WrapInfo) of a call. Ids are never reused while the app runs.
+pub(crate) fn next_call_id() -> u64 {
+    NEXT_CALL_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The id of the call from Dart running on the current thread, to be included in log records.
+/// [None] outside of a call, e.g. on a thread spawned by the function.
+pub fn current_call_id() -> Option<u64> {
+    CURRENT_CALL.with(Cell::get)
+}
+
+/// Marks the call `call_id` as running on the current thread until the returned guard is dropped.
+/// Custom executors running the tasks on threads of their own should do it around each task.
+pub fn enter(call_id: u64) -> CallScope {
+    CallScope {
+        previous: CURRENT_CALL.with(|current| current.replace(Some(call_id))),
+    }
+}
+
+/// See [enter].
+pub struct CallScope {
+    previous: Option<u64>,
+}
+
+impl Drop for CallScope {
+    fn drop(&mut self) {
+        CURRENT_CALL.with(|current| current.set(self.previous));
+    }
+}
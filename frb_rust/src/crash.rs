@@ -0,0 +1,97 @@
+//! Reports the panics of the functions called from Dart, with where and on which thread they
+//! happened, to the Dart streams of `rustCrashes()`, e.g. to forward them to a crash reporting
+//! service along with the crashes of the app itself.
+
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic;
+use std::thread;
+
+use allo_isolate::IntoDart;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+use crate::handler::Error;
+use crate::rust2dart::Rust2Dart;
+use crate::StreamSink;
+
+lazy_static! {
+    /// The panic hook is installed on first use, and stays until the process exits.
+    static ref HOOKED: () = install_hook();
+    /// The Dart streams receiving the crashes.
+    static ref SINKS: Mutex<Vec<StreamSink<Crash>>> = Mutex::new(Vec::new());
+}
+
+thread_local! {
+    /// What the panic hook saw of the last panic of this thread, taken once the panic is caught.
+    static LAST_PANIC: RefCell<Option<PanicDetails>> = const { RefCell::new(None) };
+}
+
+struct PanicDetails {
+    location: Option<String>,
+    backtrace: String,
+}
+
+#[derive(Clone)]
+struct Crash {
+    function: String,
+    message: String,
+    thread: Option<String>,
+    location: Option<String>,
+    backtrace: Option<String>,
+}
+
+impl IntoDart for Crash {
+    fn into_dart(self) -> allo_isolate::ffi::DartCObject {
+        vec![
+            self.function.into_dart(),
+            self.message.into_dart(),
+            self.thread.into_dart(),
+            self.location.into_dart(),
+            self.backtrace.into_dart(),
+        ]
+        .into_dart()
+    }
+}
+
+/// Adds the stream of `port` to those receiving the crashes, from now on.
+pub(crate) fn listen(port: i64) {
+    lazy_static::initialize(&HOOKED);
+    SINKS.lock().push(StreamSink::new(Rust2Dart::new(port)));
+}
+
+/// Records the location and backtrace of every panic, as they are lost once it is caught, and
+/// then runs the previous hook, which by default prints the panic.
+fn install_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let details = PanicDetails {
+            location: info.location().map(ToString::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+        };
+        // panicking in a panic hook aborts, e.g. while the thread-local is being destroyed
+        let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(details));
+        previous(info);
+    }));
+}
+
+/// Sends the panic of `function`, which has just been caught on the current thread, to every
+/// stream, forgetting those which nobody listens to anymore.
+pub(crate) fn report(function: &str, error: &Error) {
+    let details = LAST_PANIC
+        .try_with(|last| last.borrow_mut().take())
+        .ok()
+        .flatten();
+    let mut sinks = SINKS.lock();
+    if sinks.is_empty() {
+        return;
+    }
+    let crash = Crash {
+        function: function.to_owned(),
+        message: error.message(),
+        thread: thread::current().name().map(str::to_owned),
+        location: details.as_ref().and_then(|it| it.location.clone()),
+        backtrace: details.map(|it| it.backtrace),
+    };
+    sinks.retain(|sink| sink.add(crash.clone()));
+}
@@ -0,0 +1,147 @@
+//! Manages receiving values pushed by Dart, the opposite direction of [StreamSink](crate::StreamSink).
+
+use std::io::{self, Read};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+/// A stream of values pushed by Dart, e.g. file chunks or audio frames, to a long-running
+/// Rust function. Represented as a Dart
+/// [`Stream`](https://api.dart.dev/stable/dart-async/Stream-class.html) parameter.
+///
+/// Iterating blocks until the next value arrives, and ends once the Dart stream is done,
+/// or once the Dart side has been restarted, e.g. by a Flutter hot restart.
+pub struct DartStream<T> {
+    receiver: Receiver<T>,
+}
+
+impl<T> DartStream<T> {
+    /// Waits for the next value. Returns `None` once the Dart stream is done, or has failed.
+    pub fn recv(&self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+
+    /// Same as [DartStream::recv], but gives up after `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl<T> Iterator for DartStream<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
+
+impl DartStream<Vec<u8>> {
+    /// Reads the chunks of bytes pushed by Dart as one continuous [Read], e.g. to process a file
+    /// without holding all of it in memory. The reader reaches its end with the Dart stream.
+    pub fn into_reader(self) -> DartStreamReader {
+        DartStreamReader {
+            stream: self,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+/// A [Read] over the bytes pushed by Dart, see [DartStream::into_reader].
+pub struct DartStreamReader {
+    stream: DartStream<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for DartStreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // empty chunks are skipped, since reading nothing would mean the end
+        while self.position == self.chunk.len() {
+            match self.stream.recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// A [DartStreamChannel] of any type.
+trait Closable: Send + Sync {
+    fn close(&self);
+}
+
+impl<T: Send> Closable for DartStreamChannel<T> {
+    fn close(&self) {
+        DartStreamChannel::close(self)
+    }
+}
+
+lazy_static! {
+    /// The channels pushed to by Dart, which the Dart side of a previous session can't close.
+    static ref CHANNELS: Mutex<Vec<Weak<dyn Closable>>> = Mutex::new(Vec::new());
+}
+
+/// Ends the [DartStream]s of the previous Dart session, whose pushing side is gone.
+pub(crate) fn close_stale_streams() {
+    for channel in CHANNELS.lock().drain(..) {
+        if let Some(channel) = channel.upgrade() {
+            channel.close();
+        }
+    }
+}
+
+/// Both ends of the channel behind a handle, see [crate::support::new_dart_stream].
+pub(crate) struct DartStreamChannel<T> {
+    sender: Mutex<Option<Sender<T>>>,
+    receiver: Mutex<Option<Receiver<T>>>,
+}
+
+impl<T> DartStreamChannel<T> {
+    pub(crate) fn new() -> Arc<Self>
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let channel = Arc::new(Self {
+            sender: Mutex::new(Some(sender)),
+            receiver: Mutex::new(Some(receiver)),
+        });
+        let mut channels = CHANNELS.lock();
+        channels.retain(|channel| channel.strong_count() > 0);
+        channels.push(Arc::downgrade(&channel) as Weak<dyn Closable>);
+        channel
+    }
+
+    /// Values pushed after the Rust function has dropped its [DartStream] are ignored.
+    pub(crate) fn add(&self, value: T) {
+        if let Some(sender) = &*self.sender.lock() {
+            let _ = sender.send(value);
+        }
+    }
+
+    /// Ends the stream once the values already pushed are received.
+    pub(crate) fn close(&self) {
+        self.sender.lock().take();
+    }
+
+    pub(crate) fn take(&self) -> DartStream<T> {
+        DartStream {
+            receiver: self
+                .receiver
+                .lock()
+                .take()
+                .expect("DartStream has already been taken"),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Events sent from anywhere in the Rust code to Dart, without a pending call from Dart.
+
+use parking_lot::{const_mutex, Mutex};
+
+use crate::support::IntoDart;
+use crate::StreamSink;
+
+/// A type marked with `#[frb(event)]` in the API file, for which the code generator implements
+/// this trait and a Dart broadcast stream of its values.
+pub trait Event: IntoDart + Sized + 'static {
+    /// The sink of the Dart stream, created by the generated code.
+    fn event_sink() -> &'static EventSink<Self>;
+
+    /// Sends this event to Dart. Returns false when nobody listens on the Dart side yet, or the
+    /// event could not be sent.
+    fn emit(self) -> bool {
+        Self::event_sink().emit(self)
+    }
+}
+
+/// Holds the sink Dart listens to the events of one type with.
+pub struct EventSink<T: IntoDart> {
+    sink: Mutex<Option<StreamSink<T>>>,
+}
+
+impl<T: IntoDart> EventSink<T> {
+    pub const fn new() -> Self {
+        Self {
+            sink: const_mutex(None),
+        }
+    }
+
+    /// Called when Dart starts listening, which replaces the sink of a previous Dart session.
+    pub fn register(&self, sink: StreamSink<T>) {
+        *self.sink.lock() = Some(sink);
+    }
+
+    /// Sends `value` to Dart, see [Event::emit]. Like [StreamSink::add], blocks while the Dart
+    /// stream is paused.
+    pub fn emit(&self, value: T) -> bool {
+        match &*self.sink.lock() {
+            Some(sink) => sink.add(value),
+            None => false,
+        }
+    }
+}
+
+impl<T: IntoDart> Default for EventSink<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
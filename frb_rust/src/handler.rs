@@ -1,9 +1,13 @@
 //! Wrappers and executors for Rust functions.
 
 use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::mem::ManuallyDrop;
 use std::panic;
-use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::panic::{AssertUnwindSafe, RefUnwindSafe, UnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use allo_isolate::IntoDart;
 use anyhow::Result;
@@ -11,12 +15,16 @@ use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use threadpool::ThreadPool;
 
+use crate::cancel;
+use crate::correlation;
+use crate::crash;
 use crate::rust2dart::{Rust2Dart, TaskCallback};
-use crate::support::{into_leak_vec_ptr, WireSyncReturnStruct};
+use crate::support::{into_leak_vec_ptr, SyncReturnKind, WireSyncReturnStruct};
+use crate::trace::CallSpan;
 use crate::SyncReturn;
 
 /// The types of return values for a particular Rust function.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum FfiCallMode {
     /// The default mode, returns a Dart `Future<T>`.
     Normal,
@@ -27,7 +35,10 @@ pub enum FfiCallMode {
 }
 
 /// Supporting information to idenfity a function's operating mode.
+///
+/// More fields may be added, so it is built with [WrapInfo::new].
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct WrapInfo {
     /// A Dart `SendPort`. [None] if the mode is [FfiCallMode::Sync].
     pub port: Option<i64>,
@@ -35,6 +46,20 @@ pub struct WrapInfo {
     pub debug_name: &'static str,
     /// The call mode of this function.
     pub mode: FfiCallMode,
+    /// Identifies this call in the logs and errors, see [current_call_id](crate::current_call_id).
+    pub call_id: u64,
+}
+
+impl WrapInfo {
+    /// The information of a new call from Dart, with a new [call_id](Self::call_id).
+    pub fn new(debug_name: &'static str, port: Option<i64>, mode: FfiCallMode) -> Self {
+        WrapInfo {
+            port,
+            debug_name,
+            mode,
+            call_id: crate::correlation::next_call_id(),
+        }
+    }
 }
 
 /// Provide your own handler to customize how to execute your function calls, etc.
@@ -64,6 +89,63 @@ pub trait Handler {
     ) -> WireSyncReturnStruct
     where
         SyncTaskFn: FnOnce() -> Result<SyncReturn<Vec<u8>>> + UnwindSafe;
+
+    /// Same as [`wrap_sync`](Handler::wrap_sync), for the Rust functions returning an optional
+    /// value, where a [None] inside the [SyncReturn] becomes `null` in Dart.
+    fn wrap_sync_nullable<SyncTaskFn>(
+        &self,
+        wrap_info: WrapInfo,
+        sync_task: SyncTaskFn,
+    ) -> WireSyncReturnStruct
+    where
+        SyncTaskFn: FnOnce() -> Result<SyncReturn<Option<Vec<u8>>>> + UnwindSafe,
+    {
+        let mut null = false;
+        let mut outcome = self.wrap_sync(
+            wrap_info,
+            AssertUnwindSafe(|| {
+                sync_task().map(|SyncReturn(bytes)| {
+                    SyncReturn(bytes.unwrap_or_else(|| {
+                        null = true;
+                        Vec::new()
+                    }))
+                })
+            }),
+        );
+        if null && outcome.kind == SyncReturnKind::Success as i32 {
+            outcome.kind = SyncReturnKind::Null as i32;
+        }
+        outcome
+    }
+
+    /// Same as [`wrap_sync`](Handler::wrap_sync), for the functions taking and returning
+    /// primitives, whose value is returned as is instead of being encoded. The outcome tells Dart
+    /// whether the function panicked, in which case the value is `T::default()`.
+    fn wrap_primitive_sync<T, SyncTaskFn>(
+        &self,
+        wrap_info: WrapInfo,
+        sync_task: SyncTaskFn,
+    ) -> (T, WireSyncReturnStruct)
+    where
+        T: Default,
+        SyncTaskFn: FnOnce() -> T + UnwindSafe,
+    {
+        let mut value = None;
+        let outcome = self.wrap_sync(
+            wrap_info,
+            AssertUnwindSafe(|| {
+                value = Some(sync_task());
+                Ok(SyncReturn(Vec::new()))
+            }),
+        );
+        (value.unwrap_or_default(), outcome)
+    }
+
+    /// Reports the load of the underlying executor. Handlers that do not track it report an
+    /// empty [ExecutorHealth].
+    fn health(&self) -> ExecutorHealth {
+        ExecutorHealth::default()
+    }
 }
 
 /// The simple handler uses a simple thread pool to execute tasks.
@@ -95,6 +177,16 @@ impl Default for DefaultHandler {
     }
 }
 
+impl DefaultHandler {
+    /// The default handler, doing what `policy` says when a function panics on a worker.
+    pub fn with_panic_policy(policy: PanicPolicy) -> Self {
+        Self::new(
+            ThreadPoolExecutor::new(ReportDartErrorHandler).with_panic_policy(policy),
+            ReportDartErrorHandler,
+        )
+    }
+}
+
 impl<E: Executor, EH: ErrorHandler> Handler for SimpleHandler<E, EH> {
     fn wrap<PrepareFn, TaskFn, TaskRet>(&self, wrap_info: WrapInfo, prepare: PrepareFn)
     where
@@ -111,13 +203,22 @@ impl<E: Executor, EH: ErrorHandler> Handler for SimpleHandler<E, EH> {
         // as well. Then that new panic will go across language boundary and cause UB.
         // ref https://doc.rust-lang.org/nomicon/unwinding.html
         let _ = panic::catch_unwind(move || {
+            let _call = correlation::enter(wrap_info.call_id);
             let wrap_info2 = wrap_info.clone();
+            let span = CallSpan::new(&wrap_info);
             if let Err(error) = panic::catch_unwind(move || {
-                let task = prepare();
-                self.executor.execute(wrap_info2, task);
+                span.in_scope(|| {
+                    let task = prepare();
+                    let span = span.clone();
+                    self.executor.execute(wrap_info2, move |task_callback| {
+                        span.in_scope(|| task(task_callback))
+                    });
+                })
             }) {
+                let error = Error::Panic(error);
+                crash::report(wrap_info.debug_name, &error);
                 self.error_handler
-                    .handle_error(wrap_info.port.unwrap(), Error::Panic(error));
+                    .handle_error(wrap_info.port.unwrap(), error);
             }
         });
     }
@@ -133,27 +234,39 @@ impl<E: Executor, EH: ErrorHandler> Handler for SimpleHandler<E, EH> {
         // NOTE This extra [catch_unwind] **SHOULD** be put outside **ALL** code!
         // For reason, see comments in [wrap]
         panic::catch_unwind(move || {
+            let _call = correlation::enter(wrap_info.call_id);
+            let debug_name = wrap_info.debug_name;
             let catch_unwind_result = panic::catch_unwind(move || {
-                match self.executor.execute_sync(wrap_info, sync_task) {
-                    Ok(data) => (data.0, true),
+                let span = CallSpan::new(&wrap_info);
+                match span.in_scope(|| self.executor.execute_sync(wrap_info, sync_task)) {
+                    Ok(SyncReturn(bytes)) => {
+                        span.record_output_bytes(bytes.len());
+                        (bytes, SyncReturnKind::Success)
+                    }
                     Err(err) => (
                         self.error_handler
                             .handle_error_sync(Error::ResultError(err)),
-                        false,
+                        SyncReturnKind::Error,
                     ),
                 }
             });
 
-            let (bytes, success) = catch_unwind_result.unwrap_or_else(|error| {
+            let (bytes, kind) = catch_unwind_result.unwrap_or_else(|error| {
+                let error = Error::Panic(error);
+                crash::report(debug_name, &error);
                 (
-                    self.error_handler.handle_error_sync(Error::Panic(error)),
-                    false,
+                    self.error_handler.handle_error_sync(error),
+                    SyncReturnKind::Error,
                 )
             });
 
             let (ptr, len) = into_leak_vec_ptr(bytes);
 
-            WireSyncReturnStruct { ptr, len, success }
+            WireSyncReturnStruct {
+                ptr,
+                len,
+                kind: kind as i32,
+            }
         })
         .unwrap_or_else(|_| WireSyncReturnStruct {
             // return the simplest thing possible. Normally the inner [catch_unwind] should catch
@@ -162,9 +275,13 @@ impl<E: Executor, EH: ErrorHandler> Handler for SimpleHandler<E, EH> {
             // than let panic happen across FFI boundary - which is undefined behavior.
             ptr: ManuallyDrop::new(Vec::<u8>::new()).as_mut_ptr(),
             len: 0,
-            success: false,
+            kind: SyncReturnKind::Error as i32,
         })
     }
+
+    fn health(&self) -> ExecutorHealth {
+        self.executor.health()
+    }
 }
 
 /// An executor model for Rust functions.
@@ -187,6 +304,58 @@ pub trait Executor: RefUnwindSafe {
     ) -> Result<SyncReturn<Vec<u8>>>
     where
         SyncTaskFn: FnOnce() -> Result<SyncReturn<Vec<u8>>> + UnwindSafe;
+
+    /// Reports the current load. Executors that do not track it report an empty [ExecutorHealth].
+    fn health(&self) -> ExecutorHealth {
+        ExecutorHealth::default()
+    }
+}
+
+/// A snapshot of the load of an [Executor], so that apps can detect when it is saturated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutorHealth {
+    /// Number of tasks waiting for a worker.
+    pub queued_count: usize,
+    /// Number of tasks being executed.
+    pub active_count: usize,
+    /// How long the oldest waiting task has been queued, zero if none is waiting.
+    pub oldest_pending_age: Duration,
+}
+
+impl IntoDart for ExecutorHealth {
+    fn into_dart(self) -> allo_isolate::ffi::DartCObject {
+        vec![
+            self.queued_count as i64,
+            self.active_count as i64,
+            self.oldest_pending_age.as_millis() as i64,
+        ]
+        .into_dart()
+    }
+}
+
+const NUM_WORKERS: usize = 4;
+lazy_static! {
+    static ref THREAD_POOL: Mutex<ThreadPool> = Mutex::new(ThreadPool::with_name(
+        "frb_executor".to_string(),
+        NUM_WORKERS
+    ));
+    // Enqueue time of tasks not yet picked up by a worker, keyed by increasing task id.
+    static ref PENDING_TASKS: Mutex<BTreeMap<u64, Instant>> = Mutex::new(BTreeMap::new());
+}
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(0);
+
+/// What [ThreadPoolExecutor] does when a function panics on one of its workers.
+#[derive(Clone, Copy)]
+pub enum PanicPolicy {
+    /// Fails the call with a `PANIC_ERROR` in Dart, and lets the worker go on with the next
+    /// tasks. The default.
+    Recover,
+    /// Aborts the process, e.g. so that the app is restarted from a clean state rather than going
+    /// on after an invariant is broken.
+    Abort,
+    /// Calls the function with the name of the Rust function and the panic, then recovers as
+    /// with [PanicPolicy::Recover], e.g. to reset some state or to log the panic.
+    Callback(fn(&'static str, &Error)),
 }
 
 /// The default executor used.
@@ -194,12 +363,24 @@ pub trait Executor: RefUnwindSafe {
 /// handled by a different thread.
 pub struct ThreadPoolExecutor<EH: ErrorHandler> {
     error_handler: EH,
+    panic_policy: PanicPolicy,
 }
 
 impl<EH: ErrorHandler> ThreadPoolExecutor<EH> {
     /// Create a new executor backed by a thread pool.
     pub fn new(error_handler: EH) -> Self {
-        ThreadPoolExecutor { error_handler }
+        ThreadPoolExecutor {
+            error_handler,
+            panic_policy: PanicPolicy::Recover,
+        }
+    }
+
+    /// Sets what to do when a function panics on a worker, [PanicPolicy::Recover] by default.
+    pub fn with_panic_policy(self, panic_policy: PanicPolicy) -> Self {
+        ThreadPoolExecutor {
+            panic_policy,
+            ..self
+        }
     }
 }
 
@@ -209,21 +390,36 @@ impl<EH: ErrorHandler> Executor for ThreadPoolExecutor<EH> {
         TaskFn: FnOnce(TaskCallback) -> Result<TaskRet> + Send + UnwindSafe + 'static,
         TaskRet: IntoDart,
     {
-        const NUM_WORKERS: usize = 4;
-        lazy_static! {
-            static ref THREAD_POOL: Mutex<ThreadPool> = Mutex::new(ThreadPool::with_name(
-                "frb_executor".to_string(),
-                NUM_WORKERS
-            ));
-        }
-
         let eh = self.error_handler;
         let eh2 = self.error_handler;
+        let panic_policy = self.panic_policy;
+        let task_id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+        PENDING_TASKS.lock().insert(task_id, Instant::now());
+        if let FfiCallMode::Normal = wrap_info.mode {
+            cancel::register(wrap_info.port.unwrap());
+        }
         THREAD_POOL.lock().execute(move || {
+            PENDING_TASKS.lock().remove(&task_id);
+            let _call = correlation::enter(wrap_info.call_id);
             let wrap_info2 = wrap_info.clone();
             let thread_result = panic::catch_unwind(move || {
                 let rust2dart = Rust2Dart::new(wrap_info2.port.unwrap());
 
+                let _running = if let FfiCallMode::Normal = wrap_info2.mode {
+                    match cancel::start(wrap_info2.port.unwrap()) {
+                        Some(running) => Some(running),
+                        None => {
+                            rust2dart.error(
+                                "CANCELLED".to_string(),
+                                "The task has been cancelled before it started".to_string(),
+                            );
+                            return;
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 let ret = task(TaskCallback::new(rust2dart)).map(|ret| ret.into_dart());
 
                 match ret {
@@ -247,7 +443,19 @@ impl<EH: ErrorHandler> Executor for ThreadPoolExecutor<EH> {
             });
 
             if let Err(error) = thread_result {
-                eh.handle_error(wrap_info.port.unwrap(), Error::Panic(error));
+                let error = Error::Panic(error);
+                crash::report(wrap_info.debug_name, &error);
+                match panic_policy {
+                    PanicPolicy::Recover => {}
+                    PanicPolicy::Abort => std::process::abort(),
+                    PanicPolicy::Callback(callback) => {
+                        // a panicking callback must not keep the call from completing
+                        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                            callback(wrap_info.debug_name, &error)
+                        }));
+                    }
+                }
+                eh.handle_error(wrap_info.port.unwrap(), error);
             }
         });
     }
@@ -262,6 +470,21 @@ impl<EH: ErrorHandler> Executor for ThreadPoolExecutor<EH> {
     {
         sync_task()
     }
+
+    fn health(&self) -> ExecutorHealth {
+        let pool = THREAD_POOL.lock();
+        let oldest_pending_age = PENDING_TASKS
+            .lock()
+            .values()
+            .next()
+            .map(Instant::elapsed)
+            .unwrap_or_default();
+        ExecutorHealth {
+            queued_count: pool.queued_count(),
+            active_count: pool.active_count(),
+            oldest_pending_age,
+        }
+    }
 }
 
 /// Errors that occur from normal code execution.
@@ -296,8 +519,71 @@ impl Error {
             .to_string(),
         }
     }
+
+    /// The Rust type of the error, if it was returned as a `Box<dyn Error>` of a known type.
+    /// See [register_error_type].
+    pub fn error_type(&self) -> Option<&'static str> {
+        match self {
+            Error::ResultError(e) => e.downcast_ref::<DynError>()?.type_name,
+            Error::Panic(_) => None,
+        }
+    }
+}
+
+type ErrorTypeMatcher = fn(&(dyn std::error::Error + 'static)) -> Option<&'static str>;
+
+fn match_error_type<E: std::error::Error + 'static>(
+    error: &(dyn std::error::Error + 'static),
+) -> Option<&'static str> {
+    error.is::<E>().then(std::any::type_name::<E>)
 }
 
+lazy_static! {
+    static ref ERROR_TYPES: Mutex<Vec<ErrorTypeMatcher>> = Mutex::new(vec![
+        match_error_type::<std::io::Error>,
+        match_error_type::<std::fmt::Error>,
+        match_error_type::<std::num::ParseIntError>,
+        match_error_type::<std::num::ParseFloatError>,
+        match_error_type::<std::num::TryFromIntError>,
+        match_error_type::<std::str::ParseBoolError>,
+        match_error_type::<std::str::Utf8Error>,
+        match_error_type::<std::string::FromUtf8Error>,
+    ]);
+}
+
+/// Makes the type name of `E` available to Dart (as `FfiException.errorType`) when a function
+/// returns it as a `Box<dyn Error>`. Common errors of the standard library are registered already.
+///
+/// A boxed error is opaque, so its concrete type can only be recovered by trying to downcast it
+/// to each registered type.
+pub fn register_error_type<E: std::error::Error + 'static>() {
+    ERROR_TYPES.lock().push(match_error_type::<E>);
+}
+
+/// A `Box<dyn Error>` returned by a function, converted so it can be sent to another thread.
+#[derive(Debug)]
+pub(crate) struct DynError {
+    type_name: Option<&'static str>,
+    message: String,
+}
+
+impl DynError {
+    pub(crate) fn new(error: &(dyn std::error::Error + 'static)) -> Self {
+        Self {
+            type_name: ERROR_TYPES.lock().iter().find_map(|matcher| matcher(error)),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DynError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DynError {}
+
 /// A handler model that sends back the error to a Dart `SendPort`.
 ///
 /// For example, instead of using the default [`ReportDartErrorHandler`],
@@ -317,7 +603,11 @@ pub struct ReportDartErrorHandler;
 
 impl ErrorHandler for ReportDartErrorHandler {
     fn handle_error(&self, port: i64, error: Error) {
-        Rust2Dart::new(port).error(error.code().to_string(), error.message());
+        Rust2Dart::new(port).error_full(
+            error.code().to_string(),
+            error.message(),
+            error.error_type().map(str::to_owned),
+        );
     }
 
     fn handle_error_sync(&self, error: Error) -> Vec<u8> {
@@ -1,12 +1,29 @@
 pub use allo_isolate::ZeroCopyBuffer;
 
+pub use cancel::is_cancelled;
+pub use correlation::current_call_id;
+pub use dart2rust::DartStream;
 pub use flutter_rust_bridge_macros::frb;
-pub use handler::{FfiCallMode, Handler, WrapInfo};
-pub use rust2dart::StreamSink;
+pub use handler::{register_error_type, ExecutorHealth, FfiCallMode, Handler, WrapInfo};
+pub use opaque::RustOpaque;
+pub use rust2dart::{StreamSetup, StreamSink};
+pub use session::on_reinit;
 
+pub mod cancel;
+mod concurrency;
+pub mod correlation;
+mod crash;
+pub mod dart2rust;
+pub mod event;
 pub mod handler;
+mod opaque;
+#[cfg(feature = "rpc")]
+pub mod rpc;
 pub mod rust2dart;
+mod session;
+mod stdio;
 pub mod support;
+mod trace;
 
 /// Use this struct in return type of your function, in order to tell the code generator
 /// the function should return synchronously. Otherwise, it is by default asynchronously.
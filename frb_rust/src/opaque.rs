@@ -0,0 +1,50 @@
+//! Values which stay in Rust, and which Dart only holds handles to.
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A value handed to Dart as an opaque handle, e.g. a database connection, which Dart passes
+/// back to other functions without ever seeing its content.
+///
+/// Cloning it, on either side, shares the same value, which is dropped once the last clone is,
+/// i.e. once every Dart handle to it has been disposed of, and every Rust clone dropped.
+/// Since functions use it from the worker threads, `T` must be `Send + Sync`.
+pub struct RustOpaque<T>(pub(crate) Arc<T>);
+
+impl<T> RustOpaque<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Whether both share the same value, which is what `==` compares in Dart.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T> From<T> for RustOpaque<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Clone for RustOpaque<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for RustOpaque<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RustOpaque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,181 @@
+//! A JSON-RPC 2.0 gateway to the API, generated with `--rpc-gateway`, so that the functions called
+//! through FFI on mobile can also be called over the network, e.g. by a desktop companion app.
+//!
+//! Each API block gets an `rpc_dispatch` function calling its functions by name. [serve] answers
+//! the requests of TCP clients with it, one JSON object per line, while [handle] answers a single
+//! request, for any other transport such as HTTP or WebSocket.
+
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+pub use serde_json::Value;
+
+/// The function generated in each API block, calling the function named `method`.
+pub type Dispatch = fn(method: &str, params: Value) -> Result<Value, RpcError>;
+
+/// An error object of JSON-RPC 2.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// Returned by the API function itself, with its `Debug` output as the message.
+    pub const API_ERROR: i64 = -32000;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, format!("no method `{}`", method))
+    }
+
+    fn to_json(&self) -> Value {
+        serde_json::json!({ "code": self.code, "message": self.message })
+    }
+}
+
+/// The parameters of a request, either by position or by the names of the Rust parameters.
+pub struct Params {
+    value: Value,
+}
+
+impl Params {
+    pub fn new(value: Value) -> Result<Self, RpcError> {
+        match value {
+            Value::Array(_) | Value::Object(_) | Value::Null => Ok(Params { value }),
+            _ => Err(RpcError::new(
+                RpcError::INVALID_PARAMS,
+                "params should be an array or an object",
+            )),
+        }
+    }
+
+    /// Deserializes the parameter at `index`, or named `name`. A missing one is read as `null`,
+    /// which is what an absent `Option` would be.
+    pub fn take<T: DeserializeOwned>(&mut self, index: usize, name: &str) -> Result<T, RpcError> {
+        let value = match &mut self.value {
+            Value::Array(items) => items.get_mut(index).map(Value::take),
+            Value::Object(fields) => fields.remove(name),
+            _ => None,
+        };
+        serde_json::from_value(value.unwrap_or(Value::Null)).map_err(|error| {
+            RpcError::new(
+                RpcError::INVALID_PARAMS,
+                format!("invalid param `{}`: {}", name, error),
+            )
+        })
+    }
+}
+
+/// Serializes the output of an infallible function.
+pub fn respond<T: Serialize>(output: T) -> Result<Value, RpcError> {
+    serde_json::to_value(output)
+        .map_err(|error| RpcError::new(RpcError::INTERNAL_ERROR, error.to_string()))
+}
+
+/// Serializes the output of a fallible function.
+pub fn respond_result<T: Serialize, E: Debug>(output: Result<T, E>) -> Result<Value, RpcError> {
+    respond(output.map_err(|error| RpcError::new(RpcError::API_ERROR, format!("{:?}", error)))?)
+}
+
+/// Answers one request, or a batch of them, with the functions of `dispatch`. Returns [None] when
+/// there is nothing to answer, i.e. only notifications.
+pub fn handle(dispatch: Dispatch, request: &str) -> Option<String> {
+    let response = match serde_json::from_str::<Value>(request) {
+        Ok(Value::Array(requests)) if !requests.is_empty() => {
+            let responses = requests
+                .into_iter()
+                .filter_map(|request| handle_value(dispatch, request))
+                .collect::<Vec<_>>();
+            if responses.is_empty() {
+                return None;
+            }
+            Value::Array(responses)
+        }
+        Ok(request) => handle_value(dispatch, request)?,
+        Err(error) => response(
+            Value::Null,
+            Err(RpcError::new(RpcError::PARSE_ERROR, error.to_string())),
+        ),
+    };
+    Some(response.to_string())
+}
+
+fn handle_value(dispatch: Dispatch, request: Value) -> Option<Value> {
+    let mut request = match request {
+        Value::Object(request) => request,
+        _ => {
+            return Some(response(
+                Value::Null,
+                Err(RpcError::new(
+                    RpcError::INVALID_REQUEST,
+                    "request should be an object",
+                )),
+            ))
+        }
+    };
+    let id = request.remove("id");
+    let result = match request.remove("method") {
+        Some(Value::String(method)) => {
+            let params = request.remove("params").unwrap_or(Value::Null);
+            panic::catch_unwind(AssertUnwindSafe(|| dispatch(&method, params)))
+                .unwrap_or_else(|_| Err(RpcError::new(RpcError::INTERNAL_ERROR, "panicked")))
+        }
+        _ => Err(RpcError::new(
+            RpcError::INVALID_REQUEST,
+            "method should be a string",
+        )),
+    };
+    // requests without an id are notifications, which get no response
+    id.map(|id| response(id, result))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error.to_json() }),
+    }
+}
+
+/// Listens on `addr`, answering each line of a client, a request or a batch, with a line, on one
+/// thread per client. Only returns if listening fails.
+pub fn serve(addr: impl ToSocketAddrs, dispatch: Dispatch) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::Builder::new()
+            .name("frb_rpc".to_owned())
+            .spawn(move || serve_client(stream, dispatch))?;
+    }
+    Ok(())
+}
+
+fn serve_client(stream: TcpStream, dispatch: Dispatch) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(dispatch, &line) {
+            writeln!(writer, "{}", response)?;
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,12 @@
 //! Manages receiving and sending values across the FFI boundary.
 
+use std::collections::HashMap;
+use std::io::{self, Write};
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::Thread;
+use std::time::{Duration, Instant};
 
 /// The representation of a Dart object outside of the Dart heap.
 ///
@@ -9,16 +15,23 @@ use std::marker::PhantomData;
 pub use allo_isolate::ffi::DartCObject;
 pub use allo_isolate::IntoDart;
 use allo_isolate::Isolate;
+use lazy_static::lazy_static;
+use parking_lot::{Condvar, Mutex};
+
+use crate::handler::Error;
+use crate::session;
 
 /// A wrapper around a Dart [`Isolate`].
 #[derive(Copy, Clone)]
 pub struct Rust2Dart {
     isolate: Isolate,
+    port: i64,
 }
 
 const RUST2DART_ACTION_SUCCESS: i32 = 0;
 const RUST2DART_ACTION_ERROR: i32 = 1;
 const RUST2DART_ACTION_CLOSE_STREAM: i32 = 2;
+const RUST2DART_ACTION_STREAM_READY: i32 = 3;
 
 // api signatures is similar to Flutter Android's callback https://api.flutter.dev/javadoc/io/flutter/plugin/common/MethodChannel.Result.html
 impl Rust2Dart {
@@ -26,6 +39,7 @@ impl Rust2Dart {
     pub fn new(port: i64) -> Self {
         Rust2Dart {
             isolate: Isolate::new(port),
+            port,
         }
     }
 
@@ -42,7 +56,8 @@ impl Rust2Dart {
         self.error_full(error_code, error_message, ())
     }
 
-    /// Send a detailed error back to the specified port.
+    /// Send a detailed error back to the specified port, along with the id of the call running on
+    /// the current thread.
     pub fn error_full(
         &self,
         error_code: String,
@@ -54,6 +69,7 @@ impl Rust2Dart {
             error_code.into_dart(),
             error_message.into_dart(),
             error_details.into_dart(),
+            crate::current_call_id().map(|id| id as i64).into_dart(),
         ])
     }
 
@@ -62,6 +78,12 @@ impl Rust2Dart {
         self.isolate
             .post(vec![RUST2DART_ACTION_CLOSE_STREAM.into_dart()])
     }
+
+    /// Tell the Dart stream waiting for its setup, see [StreamSetup], that it succeeded.
+    pub fn stream_ready(&self) -> bool {
+        self.isolate
+            .post(vec![RUST2DART_ACTION_STREAM_READY.into_dart()])
+    }
 }
 
 /// A callback that receives the return value of Rust functions.
@@ -79,14 +101,54 @@ impl TaskCallback {
     pub fn stream_sink<T: IntoDart>(&self) -> StreamSink<T> {
         StreamSink::new(self.rust2dart)
     }
+
+    /// Tell Dart that the stream is set up, then run the producer of `setup`, if any.
+    pub fn start_stream(&self, setup: StreamSetup) {
+        self.rust2dart.stream_ready();
+        if let Some(produce) = setup.produce {
+            produce();
+        }
+    }
+}
+
+/// Returned by a function taking a [StreamSink], as in `anyhow::Result<StreamSetup>`, once the
+/// stream is set up. Dart then gets the stream, while an error returned instead, e.g. for bad
+/// arguments, fails the Dart `Future` of the stream before it yields anything.
+pub struct StreamSetup {
+    produce: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl StreamSetup {
+    /// The items are added elsewhere, e.g. by a thread holding the sink.
+    pub fn ready() -> Self {
+        StreamSetup { produce: None }
+    }
+
+    /// The items are added by `produce`, which runs on the same worker once Dart knows that the
+    /// setup succeeded.
+    pub fn produce(produce: impl FnOnce() + Send + 'static) -> Self {
+        StreamSetup {
+            produce: Some(Box::new(produce)),
+        }
+    }
 }
 
 /// A sink to send asynchronous data back to Dart.
 /// Represented as a Dart
 /// [`Stream`](https://api.dart.dev/stable/dart-async/Stream-class.html).
-#[derive(Clone)]
+///
+/// The Dart side may pause or throttle the stream, in which case [StreamSink::add] blocks
+/// until the value can be sent, slowing down the producer. A producer running on the executor,
+/// e.g. with [StreamSetup::produce], keeps its worker while blocked, so that a stream paused for
+/// long takes a worker away from the other calls: such producers better run on their own thread,
+/// or check [StreamSink::is_paused] to skip values instead.
+///
+/// Once the Dart side has been restarted, e.g. by a Flutter hot restart, the sink is stale:
+/// its port is dead, and it never sends again.
 pub struct StreamSink<T: IntoDart> {
     rust2dart: Rust2Dart,
+    control: Arc<StreamControl>,
+    session: u64,
     _phantom_data: PhantomData<T>,
 }
 
@@ -95,19 +157,314 @@ impl<T: IntoDart> StreamSink<T> {
     pub fn new(rust2dart: Rust2Dart) -> Self {
         Self {
             rust2dart,
+            control: StreamControl::attach(rust2dart.port),
+            session: session::current(),
             _phantom_data: PhantomData,
         }
     }
 
     /// Add data to the stream. Returns false when data could not be sent,
-    /// or the stream has been closed.
+    /// or the stream has been closed or is stale.
+    ///
+    /// Blocks while the stream is paused, or until the throttling interval has elapsed, see
+    /// [StreamSink] for the cost of blocking a worker of the executor.
     pub fn add(&self, value: T) -> bool {
-        self.rust2dart.success(value)
+        if self.is_stale() {
+            return false;
+        }
+        self.control.wait_until_ready();
+        !self.is_stale() && self.rust2dart.success(value)
+    }
+
+    /// Whether the Dart side has paused the stream.
+    pub fn is_paused(&self) -> bool {
+        *self.control.paused.lock()
+    }
+
+    /// Whether the Dart side has been restarted since the sink was created, so that nobody
+    /// listens anymore. Long-running producers should stop then.
+    pub fn is_stale(&self) -> bool {
+        self.session != session::current()
     }
 
     /// Close the stream and ignore further messages. Returns false when
     /// the stream could not be closed, or when it has already been closed.
     pub fn close(&self) -> bool {
-        self.rust2dart.close_stream()
+        // the clones of the sink may still be blocked, and nobody resumes a closed stream
+        self.control.dispose();
+        !self.is_stale() && self.rust2dart.close_stream()
+    }
+
+    /// Close the stream with `error`, which Dart receives as a `FfiException` of code
+    /// `RESULT_ERROR` right before the stream is done, like the error of a function returning
+    /// `Err`. The handler is not involved. Returns false in the same cases as [StreamSink::close].
+    pub fn close_with_error(&self, error: impl Into<anyhow::Error>) -> bool {
+        self.control.dispose();
+        let error = Error::ResultError(error.into());
+        !self.is_stale()
+            && self.rust2dart.error_full(
+                error.code().to_string(),
+                error.message(),
+                error.error_type().map(str::to_owned),
+            )
+    }
+
+    /// Waits on the current thread for `future`, e.g. the next message of a channel. Returns
+    /// `None` as soon as nobody listens anymore, i.e. the Dart stream is cancelled or stale.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn block_on<F: std::future::Future>(&self, future: F) -> Option<F::Output> {
+        use std::task::{Context, Poll, Wake, Waker};
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut context = Context::from_waker(&waker);
+        // registered before the first check, so that a disposal in between unparks the thread
+        *self.control.waiting.lock() = Some(std::thread::current());
+        let output = loop {
+            if self.is_stale() || self.control.disposed.load(Ordering::Relaxed) {
+                break None;
+            }
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                break Some(output);
+            }
+            std::thread::park();
+        };
+        *self.control.waiting.lock() = None;
+        output
+    }
+}
+
+impl<T: IntoDart> Clone for StreamSink<T> {
+    fn clone(&self) -> Self {
+        self.control.sinks.fetch_add(1, Ordering::Relaxed);
+        Self {
+            rust2dart: self.rust2dart,
+            control: self.control.clone(),
+            session: self.session,
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T: IntoDart> Drop for StreamSink<T> {
+    fn drop(&mut self) {
+        let mut controls = STREAM_CONTROLS.lock();
+        let port = self.rust2dart.port;
+        if self.control.sinks.fetch_sub(1, Ordering::Relaxed) == 1
+            && controls
+                .get(&port)
+                .is_some_and(|control| Arc::ptr_eq(control, &self.control))
+        {
+            controls.remove(&port);
+        }
+    }
+}
+
+impl StreamSink<Vec<u8>> {
+    /// Sends what is written as chunks of bytes, e.g. to produce a file without holding all of it
+    /// in memory. Writes are buffered into chunks of [StreamSinkWriter::CHUNK_SIZE] bytes, and the
+    /// rest is sent when the writer is flushed or dropped. Dropping the writer also closes the stream.
+    pub fn into_writer(self) -> StreamSinkWriter {
+        StreamSinkWriter {
+            sink: self,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// A [Write] sending the bytes to Dart, see [StreamSink::into_writer].
+pub struct StreamSinkWriter {
+    sink: StreamSink<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl StreamSinkWriter {
+    /// Size of the chunks sent to Dart, so that small writes do not cost a message each.
+    pub const CHUNK_SIZE: usize = 64 * 1024;
+
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(Self::CHUNK_SIZE));
+        if self.sink.add(chunk) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the Dart stream is closed",
+            ))
+        }
+    }
+}
+
+impl Write for StreamSinkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(Self::CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == Self::CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+impl Drop for StreamSinkWriter {
+    fn drop(&mut self) {
+        let _ = self.send_buffer();
+        self.sink.close();
+    }
+}
+
+/// Commands sent by the Dart side to control a stream, see [StreamControl::apply].
+const STREAM_CONTROL_PAUSE: i32 = 0;
+const STREAM_CONTROL_RESUME: i32 = 1;
+const STREAM_CONTROL_THROTTLE: i32 = 2;
+const STREAM_CONTROL_DISPOSE: i32 = 3;
+
+lazy_static! {
+    static ref STREAM_CONTROLS: Mutex<HashMap<i64, Arc<StreamControl>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Releases the producers of the previous Dart session, which may be blocked by a paused or
+/// throttled stream that nobody will ever resume.
+pub(crate) fn dispose_stale_streams() {
+    let ports = STREAM_CONTROLS.lock().keys().copied().collect::<Vec<_>>();
+    for port in ports {
+        StreamControl::apply(port, STREAM_CONTROL_DISPOSE, 0);
+    }
+}
+
+/// Pause and throttling state of a stream, keyed by its port.
+#[derive(Default)]
+pub(crate) struct StreamControl {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+    throttle_millis: AtomicU64,
+    last_sent: Mutex<Option<Instant>>,
+    /// Set once the Dart side does not listen anymore.
+    disposed: AtomicBool,
+    /// The thread waiting for a value to send, see [StreamSink::block_on].
+    waiting: Mutex<Option<Thread>>,
+    /// Number of live [StreamSink]s of the stream, the control is removed with the last one.
+    sinks: AtomicUsize,
+}
+
+impl StreamControl {
+    fn get_or_insert(port: i64) -> Arc<Self> {
+        STREAM_CONTROLS.lock().entry(port).or_default().clone()
+    }
+
+    /// Like [StreamControl::get_or_insert], for a new sink. Counted under the lock of the map, so
+    /// that the last sink dropping meanwhile does not remove the control from under it.
+    fn attach(port: i64) -> Arc<Self> {
+        let mut controls = STREAM_CONTROLS.lock();
+        let control = controls.entry(port).or_default();
+        control.sinks.fetch_add(1, Ordering::Relaxed);
+        control.clone()
+    }
+
+    fn remove(port: i64) -> Option<Arc<Self>> {
+        STREAM_CONTROLS.lock().remove(&port)
+    }
+
+    /// Applies a command sent by the Dart side to the stream of the given port.
+    pub(crate) fn apply(port: i64, command: i32, value: i64) {
+        let control = match command {
+            STREAM_CONTROL_DISPOSE => match Self::remove(port) {
+                Some(control) => control,
+                None => return,
+            },
+            _ => Self::get_or_insert(port),
+        };
+        match command {
+            STREAM_CONTROL_PAUSE => control.set_paused(true),
+            STREAM_CONTROL_RESUME => control.set_paused(false),
+            STREAM_CONTROL_THROTTLE => control
+                .throttle_millis
+                .store(value.max(0) as u64, Ordering::Relaxed),
+            STREAM_CONTROL_DISPOSE => control.dispose(),
+            _ => {}
+        }
+    }
+
+    /// A disposed stream must never block its producer.
+    fn dispose(&self) {
+        self.throttle_millis.store(0, Ordering::Relaxed);
+        self.set_paused(false);
+        self.disposed.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.waiting.lock().take() {
+            thread.unpark();
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        *self.paused.lock() = paused;
+        if !paused {
+            self.resumed.notify_all();
+        }
+    }
+
+    fn wait_until_ready(&self) {
+        let mut paused = self.paused.lock();
+        while *paused {
+            self.resumed.wait(&mut paused);
+        }
+        drop(paused);
+
+        let throttle = Duration::from_millis(self.throttle_millis.load(Ordering::Relaxed));
+        let mut last_sent = self.last_sent.lock();
+        if let Some(elapsed) = last_sent.map(|it| it.elapsed()) {
+            if elapsed < throttle {
+                std::thread::sleep(throttle - elapsed);
+            }
+        }
+        *last_sent = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_the_last_sink_removes_its_stream_control() {
+        let port = 902;
+        let sink = StreamSink::<i32>::new(Rust2Dart::new(port));
+        let clone = sink.clone();
+        drop(sink);
+        assert!(STREAM_CONTROLS.lock().contains_key(&port));
+        drop(clone);
+        assert!(!STREAM_CONTROLS.lock().contains_key(&port));
+    }
+
+    #[test]
+    fn closing_a_sink_keeps_the_stream_control_of_its_clones() {
+        let port = 9021;
+        let sink = StreamSink::<i32>::new(Rust2Dart::new(port));
+        let clone = sink.clone();
+        StreamControl::apply(port, STREAM_CONTROL_PAUSE, 0);
+        sink.close();
+        // the clone is not blocked by the pause anymore
+        assert!(!clone.is_paused());
+        // commands sent meanwhile do not count as a sink
+        StreamControl::apply(port, STREAM_CONTROL_THROTTLE, 10);
+        drop(sink);
+        assert!(STREAM_CONTROLS.lock().contains_key(&port));
+        drop(clone);
+        assert!(!STREAM_CONTROLS.lock().contains_key(&port));
     }
 }
@@ -0,0 +1,54 @@
+//! Detects that the Dart side has been restarted, e.g. by a Flutter hot restart, while the Rust
+//! library stays loaded, and tears down what belonged to the previous Dart session.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+type ReinitHook = Box<dyn Fn() + Send + Sync>;
+
+/// Incremented whenever a new Dart session starts, see [begin].
+static SESSION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    /// The token of the current Dart session, unique to each run of the Dart isolate.
+    static ref SESSION_TOKEN: Mutex<Option<i64>> = Mutex::new(None);
+    static ref REINIT_HOOKS: Mutex<Vec<ReinitHook>> = Mutex::new(Vec::new());
+}
+
+/// Registers a function called when the Dart side has been restarted, e.g. by a Flutter hot
+/// restart, before any call of the new Dart session.
+///
+/// Statics survive a hot restart since the Rust library is not reloaded, so use it to reset
+/// the ones that must be initialized once per Dart session, or to stop background threads
+/// started by the previous one.
+pub fn on_reinit(hook: impl Fn() + Send + Sync + 'static) {
+    REINIT_HOOKS.lock().push(Box::new(hook));
+}
+
+pub(crate) fn current() -> u64 {
+    SESSION.load(Ordering::SeqCst)
+}
+
+/// Starts the Dart session identified by `token`. Called by every API class when it is created,
+/// so only a token different from the current one means a restart. Returns whether the state of
+/// a previous session has been torn down.
+pub(crate) fn begin(token: i64) -> bool {
+    let mut current_token = SESSION_TOKEN.lock();
+    if *current_token == Some(token) {
+        return false;
+    }
+    let reinit = current_token.is_some();
+    *current_token = Some(token);
+    SESSION.fetch_add(1, Ordering::SeqCst);
+
+    if reinit {
+        crate::rust2dart::dispose_stale_streams();
+        crate::dart2rust::close_stale_streams();
+        for hook in REINIT_HOOKS.lock().iter() {
+            hook();
+        }
+    }
+    reinit
+}
@@ -0,0 +1,103 @@
+//! Forwards what the process writes to its standard output and error, e.g. with `println!`, to
+//! Dart, where it is otherwise invisible in some cases, such as iOS release builds.
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+
+use crate::rust2dart::Rust2Dart;
+use crate::StreamSink;
+
+lazy_static! {
+    /// Capturing starts on first use, and goes on until the process exits.
+    static ref STARTED: Result<(), String> = imp::start().map_err(|error| error.to_string());
+    /// The Dart streams receiving the captured lines.
+    static ref SINKS: Mutex<Vec<StreamSink<String>>> = Mutex::new(Vec::new());
+}
+
+/// Adds the stream of `port` to those receiving the captured lines. The output is still written
+/// to the original standard output and error as well.
+pub(crate) fn capture(port: i64) {
+    let rust2dart = Rust2Dart::new(port);
+    match &*STARTED {
+        Ok(()) => SINKS.lock().push(StreamSink::new(rust2dart)),
+        Err(error) => {
+            rust2dart.error("STDIO_CAPTURE".to_owned(), error.clone());
+            rust2dart.close_stream();
+        }
+    }
+}
+
+/// Sends a line to every stream, forgetting those which nobody listens to anymore.
+fn forward(line: &[u8]) {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end_matches(&['\r', '\n'][..]);
+    SINKS.lock().retain(|sink| sink.add(line.to_owned()));
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::io::{FromRawFd, RawFd};
+    use std::thread;
+
+    /// Redirects the standard output and error into pipes, read by one thread each.
+    pub(super) fn start() -> io::Result<()> {
+        for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            let (reader, original) = unsafe { redirect(fd)? };
+            thread::Builder::new()
+                .name(format!("frb_stdio_{}", fd))
+                .spawn(move || pump(reader, original))?;
+        }
+        Ok(())
+    }
+
+    /// # Safety
+    /// `fd` must be open. It is replaced by the write end of a new pipe.
+    unsafe fn redirect(fd: RawFd) -> io::Result<(File, File)> {
+        let mut pipe = [0; 2];
+        if libc::pipe(pipe.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = libc::dup(fd);
+        if original < 0 || libc::dup2(pipe[1], fd) < 0 {
+            let error = io::Error::last_os_error();
+            libc::close(pipe[0]);
+            libc::close(pipe[1]);
+            if original >= 0 {
+                libc::close(original);
+            }
+            return Err(error);
+        }
+        libc::close(pipe[1]);
+        Ok((File::from_raw_fd(pipe[0]), File::from_raw_fd(original)))
+    }
+
+    /// Never writes to the standard output or error itself, which would loop.
+    fn pump(reader: File, mut original: File) {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {
+                    let _ = original.write_all(&line);
+                    super::forward(&line);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+
+    pub(super) fn start() -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "capturing the standard output and error is only supported on unix-like platforms",
+        ))
+    }
+}
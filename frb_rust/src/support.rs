@@ -2,13 +2,23 @@
 //! These functions are *not* meant to be used by humans directly.
 #![doc(hidden)]
 
+use std::convert::TryFrom;
 use std::mem;
+use std::panic;
+use std::panic::UnwindSafe;
+use std::sync::{Arc, Mutex};
 
 pub use allo_isolate::ffi::DartCObject;
 pub use allo_isolate::{IntoDart, IntoDartExceptPrimitive};
+pub use anyhow;
 pub use lazy_static::lazy_static;
 
+pub use crate::concurrency::ConcurrencyLimit;
+use crate::dart2rust::{DartStream, DartStreamChannel, DartStreamReader};
 pub use crate::handler::DefaultHandler;
+use crate::handler::{DynError, Error, ExecutorHealth};
+use crate::rust2dart::{Rust2Dart, StreamControl, StreamSink, StreamSinkWriter};
+use crate::{RustOpaque, SyncReturn};
 
 // ref https://stackoverflow.com/questions/39224904/how-to-expose-a-rust-vect-to-ffi
 pub fn new_leak_vec_ptr<T: Clone>(fill: T, length: i32) -> *mut T {
@@ -42,11 +52,672 @@ pub unsafe fn box_from_leak_ptr<T>(ptr: *mut T) -> Box<T> {
     Box::from_raw(ptr)
 }
 
+/// Splits the strings of a `Vec<String>` packed by Dart into one buffer, `ends` holding the
+/// offset after each string. Fails on offsets out of order or out of the buffer, and on strings
+/// which are not UTF-8.
+pub fn unpack_strings(bytes: &[u8], ends: &[i32]) -> anyhow::Result<Vec<String>> {
+    let mut start = 0;
+    ends.iter()
+        .map(|&end| {
+            let end = usize::try_from(end)
+                .ok()
+                .filter(|end| (start..=bytes.len()).contains(end))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "string end {} is not within {}..={}",
+                        end,
+                        start,
+                        bytes.len()
+                    )
+                })?;
+            let string = String::from_utf8(bytes[start..end].to_vec())?;
+            start = end;
+            Ok(string)
+        })
+        .collect()
+}
+
 /// NOTE for maintainer: Please keep this struct in sync with `DUMMY_WIRE_CODE_FOR_BINDGEN`
 /// in the code generator
 #[repr(C)]
 pub struct WireSyncReturnStruct {
     pub ptr: *mut u8,
     pub len: i32,
-    pub success: bool,
+    /// A [SyncReturnKind], telling Dart how to interpret the bytes.
+    pub kind: i32,
+}
+
+// Dart reads the struct as a pointer followed by two `Int32`, and a `usize` as the 8 bytes of a
+// `u64`, see [IntoSyncReturnBytes].
+const _: () = assert!(mem::size_of::<WireSyncReturnStruct>() == mem::size_of::<*mut u8>() + 8);
+const _: () = assert!(mem::size_of::<usize>() <= 8);
+
+/// The [WireSyncReturnStruct] which Dart passes to the functions of the primitive fast path to
+/// learn their outcome, see [Handler::wrap_primitive_sync](crate::handler::Handler::wrap_primitive_sync).
+/// It is reused by all the calls, so it is never freed.
+pub fn new_sync_outcome() -> *mut WireSyncReturnStruct {
+    new_leak_box_ptr(WireSyncReturnStruct {
+        ptr: std::ptr::null_mut(),
+        len: 0,
+        kind: SyncReturnKind::Null as i32,
+    })
+}
+
+/// What the bytes of a [WireSyncReturnStruct] hold.
+///
+/// NOTE for maintainer: Please keep the values in sync with `WireSyncReturnStruct` in Dart
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncReturnKind {
+    /// The encoded return value.
+    Success = 0,
+    /// The error message, which is thrown in Dart.
+    Error = 1,
+    /// No bytes, the return value is `null` in Dart.
+    Null = 2,
+}
+
+/// Accepts both `Result<SyncReturn<T>>` and `SyncReturn<Result<T>>` as the return type of a
+/// fallible sync function.
+pub trait IntoSyncReturnResult<T> {
+    fn into_sync_return_result(self) -> anyhow::Result<SyncReturn<T>>;
+}
+
+impl<T> IntoSyncReturnResult<T> for anyhow::Result<SyncReturn<T>> {
+    fn into_sync_return_result(self) -> anyhow::Result<SyncReturn<T>> {
+        self
+    }
+}
+
+impl<T> IntoSyncReturnResult<T> for SyncReturn<anyhow::Result<T>> {
+    fn into_sync_return_result(self) -> anyhow::Result<SyncReturn<T>> {
+        self.0.map(SyncReturn)
+    }
+}
+
+/// Encodes the value inside a [`SyncReturn`](crate::SyncReturn) into the bytes carried by
+/// [WireSyncReturnStruct]. Primitives are little-endian whatever the platform, and read with
+/// `Endian.little` in Dart, so that the encoding does not depend on where it runs.
+pub trait IntoSyncReturnBytes {
+    fn into_sync_return_bytes(self) -> Vec<u8>;
+}
+
+macro_rules! impl_into_sync_return_bytes_for_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl IntoSyncReturnBytes for $t {
+                fn into_sync_return_bytes(self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_sync_return_bytes_for_primitive!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl IntoSyncReturnBytes for usize {
+    fn into_sync_return_bytes(self) -> Vec<u8> {
+        (self as u64).into_sync_return_bytes()
+    }
+}
+
+impl IntoSyncReturnBytes for bool {
+    fn into_sync_return_bytes(self) -> Vec<u8> {
+        vec![self as u8]
+    }
+}
+
+impl IntoSyncReturnBytes for () {
+    fn into_sync_return_bytes(self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+impl IntoSyncReturnBytes for String {
+    fn into_sync_return_bytes(self) -> Vec<u8> {
+        self.into_bytes()
+    }
+}
+
+/// Decodes the inputs of a function of `--codec msgpack`, sent by `msgpackEncode` in Dart as an
+/// array of the arguments.
+#[cfg(feature = "msgpack")]
+pub fn msgpack_decode<T: serde::de::DeserializeOwned>(payload: &[u8]) -> anyhow::Result<T> {
+    rmp_serde::from_slice(payload)
+        .map_err(|err| anyhow::anyhow!("invalid MessagePack arguments: {}", err))
+}
+
+/// Encodes the output of a function of `--codec msgpack`, decoded by `msgpackDecode` in Dart.
+/// Structs are arrays of their fields, and enums the name of the variant, or a map from it to
+/// the fields, as serde derives them.
+#[cfg(feature = "msgpack")]
+pub fn msgpack_encode<T: serde::Serialize + ?Sized>(
+    value: &T,
+) -> anyhow::Result<allo_isolate::ZeroCopyBuffer<Vec<u8>>> {
+    rmp_serde::to_vec(value)
+        .map(allo_isolate::ZeroCopyBuffer)
+        .map_err(|err| anyhow::anyhow!("cannot encode the output to MessagePack: {}", err))
+}
+
+/// Sends the health of the executor to Dart. It is called directly from the wire function instead
+/// of going through the executor, so that it still answers when all workers are busy.
+pub fn report_health<F>(port: i64, health: F)
+where
+    F: FnOnce() -> ExecutorHealth + UnwindSafe,
+{
+    // As in [Handler::wrap], a panic must never cross the FFI boundary.
+    let _ = panic::catch_unwind(move || {
+        let rust2dart = Rust2Dart::new(port);
+        match panic::catch_unwind(health) {
+            Ok(health) => rust2dart.success(health),
+            Err(error) => {
+                let error = Error::Panic(error);
+                rust2dart.error(error.code().to_string(), error.message())
+            }
+        };
+    });
+}
+
+/// Pauses, resumes, throttles or disposes the stream of the given port, see [StreamSink](crate::StreamSink).
+pub fn stream_control(port: i64, command: i32, value: i64) {
+    let _ = panic::catch_unwind(move || StreamControl::apply(port, command, value));
+}
+
+/// Requests the cancellation of the task answering to the given port, see [is_cancelled](crate::is_cancelled).
+pub fn cancel_task(port: i64) {
+    let _ = panic::catch_unwind(move || crate::cancel::cancel(port));
+}
+
+/// Forwards the standard output and error of the process to the Dart stream of `port`.
+pub fn capture_stdio(port: i64) {
+    let _ = panic::catch_unwind(move || crate::stdio::capture(port));
+}
+
+/// Sends the panics of the functions called from Dart to the Dart stream of `port`.
+pub fn crash_reports(port: i64) {
+    let _ = panic::catch_unwind(move || crate::crash::listen(port));
+}
+
+/// Sets the maximum level of the `log` crate, from the index of `LogLevel` in Dart: 0 turns the
+/// logs off, and 1 to 5 go from `Error` to `Trace`.
+#[cfg(feature = "log")]
+pub fn set_log_level(level: i32) {
+    let filter = match level {
+        i32::MIN..=0 => log::LevelFilter::Off,
+        1 => log::LevelFilter::Error,
+        2 => log::LevelFilter::Warn,
+        3 => log::LevelFilter::Info,
+        4 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    log::set_max_level(filter);
+}
+
+/// Starts the Dart session identified by `token`, tearing down the state of the previous one
+/// after a restart, see [on_reinit](crate::on_reinit).
+pub fn reinit(token: i64) {
+    let _ = panic::catch_unwind(move || crate::session::begin(token));
+}
+
+/// Leaks a closure returned to Dart, where it is held as an opaque handle until
+/// [drop_closure_handle] is called.
+pub fn new_closure_handle<F: ?Sized>(f: Box<F>) -> i64 {
+    Box::into_raw(Box::new(Arc::<F>::from(f))) as i64
+}
+
+/// Borrows the closure behind a handle, so that it can be called from another thread even if
+/// the handle is dropped meanwhile.
+///
+/// # Safety
+/// The handle must come from [new_closure_handle] with the same `F`, and must not be dropped yet.
+/// Calls and drops are made from the Dart thread, so they never race with each other.
+pub unsafe fn clone_closure_handle<F: ?Sized>(handle: i64) -> Arc<F> {
+    (*(handle as *const Arc<F>)).clone()
+}
+
+/// # Safety
+/// Use it in pair with [new_closure_handle], at most once per handle.
+pub unsafe fn drop_closure_handle<F: ?Sized>(handle: i64) {
+    drop(Box::from_raw(handle as *mut Arc<F>));
+}
+
+/// Shares a value returned to Dart, where it is held as an opaque handle until as many calls to
+/// [drop_opaque_handle] as to [new_opaque_handle] and [share_opaque_handle] are made. The handle
+/// is the address of the value, so that all the handles to the same value are equal in Dart.
+pub fn new_opaque_handle<T>(opaque: RustOpaque<T>) -> i64 {
+    Arc::into_raw(opaque.0) as i64
+}
+
+/// Shares the value behind a handle with a function, which may run on another thread even if
+/// the handle is dropped meanwhile.
+///
+/// # Safety
+/// The handle must come from [new_opaque_handle] with the same `T`, and must not be dropped yet.
+/// Handles are used and dropped from the Dart thread, so they never race with each other.
+pub unsafe fn clone_opaque_handle<T>(handle: i64) -> RustOpaque<T> {
+    share_opaque_handle::<T>(handle);
+    RustOpaque(Arc::from_raw(handle as *const T))
+}
+
+/// Counts another Dart handle to the same value, released by its own [drop_opaque_handle].
+///
+/// # Safety
+/// The same as [clone_opaque_handle].
+pub unsafe fn share_opaque_handle<T>(handle: i64) {
+    Arc::increment_strong_count(handle as *const T);
+}
+
+/// # Safety
+/// The same as [clone_opaque_handle].
+pub unsafe fn opaque_debug_string<T: std::fmt::Debug>(handle: i64) -> String {
+    format!("{:?}", &*(handle as *const T))
+}
+
+/// # Safety
+/// Use it once per call to [new_opaque_handle] or [share_opaque_handle].
+pub unsafe fn drop_opaque_handle<T>(handle: i64) {
+    drop(Arc::from_raw(handle as *const T));
+}
+
+/// An iterator returned to Dart, read a page at a time by [iterator_next]. The lock lets pages be
+/// fetched from any worker, one after the other.
+pub struct IteratorHandle<T>(Mutex<Box<dyn Iterator<Item = T> + Send>>);
+
+/// Leaks an iterator returned to Dart, where it is held as an opaque handle until
+/// [drop_iterator_handle] is called.
+pub fn new_iterator_handle<T: 'static>(iterator: Box<dyn Iterator<Item = T> + Send>) -> i64 {
+    Box::into_raw(Box::new(Arc::new(IteratorHandle(Mutex::new(iterator))))) as i64
+}
+
+/// Borrows the iterator behind a handle, so that it can be read from another thread even if
+/// the handle is dropped meanwhile.
+///
+/// # Safety
+/// The handle must come from [new_iterator_handle] with the same `T`, and must not be dropped yet.
+/// Reads and drops are requested from the Dart thread, so they never race with each other.
+pub unsafe fn clone_iterator_handle<T>(handle: i64) -> Arc<IteratorHandle<T>> {
+    (*(handle as *const Arc<IteratorHandle<T>>)).clone()
+}
+
+/// The next `count` items, fewer only once the iterator is exhausted.
+pub fn iterator_next<T>(iterator: &IteratorHandle<T>, count: usize) -> Vec<T> {
+    let mut iterator = iterator
+        .0
+        .lock()
+        .expect("the iterator panicked while fetching a previous page");
+    iterator.by_ref().take(count).collect()
+}
+
+/// # Safety
+/// Use it in pair with [new_iterator_handle], at most once per handle.
+pub unsafe fn drop_iterator_handle<T>(handle: i64) {
+    drop(Box::from_raw(handle as *mut Arc<IteratorHandle<T>>));
+}
+
+/// Creates the handle through which Dart pushes values to a [DartStream](crate::DartStream).
+/// The handle holds two references to the channel, released by [take_dart_stream] when the
+/// function is called, and by [close_dart_stream] when the Dart stream is done.
+pub fn new_dart_stream<T: Send + 'static>() -> i64 {
+    let channel = DartStreamChannel::<T>::new();
+    let handle = Arc::into_raw(channel.clone()) as i64;
+    mem::forget(channel);
+    handle
+}
+
+/// # Safety
+/// The handle must come from [new_dart_stream] with the same `T`, and must not be closed yet.
+/// Values are pushed from the Dart thread, so they never race with [close_dart_stream].
+pub unsafe fn dart_stream_add<T>(handle: i64, value: T) {
+    (*(handle as *const DartStreamChannel<T>)).add(value);
+}
+
+/// # Safety
+/// Use it in pair with [new_dart_stream], at most once per handle.
+pub unsafe fn close_dart_stream<T>(handle: i64) {
+    Arc::from_raw(handle as *const DartStreamChannel<T>).close();
+}
+
+/// Takes the receiving end of the channel, on the Dart thread so that the handle is never used
+/// after being released.
+///
+/// # Safety
+/// Use it in pair with [new_dart_stream], at most once per handle.
+pub unsafe fn take_dart_stream<T>(handle: i64) -> DartStream<T> {
+    Arc::from_raw(handle as *const DartStreamChannel<T>).take()
+}
+
+/// The `impl Read` argument of a function, over the bytes of a Dart stream.
+pub fn dart_stream_reader(stream: DartStream<Vec<u8>>) -> DartStreamReader {
+    stream.into_reader()
+}
+
+/// The `impl Write` argument of a function, sending the bytes to the Dart stream it returns.
+pub fn stream_sink_writer(sink: StreamSink<Vec<u8>>) -> StreamSinkWriter {
+    sink.into_writer()
+}
+
+/// Sends the current value of a `watch` channel returned by a function, then each change, to the
+/// Dart stream it returns, until either side is gone.
+#[cfg(feature = "tokio")]
+pub fn forward_watch<T>(mut receiver: tokio::sync::watch::Receiver<T>, sink: StreamSink<T>)
+where
+    T: IntoDart + Clone + Send + Sync + 'static,
+{
+    std::thread::spawn(move || {
+        loop {
+            let value = receiver.borrow_and_update().clone();
+            if !sink.add(value) {
+                break;
+            }
+            match sink.block_on(receiver.changed()) {
+                Some(Ok(())) => {}
+                // the sender is dropped, or Dart does not listen anymore
+                _ => break,
+            }
+        }
+        sink.close();
+    });
+}
+
+/// Sends the messages of a `broadcast` channel returned by a function to the Dart stream it
+/// returns, until either side is gone. Messages missed by a slow listener are skipped.
+#[cfg(feature = "tokio")]
+pub fn forward_broadcast<T>(mut receiver: tokio::sync::broadcast::Receiver<T>, sink: StreamSink<T>)
+where
+    T: IntoDart + Clone + Send + 'static,
+{
+    use tokio::sync::broadcast::error::RecvError;
+
+    std::thread::spawn(move || {
+        loop {
+            match sink.block_on(receiver.recv()) {
+                Some(Ok(value)) => {
+                    if !sink.add(value) {
+                        break;
+                    }
+                }
+                Some(Err(RecvError::Lagged(_))) => {}
+                Some(Err(RecvError::Closed)) | None => break,
+            }
+        }
+        sink.close();
+    });
+}
+
+/// A raw pointer taken by a function marked `#[frb(unsafe_ffi)]`, moved to the worker thread
+/// as is. Whether the pointee may be used from there is up to the function, which opted in.
+pub struct UnsafeSend<T>(pub T);
+
+unsafe impl<T> Send for UnsafeSend<T> {}
+
+impl<T> UnsafeSend<T> {
+    /// Taken by a method, so that closures capture the whole wrapper rather than its field.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// A boxed error returned by a function, e.g. `Box<dyn Error>` or `Box<dyn Error + Send + Sync>`.
+pub trait AsDynError {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'static);
+}
+
+impl AsDynError for Box<dyn std::error::Error> {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'static) {
+        &**self
+    }
+}
+
+impl AsDynError for Box<dyn std::error::Error + Send> {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'static) {
+        &**self
+    }
+}
+
+impl AsDynError for Box<dyn std::error::Error + Send + Sync> {
+    fn as_dyn_error(&self) -> &(dyn std::error::Error + 'static) {
+        &**self
+    }
+}
+
+/// Converts the result of a function returning `Result<T, Box<dyn Error>>`, keeping the type name
+/// of the error when it is known.
+pub fn dyn_error_result<T, E: AsDynError>(result: Result<T, E>) -> anyhow::Result<T> {
+    result.map_err(|e| DynError::new(e.as_dyn_error()).into())
+}
+
+/// Payloads shorter than this are not worth compressing, and are sent as is.
+#[cfg(feature = "zstd")]
+const COMPRESS_MIN_LEN: usize = 1024;
+
+/// Compresses the output of a function marked with `#[frb(compress = "zstd")]`.
+/// The first byte tells Dart whether the rest is compressed, see `decompressPayload` in Dart.
+#[cfg(feature = "zstd")]
+pub fn compress_zstd(data: impl AsRef<[u8]>) -> Vec<u8> {
+    const UNCOMPRESSED: u8 = 0;
+    const ZSTD: u8 = 1;
+
+    let data = data.as_ref();
+    if data.len() >= COMPRESS_MIN_LEN {
+        if let Ok(compressed) = zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL) {
+            if compressed.len() < data.len() {
+                return [&[ZSTD][..], &compressed].concat();
+            }
+        }
+    }
+    [&[UNCOMPRESSED][..], data].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_strings_splits_at_the_ends() {
+        let strings = unpack_strings("abcé".as_bytes(), &[2, 2, 5]).unwrap();
+        assert_eq!(strings, ["ab", "", "cé"]);
+    }
+
+    #[test]
+    fn unpack_strings_rejects_invalid_ends_and_utf8() {
+        assert!(unpack_strings(b"abc", &[2, 1]).is_err());
+        assert!(unpack_strings(b"abc", &[4]).is_err());
+        assert!(unpack_strings(b"abc", &[-1]).is_err());
+        assert!(unpack_strings(&[0xff], &[1]).is_err());
+    }
+
+    #[test]
+    fn sync_return_bytes_are_little_endian() {
+        assert_eq!(0x0102u16.into_sync_return_bytes(), [0x02, 0x01]);
+        assert_eq!((-2i32).into_sync_return_bytes(), [0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(1usize.into_sync_return_bytes(), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            1.0f64.into_sync_return_bytes(),
+            [0, 0, 0, 0, 0, 0, 0xf0, 0x3f]
+        );
+        assert_eq!(true.into_sync_return_bytes(), [1]);
+    }
+
+    #[test]
+    fn primitive_sync_panics_become_errors() {
+        use crate::handler::{FfiCallMode, Handler, WrapInfo};
+
+        let handler = DefaultHandler::default();
+        let info = || WrapInfo::new("add", None, FfiCallMode::Sync);
+
+        let (value, outcome) = handler.wrap_primitive_sync(info(), || 1 + 2);
+        assert_eq!(value, 3);
+        assert_eq!(outcome.kind, SyncReturnKind::Success as i32);
+
+        let (value, outcome) = handler.wrap_primitive_sync(info(), || -> i32 { panic!("oops") });
+        assert_eq!(value, 0);
+        assert_eq!(outcome.kind, SyncReturnKind::Error as i32);
+        let message = unsafe { vec_from_leak_ptr(outcome.ptr, outcome.len) };
+        assert!(String::from_utf8(message).unwrap().contains("oops"));
+    }
+
+    #[test]
+    fn nullable_sync_returns_tell_null_from_empty() {
+        use crate::handler::{FfiCallMode, Handler, WrapInfo};
+
+        let handler = DefaultHandler::default();
+        let info = || WrapInfo::new("find", None, FfiCallMode::Sync);
+
+        let outcome = handler.wrap_sync_nullable(info(), || Ok(SyncReturn(None)));
+        assert_eq!(outcome.kind, SyncReturnKind::Null as i32);
+
+        let outcome = handler.wrap_sync_nullable(info(), || Ok(SyncReturn(Some(Vec::new()))));
+        assert_eq!(outcome.kind, SyncReturnKind::Success as i32);
+
+        let outcome = handler
+            .wrap_sync_nullable(info(), || -> anyhow::Result<SyncReturn<Option<Vec<u8>>>> {
+                Err(anyhow::anyhow!("not found"))
+            });
+        assert_eq!(outcome.kind, SyncReturnKind::Error as i32);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_decode_reads_what_dart_writes() {
+        // `msgpackEncode` takes the smallest encoding of integers, and float 64 for all doubles
+        let payload = [
+            0x93, 0xcc, 0xc8, 0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0, 0x92, 0xa1, 0x61, 0xc0,
+        ];
+        let decoded: (u32, f32, Vec<Option<String>>) = msgpack_decode(&payload).unwrap();
+        assert_eq!(decoded, (200, 1.5, vec![Some("a".to_owned()), None]));
+        assert!(msgpack_decode::<(u32,)>(&[0x91, 0xa1, 0x61]).is_err());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_encode_writes_arrays_of_the_fields() {
+        let encoded = msgpack_encode(&(1u8, "a", vec![true])).unwrap();
+        assert_eq!(encoded.0, [0x93, 0x01, 0xa1, 0x61, 0x91, 0xc3]);
+    }
+
+    /// Step of the generator of [sample_text] and [sample_noise].
+    #[cfg(feature = "zstd")]
+    fn xorshift(state: &mut u32) -> u32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        *state
+    }
+
+    /// Text which compresses well, the same as `sampleText` in `compression_test.dart`: words
+    /// picked by a xorshift generator, with a new one a quarter of the time.
+    #[cfg(feature = "zstd")]
+    fn sample_text(len: usize) -> Vec<u8> {
+        const WORDS: [&str; 8] = [
+            "flutter", "rust", "bridge", "dart", "frame", "block", "literal", "sequence",
+        ];
+        let mut state = 0x2545f491;
+        let mut word = 0;
+        let mut text = Vec::with_capacity(len + 9);
+        while text.len() < len {
+            if xorshift(&mut state) & 3 == 0 {
+                word = (state >> 8) as usize % WORDS.len();
+            }
+            text.extend_from_slice(WORDS[word].as_bytes());
+            text.push(b' ');
+        }
+        text.truncate(len);
+        text
+    }
+
+    /// Bytes which do not compress, the same as `sampleNoise` in `compression_test.dart`.
+    #[cfg(feature = "zstd")]
+    fn sample_noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545f491;
+        (0..len)
+            .map(|_| (xorshift(&mut state) >> 24) as u8)
+            .collect()
+    }
+
+    /// A skippable frame, which decoders must step over.
+    #[cfg(feature = "zstd")]
+    fn skippable_frame(content: &[u8]) -> Vec<u8> {
+        [
+            &0x184d2a50u32.to_le_bytes()[..],
+            &(content.len() as u32).to_le_bytes(),
+            content,
+        ]
+        .concat()
+    }
+
+    /// The vectors of the Zstandard decoder of Dart, in `frb_dart/test/zstd`, along with their
+    /// contents, which `compression_test.dart` builds the same way.
+    #[cfg(feature = "zstd")]
+    fn zstd_test_vectors() -> Vec<(&'static str, Vec<u8>, Vec<u8>)> {
+        let compress = |data: &[u8], level| zstd::bulk::compress(data, level).unwrap();
+        vec![
+            // a raw block
+            (
+                "raw.zst",
+                sample_noise(3000),
+                compress(&sample_noise(3000), 3),
+            ),
+            // a compressed block, then RLE blocks
+            (
+                "rle.zst",
+                vec![b'a'; 300_000],
+                compress(&[b'a'; 300_000], 3),
+            ),
+            // two compressed blocks, the second one repeating the Huffman table of the literals
+            // of the first one, and the FSE tables of the offsets and of the match lengths
+            (
+                "blocks.zst",
+                sample_text(200_000),
+                compress(&sample_text(200_000), 9),
+            ),
+            (
+                "frames.zst",
+                [sample_text(2000), sample_noise(100)].concat(),
+                [
+                    skippable_frame(b"frb"),
+                    compress(&sample_text(2000), 3),
+                    compress(&sample_noise(100), 3),
+                    skippable_frame(&[]),
+                ]
+                .concat(),
+            ),
+        ]
+    }
+
+    /// Checks that the vectors hold what the Dart test expects, or rewrites them with
+    /// `FRB_UPDATE_ZSTD_VECTORS=1 cargo test --features zstd`.
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_test_vectors_are_up_to_date() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../frb_dart/test/zstd");
+        for (name, content, vector) in zstd_test_vectors() {
+            let path = dir.join(name);
+            if std::env::var_os("FRB_UPDATE_ZSTD_VECTORS").is_some() {
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(&path, vector).unwrap();
+                continue;
+            }
+            let vector = std::fs::read(&path).unwrap();
+            let decoded = zstd::stream::decode_all(&vector[..]).unwrap();
+            assert!(decoded == content, "{} does not hold its content", name);
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn compress_zstd_only_keeps_the_compression_when_it_pays() {
+        assert_eq!(compress_zstd(b"short"), b"\0short");
+        assert_eq!(
+            compress_zstd(sample_noise(3000))[1..],
+            sample_noise(3000)[..]
+        );
+
+        let compressed = compress_zstd(sample_text(3000));
+        assert_eq!(compressed[0], 1);
+        assert_eq!(
+            zstd::bulk::decompress(&compressed[1..], 3000).unwrap(),
+            sample_text(3000)
+        );
+    }
 }
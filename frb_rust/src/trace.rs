@@ -0,0 +1,55 @@
+//! Spans of the `tracing` crate around the calls from Dart, with the `tracing` feature, so that
+//! traces and flame graphs show the calls through the bridge without instrumenting each function.
+//! Without the feature, [CallSpan] does nothing and costs nothing.
+
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+use crate::handler::WrapInfo;
+
+/// The span of one call, covering the decoding of its arguments on the Dart thread and its
+/// execution, on whichever thread the executor runs it.
+#[derive(Clone)]
+pub(crate) struct CallSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+// a span holds no state that a panic could leave broken
+impl UnwindSafe for CallSpan {}
+impl RefUnwindSafe for CallSpan {}
+
+impl CallSpan {
+    #[cfg(feature = "tracing")]
+    pub(crate) fn new(wrap_info: &WrapInfo) -> Self {
+        CallSpan {
+            span: tracing::info_span!(
+                "frb_call",
+                function = wrap_info.debug_name,
+                call_id = wrap_info.call_id,
+                mode = ?wrap_info.mode,
+                output_bytes = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn new(_wrap_info: &WrapInfo) -> Self {
+        CallSpan {}
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.span.in_scope(f)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        f()
+    }
+
+    /// Records the size of the encoded return value of a sync function.
+    pub(crate) fn record_output_bytes(&self, _len: usize) {
+        #[cfg(feature = "tracing")]
+        self.span.record("output_bytes", _len as u64);
+    }
+}